@@ -0,0 +1,156 @@
+//! Anthropic Messages API response, stream event and error schema.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Non-streaming `message` object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicMessageResponse {
+    pub id: String,
+
+    /// Always `message`.
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// Always `assistant`.
+    pub role: String,
+
+    pub model: String,
+    pub content: Vec<AnthropicResponseBlock>,
+
+    /// `end_turn | max_tokens | stop_sequence | tool_use | refusal`; `null` in `message_start`.
+    pub stop_reason: Option<String>,
+    pub stop_sequence: Option<String>,
+    pub usage: AnthropicUsage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicResponseBlock {
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// Server-sent event of a streamed message; the SSE `event:` name equals the `type` tag.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicStreamEvent {
+    MessageStart {
+        message: AnthropicMessageResponse,
+    },
+    ContentBlockStart {
+        index: u32,
+        content_block: AnthropicResponseBlock,
+    },
+    ContentBlockDelta {
+        index: u32,
+        delta: AnthropicBlockDelta,
+    },
+    ContentBlockStop {
+        index: u32,
+    },
+    MessageDelta {
+        delta: AnthropicMessageDelta,
+        usage: AnthropicUsage,
+    },
+    MessageStop,
+    Ping,
+    Error {
+        error: AnthropicErrorObject,
+    },
+}
+
+impl AnthropicStreamEvent {
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            AnthropicStreamEvent::MessageStart { .. } => "message_start",
+            AnthropicStreamEvent::ContentBlockStart { .. } => "content_block_start",
+            AnthropicStreamEvent::ContentBlockDelta { .. } => "content_block_delta",
+            AnthropicStreamEvent::ContentBlockStop { .. } => "content_block_stop",
+            AnthropicStreamEvent::MessageDelta { .. } => "message_delta",
+            AnthropicStreamEvent::MessageStop => "message_stop",
+            AnthropicStreamEvent::Ping => "ping",
+            AnthropicStreamEvent::Error { .. } => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicBlockDelta {
+    TextDelta { text: String },
+    ThinkingDelta { thinking: String },
+    InputJsonDelta { partial_json: String },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicMessageDelta {
+    pub stop_reason: Option<String>,
+    pub stop_sequence: Option<String>,
+}
+
+/// Anthropic-compatible error response schema.
+///
+/// Standard envelope:
+/// `{ "type": "error", "error": { "type": "invalid_request_error", "message": "..." } }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicErrorBody {
+    /// Always `error`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub error: AnthropicErrorObject,
+}
+
+impl AnthropicErrorBody {
+    pub fn new(error: AnthropicErrorObject) -> Self {
+        Self {
+            kind: "error".to_string(),
+            error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicErrorObject {
+    /// `invalid_request_error | authentication_error | permission_error | not_found_error |
+    /// request_too_large | rate_limit_error | api_error | overloaded_error`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub message: String,
+}
+
+impl AnthropicErrorObject {
+    /// Error type Anthropic uses for an HTTP status code.
+    pub fn for_status(status: u16, message: impl Into<String>) -> Self {
+        let kind = match status {
+            400 => "invalid_request_error",
+            401 => "authentication_error",
+            403 => "permission_error",
+            404 => "not_found_error",
+            413 => "request_too_large",
+            429 => "rate_limit_error",
+            503 | 529 => "overloaded_error",
+            _ => "api_error",
+        };
+        Self {
+            kind: kind.to_string(),
+            message: message.into(),
+        }
+    }
+}
@@ -0,0 +1,13 @@
+mod messages_request;
+mod messages_response;
+mod openai_chat;
+
+pub use messages_request::{
+    AnthropicContent, AnthropicContentBlock, AnthropicImageSource, AnthropicMessage,
+    AnthropicMessagesRequest, AnthropicThinking, AnthropicTool, AnthropicToolChoice,
+};
+pub use messages_response::{
+    AnthropicBlockDelta, AnthropicErrorBody, AnthropicErrorObject, AnthropicMessageDelta,
+    AnthropicMessageResponse, AnthropicResponseBlock, AnthropicStreamEvent, AnthropicUsage,
+};
+pub use openai_chat::AnthropicChatStream;
@@ -0,0 +1,627 @@
+//! Translation between Anthropic Messages and OpenAI Chat Completions.
+//!
+//! Chat Completions is the pivot format: both upstream pools already speak it through their
+//! own translators, so Messages only needs to map to and from chat.
+
+use crate::anthropic::{
+    AnthropicBlockDelta, AnthropicContent, AnthropicContentBlock, AnthropicImageSource,
+    AnthropicMessageDelta, AnthropicMessageResponse, AnthropicMessagesRequest,
+    AnthropicResponseBlock, AnthropicStreamEvent, AnthropicThinking, AnthropicToolChoice,
+    AnthropicUsage,
+};
+use crate::openai::{
+    ChatContent, ChatContentPart, ChatFunctionCall, ChatFunctionDefinition, ChatImageUrl,
+    ChatMessage, ChatStreamOptions, ChatTool, ChatToolCall, ChatUsage, OpenaiChatCompletion,
+    OpenaiChatCompletionChunk, OpenaiChatRequestBody,
+};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+impl From<AnthropicMessagesRequest> for OpenaiChatRequestBody {
+    fn from(body: AnthropicMessagesRequest) -> Self {
+        let mut messages = Vec::new();
+        if let Some(system) = &body.system {
+            let text = system.text();
+            if !text.is_empty() {
+                messages.push(chat_message("system", Some(ChatContent::Text(text))));
+            }
+        }
+        for message in body.messages {
+            match message.role.as_str() {
+                "user" => push_user_messages(&mut messages, message.content),
+                "assistant" => messages.push(assistant_message(message.content)),
+                _ => {}
+            }
+        }
+
+        let tools: Vec<ChatTool> = body
+            .tools
+            .into_iter()
+            .flatten()
+            .filter(|tool| tool.is_client_tool())
+            .map(|tool| ChatTool {
+                kind: "function".to_string(),
+                function: ChatFunctionDefinition {
+                    name: tool.name,
+                    description: tool.description,
+                    parameters: tool.input_schema,
+                    strict: None,
+                },
+            })
+            .collect();
+
+        let tool_choice = body.tool_choice.map(|choice| match choice {
+            AnthropicToolChoice::Auto => json!("auto"),
+            AnthropicToolChoice::Any => json!("required"),
+            AnthropicToolChoice::None => json!("none"),
+            AnthropicToolChoice::Tool { name } => {
+                json!({ "type": "function", "function": { "name": name } })
+            }
+        });
+
+        let reasoning_effort = match body.thinking {
+            Some(AnthropicThinking::Enabled { budget_tokens }) => {
+                Some(effort_for_budget(budget_tokens).to_string())
+            }
+            _ => None,
+        };
+
+        OpenaiChatRequestBody {
+            model: body.model,
+            messages,
+            stream: body.stream,
+            // Anthropic always reports usage in `message_delta`, so always ask for it.
+            stream_options: body.stream.then_some(ChatStreamOptions {
+                include_usage: true,
+            }),
+            temperature: body.temperature,
+            top_p: body.top_p,
+            max_tokens: None,
+            max_completion_tokens: body.max_tokens,
+            n: None,
+            stop: body.stop_sequences,
+            presence_penalty: None,
+            frequency_penalty: None,
+            seed: None,
+            tools: (!tools.is_empty()).then_some(tools),
+            tool_choice,
+            parallel_tool_calls: None,
+            response_format: None,
+            reasoning_effort,
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+/// Bucket an extended-thinking token budget into an OpenAI reasoning effort.
+fn effort_for_budget(budget_tokens: u32) -> &'static str {
+    match budget_tokens {
+        0..=1024 => "low",
+        1025..=8192 => "medium",
+        _ => "high",
+    }
+}
+
+fn chat_message(role: &str, content: Option<ChatContent>) -> ChatMessage {
+    ChatMessage {
+        role: role.to_string(),
+        content,
+        name: None,
+        tool_calls: None,
+        tool_call_id: None,
+        extra: BTreeMap::new(),
+    }
+}
+
+/// A user turn may carry `tool_result` blocks next to regular content. Chat wants each result
+/// as its own `tool` message directly after the assistant call, so results go first.
+fn push_user_messages(messages: &mut Vec<ChatMessage>, content: AnthropicContent) {
+    let blocks = match content {
+        AnthropicContent::Text(text) => {
+            messages.push(chat_message("user", Some(ChatContent::Text(text))));
+            return;
+        }
+        AnthropicContent::Blocks(blocks) => blocks,
+    };
+
+    let mut parts = Vec::new();
+    for block in blocks {
+        match block {
+            AnthropicContentBlock::Text { text } => parts.push(ChatContentPart::Text { text }),
+            AnthropicContentBlock::Image { source } => parts.push(ChatContentPart::ImageUrl {
+                image_url: image_url(source),
+            }),
+            AnthropicContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            } => {
+                let mut text = content.map(|content| content.text()).unwrap_or_default();
+                if is_error == Some(true) {
+                    text = format!("Error: {text}");
+                }
+                let mut message = chat_message("tool", Some(ChatContent::Text(text)));
+                message.tool_call_id = Some(tool_use_id);
+                messages.push(message);
+            }
+            _ => {}
+        }
+    }
+    if !parts.is_empty() {
+        messages.push(chat_message("user", Some(ChatContent::Parts(parts))));
+    }
+}
+
+fn assistant_message(content: AnthropicContent) -> ChatMessage {
+    let blocks = match content {
+        AnthropicContent::Text(text) => {
+            return chat_message("assistant", Some(ChatContent::Text(text)));
+        }
+        AnthropicContent::Blocks(blocks) => blocks,
+    };
+
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    for block in blocks {
+        match block {
+            AnthropicContentBlock::Text { text: part } => text.push_str(&part),
+            AnthropicContentBlock::ToolUse { id, name, input } => tool_calls.push(ChatToolCall {
+                id,
+                kind: "function".to_string(),
+                function: ChatFunctionCall {
+                    name,
+                    arguments: input.to_string(),
+                },
+            }),
+            _ => {}
+        }
+    }
+
+    let mut message = chat_message(
+        "assistant",
+        (!text.is_empty()).then_some(ChatContent::Text(text)),
+    );
+    message.tool_calls = (!tool_calls.is_empty()).then_some(tool_calls);
+    message
+}
+
+fn image_url(source: AnthropicImageSource) -> ChatImageUrl {
+    let url = match source {
+        AnthropicImageSource::Base64 { media_type, data } => {
+            format!("data:{media_type};base64,{data}")
+        }
+        AnthropicImageSource::Url { url } => url,
+    };
+    ChatImageUrl { url, detail: None }
+}
+
+fn map_finish_reason(reason: &str) -> String {
+    match reason {
+        "length" => "max_tokens",
+        "tool_calls" => "tool_use",
+        "content_filter" => "refusal",
+        _ => "end_turn",
+    }
+    .to_string()
+}
+
+fn message_id(chat_id: &str) -> String {
+    format!(
+        "msg_{}",
+        chat_id.strip_prefix("chatcmpl-").unwrap_or(chat_id)
+    )
+}
+
+fn usage_from_chat(usage: Option<&ChatUsage>) -> AnthropicUsage {
+    usage
+        .map(|usage| AnthropicUsage {
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+        })
+        .unwrap_or_default()
+}
+
+impl From<OpenaiChatCompletion> for AnthropicMessageResponse {
+    fn from(completion: OpenaiChatCompletion) -> Self {
+        let usage = usage_from_chat(completion.usage.as_ref());
+        let choice = completion.choices.into_iter().next();
+
+        let mut content = Vec::new();
+        let mut stop_reason = None;
+        if let Some(choice) = choice {
+            let message = choice.message;
+            if let Some(thinking) = message.reasoning_content.filter(|text| !text.is_empty()) {
+                content.push(AnthropicResponseBlock::Thinking {
+                    thinking,
+                    signature: String::new(),
+                });
+            }
+            if let Some(text) = message.content.filter(|text| !text.is_empty()) {
+                content.push(AnthropicResponseBlock::Text { text });
+            }
+            for call in message.tool_calls.into_iter().flatten() {
+                content.push(AnthropicResponseBlock::ToolUse {
+                    id: call.id,
+                    name: call.function.name,
+                    input: parse_arguments(&call.function.arguments),
+                });
+            }
+            stop_reason = choice.finish_reason.as_deref().map(map_finish_reason);
+        }
+
+        AnthropicMessageResponse {
+            id: message_id(&completion.id),
+            kind: "message".to_string(),
+            role: "assistant".to_string(),
+            model: completion.model,
+            content,
+            stop_reason: Some(stop_reason.unwrap_or_else(|| "end_turn".to_string())),
+            stop_sequence: None,
+            usage,
+        }
+    }
+}
+
+/// Tool arguments are a JSON string in chat; keep unparsable ones as a string value.
+fn parse_arguments(arguments: &str) -> Value {
+    if arguments.trim().is_empty() {
+        return json!({});
+    }
+    serde_json::from_str(arguments).unwrap_or_else(|_| Value::String(arguments.to_string()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OpenBlock {
+    Text,
+    Thinking,
+    /// Chat `tool_calls[].index` the block belongs to.
+    Tool(u32),
+}
+
+/// Stateful translator for `chat.completion.chunk`s into Messages stream events.
+///
+/// Only choice `0` is read. Text, reasoning and each tool call get their own content block;
+/// a block is closed as soon as a delta for a different block arrives.
+pub struct AnthropicChatStream {
+    model: String,
+    started: bool,
+    next_index: u32,
+    open: Option<(u32, OpenBlock)>,
+    stop_reason: Option<String>,
+    usage: AnthropicUsage,
+}
+
+impl AnthropicChatStream {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            started: false,
+            next_index: 0,
+            open: None,
+            stop_reason: None,
+            usage: AnthropicUsage::default(),
+        }
+    }
+
+    /// Translate one chunk into zero or more events; the first call emits `message_start`.
+    pub fn push(&mut self, chunk: OpenaiChatCompletionChunk) -> Vec<AnthropicStreamEvent> {
+        let mut events = Vec::new();
+        self.start(&chunk.id, &mut events);
+        if chunk.usage.is_some() {
+            self.usage = usage_from_chat(chunk.usage.as_ref());
+        }
+
+        let Some(choice) = chunk.choices.into_iter().find(|choice| choice.index == 0) else {
+            return events;
+        };
+        let delta = choice.delta;
+
+        if let Some(thinking) = delta.reasoning_content.filter(|text| !text.is_empty()) {
+            let index = self.open_block(OpenBlock::Thinking, &mut events, || {
+                AnthropicResponseBlock::Thinking {
+                    thinking: String::new(),
+                    signature: String::new(),
+                }
+            });
+            events.push(AnthropicStreamEvent::ContentBlockDelta {
+                index,
+                delta: AnthropicBlockDelta::ThinkingDelta { thinking },
+            });
+        }
+
+        if let Some(text) = delta.content.filter(|text| !text.is_empty()) {
+            let index = self.open_block(OpenBlock::Text, &mut events, || {
+                AnthropicResponseBlock::Text {
+                    text: String::new(),
+                }
+            });
+            events.push(AnthropicStreamEvent::ContentBlockDelta {
+                index,
+                delta: AnthropicBlockDelta::TextDelta { text },
+            });
+        }
+
+        for call in delta.tool_calls.into_iter().flatten() {
+            let block = OpenBlock::Tool(call.index);
+            // A new id always starts a new block, even if the chat index repeats.
+            if call.id.is_some() && self.open.is_some_and(|(_, open)| open == block) {
+                self.close_block(&mut events);
+            }
+            let id = call.id.unwrap_or_default();
+            let name = call.function.name.unwrap_or_default();
+            let index = self.open_block(block, &mut events, || AnthropicResponseBlock::ToolUse {
+                id,
+                name,
+                input: json!({}),
+            });
+            if let Some(partial_json) = call.function.arguments.filter(|args| !args.is_empty()) {
+                events.push(AnthropicStreamEvent::ContentBlockDelta {
+                    index,
+                    delta: AnthropicBlockDelta::InputJsonDelta { partial_json },
+                });
+            }
+        }
+
+        if let Some(reason) = choice.finish_reason {
+            self.stop_reason = Some(map_finish_reason(&reason));
+        }
+        events
+    }
+
+    /// Close the open block and emit `message_delta` plus `message_stop`.
+    pub fn finish(mut self) -> Vec<AnthropicStreamEvent> {
+        let mut events = Vec::new();
+        self.start("", &mut events);
+        self.close_block(&mut events);
+        events.push(AnthropicStreamEvent::MessageDelta {
+            delta: AnthropicMessageDelta {
+                stop_reason: Some(
+                    self.stop_reason
+                        .take()
+                        .unwrap_or_else(|| "end_turn".to_string()),
+                ),
+                stop_sequence: None,
+            },
+            usage: self.usage,
+        });
+        events.push(AnthropicStreamEvent::MessageStop);
+        events
+    }
+
+    fn start(&mut self, chat_id: &str, events: &mut Vec<AnthropicStreamEvent>) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+        let id = if chat_id.is_empty() {
+            format!("msg_{}", chrono::Utc::now().timestamp_millis())
+        } else {
+            message_id(chat_id)
+        };
+        events.push(AnthropicStreamEvent::MessageStart {
+            message: AnthropicMessageResponse {
+                id,
+                kind: "message".to_string(),
+                role: "assistant".to_string(),
+                model: self.model.clone(),
+                content: Vec::new(),
+                stop_reason: None,
+                stop_sequence: None,
+                usage: AnthropicUsage::default(),
+            },
+        });
+    }
+
+    /// Index of the open block of kind `block`, starting a new one if needed.
+    fn open_block(
+        &mut self,
+        block: OpenBlock,
+        events: &mut Vec<AnthropicStreamEvent>,
+        content_block: impl FnOnce() -> AnthropicResponseBlock,
+    ) -> u32 {
+        if let Some((index, open)) = self.open
+            && open == block
+        {
+            return index;
+        }
+        self.close_block(events);
+        let index = self.next_index;
+        self.next_index += 1;
+        self.open = Some((index, block));
+        events.push(AnthropicStreamEvent::ContentBlockStart {
+            index,
+            content_block: content_block(),
+        });
+        index
+    }
+
+    fn close_block(&mut self, events: &mut Vec<AnthropicStreamEvent>) {
+        if let Some((index, _)) = self.open.take() {
+            events.push(AnthropicStreamEvent::ContentBlockStop { index });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_maps_system_tool_results_and_thinking() {
+        let body: AnthropicMessagesRequest = serde_json::from_value(json!({
+            "model": "gemini-2.5-pro",
+            "max_tokens": 1024,
+            "stream": true,
+            "system": [{ "type": "text", "text": "be brief", "cache_control": { "type": "ephemeral" } }],
+            "thinking": { "type": "enabled", "budget_tokens": 4096 },
+            "tools": [
+                { "name": "get_weather", "input_schema": { "type": "object" } },
+                { "type": "web_search_20250305", "name": "web_search" }
+            ],
+            "tool_choice": { "type": "tool", "name": "get_weather" },
+            "messages": [
+                { "role": "user", "content": "weather in Paris?" },
+                { "role": "assistant", "content": [
+                    { "type": "thinking", "thinking": "hmm", "signature": "sig" },
+                    { "type": "text", "text": "checking" },
+                    { "type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": { "city": "Paris" } }
+                ]},
+                { "role": "user", "content": [
+                    { "type": "tool_result", "tool_use_id": "toolu_1", "content": "sunny" },
+                    { "type": "text", "text": "thanks" }
+                ]}
+            ]
+        }))
+        .expect("messages request parses");
+
+        let chat = OpenaiChatRequestBody::from(body);
+        let roles: Vec<&str> = chat.messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["system", "user", "assistant", "tool", "user"]);
+        assert_eq!(
+            chat.messages[0].content,
+            Some(ChatContent::Text("be brief".into()))
+        );
+
+        let call = &chat.messages[2].tool_calls.as_ref().expect("tool calls")[0];
+        assert_eq!(call.id, "toolu_1");
+        assert_eq!(call.function.arguments, r#"{"city":"Paris"}"#);
+        assert_eq!(chat.messages[3].tool_call_id.as_deref(), Some("toolu_1"));
+
+        assert_eq!(chat.tools.as_ref().map(Vec::len), Some(1));
+        assert_eq!(
+            chat.tool_choice,
+            Some(json!({ "type": "function", "function": { "name": "get_weather" } }))
+        );
+        assert_eq!(chat.reasoning_effort.as_deref(), Some("medium"));
+        assert_eq!(chat.max_output_tokens(), Some(1024));
+        assert!(chat.include_usage());
+    }
+
+    #[test]
+    fn completion_maps_blocks_and_stop_reason() {
+        let completion: OpenaiChatCompletion = serde_json::from_value(json!({
+            "id": "chatcmpl-abc",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "gpt-5",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": "let me look",
+                    "reasoning_content": "thinking",
+                    "tool_calls": [{ "id": "call_1", "type": "function",
+                        "function": { "name": "lookup", "arguments": "{\"q\":1}" } }]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "usage": { "prompt_tokens": 5, "completion_tokens": 7, "total_tokens": 12 }
+        }))
+        .expect("completion parses");
+
+        let message = AnthropicMessageResponse::from(completion);
+        assert_eq!(message.id, "msg_abc");
+        assert_eq!(message.stop_reason.as_deref(), Some("tool_use"));
+        assert_eq!(
+            message.usage,
+            AnthropicUsage {
+                input_tokens: 5,
+                output_tokens: 7
+            }
+        );
+        assert_eq!(
+            message.content,
+            vec![
+                AnthropicResponseBlock::Thinking {
+                    thinking: "thinking".into(),
+                    signature: String::new()
+                },
+                AnthropicResponseBlock::Text {
+                    text: "let me look".into()
+                },
+                AnthropicResponseBlock::ToolUse {
+                    id: "call_1".into(),
+                    name: "lookup".into(),
+                    input: json!({ "q": 1 })
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn stream_opens_and_closes_blocks_in_order() {
+        let chunk = |delta: Value, finish: Value, usage: Value| -> OpenaiChatCompletionChunk {
+            serde_json::from_value(json!({
+                "id": "chatcmpl-1", "object": "chat.completion.chunk", "created": 1, "model": "m",
+                "choices": if delta.is_null() { json!([]) } else {
+                    json!([{ "index": 0, "delta": delta, "finish_reason": finish }])
+                },
+                "usage": usage
+            }))
+            .expect("chunk parses")
+        };
+
+        let mut stream = AnthropicChatStream::new("claude-alias");
+        let mut events = stream.push(chunk(
+            json!({ "role": "assistant", "content": "Hi" }),
+            Value::Null,
+            Value::Null,
+        ));
+        events.extend(stream.push(chunk(
+            json!({ "tool_calls": [{ "index": 0, "id": "call_1", "type": "function",
+                "function": { "name": "f", "arguments": "{\"a\":" } }] }),
+            Value::Null,
+            Value::Null,
+        )));
+        events.extend(stream.push(chunk(
+            json!({ "tool_calls": [{ "index": 0, "function": { "arguments": "1}" } }] }),
+            json!("tool_calls"),
+            Value::Null,
+        )));
+        events.extend(stream.push(chunk(
+            Value::Null,
+            Value::Null,
+            json!({ "prompt_tokens": 3, "completion_tokens": 4, "total_tokens": 7 }),
+        )));
+        events.extend(stream.finish());
+
+        let names: Vec<&str> = events
+            .iter()
+            .map(AnthropicStreamEvent::event_name)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "message_start",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_stop",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "message_delta",
+                "message_stop",
+            ]
+        );
+        assert_eq!(
+            events[8],
+            AnthropicStreamEvent::MessageDelta {
+                delta: AnthropicMessageDelta {
+                    stop_reason: Some("tool_use".into()),
+                    stop_sequence: None
+                },
+                usage: AnthropicUsage {
+                    input_tokens: 3,
+                    output_tokens: 4
+                },
+            }
+        );
+        let json = serde_json::to_value(&events[4]).expect("event serializes");
+        assert_eq!(
+            json,
+            json!({ "type": "content_block_start", "index": 1,
+                "content_block": { "type": "tool_use", "id": "call_1", "name": "f", "input": {} } })
+        );
+    }
+}
@@ -1,7 +1,9 @@
 mod model_list;
+mod openai_chat;
 mod v1beta_response;
 
 pub use model_list::{GeminiModel, GeminiModelList};
+pub use openai_chat::{GeminiChatStream, chat_completion_from_gemini, gemini_request_from_chat};
 pub(crate) use v1beta_response::Candidate;
 pub use v1beta_response::GeminiResponseBody;
 
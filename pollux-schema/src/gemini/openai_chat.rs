@@ -0,0 +1,716 @@
+//! Translation between OpenAI Chat Completions and Gemini v1beta `generateContent`.
+
+use crate::gemini::{Candidate, GeminiRequestBody, GeminiResponseBody};
+use crate::openai::{
+    ChatChoice, ChatChunkChoice, ChatCompletionTokensDetails, ChatContent, ChatContentPart,
+    ChatDelta, ChatFunctionCall, ChatFunctionCallDelta, ChatMessage, ChatResponseFormat,
+    ChatResponseMessage, ChatToolCall, ChatToolCallDelta, ChatUsage, OpenaiChatCompletion,
+    OpenaiChatCompletionChunk, OpenaiChatRequestBody,
+};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, HashMap};
+
+/// Gemini 3 rejects replayed `functionCall` parts without a thought signature. Chat
+/// history carries none, so we send the documented placeholder that skips validation.
+const SKIP_THOUGHT_SIGNATURE: &str = "skip_thought_signature_validator";
+
+/// Build a Gemini `generateContent` body from an OpenAI chat request.
+///
+/// System and developer messages become `systemInstruction`; tool results are sent back as
+/// `functionResponse` parts, named after the assistant tool call they answer.
+pub fn gemini_request_from_chat(body: &OpenaiChatRequestBody) -> GeminiRequestBody {
+    let mut system_parts = Vec::new();
+    let mut contents: Vec<(&'static str, Vec<Value>)> = Vec::new();
+    // Filled as we walk the history, so a reused id resolves to its latest call.
+    let mut tool_names: HashMap<&str, &str> = HashMap::new();
+
+    for message in &body.messages {
+        let (role, parts) = match message.role.as_str() {
+            "system" | "developer" => {
+                if let Some(content) = &message.content {
+                    let text = content.text();
+                    if !text.is_empty() {
+                        system_parts.push(json!({ "text": text }));
+                    }
+                }
+                continue;
+            }
+            "user" => ("user", user_parts(message)),
+            "assistant" => {
+                for call in message.tool_calls.iter().flatten() {
+                    tool_names.insert(call.id.as_str(), call.function.name.as_str());
+                }
+                ("model", assistant_parts(message))
+            }
+            "tool" => {
+                let name = message
+                    .tool_call_id
+                    .as_deref()
+                    .and_then(|id| tool_names.get(id).copied())
+                    .or(message.name.as_deref())
+                    .unwrap_or_default();
+                ("user", vec![function_response_part(name, message)])
+            }
+            _ => continue,
+        };
+
+        if parts.is_empty() {
+            continue;
+        }
+        // Gemini expects alternating turns; fold consecutive same-role messages together.
+        match contents.last_mut() {
+            Some((last_role, last_parts)) if *last_role == role => last_parts.extend(parts),
+            _ => contents.push((role, parts)),
+        }
+    }
+
+    let mut request = Map::new();
+    request.insert(
+        "contents".to_string(),
+        Value::Array(
+            contents
+                .into_iter()
+                .map(|(role, parts)| json!({ "role": role, "parts": parts }))
+                .collect(),
+        ),
+    );
+    if !system_parts.is_empty() {
+        request.insert(
+            "systemInstruction".to_string(),
+            json!({ "parts": system_parts }),
+        );
+    }
+    if let Some(tools) = body.tools.as_ref().filter(|tools| !tools.is_empty()) {
+        let declarations: Vec<Value> = tools
+            .iter()
+            .map(|tool| {
+                let mut declaration = Map::new();
+                declaration.insert("name".to_string(), json!(tool.function.name));
+                if let Some(description) = &tool.function.description {
+                    declaration.insert("description".to_string(), json!(description));
+                }
+                if let Some(parameters) = &tool.function.parameters {
+                    declaration.insert("parametersJsonSchema".to_string(), parameters.clone());
+                }
+                Value::Object(declaration)
+            })
+            .collect();
+        request.insert(
+            "tools".to_string(),
+            json!([{ "functionDeclarations": declarations }]),
+        );
+    }
+    if let Some(config) = body.tool_choice.as_ref().and_then(function_calling_config) {
+        request.insert(
+            "toolConfig".to_string(),
+            json!({ "functionCallingConfig": config }),
+        );
+    }
+    let generation_config = generation_config(body);
+    if !generation_config.is_empty() {
+        request.insert(
+            "generationConfig".to_string(),
+            Value::Object(generation_config),
+        );
+    }
+
+    Value::Object(request)
+}
+
+fn user_parts(message: &ChatMessage) -> Vec<Value> {
+    match &message.content {
+        None => Vec::new(),
+        Some(ChatContent::Text(text)) => vec![json!({ "text": text })],
+        Some(ChatContent::Parts(parts)) => parts
+            .iter()
+            .filter_map(|part| match part {
+                ChatContentPart::Text { text } => Some(json!({ "text": text })),
+                ChatContentPart::ImageUrl { image_url } => Some(image_part(&image_url.url)),
+                ChatContentPart::Unsupported => None,
+            })
+            .collect(),
+    }
+}
+
+fn image_part(url: &str) -> Value {
+    if let Some((mime_type, data)) = url
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+    {
+        return json!({ "inlineData": { "mimeType": mime_type, "data": data } });
+    }
+    json!({ "fileData": { "mimeType": guess_image_mime(url), "fileUri": url } })
+}
+
+fn guess_image_mime(url: &str) -> &'static str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = path.rsplit('.').next().unwrap_or_default();
+    match extension.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "heif" => "image/heif",
+        _ => "image/jpeg",
+    }
+}
+
+fn assistant_parts(message: &ChatMessage) -> Vec<Value> {
+    let mut parts = Vec::new();
+    if let Some(content) = &message.content {
+        let text = content.text();
+        if !text.is_empty() {
+            parts.push(json!({ "text": text }));
+        }
+    }
+    for call in message.tool_calls.iter().flatten() {
+        let args = serde_json::from_str::<Value>(&call.function.arguments)
+            .ok()
+            .filter(Value::is_object)
+            .unwrap_or_else(|| json!({}));
+        parts.push(json!({
+            "functionCall": { "name": call.function.name, "args": args },
+            "thoughtSignature": SKIP_THOUGHT_SIGNATURE,
+        }));
+    }
+    parts
+}
+
+fn function_response_part(name: &str, message: &ChatMessage) -> Value {
+    let text = message
+        .content
+        .as_ref()
+        .map(ChatContent::text)
+        .unwrap_or_default();
+    // `functionResponse.response` must be an object; wrap anything else.
+    let response = match serde_json::from_str::<Value>(&text) {
+        Ok(value @ Value::Object(_)) => value,
+        _ => json!({ "content": text }),
+    };
+    json!({ "functionResponse": { "name": name, "response": response } })
+}
+
+fn function_calling_config(tool_choice: &Value) -> Option<Value> {
+    match tool_choice {
+        Value::String(mode) => match mode.as_str() {
+            "none" => Some(json!({ "mode": "NONE" })),
+            "auto" => Some(json!({ "mode": "AUTO" })),
+            "required" => Some(json!({ "mode": "ANY" })),
+            _ => None,
+        },
+        Value::Object(choice) => {
+            let name = choice.get("function")?.get("name")?.as_str()?;
+            Some(json!({ "mode": "ANY", "allowedFunctionNames": [name] }))
+        }
+        _ => None,
+    }
+}
+
+fn generation_config(body: &OpenaiChatRequestBody) -> Map<String, Value> {
+    let mut config = Map::new();
+    if let Some(temperature) = body.temperature {
+        config.insert("temperature".to_string(), json!(temperature));
+    }
+    if let Some(top_p) = body.top_p {
+        config.insert("topP".to_string(), json!(top_p));
+    }
+    if let Some(max_tokens) = body.max_output_tokens() {
+        config.insert("maxOutputTokens".to_string(), json!(max_tokens));
+    }
+    if let Some(n) = body.n.filter(|n| *n > 1) {
+        config.insert("candidateCount".to_string(), json!(n));
+    }
+    if let Some(stop) = body.stop.as_ref().filter(|stop| !stop.is_empty()) {
+        config.insert("stopSequences".to_string(), json!(stop));
+    }
+    if let Some(penalty) = body.presence_penalty {
+        config.insert("presencePenalty".to_string(), json!(penalty));
+    }
+    if let Some(penalty) = body.frequency_penalty {
+        config.insert("frequencyPenalty".to_string(), json!(penalty));
+    }
+    if let Some(seed) = body.seed {
+        config.insert("seed".to_string(), json!(seed));
+    }
+    match &body.response_format {
+        Some(ChatResponseFormat::JsonObject) => {
+            config.insert("responseMimeType".to_string(), json!("application/json"));
+        }
+        Some(ChatResponseFormat::JsonSchema { json_schema }) => {
+            config.insert("responseMimeType".to_string(), json!("application/json"));
+            if let Some(schema) = &json_schema.schema {
+                config.insert("responseJsonSchema".to_string(), schema.clone());
+            }
+        }
+        Some(ChatResponseFormat::Text) | None => {}
+    }
+    let budget = match body.reasoning_effort.as_deref() {
+        Some("minimal") => Some(128),
+        Some("low") => Some(1024),
+        Some("medium") => Some(8192),
+        Some("high") => Some(24576),
+        _ => None,
+    };
+    if let Some(budget) = budget {
+        config.insert(
+            "thinkingConfig".to_string(),
+            json!({ "thinkingBudget": budget, "includeThoughts": true }),
+        );
+    }
+    config
+}
+
+/// Translated content of one Gemini candidate.
+#[derive(Default)]
+struct CandidateOutput {
+    content: String,
+    reasoning: String,
+    /// `(upstream id, name, JSON arguments)`
+    calls: Vec<(Option<String>, String, String)>,
+    finish_reason: Option<String>,
+}
+
+fn read_candidate(candidate: &Candidate) -> CandidateOutput {
+    let mut out = CandidateOutput {
+        finish_reason: candidate
+            .extra
+            .get("finishReason")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        ..Default::default()
+    };
+    let parts = candidate
+        .content
+        .as_ref()
+        .and_then(|content| content.get("parts"))
+        .and_then(Value::as_array);
+    for part in parts.into_iter().flatten() {
+        if let Some(call) = part.get("functionCall") {
+            let name = call.get("name").and_then(Value::as_str).unwrap_or_default();
+            let args = call.get("args").cloned().unwrap_or_else(|| json!({}));
+            let id = call.get("id").and_then(Value::as_str).map(str::to_string);
+            out.calls.push((id, name.to_string(), args.to_string()));
+        } else if let Some(text) = part.get("text").and_then(Value::as_str) {
+            if part.get("thought").and_then(Value::as_bool) == Some(true) {
+                out.reasoning.push_str(text);
+            } else {
+                out.content.push_str(text);
+            }
+        }
+    }
+    out
+}
+
+fn candidate_index(candidate: &Candidate, position: usize) -> u32 {
+    candidate
+        .extra
+        .get("index")
+        .and_then(Value::as_u64)
+        .map_or(position as u32, |index| index as u32)
+}
+
+fn map_finish_reason(reason: &str, called_tools: bool) -> String {
+    match reason {
+        "STOP" if called_tools => "tool_calls",
+        "MAX_TOKENS" => "length",
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" | "IMAGE_SAFETY" => {
+            "content_filter"
+        }
+        _ if called_tools => "tool_calls",
+        _ => "stop",
+    }
+    .to_string()
+}
+
+fn prompt_blocked(resp: &GeminiResponseBody) -> bool {
+    resp.promptFeedback
+        .as_ref()
+        .and_then(|feedback| feedback.get("blockReason"))
+        .is_some()
+}
+
+fn usage_from_metadata(metadata: &Value) -> ChatUsage {
+    let count = |key: &str| metadata.get(key).and_then(Value::as_u64).unwrap_or(0);
+    let prompt_tokens = count("promptTokenCount");
+    let reasoning_tokens = count("thoughtsTokenCount");
+    let completion_tokens = count("candidatesTokenCount") + reasoning_tokens;
+    ChatUsage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: metadata
+            .get("totalTokenCount")
+            .and_then(Value::as_u64)
+            .unwrap_or(prompt_tokens + completion_tokens),
+        completion_tokens_details: (reasoning_tokens > 0)
+            .then_some(ChatCompletionTokensDetails { reasoning_tokens }),
+    }
+}
+
+fn completion_id(resp: &GeminiResponseBody, created: i64) -> String {
+    match &resp.responseId {
+        Some(id) if !id.is_empty() => format!("chatcmpl-{id}"),
+        _ => format!("chatcmpl-{created}"),
+    }
+}
+
+fn non_empty(text: String) -> Option<String> {
+    (!text.is_empty()).then_some(text)
+}
+
+/// Translate a complete Gemini response into a `chat.completion` object.
+pub fn chat_completion_from_gemini(resp: GeminiResponseBody, model: &str) -> OpenaiChatCompletion {
+    let created = chrono::Utc::now().timestamp();
+    let id = completion_id(&resp, created);
+
+    let mut choices: Vec<ChatChoice> = resp
+        .candidates
+        .iter()
+        .enumerate()
+        .map(|(position, candidate)| {
+            let index = candidate_index(candidate, position);
+            let out = read_candidate(candidate);
+            let called_tools = !out.calls.is_empty();
+            let tool_calls: Vec<ChatToolCall> = out
+                .calls
+                .into_iter()
+                .enumerate()
+                .map(|(n, (upstream_id, name, arguments))| ChatToolCall {
+                    id: upstream_id.unwrap_or_else(|| format!("call_{created}_{index}_{n}")),
+                    kind: "function".to_string(),
+                    function: ChatFunctionCall { name, arguments },
+                })
+                .collect();
+            ChatChoice {
+                index,
+                message: ChatResponseMessage {
+                    role: "assistant".to_string(),
+                    content: non_empty(out.content),
+                    reasoning_content: non_empty(out.reasoning),
+                    tool_calls: called_tools.then_some(tool_calls),
+                },
+                finish_reason: Some(map_finish_reason(
+                    out.finish_reason.as_deref().unwrap_or("STOP"),
+                    called_tools,
+                )),
+            }
+        })
+        .collect();
+
+    if choices.is_empty() && prompt_blocked(&resp) {
+        choices.push(ChatChoice {
+            index: 0,
+            message: ChatResponseMessage {
+                role: "assistant".to_string(),
+                ..Default::default()
+            },
+            finish_reason: Some("content_filter".to_string()),
+        });
+    }
+
+    OpenaiChatCompletion {
+        id,
+        object: "chat.completion".to_string(),
+        created,
+        model: model.to_string(),
+        choices,
+        usage: resp.usageMetadata.as_ref().map(usage_from_metadata),
+    }
+}
+
+/// Stateful translator for streamed Gemini responses into `chat.completion.chunk`s.
+///
+/// Gemini streams whole parts (function calls arrive complete), so each call becomes a single
+/// tool call delta carrying its id, name and full arguments.
+pub struct GeminiChatStream {
+    model: String,
+    include_usage: bool,
+    created: i64,
+    id: Option<String>,
+    usage: Option<ChatUsage>,
+    /// Per choice index: whether the role was sent, and how many tool calls were emitted.
+    choices: BTreeMap<u32, (bool, u32)>,
+}
+
+impl GeminiChatStream {
+    pub fn new(model: impl Into<String>, include_usage: bool) -> Self {
+        Self {
+            model: model.into(),
+            include_usage,
+            created: chrono::Utc::now().timestamp(),
+            id: None,
+            usage: None,
+            choices: BTreeMap::new(),
+        }
+    }
+
+    /// Translate one upstream event; `None` when it carries nothing for the client.
+    pub fn push(&mut self, resp: GeminiResponseBody) -> Option<OpenaiChatCompletionChunk> {
+        let created = self.created;
+        let id = self
+            .id
+            .get_or_insert_with(|| completion_id(&resp, created))
+            .clone();
+        if let Some(metadata) = &resp.usageMetadata {
+            self.usage = Some(usage_from_metadata(metadata));
+        }
+
+        let mut choices = Vec::new();
+        for (position, candidate) in resp.candidates.iter().enumerate() {
+            let index = candidate_index(candidate, position);
+            let out = read_candidate(candidate);
+            let (role_sent, emitted_calls) = self.choices.entry(index).or_default();
+
+            let tool_calls: Vec<ChatToolCallDelta> = out
+                .calls
+                .into_iter()
+                .map(|(upstream_id, name, arguments)| {
+                    let n = *emitted_calls;
+                    *emitted_calls += 1;
+                    ChatToolCallDelta {
+                        index: n,
+                        id: Some(
+                            upstream_id.unwrap_or_else(|| format!("call_{created}_{index}_{n}")),
+                        ),
+                        kind: Some("function".to_string()),
+                        function: ChatFunctionCallDelta {
+                            name: Some(name),
+                            arguments: Some(arguments),
+                        },
+                    }
+                })
+                .collect();
+
+            let mut delta = ChatDelta {
+                role: None,
+                content: non_empty(out.content),
+                reasoning_content: non_empty(out.reasoning),
+                tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+            };
+            let finish_reason = out
+                .finish_reason
+                .as_deref()
+                .map(|reason| map_finish_reason(reason, *emitted_calls > 0));
+            if delta == ChatDelta::default() && finish_reason.is_none() {
+                continue;
+            }
+            if !*role_sent {
+                *role_sent = true;
+                delta.role = Some("assistant".to_string());
+            }
+            choices.push(ChatChunkChoice {
+                index,
+                delta,
+                finish_reason,
+            });
+        }
+
+        if choices.is_empty() && prompt_blocked(&resp) {
+            choices.push(ChatChunkChoice {
+                index: 0,
+                delta: ChatDelta::default(),
+                finish_reason: Some("content_filter".to_string()),
+            });
+        }
+        if choices.is_empty() {
+            return None;
+        }
+
+        Some(OpenaiChatCompletionChunk {
+            id,
+            object: "chat.completion.chunk".to_string(),
+            created,
+            model: self.model.clone(),
+            choices,
+            usage: None,
+        })
+    }
+
+    /// Trailing usage-only chunk, emitted when the client asked for `include_usage`.
+    pub fn finish(self) -> Option<OpenaiChatCompletionChunk> {
+        if !self.include_usage {
+            return None;
+        }
+        Some(OpenaiChatCompletionChunk {
+            id: self
+                .id
+                .unwrap_or_else(|| format!("chatcmpl-{}", self.created)),
+            object: "chat.completion.chunk".to_string(),
+            created: self.created,
+            model: self.model,
+            choices: Vec::new(),
+            usage: Some(self.usage.unwrap_or_default()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chat_request(value: Value) -> OpenaiChatRequestBody {
+        serde_json::from_value(value).expect("chat request parses")
+    }
+
+    fn gemini_response(value: Value) -> GeminiResponseBody {
+        serde_json::from_value(value).expect("gemini response parses")
+    }
+
+    #[test]
+    fn request_maps_roles_system_instruction_and_tool_round_trip() {
+        let body = chat_request(json!({
+            "model": "gemini-2.5-pro",
+            "messages": [
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": "weather?"},
+                {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1", "type": "function",
+                    "function": {"name": "get_weather", "arguments": "{\"city\":\"Oslo\"}"}
+                }]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "sunny"},
+                {"role": "user", "content": "thanks"}
+            ]
+        }));
+
+        let request = gemini_request_from_chat(&body);
+        assert_eq!(
+            request["systemInstruction"],
+            json!({"parts": [{"text": "be brief"}]})
+        );
+        let contents = request["contents"].as_array().expect("contents");
+        assert_eq!(contents.len(), 3, "tool result merges with next user turn");
+        assert_eq!(contents[1]["role"], "model");
+        assert_eq!(
+            contents[1]["parts"][0]["functionCall"],
+            json!({"name": "get_weather", "args": {"city": "Oslo"}})
+        );
+        assert_eq!(contents[2]["role"], "user");
+        assert_eq!(
+            contents[2]["parts"][0]["functionResponse"],
+            json!({"name": "get_weather", "response": {"content": "sunny"}})
+        );
+        assert_eq!(contents[2]["parts"][1], json!({"text": "thanks"}));
+    }
+
+    #[test]
+    fn request_maps_tools_response_format_and_sampling() {
+        let body = chat_request(json!({
+            "model": "m",
+            "messages": [{"role": "user", "content": [
+                {"type": "text", "text": "describe"},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,AAAA"}}
+            ]}],
+            "tools": [{"type": "function", "function": {
+                "name": "lookup", "description": "d",
+                "parameters": {"type": "object", "properties": {}}
+            }}],
+            "tool_choice": {"type": "function", "function": {"name": "lookup"}},
+            "response_format": {"type": "json_schema", "json_schema": {
+                "name": "out", "schema": {"type": "object"}
+            }},
+            "temperature": 0.5,
+            "max_tokens": 10,
+            "max_completion_tokens": 20,
+            "stop": ["x"]
+        }));
+
+        let request = gemini_request_from_chat(&body);
+        assert_eq!(
+            request["contents"][0]["parts"][1],
+            json!({"inlineData": {"mimeType": "image/png", "data": "AAAA"}})
+        );
+        assert_eq!(
+            request["tools"][0]["functionDeclarations"][0],
+            json!({
+                "name": "lookup", "description": "d",
+                "parametersJsonSchema": {"type": "object", "properties": {}}
+            })
+        );
+        assert_eq!(
+            request["toolConfig"]["functionCallingConfig"],
+            json!({"mode": "ANY", "allowedFunctionNames": ["lookup"]})
+        );
+        let config = &request["generationConfig"];
+        assert_eq!(config["temperature"], json!(0.5));
+        assert_eq!(config["maxOutputTokens"], json!(20));
+        assert_eq!(config["stopSequences"], json!(["x"]));
+        assert_eq!(config["responseMimeType"], "application/json");
+        assert_eq!(config["responseJsonSchema"], json!({"type": "object"}));
+    }
+
+    #[test]
+    fn completion_maps_text_thoughts_calls_and_usage() {
+        let resp = gemini_response(json!({
+            "responseId": "abc",
+            "candidates": [{
+                "content": {"role": "model", "parts": [
+                    {"text": "thinking", "thought": true},
+                    {"text": "hello"},
+                    {"functionCall": {"name": "f", "args": {"a": 1}}}
+                ]},
+                "finishReason": "STOP",
+                "index": 0
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 3, "candidatesTokenCount": 4,
+                "thoughtsTokenCount": 2, "totalTokenCount": 9
+            }
+        }));
+
+        let completion = chat_completion_from_gemini(resp, "gemini-2.5-pro");
+        assert_eq!(completion.id, "chatcmpl-abc");
+        assert_eq!(completion.object, "chat.completion");
+        let choice = &completion.choices[0];
+        assert_eq!(choice.message.content.as_deref(), Some("hello"));
+        assert_eq!(
+            choice.message.reasoning_content.as_deref(),
+            Some("thinking")
+        );
+        let calls = choice.message.tool_calls.as_ref().expect("tool calls");
+        assert_eq!(calls[0].function.name, "f");
+        assert_eq!(calls[0].function.arguments, r#"{"a":1}"#);
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+        let usage = completion.usage.expect("usage");
+        assert_eq!(
+            (
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.total_tokens
+            ),
+            (3, 6, 9)
+        );
+    }
+
+    #[test]
+    fn stream_sends_role_once_and_trailing_usage() {
+        let mut stream = GeminiChatStream::new("m", true);
+        let first = stream
+            .push(gemini_response(json!({
+                "responseId": "r1",
+                "candidates": [{"content": {"parts": [{"text": "he"}]}}]
+            })))
+            .expect("first chunk");
+        assert_eq!(first.choices[0].delta.role.as_deref(), Some("assistant"));
+        assert_eq!(first.choices[0].delta.content.as_deref(), Some("he"));
+
+        let second = stream
+            .push(gemini_response(json!({
+                "responseId": "r1",
+                "candidates": [{"content": {"parts": [{"text": "llo"}]}, "finishReason": "MAX_TOKENS"}],
+                "usageMetadata": {"promptTokenCount": 1, "candidatesTokenCount": 2, "totalTokenCount": 3}
+            })))
+            .expect("second chunk");
+        assert_eq!(second.id, first.id);
+        assert_eq!(second.choices[0].delta.role, None);
+        assert_eq!(second.choices[0].finish_reason.as_deref(), Some("length"));
+
+        assert!(
+            stream
+                .push(gemini_response(json!({"candidates": []})))
+                .is_none()
+        );
+
+        let last = stream.finish().expect("usage chunk");
+        assert!(last.choices.is_empty());
+        assert_eq!(last.usage.expect("usage").total_tokens, 3);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod anthropic;
 pub mod codex;
 pub mod gemini;
 pub mod geminicli;
@@ -0,0 +1,549 @@
+//! OpenAI Chat Completions API schema.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// OpenAI Chat Completions request body for `POST /v1/chat/completions`.
+///
+/// Schema reference:
+/// https://platform.openai.com/docs/api-reference/chat/create
+///
+/// Only fields Pollux can translate for an upstream are typed; everything else lands in
+/// `extra` and is ignored by the translators.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenaiChatRequestBody {
+    /// OpenAI docs: `string`, required.
+    #[serde(default)]
+    pub model: String,
+
+    /// OpenAI docs: `array`, required.
+    #[serde(default)]
+    pub messages: Vec<ChatMessage>,
+
+    /// OpenAI docs: `boolean`, optional, default `false`.
+    #[serde(default)]
+    pub stream: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<ChatStreamOptions>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+
+    /// Deprecated by OpenAI in favour of `max_completion_tokens`, still widely sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+
+    /// OpenAI docs: `string | array`, optional.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_stop",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub stop: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<ChatTool>>,
+
+    /// OpenAI docs: `"none" | "auto" | "required" | {"type":"function","function":{"name":..}}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<Value>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ChatResponseFormat>,
+
+    /// OpenAI docs: `"minimal" | "low" | "medium" | "high"`, reasoning models only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<String>,
+
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl OpenaiChatRequestBody {
+    pub fn include_usage(&self) -> bool {
+        self.stream_options
+            .as_ref()
+            .is_some_and(|options| options.include_usage)
+    }
+
+    /// `max_completion_tokens` wins over the deprecated `max_tokens`.
+    pub fn max_output_tokens(&self) -> Option<u32> {
+        self.max_completion_tokens.or(self.max_tokens)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChatStreamOptions {
+    #[serde(default)]
+    pub include_usage: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// `system | developer | user | assistant | tool` (legacy `function` is not supported).
+    pub role: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<ChatContent>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Assistant messages only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ChatToolCall>>,
+
+    /// Tool messages only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ChatContent {
+    Text(String),
+    Parts(Vec<ChatContentPart>),
+}
+
+impl ChatContent {
+    /// Concatenated text of all text parts; non-text parts are skipped.
+    pub fn text(&self) -> String {
+        match self {
+            ChatContent::Text(text) => text.clone(),
+            ChatContent::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    ChatContentPart::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join(""),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatContentPart {
+    Text {
+        text: String,
+    },
+    ImageUrl {
+        image_url: ChatImageUrl,
+    },
+    /// Audio, files and future part types; translators drop these.
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatImageUrl {
+    /// `https://...` or `data:<mime>;base64,<data>`.
+    pub url: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatTool {
+    /// Always `function` today.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: ChatFunctionDefinition,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatFunctionDefinition {
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// JSON Schema object.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatToolCall {
+    pub id: String,
+
+    #[serde(rename = "type", default = "function_kind")]
+    pub kind: String,
+
+    pub function: ChatFunctionCall,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatFunctionCall {
+    pub name: String,
+
+    /// JSON-encoded arguments, as OpenAI sends them.
+    #[serde(default)]
+    pub arguments: String,
+}
+
+fn function_kind() -> String {
+    "function".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema { json_schema: ChatJsonSchema },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatJsonSchema {
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+fn deserialize_stop<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawStop {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(
+        Option::<RawStop>::deserialize(deserializer)?.map(|raw| match raw {
+            RawStop::One(stop) => vec![stop],
+            RawStop::Many(stops) => stops,
+        }),
+    )
+}
+
+/// Non-streaming `chat.completion` object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenaiChatCompletion {
+    pub id: String,
+    pub object: String,
+    pub created: i64,
+    pub model: String,
+    pub choices: Vec<ChatChoice>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatChoice {
+    pub index: u32,
+    pub message: ChatResponseMessage,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatResponseMessage {
+    pub role: String,
+    pub content: Option<String>,
+
+    /// Thought summaries; not part of the OpenAI schema but understood by most clients.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ChatToolCall>>,
+}
+
+/// Streaming `chat.completion.chunk` object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenaiChatCompletionChunk {
+    pub id: String,
+    pub object: String,
+    pub created: i64,
+    pub model: String,
+    pub choices: Vec<ChatChunkChoice>,
+
+    /// Only on the final chunk, and only when `stream_options.include_usage` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatChunkChoice {
+    pub index: u32,
+    pub delta: ChatDelta,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatDelta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ChatToolCallDelta>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatToolCallDelta {
+    pub index: u32,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+
+    pub function: ChatFunctionCallDelta,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatFunctionCallDelta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completion_tokens_details: Option<ChatCompletionTokensDetails>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatCompletionTokensDetails {
+    pub reasoning_tokens: u64,
+}
+
+impl OpenaiChatCompletion {
+    /// Fold a chunk sequence back into a single completion, as a non-streaming client
+    /// would have received it. `None` when there were no chunks at all.
+    pub fn from_chunks<I>(chunks: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = OpenaiChatCompletionChunk>,
+    {
+        let mut chunks = chunks.into_iter().peekable();
+        let first = chunks.peek()?;
+        let mut completion = OpenaiChatCompletion {
+            id: first.id.clone(),
+            object: "chat.completion".to_string(),
+            created: first.created,
+            model: first.model.clone(),
+            choices: Vec::new(),
+            usage: None,
+        };
+
+        for chunk in chunks {
+            if chunk.usage.is_some() {
+                completion.usage = chunk.usage;
+            }
+            for choice in chunk.choices {
+                let position = match completion
+                    .choices
+                    .iter()
+                    .position(|existing| existing.index == choice.index)
+                {
+                    Some(position) => position,
+                    None => {
+                        completion.choices.push(ChatChoice {
+                            index: choice.index,
+                            message: ChatResponseMessage {
+                                role: "assistant".to_string(),
+                                ..Default::default()
+                            },
+                            finish_reason: None,
+                        });
+                        completion.choices.len() - 1
+                    }
+                };
+                let target = &mut completion.choices[position];
+                if choice.finish_reason.is_some() {
+                    target.finish_reason = choice.finish_reason;
+                }
+
+                let delta = choice.delta;
+                let message = &mut target.message;
+                if let Some(content) = delta.content {
+                    message.content.get_or_insert_default().push_str(&content);
+                }
+                if let Some(reasoning) = delta.reasoning_content {
+                    message
+                        .reasoning_content
+                        .get_or_insert_default()
+                        .push_str(&reasoning);
+                }
+                for call in delta.tool_calls.into_iter().flatten() {
+                    let calls = message.tool_calls.get_or_insert_default();
+                    let index = call.index as usize;
+                    while calls.len() <= index {
+                        calls.push(ChatToolCall {
+                            id: String::new(),
+                            kind: function_kind(),
+                            function: ChatFunctionCall {
+                                name: String::new(),
+                                arguments: String::new(),
+                            },
+                        });
+                    }
+                    let target = &mut calls[index];
+                    if let Some(id) = call.id {
+                        target.id = id;
+                    }
+                    if let Some(name) = call.function.name {
+                        target.function.name = name;
+                    }
+                    if let Some(arguments) = call.function.arguments {
+                        target.function.arguments.push_str(&arguments);
+                    }
+                }
+            }
+        }
+
+        completion.choices.sort_by_key(|choice| choice.index);
+        Some(completion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn chat_request_normalizes_stop_and_content_forms() {
+        let body: OpenaiChatRequestBody = serde_json::from_value(json!({
+            "model": "m",
+            "stop": "END",
+            "messages": [
+                {"role": "user", "content": "hi"},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "look"},
+                    {"type": "image_url", "image_url": {"url": "https://x/y.png"}},
+                    {"type": "input_audio", "input_audio": {"data": "...", "format": "wav"}}
+                ]}
+            ]
+        }))
+        .expect("chat request parses");
+
+        assert_eq!(body.stop, Some(vec!["END".to_string()]));
+        assert_eq!(
+            body.messages[0].content,
+            Some(ChatContent::Text("hi".to_string()))
+        );
+        let Some(ChatContent::Parts(parts)) = &body.messages[1].content else {
+            panic!("expected content parts");
+        };
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[2], ChatContentPart::Unsupported);
+        assert_eq!(body.messages[1].content.as_ref().unwrap().text(), "look");
+    }
+
+    #[test]
+    fn chat_request_parses_response_format_variants() {
+        let format: ChatResponseFormat = serde_json::from_value(json!({
+            "type": "json_schema",
+            "json_schema": {"name": "out", "schema": {"type": "object"}, "strict": true}
+        }))
+        .expect("json_schema parses");
+        let ChatResponseFormat::JsonSchema { json_schema } = format else {
+            panic!("expected json_schema");
+        };
+        assert_eq!(json_schema.schema, Some(json!({"type": "object"})));
+
+        let format: ChatResponseFormat =
+            serde_json::from_value(json!({"type": "json_object"})).expect("json_object parses");
+        assert_eq!(format, ChatResponseFormat::JsonObject);
+    }
+
+    #[test]
+    fn completion_from_chunks_concatenates_deltas_and_tool_arguments() {
+        let chunk = |choices: Value, usage: Value| -> OpenaiChatCompletionChunk {
+            serde_json::from_value(json!({
+                "id": "c1", "object": "chat.completion.chunk", "created": 1, "model": "m",
+                "choices": choices, "usage": usage
+            }))
+            .expect("chunk parses")
+        };
+        let chunks = vec![
+            chunk(
+                json!([{"index": 0, "delta": {"role": "assistant", "content": "he"}, "finish_reason": null}]),
+                Value::Null,
+            ),
+            chunk(
+                json!([{"index": 0, "delta": {"content": "y", "tool_calls": [
+                    {"index": 0, "id": "call_1", "type": "function", "function": {"name": "f", "arguments": "{\"a\""}}
+                ]}, "finish_reason": null}]),
+                Value::Null,
+            ),
+            chunk(
+                json!([{"index": 0, "delta": {"tool_calls": [
+                    {"index": 0, "function": {"arguments": ":1}"}}
+                ]}, "finish_reason": "tool_calls"}]),
+                Value::Null,
+            ),
+            chunk(
+                json!([]),
+                json!({"prompt_tokens": 1, "completion_tokens": 2, "total_tokens": 3}),
+            ),
+        ];
+
+        let completion = OpenaiChatCompletion::from_chunks(chunks).expect("completion");
+        assert_eq!(completion.object, "chat.completion");
+        let choice = &completion.choices[0];
+        assert_eq!(choice.message.content.as_deref(), Some("hey"));
+        let calls = choice.message.tool_calls.as_ref().expect("tool calls");
+        assert_eq!(calls[0].id, "call_1");
+        assert_eq!(calls[0].function.arguments, r#"{"a":1}"#);
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(completion.usage.expect("usage").total_tokens, 3);
+    }
+}
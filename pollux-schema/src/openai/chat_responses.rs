@@ -0,0 +1,523 @@
+//! Translation between OpenAI Chat Completions and the Responses API.
+
+use crate::openai::{
+    ChatChunkChoice, ChatCompletionTokensDetails, ChatContent, ChatContentPart, ChatDelta,
+    ChatFunctionCallDelta, ChatMessage, ChatResponseFormat, ChatToolCallDelta, ChatUsage,
+    OpenaiChatCompletionChunk, OpenaiChatRequestBody, OpenaiInput, OpenaiInputContent,
+    OpenaiInputItem, OpenaiRequestBody, Reasoning,
+};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+
+impl From<OpenaiChatRequestBody> for OpenaiRequestBody {
+    /// Build a Responses API request from a chat request.
+    ///
+    /// Behavior:
+    /// - Messages become message input items; `system` messages keep their role so the Codex
+    ///   conversion folds them into `instructions`.
+    /// - Assistant `tool_calls` become `function_call` items and `tool` messages become
+    ///   `function_call_output` items, linked by `call_id`.
+    /// - `tools`, `tool_choice` and `response_format` are reshaped into their flat Responses
+    ///   forms; `reasoning_effort` becomes `reasoning.effort` with an automatic summary.
+    /// - Chat-only sampling fields without a Responses equivalent (`n`, `stop`, penalties,
+    ///   `seed`) are dropped.
+    fn from(body: OpenaiChatRequestBody) -> Self {
+        let input = body.messages.iter().flat_map(input_items).collect();
+
+        let mut extra = BTreeMap::new();
+        if let Some(tools) = body.tools.as_ref().filter(|tools| !tools.is_empty()) {
+            let tools = tools
+                .iter()
+                .map(|tool| {
+                    let mut out = json!({ "type": "function", "name": tool.function.name });
+                    if let Some(description) = &tool.function.description {
+                        out["description"] = json!(description);
+                    }
+                    if let Some(parameters) = &tool.function.parameters {
+                        out["parameters"] = parameters.clone();
+                    }
+                    if let Some(strict) = tool.function.strict {
+                        out["strict"] = json!(strict);
+                    }
+                    out
+                })
+                .collect();
+            extra.insert("tools".to_string(), Value::Array(tools));
+        }
+        if let Some(tool_choice) = &body.tool_choice {
+            let tool_choice = match tool_choice
+                .get("function")
+                .and_then(|function| function.get("name"))
+            {
+                Some(name) => json!({ "type": "function", "name": name }),
+                None => tool_choice.clone(),
+            };
+            extra.insert("tool_choice".to_string(), tool_choice);
+        }
+        let format = match &body.response_format {
+            Some(ChatResponseFormat::JsonObject) => Some(json!({ "type": "json_object" })),
+            Some(ChatResponseFormat::JsonSchema { json_schema }) => {
+                let mut format = json!({ "type": "json_schema", "name": json_schema.name });
+                if let Some(description) = &json_schema.description {
+                    format["description"] = json!(description);
+                }
+                if let Some(schema) = &json_schema.schema {
+                    format["schema"] = schema.clone();
+                }
+                if let Some(strict) = json_schema.strict {
+                    format["strict"] = json!(strict);
+                }
+                Some(format)
+            }
+            Some(ChatResponseFormat::Text) | None => None,
+        };
+        if let Some(format) = format {
+            extra.insert("text".to_string(), json!({ "format": format }));
+        }
+
+        Self {
+            include: None,
+            input: Some(OpenaiInput::Items(input)),
+            instructions: None,
+            max_output_tokens: body.max_output_tokens(),
+            parallel_tool_calls: body.parallel_tool_calls,
+            reasoning: body.reasoning_effort.map(|effort| Reasoning {
+                effort: Some(effort),
+                summary: Some("auto".to_string()),
+            }),
+            service_tier: None,
+            store: None,
+            stream: body.stream,
+            temperature: body.temperature,
+            top_p: body.top_p,
+            model: body.model,
+            extra,
+        }
+    }
+}
+
+fn input_items(message: &ChatMessage) -> Vec<OpenaiInputItem> {
+    let item = |role: Option<&str>, content: Option<Vec<Value>>, extra: Value| {
+        let Value::Object(extra) = extra else {
+            unreachable!("input item extras are built as objects");
+        };
+        OpenaiInputItem {
+            role: role.map(str::to_string),
+            content: content.map(OpenaiInputContent::Parts),
+            extra: extra.into_iter().collect(),
+        }
+    };
+
+    match message.role.as_str() {
+        "system" | "developer" | "user" => {
+            let parts = content_parts(message.content.as_ref(), "input_text");
+            vec![item(
+                Some(&message.role),
+                Some(parts),
+                json!({ "type": "message" }),
+            )]
+        }
+        "assistant" => {
+            let mut items = Vec::new();
+            let parts = content_parts(message.content.as_ref(), "output_text");
+            if !parts.is_empty() {
+                items.push(item(
+                    Some("assistant"),
+                    Some(parts),
+                    json!({ "type": "message" }),
+                ));
+            }
+            for call in message.tool_calls.iter().flatten() {
+                items.push(item(
+                    None,
+                    None,
+                    json!({
+                        "type": "function_call",
+                        "call_id": call.id,
+                        "name": call.function.name,
+                        "arguments": call.function.arguments,
+                    }),
+                ));
+            }
+            items
+        }
+        "tool" => {
+            let output = message
+                .content
+                .as_ref()
+                .map(ChatContent::text)
+                .unwrap_or_default();
+            vec![item(
+                None,
+                None,
+                json!({
+                    "type": "function_call_output",
+                    "call_id": message.tool_call_id.clone().unwrap_or_default(),
+                    "output": output,
+                }),
+            )]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// `text_type` is `input_text` for client turns and `output_text` for replayed assistant turns.
+fn content_parts(content: Option<&ChatContent>, text_type: &str) -> Vec<Value> {
+    match content {
+        None => Vec::new(),
+        Some(ChatContent::Text(text)) if text.is_empty() => Vec::new(),
+        Some(ChatContent::Text(text)) => vec![json!({ "type": text_type, "text": text })],
+        Some(ChatContent::Parts(parts)) => parts
+            .iter()
+            .filter_map(|part| match part {
+                ChatContentPart::Text { text } => Some(json!({ "type": text_type, "text": text })),
+                ChatContentPart::ImageUrl { image_url } => {
+                    let mut image = json!({ "type": "input_image", "image_url": image_url.url });
+                    if let Some(detail) = &image_url.detail {
+                        image["detail"] = json!(detail);
+                    }
+                    Some(image)
+                }
+                ChatContentPart::Unsupported => None,
+            })
+            .collect(),
+    }
+}
+
+/// Stateful translator for Responses API stream events into `chat.completion.chunk`s.
+///
+/// Handles text, reasoning summary and function call deltas; the terminal
+/// `response.completed` event yields the finish reason and, when asked for, a usage chunk.
+pub struct ResponsesChatStream {
+    model: String,
+    include_usage: bool,
+    id: String,
+    created: i64,
+    role_sent: bool,
+    /// Responses `output_index` -> chat `tool_calls[].index`.
+    tool_calls: HashMap<u64, u32>,
+    finished: bool,
+}
+
+impl ResponsesChatStream {
+    pub fn new(model: impl Into<String>, include_usage: bool) -> Self {
+        let created = chrono::Utc::now().timestamp();
+        Self {
+            model: model.into(),
+            include_usage,
+            id: format!("chatcmpl-{created}"),
+            created,
+            role_sent: false,
+            tool_calls: HashMap::new(),
+            finished: false,
+        }
+    }
+
+    /// Whether a terminal event has been seen; later events are ignored.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Translate one upstream event into zero or more chunks.
+    ///
+    /// `Err` carries the upstream message of a `response.failed` or `error` event.
+    pub fn push(&mut self, event: &Value) -> Result<Vec<OpenaiChatCompletionChunk>, String> {
+        if self.finished {
+            return Ok(Vec::new());
+        }
+        let kind = event
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let output_index = event.get("output_index").and_then(Value::as_u64);
+
+        let delta = match kind {
+            "response.created" => {
+                let response = event.get("response");
+                if let Some(id) = response
+                    .and_then(|response| response.get("id"))
+                    .and_then(Value::as_str)
+                {
+                    self.id = format!("chatcmpl-{id}");
+                }
+                if let Some(created) = response
+                    .and_then(|response| response.get("created_at"))
+                    .and_then(Value::as_i64)
+                {
+                    self.created = created;
+                }
+                return Ok(Vec::new());
+            }
+            "response.output_text.delta" => ChatDelta {
+                content: event_delta(event),
+                ..Default::default()
+            },
+            "response.reasoning_summary_text.delta" => ChatDelta {
+                reasoning_content: event_delta(event),
+                ..Default::default()
+            },
+            "response.output_item.added" => {
+                let Some(item) = event.get("item").filter(|item| {
+                    item.get("type").and_then(Value::as_str) == Some("function_call")
+                }) else {
+                    return Ok(Vec::new());
+                };
+                let index = self.tool_calls.len() as u32;
+                self.tool_calls
+                    .insert(output_index.unwrap_or_default(), index);
+                let text = |key: &str| item.get(key).and_then(Value::as_str).map(str::to_string);
+                ChatDelta {
+                    tool_calls: Some(vec![ChatToolCallDelta {
+                        index,
+                        id: text("call_id"),
+                        kind: Some("function".to_string()),
+                        function: ChatFunctionCallDelta {
+                            name: text("name"),
+                            arguments: Some(text("arguments").unwrap_or_default()),
+                        },
+                    }]),
+                    ..Default::default()
+                }
+            }
+            "response.function_call_arguments.delta" => {
+                let Some(index) = output_index.and_then(|output| self.tool_calls.get(&output))
+                else {
+                    return Ok(Vec::new());
+                };
+                ChatDelta {
+                    tool_calls: Some(vec![ChatToolCallDelta {
+                        index: *index,
+                        id: None,
+                        kind: None,
+                        function: ChatFunctionCallDelta {
+                            name: None,
+                            arguments: event_delta(event),
+                        },
+                    }]),
+                    ..Default::default()
+                }
+            }
+            "response.completed" | "response.incomplete" => {
+                self.finished = true;
+                return Ok(self.finish(event.get("response")));
+            }
+            "response.failed" | "error" => {
+                self.finished = true;
+                let message = event
+                    .pointer("/response/error/message")
+                    .or_else(|| event.pointer("/error/message"))
+                    .or_else(|| event.get("message"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("upstream response failed");
+                return Err(message.to_string());
+            }
+            _ => return Ok(Vec::new()),
+        };
+
+        if delta == ChatDelta::default() {
+            return Ok(Vec::new());
+        }
+        Ok(vec![self.chunk(delta, None, None)])
+    }
+
+    fn finish(&mut self, response: Option<&Value>) -> Vec<OpenaiChatCompletionChunk> {
+        let incomplete_reason = response
+            .and_then(|response| response.pointer("/incomplete_details/reason"))
+            .and_then(Value::as_str);
+        let finish_reason = match incomplete_reason {
+            Some("max_output_tokens") => "length",
+            Some("content_filter") => "content_filter",
+            _ if !self.tool_calls.is_empty() => "tool_calls",
+            _ => "stop",
+        };
+
+        let mut chunks =
+            vec![self.chunk(ChatDelta::default(), Some(finish_reason.to_string()), None)];
+        if self.include_usage {
+            let usage = response
+                .and_then(|response| response.get("usage"))
+                .map(usage_from_responses)
+                .unwrap_or_default();
+            let mut usage_chunk = self.chunk(ChatDelta::default(), None, Some(usage));
+            usage_chunk.choices.clear();
+            chunks.push(usage_chunk);
+        }
+        chunks
+    }
+
+    fn chunk(
+        &mut self,
+        mut delta: ChatDelta,
+        finish_reason: Option<String>,
+        usage: Option<ChatUsage>,
+    ) -> OpenaiChatCompletionChunk {
+        if !self.role_sent {
+            self.role_sent = true;
+            delta.role = Some("assistant".to_string());
+        }
+        OpenaiChatCompletionChunk {
+            id: self.id.clone(),
+            object: "chat.completion.chunk".to_string(),
+            created: self.created,
+            model: self.model.clone(),
+            choices: vec![ChatChunkChoice {
+                index: 0,
+                delta,
+                finish_reason,
+            }],
+            usage,
+        }
+    }
+}
+
+fn event_delta(event: &Value) -> Option<String> {
+    event
+        .get("delta")
+        .and_then(Value::as_str)
+        .filter(|delta| !delta.is_empty())
+        .map(str::to_string)
+}
+
+fn usage_from_responses(usage: &Value) -> ChatUsage {
+    let count = |pointer: &str| usage.pointer(pointer).and_then(Value::as_u64).unwrap_or(0);
+    let prompt_tokens = count("/input_tokens");
+    let completion_tokens = count("/output_tokens");
+    let reasoning_tokens = count("/output_tokens_details/reasoning_tokens");
+    ChatUsage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: usage
+            .get("total_tokens")
+            .and_then(Value::as_u64)
+            .unwrap_or(prompt_tokens + completion_tokens),
+        completion_tokens_details: (reasoning_tokens > 0)
+            .then_some(ChatCompletionTokensDetails { reasoning_tokens }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openai::OpenaiChatCompletion;
+
+    #[test]
+    fn chat_request_maps_messages_tools_and_format_to_responses() {
+        let chat: OpenaiChatRequestBody = serde_json::from_value(json!({
+            "model": "gpt-5-codex",
+            "stream": true,
+            "reasoning_effort": "low",
+            "messages": [
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "what is this"},
+                    {"type": "image_url", "image_url": {"url": "https://x/y.png", "detail": "low"}}
+                ]},
+                {"role": "assistant", "content": "calling", "tool_calls": [{
+                    "id": "call_1", "type": "function",
+                    "function": {"name": "look", "arguments": "{}"}
+                }]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "a cat"}
+            ],
+            "tools": [{"type": "function", "function": {"name": "look", "parameters": {"type": "object"}}}],
+            "tool_choice": {"type": "function", "function": {"name": "look"}},
+            "response_format": {"type": "json_schema", "json_schema": {"name": "out", "schema": {"type": "object"}, "strict": true}}
+        }))
+        .expect("chat request parses");
+
+        let body: OpenaiRequestBody = chat.into();
+        let value = serde_json::to_value(&body).expect("responses request serializes");
+        assert_eq!(value["stream"], true);
+        assert_eq!(
+            value["reasoning"],
+            json!({"effort": "low", "summary": "auto"})
+        );
+        assert_eq!(
+            value["input"],
+            json!([
+                {"type": "message", "role": "system", "content": [{"type": "input_text", "text": "be brief"}]},
+                {"type": "message", "role": "user", "content": [
+                    {"type": "input_text", "text": "what is this"},
+                    {"type": "input_image", "image_url": "https://x/y.png", "detail": "low"}
+                ]},
+                {"type": "message", "role": "assistant", "content": [{"type": "output_text", "text": "calling"}]},
+                {"type": "function_call", "call_id": "call_1", "name": "look", "arguments": "{}"},
+                {"type": "function_call_output", "call_id": "call_1", "output": "a cat"}
+            ])
+        );
+        assert_eq!(
+            value["tools"],
+            json!([{"type": "function", "name": "look", "parameters": {"type": "object"}}])
+        );
+        assert_eq!(
+            value["tool_choice"],
+            json!({"type": "function", "name": "look"})
+        );
+        assert_eq!(
+            value["text"],
+            json!({"format": {"type": "json_schema", "name": "out", "schema": {"type": "object"}, "strict": true}})
+        );
+    }
+
+    #[test]
+    fn stream_translates_text_tool_calls_and_final_usage() {
+        let events = [
+            json!({"type": "response.created", "response": {"id": "resp_1", "created_at": 7}}),
+            json!({"type": "response.output_text.delta", "output_index": 0, "delta": "hi"}),
+            json!({"type": "response.output_item.added", "output_index": 1, "item": {
+                "type": "function_call", "id": "fc_1", "call_id": "call_9", "name": "f", "arguments": ""
+            }}),
+            json!({"type": "response.function_call_arguments.delta", "output_index": 1, "delta": "{\"a\":"}),
+            json!({"type": "response.function_call_arguments.delta", "output_index": 1, "delta": "1}"}),
+            json!({"type": "response.completed", "response": {"usage": {
+                "input_tokens": 5, "output_tokens": 7, "total_tokens": 12,
+                "output_tokens_details": {"reasoning_tokens": 2}
+            }}}),
+        ];
+
+        let mut stream = ResponsesChatStream::new("gpt-5-codex", true);
+        let chunks: Vec<_> = events
+            .iter()
+            .flat_map(|event| stream.push(event).expect("no upstream failure"))
+            .collect();
+        assert!(stream.is_finished());
+
+        assert_eq!(chunks[0].id, "chatcmpl-resp_1");
+        assert_eq!(chunks[0].created, 7);
+        assert_eq!(
+            chunks[0].choices[0].delta.role.as_deref(),
+            Some("assistant")
+        );
+        let last = chunks.last().expect("usage chunk");
+        assert!(last.choices.is_empty());
+        assert_eq!(last.usage.as_ref().expect("usage").total_tokens, 12);
+
+        let completion = OpenaiChatCompletion::from_chunks(chunks).expect("completion");
+        let choice = &completion.choices[0];
+        assert_eq!(choice.message.content.as_deref(), Some("hi"));
+        let calls = choice.message.tool_calls.as_ref().expect("tool calls");
+        assert_eq!(calls[0].id, "call_9");
+        assert_eq!(calls[0].function.arguments, r#"{"a":1}"#);
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+    }
+
+    #[test]
+    fn stream_reports_failed_responses_and_truncation() {
+        let mut stream = ResponsesChatStream::new("m", false);
+        let err = stream
+            .push(&json!({"type": "response.failed", "response": {"error": {"message": "boom"}}}))
+            .expect_err("failed response surfaces");
+        assert_eq!(err, "boom");
+
+        let mut stream = ResponsesChatStream::new("m", false);
+        let chunks = stream
+            .push(&json!({"type": "response.incomplete", "response": {
+                "incomplete_details": {"reason": "max_output_tokens"}
+            }}))
+            .expect("incomplete is not an error");
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(
+            chunks[0].choices[0].finish_reason.as_deref(),
+            Some("length")
+        );
+    }
+}
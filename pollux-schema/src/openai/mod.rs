@@ -1,7 +1,17 @@
+mod chat_completions;
+mod chat_responses;
 mod model_list;
 mod responses_error;
 mod responses_request;
 
+pub use chat_completions::{
+    ChatChoice, ChatChunkChoice, ChatCompletionTokensDetails, ChatContent, ChatContentPart,
+    ChatDelta, ChatFunctionCall, ChatFunctionCallDelta, ChatFunctionDefinition, ChatImageUrl,
+    ChatJsonSchema, ChatMessage, ChatResponseFormat, ChatResponseMessage, ChatStreamOptions,
+    ChatTool, ChatToolCall, ChatToolCallDelta, ChatUsage, OpenaiChatCompletion,
+    OpenaiChatCompletionChunk, OpenaiChatRequestBody,
+};
+pub use chat_responses::ResponsesChatStream;
 pub use model_list::{OpenaiModel, OpenaiModelList};
 pub use responses_error::{OpenaiResponsesErrorBody, OpenaiResponsesErrorObject};
 pub use responses_request::{
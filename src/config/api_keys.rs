@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU32;
+
+/// One client API key, in addition to the master `basic.pollux_key`.
+/// TOML: one `[[api_keys]]` table per key.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApiKeyConfig {
+    /// Identity shown in access logs and metrics; must be unique.
+    /// TOML: `api_keys[].name`.
+    pub name: String,
+
+    /// The secret clients send.
+    /// TOML: `api_keys[].key`.
+    pub key: String,
+
+    /// Disabled keys are rejected with `403` but stay in the config.
+    /// TOML: `api_keys[].enabled`. Default: `true`.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Allowed models: exact model names, or a provider name (`geminicli`, `codex`) for all
+    /// of that provider's models. Unset allows every model.
+    /// TOML: `api_keys[].models`. Example: `["codex", "gemini-2.5-pro"]`.
+    #[serde(default)]
+    pub models: Option<Vec<String>>,
+
+    /// Requests per minute across all routes. Unset means unlimited.
+    /// TOML: `api_keys[].rpm`. Example: `60`.
+    #[serde(default)]
+    pub rpm: Option<NonZeroU32>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
@@ -1,6 +1,11 @@
+use crate::db::TokenKeyring;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
+
+/// Environment variable that overrides `basic.token_encryption_key(_file)`.
+pub const TOKEN_ENCRYPTION_KEY_ENV: &str = "POLLUX_TOKEN_ENCRYPTION_KEY";
 
 /// Basic (core) configuration managed by Figment.
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -37,6 +42,51 @@ pub struct BasicConfig {
     /// Keep `false` in production/HTTPS. Set `true` only for local plain-HTTP testing.
     #[serde(default)]
     pub insecure_cookie: bool,
+
+    /// Master key (base64, 32 bytes) that encrypts stored OAuth tokens.
+    /// TOML: `basic.token_encryption_key`. Default: unset, tokens are stored in plaintext.
+    ///
+    /// `POLLUX_TOKEN_ENCRYPTION_KEY` and `token_encryption_key_file` take precedence, in that
+    /// order. Once set, the database cannot be opened without it.
+    #[serde(default)]
+    pub token_encryption_key: Option<String>,
+
+    /// File holding the master key, e.g. a mounted secret.
+    /// TOML: `basic.token_encryption_key_file`. Default: unset.
+    #[serde(default)]
+    pub token_encryption_key_file: Option<PathBuf>,
+
+    /// Former master keys, tried when the current one does not open the database. Startup
+    /// re-encrypts under the current key, after which these can be removed.
+    /// TOML: `basic.previous_token_encryption_keys`. Default: empty.
+    #[serde(default)]
+    pub previous_token_encryption_keys: Vec<String>,
+}
+
+impl BasicConfig {
+    /// The configured token encryption keys, or `None` when encryption is off.
+    pub fn token_keyring(&self) -> Result<Option<TokenKeyring>, String> {
+        let current = match std::env::var(TOKEN_ENCRYPTION_KEY_ENV) {
+            Ok(key) => key,
+            Err(_) => match (&self.token_encryption_key_file, &self.token_encryption_key) {
+                (Some(path), _) => std::fs::read_to_string(path).map_err(|e| {
+                    format!(
+                        "failed to read basic.token_encryption_key_file {}: {e}",
+                        path.display()
+                    )
+                })?,
+                (None, Some(key)) => key.clone(),
+                (None, None) if self.previous_token_encryption_keys.is_empty() => return Ok(None),
+                (None, None) => {
+                    return Err(
+                        "basic.previous_token_encryption_keys needs a current token encryption key"
+                            .to_string(),
+                    );
+                }
+            },
+        };
+        TokenKeyring::new(&current, &self.previous_token_encryption_keys).map(Some)
+    }
 }
 
 impl Default for BasicConfig {
@@ -49,6 +99,9 @@ impl Default for BasicConfig {
             // No insecure default. `Config::from_toml()` enforces non-empty.
             pollux_key: "".to_string(),
             insecure_cookie: false,
+            token_encryption_key: None,
+            token_encryption_key_file: None,
+            previous_token_encryption_keys: Vec::new(),
         }
     }
 }
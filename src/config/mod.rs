@@ -1,11 +1,17 @@
+mod api_keys;
 mod basic;
+mod models;
 mod providers;
+mod reloadable;
 
-pub use basic::BasicConfig;
+pub use api_keys::ApiKeyConfig;
+pub use basic::{BasicConfig, TOKEN_ENCRYPTION_KEY_ENV};
+pub use models::ModelsConfig;
 pub use providers::{
     CodexConfig, CodexResolvedConfig, GeminiCliConfig, GeminiCliResolvedConfig, ProviderDefaults,
-    ProvidersConfig,
+    ProvidersConfig, SchedulingStrategy,
 };
+pub use reloadable::Reloadable;
 
 use figment::{
     Figment,
@@ -24,6 +30,16 @@ pub struct Config {
     /// Provider and upstream settings (see `providers` table in config.toml).
     #[serde(default)]
     pub providers: ProvidersConfig,
+
+    /// Named client keys with their own model allowlist and rate limit
+    /// (see `[[api_keys]]` tables in config.toml).
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+
+    /// Model aliases and fallback chains (see `[models.aliases]` and `[models.fallbacks]`
+    /// tables in config.toml).
+    #[serde(default)]
+    pub models: ModelsConfig,
 }
 
 const DEFAULT_CONFIG_FILE: &str = "config.toml";
@@ -51,20 +67,104 @@ impl Config {
     }
 
     /// Loads configuration from the TOML file (with defaults) and validates required fields.
+    ///
+    /// # Panics
+    /// Panics with the validation message; binaries call this once at startup.
     pub fn from_toml() -> Self {
+        Self::try_from_toml().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Like `from_toml`, but returns the problem instead of panicking, for reloads at runtime.
+    pub fn try_from_toml() -> Result<Self, String> {
         if !PathBuf::from(DEFAULT_CONFIG_FILE).is_file() {
-            panic!("config file not found: {}", DEFAULT_CONFIG_FILE);
+            return Err(format!("config file not found: {}", DEFAULT_CONFIG_FILE));
         }
-        let cfg: Self = Self::figment().extract().unwrap_or_else(|err| {
-            panic!(
+        let cfg: Self = Self::figment().extract().map_err(|err| {
+            format!(
                 "failed to extract configuration from {}: {err}",
                 DEFAULT_CONFIG_FILE
             )
-        });
-        if cfg.basic.pollux_key.trim().is_empty() {
-            panic!("basic.pollux_key must be set and non-empty");
+        })?;
+        cfg.validate()?;
+        Ok(cfg)
+    }
+
+    /// Checks required fields and cross-references between tables.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.basic.pollux_key.trim().is_empty() {
+            return Err("basic.pollux_key must be set and non-empty".to_string());
+        }
+        self.basic.token_keyring()?;
+        self.validate_api_keys()?;
+        self.validate_model_aliases()?;
+        self.validate_model_fallbacks()
+    }
+
+    fn configured_models(&self) -> std::collections::HashSet<String> {
+        self.geminicli()
+            .model_list
+            .into_iter()
+            .chain(self.codex().model_list)
+            .collect()
+    }
+
+    /// Aliases must point at a configured model and must not shadow one.
+    fn validate_model_aliases(&self) -> Result<(), String> {
+        let models = self.configured_models();
+        for (alias, target) in &self.models.aliases {
+            if alias.is_empty() || models.contains(alias) {
+                return Err(format!(
+                    "models.aliases key {alias:?} must be non-empty and not a configured model"
+                ));
+            }
+            if !models.contains(target) {
+                return Err(format!(
+                    "models.aliases.{alias:?} points at {target:?}, which no provider serves"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Fallback chains may only name configured models, and a model may not fall back to itself.
+    fn validate_model_fallbacks(&self) -> Result<(), String> {
+        let models = self.configured_models();
+        for (model, chain) in &self.models.fallbacks {
+            if !models.contains(model) {
+                return Err(format!(
+                    "models.fallbacks key {model:?} is not a configured model"
+                ));
+            }
+            for target in chain {
+                if target == model || !models.contains(target) {
+                    return Err(format!(
+                        "models.fallbacks.{model:?} entry {target:?} must be another configured model"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Client keys need a unique name and a non-empty secret distinct from every other key.
+    fn validate_api_keys(&self) -> Result<(), String> {
+        let mut names = std::collections::HashSet::new();
+        let mut secrets = std::collections::HashSet::from([self.basic.pollux_key.as_str()]);
+        for key in &self.api_keys {
+            if key.name.trim().is_empty() || !names.insert(key.name.as_str()) {
+                return Err(format!(
+                    "api_keys[].name must be non-empty and unique: {:?}",
+                    key.name
+                ));
+            }
+            if key.key.trim().is_empty() || !secrets.insert(key.key.as_str()) {
+                return Err(format!(
+                    "api_keys[].key for {:?} must be non-empty and unique",
+                    key.name
+                ));
+            }
         }
-        cfg
+        Ok(())
     }
 
     pub fn geminicli(&self) -> GeminiCliResolvedConfig {
@@ -77,4 +177,7 @@ impl Config {
 }
 
 /// Global, lazily-initialized configuration instance.
+///
+/// This is the configuration seen at first touch. Settings that can be hot reloaded are read
+/// from their [`Reloadable`] holders instead.
 pub static CONFIG: LazyLock<Config> = LazyLock::new(Config::from_optional_toml);
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Model name settings shared by every provider.
+/// TOML: `[models]`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ModelsConfig {
+    /// Extra names clients may use, each mapped to a model from some provider's `model_list`.
+    /// A key ending in `*` matches any name with that prefix; the longest prefix wins and exact
+    /// names always take precedence.
+    /// TOML: `[models.aliases]`. Example: `gemini-pro = "gemini-2.5-pro"`, `"gpt-5*" = "gpt-5.2"`.
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+
+    /// Models to try, in order, when no credential can serve the requested one or upstream
+    /// rate-limits it or reports it unsupported. Entries may belong to either provider:
+    /// `/anthropic` and the chat-completions routes cross providers, the native routes cannot and
+    /// say so once only such entries are left.
+    /// TOML: `[models.fallbacks]`. Example: `"gpt-5.3-codex" = ["gpt-5.2-codex", "gpt-5.2"]`.
+    #[serde(default)]
+    pub fallbacks: BTreeMap<String, Vec<String>>,
+}
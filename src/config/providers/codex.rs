@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    num::{NonZeroU32, NonZeroU64},
+};
 use url::Url;
 
-use super::ProviderDefaults;
+use super::{ProviderDefaults, SchedulingStrategy};
+use crate::providers::codex::CODEX_RESPONSES_URL;
 
 /// Codex provider configuration managed by Figment.
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -13,6 +18,11 @@ pub struct CodexConfig {
     #[serde(default)]
     pub proxy: Option<Url>,
 
+    /// Codex Responses endpoint requests are forwarded to.
+    /// TOML: `providers.codex.responses_url`. Default: ChatGPT's Codex backend.
+    #[serde(default)]
+    pub responses_url: Option<Url>,
+
     /// OAuth refresh requests per second (TPS) for the refresh worker.
     /// TOML: `providers.codex.oauth_tps`. Default: `5`.
     #[serde(default = "default_oauth_tps")]
@@ -35,27 +45,79 @@ pub struct CodexConfig {
     /// Falls back to `providers.defaults.retry_max_times`.
     #[serde(default)]
     pub retry_max_times: Option<usize>,
+
+    /// Credential selection strategy.
+    /// TOML: `providers.codex.scheduling`. Default: `"round_robin"`.
+    #[serde(default)]
+    pub scheduling: SchedulingStrategy,
+
+    /// Relative share per plan (`chatgpt_plan_type`) under `scheduling = "weighted"`;
+    /// unlisted values (and credentials without one) weigh `1`.
+    /// TOML: `providers.codex.weights`. Example: `{ plus = 1, pro = 4 }`.
+    #[serde(default)]
+    pub weights: BTreeMap<String, NonZeroU32>,
+
+    /// Keep each conversation on the credential that served it last while that credential is
+    /// healthy. Keyed by the `x-pollux-session` header, else the body's `prompt_cache_key`
+    /// (OpenAI routes) or `metadata.user_id` (Anthropic route).
+    /// TOML: `providers.codex.session_affinity`. Default: `false`.
+    #[serde(default)]
+    pub session_affinity: bool,
+
+    /// Requests one credential may serve at once; a lease lasts until the response (stream
+    /// included) ends. Credentials at the limit are skipped.
+    /// TOML: `providers.codex.max_concurrent_per_credential`. Default: unlimited.
+    #[serde(default)]
+    pub max_concurrent_per_credential: Option<NonZeroU32>,
+
+    /// How long a request may wait for a credential when none is free, instead of failing
+    /// at once. Waiting requests are served in arrival order.
+    /// TOML: `providers.codex.queue_timeout_secs`. Default: no waiting.
+    #[serde(default)]
+    pub queue_timeout_secs: Option<NonZeroU64>,
+
+    /// Requests that may wait at once; beyond this they get `503` with `Retry-After`.
+    /// TOML: `providers.codex.queue_max_depth`. Default: `100`.
+    #[serde(default = "default_queue_max_depth")]
+    pub queue_max_depth: usize,
 }
 
 #[derive(Debug, Clone)]
 pub struct CodexResolvedConfig {
     pub proxy: Option<Url>,
+    pub responses_url: Url,
     pub oauth_tps: usize,
     pub model_list: Vec<String>,
     pub enable_multiplexing: bool,
     pub retry_max_times: usize,
+    pub scheduling: SchedulingStrategy,
+    pub weights: BTreeMap<String, NonZeroU32>,
+    pub session_affinity: bool,
+    pub max_concurrent_per_credential: Option<NonZeroU32>,
+    pub queue_timeout_secs: Option<NonZeroU64>,
+    pub queue_max_depth: usize,
 }
 
 impl CodexConfig {
     pub fn resolve(&self, defaults: &ProviderDefaults) -> CodexResolvedConfig {
         CodexResolvedConfig {
             proxy: self.proxy.clone().or_else(|| defaults.proxy.clone()),
+            responses_url: self
+                .responses_url
+                .clone()
+                .unwrap_or_else(|| CODEX_RESPONSES_URL.clone()),
             oauth_tps: self.oauth_tps,
             model_list: self.model_list.clone(),
             enable_multiplexing: self
                 .enable_multiplexing
                 .unwrap_or(defaults.enable_multiplexing),
             retry_max_times: self.retry_max_times.unwrap_or(defaults.retry_max_times),
+            scheduling: self.scheduling,
+            weights: self.weights.clone(),
+            session_affinity: self.session_affinity,
+            max_concurrent_per_credential: self.max_concurrent_per_credential,
+            queue_timeout_secs: self.queue_timeout_secs,
+            queue_max_depth: self.queue_max_depth,
         }
     }
 }
@@ -64,14 +126,25 @@ impl Default for CodexConfig {
     fn default() -> Self {
         Self {
             proxy: None,
+            responses_url: None,
             oauth_tps: default_oauth_tps(),
             model_list: default_model_list(),
             enable_multiplexing: None,
             retry_max_times: None,
+            scheduling: SchedulingStrategy::default(),
+            weights: BTreeMap::new(),
+            session_affinity: false,
+            max_concurrent_per_credential: None,
+            queue_timeout_secs: None,
+            queue_max_depth: default_queue_max_depth(),
         }
     }
 }
 
+fn default_queue_max_depth() -> usize {
+    100
+}
+
 fn default_oauth_tps() -> usize {
     5
 }
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    num::{NonZeroU32, NonZeroU64},
+};
 use url::Url;
 
-use super::ProviderDefaults;
+use super::{ProviderDefaults, SchedulingStrategy};
 
 /// Gemini CLI provider configuration managed by Figment.
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -33,6 +37,41 @@ pub struct GeminiCliConfig {
     /// Falls back to `providers.defaults.retry_max_times`.
     #[serde(default)]
     pub retry_max_times: Option<usize>,
+
+    /// Credential selection strategy.
+    /// TOML: `providers.geminicli.scheduling`. Default: `"round_robin"`.
+    #[serde(default)]
+    pub scheduling: SchedulingStrategy,
+
+    /// Relative share per tier (`free-tier`, `standard-tier`, `legacy-tier`) under `scheduling = "weighted"`;
+    /// unlisted values (and credentials without one) weigh `1`.
+    /// TOML: `providers.geminicli.weights`. Example: `{ "free-tier" = 1, "standard-tier" = 3 }`.
+    #[serde(default)]
+    pub weights: BTreeMap<String, NonZeroU32>,
+
+    /// Keep each conversation on the credential that served it last while that credential is
+    /// healthy. Keyed by the `x-pollux-session` header, else the body's `prompt_cache_key`
+    /// (OpenAI routes) or `metadata.user_id` (Anthropic route).
+    /// TOML: `providers.geminicli.session_affinity`. Default: `false`.
+    #[serde(default)]
+    pub session_affinity: bool,
+
+    /// Requests one credential may serve at once; a lease lasts until the response (stream
+    /// included) ends. Credentials at the limit are skipped.
+    /// TOML: `providers.geminicli.max_concurrent_per_credential`. Default: unlimited.
+    #[serde(default)]
+    pub max_concurrent_per_credential: Option<NonZeroU32>,
+
+    /// How long a request may wait for a credential when none is free, instead of failing
+    /// at once. Waiting requests are served in arrival order.
+    /// TOML: `providers.geminicli.queue_timeout_secs`. Default: no waiting.
+    #[serde(default)]
+    pub queue_timeout_secs: Option<NonZeroU64>,
+
+    /// Requests that may wait at once; beyond this they get `503` with `Retry-After`.
+    /// TOML: `providers.geminicli.queue_max_depth`. Default: `100`.
+    #[serde(default = "default_queue_max_depth")]
+    pub queue_max_depth: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -42,6 +81,12 @@ pub struct GeminiCliResolvedConfig {
     pub model_list: Vec<String>,
     pub enable_multiplexing: bool,
     pub retry_max_times: usize,
+    pub scheduling: SchedulingStrategy,
+    pub weights: BTreeMap<String, NonZeroU32>,
+    pub session_affinity: bool,
+    pub max_concurrent_per_credential: Option<NonZeroU32>,
+    pub queue_timeout_secs: Option<NonZeroU64>,
+    pub queue_max_depth: usize,
 }
 
 impl GeminiCliConfig {
@@ -54,6 +99,12 @@ impl GeminiCliConfig {
                 .enable_multiplexing
                 .unwrap_or(defaults.enable_multiplexing),
             retry_max_times: self.retry_max_times.unwrap_or(defaults.retry_max_times),
+            scheduling: self.scheduling,
+            weights: self.weights.clone(),
+            session_affinity: self.session_affinity,
+            max_concurrent_per_credential: self.max_concurrent_per_credential,
+            queue_timeout_secs: self.queue_timeout_secs,
+            queue_max_depth: self.queue_max_depth,
         }
     }
 }
@@ -66,10 +117,20 @@ impl Default for GeminiCliConfig {
             model_list: default_model_list(),
             enable_multiplexing: None,
             retry_max_times: None,
+            scheduling: SchedulingStrategy::default(),
+            weights: BTreeMap::new(),
+            session_affinity: false,
+            max_concurrent_per_credential: None,
+            queue_timeout_secs: None,
+            queue_max_depth: default_queue_max_depth(),
         }
     }
 }
 
+fn default_queue_max_depth() -> usize {
+    100
+}
+
 fn default_oauth_tps() -> usize {
     5
 }
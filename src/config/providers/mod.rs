@@ -7,6 +7,22 @@ pub use geminicli::{GeminiCliConfig, GeminiCliResolvedConfig};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+/// How a provider picks among the credentials that can serve a request.
+/// TOML: `providers.<provider>.scheduling`. Default: `"round_robin"`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchedulingStrategy {
+    /// Rotate through credentials in order.
+    #[default]
+    RoundRobin,
+    /// Share requests in proportion to `weights` (by Codex plan or Gemini tier).
+    Weighted,
+    /// Pick the credential idle the longest.
+    LeastRecentlyUsed,
+    /// Pick the credential whose last rate limit is oldest; never-limited ones first.
+    LeastRecentlyRateLimited,
+}
+
 /// Global provider defaults (used when provider-level config is unset).
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ProviderDefaults {
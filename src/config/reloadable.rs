@@ -0,0 +1,27 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// A value that a config reload may replace while requests are reading it.
+///
+/// Readers take a cheap `Arc` snapshot, so work already in flight keeps the value it started
+/// with.
+#[derive(Debug)]
+pub struct Reloadable<T>(RwLock<Arc<T>>);
+
+impl<T> Reloadable<T> {
+    pub fn new(value: T) -> Self {
+        Self(RwLock::new(Arc::new(value)))
+    }
+
+    /// Current value.
+    pub fn load(&self) -> Arc<T> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the value; existing snapshots are unaffected.
+    pub fn store(&self, value: T) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(value);
+    }
+}
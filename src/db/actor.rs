@@ -1,14 +1,16 @@
-use crate::db::models::{DbCodexResource, DbGeminiCliResource};
+use crate::db::crypto::{self, ACCESS_TOKEN, REFRESH_TOKEN, TokenCipher, TokenKeyring, TokenRow};
+use crate::db::models::{
+    DbCodexResource, DbGeminiCliResource, DbModelState, DbPage, DbUsageSummary, ModelStateUpdate,
+    RefreshLease, UsageCreate, UsageQuery,
+};
 use crate::db::patch::{ProviderCreate, ProviderPatch};
-use crate::db::schema::SQLITE_INIT;
+use crate::db::store::{self, Store};
 use crate::db::traits::DbPatchable;
 use crate::error::PolluxError;
 use chrono::Utc;
 use ractor::{Actor, ActorProcessingErr, ActorRef, RpcReplyPort};
-use sqlx::SqlitePool;
-use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
-use std::{str::FromStr, time::Duration};
-use tracing::info;
+use std::time::Duration;
+use tracing::{info, warn};
 
 #[derive(Debug)]
 pub enum DbActorMessage {
@@ -26,6 +28,56 @@ pub enum DbActorMessage {
 
     /// Get Codex key by id.
     GetCodexById(i64, RpcReplyPort<Result<DbCodexResource, PolluxError>>),
+
+    /// Page through all Gemini CLI credentials (any status), ordered by id: (offset, limit).
+    ListGeminiCli(
+        i64,
+        i64,
+        RpcReplyPort<Result<DbPage<DbGeminiCliResource>, PolluxError>>,
+    ),
+
+    /// Page through all Codex keys (any status), ordered by id: (offset, limit).
+    ListCodex(
+        i64,
+        i64,
+        RpcReplyPort<Result<DbPage<DbCodexResource>, PolluxError>>,
+    ),
+
+    /// Get Gemini CLI credential by id.
+    GetGeminiCliById(i64, RpcReplyPort<Result<DbGeminiCliResource, PolluxError>>),
+
+    /// Delete a Gemini CLI credential row by id.
+    DeleteGeminiCli(i64, RpcReplyPort<Result<(), PolluxError>>),
+
+    /// Delete a Codex key row by id.
+    DeleteCodex(i64, RpcReplyPort<Result<(), PolluxError>>),
+
+    /// Append one token usage row (fire-and-forget).
+    RecordUsage(UsageCreate),
+
+    /// Sum token usage per day, key, provider and model.
+    QueryUsage(
+        UsageQuery,
+        RpcReplyPort<Result<Vec<DbUsageSummary>, PolluxError>>,
+    ),
+
+    /// Merge one (credential, model) scheduler state (fire-and-forget).
+    SaveModelState(ModelStateUpdate),
+
+    /// Drop expired state of a provider, then list what is left.
+    ListModelState(
+        &'static str,
+        RpcReplyPort<Result<Vec<DbModelState>, PolluxError>>,
+    ),
+
+    /// Forget every saved model state of one credential: (provider, credential id).
+    ClearModelState(&'static str, i64, RpcReplyPort<Result<(), PolluxError>>),
+
+    /// Take a credential's refresh lease if it is free; replies whether the holder has it.
+    AcquireRefreshLease(RefreshLease, RpcReplyPort<Result<bool, PolluxError>>),
+
+    /// Give up a refresh lease (fire-and-forget): (provider, credential id, holder).
+    ReleaseRefreshLease(&'static str, i64, String),
 }
 
 #[derive(Clone)]
@@ -61,10 +113,132 @@ impl DbActorHandle {
             PolluxError::RactorError(format!("DbActor GetCodexById RPC failed: {e}"))
         })?
     }
+
+    pub async fn list_geminicli(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<DbPage<DbGeminiCliResource>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::ListGeminiCli, offset, limit).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor ListGeminiCli RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn list_codex(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<DbPage<DbCodexResource>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::ListCodex, offset, limit)
+            .map_err(|e| PolluxError::RactorError(format!("DbActor ListCodex RPC failed: {e}")))?
+    }
+
+    pub async fn get_geminicli_by_id(&self, id: i64) -> Result<DbGeminiCliResource, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::GetGeminiCliById, id).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor GetGeminiCliById RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn delete_geminicli(&self, id: i64) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::DeleteGeminiCli, id).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor DeleteGeminiCli RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn delete_codex(&self, id: i64) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::DeleteCodex, id)
+            .map_err(|e| PolluxError::RactorError(format!("DbActor DeleteCodex RPC failed: {e}")))?
+    }
+
+    /// Usage is written after the response has been sent, so nobody waits for the insert.
+    pub fn record_usage(&self, usage: UsageCreate) {
+        if let Err(e) = self.actor.cast(DbActorMessage::RecordUsage(usage)) {
+            warn!("DbActor RecordUsage cast failed: {e}");
+        }
+    }
+
+    pub async fn query_usage(&self, query: UsageQuery) -> Result<Vec<DbUsageSummary>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::QueryUsage, query)
+            .map_err(|e| PolluxError::RactorError(format!("DbActor QueryUsage RPC failed: {e}")))?
+    }
+
+    /// Written from the scheduler's report paths, which must not wait on SQLite.
+    pub fn save_model_state(&self, update: ModelStateUpdate) {
+        if let Err(e) = self.actor.cast(DbActorMessage::SaveModelState(update)) {
+            warn!("DbActor SaveModelState cast failed: {e}");
+        }
+    }
+
+    pub async fn list_model_state(
+        &self,
+        provider: &'static str,
+    ) -> Result<Vec<DbModelState>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::ListModelState, provider).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor ListModelState RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn clear_model_state(
+        &self,
+        provider: &'static str,
+        credential_id: i64,
+    ) -> Result<(), PolluxError> {
+        ractor::call!(
+            self.actor,
+            DbActorMessage::ClearModelState,
+            provider,
+            credential_id
+        )
+        .map_err(|e| PolluxError::RactorError(format!("DbActor ClearModelState RPC failed: {e}")))?
+    }
+
+    /// Claim the right to refresh a credential for `ttl`, unless another holder's lease is
+    /// still running. Renews the lease when `holder` already has it.
+    pub async fn acquire_refresh_lease(
+        &self,
+        provider: &'static str,
+        credential_id: i64,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<bool, PolluxError> {
+        let expires_at = chrono::Duration::from_std(ttl)
+            .ok()
+            .and_then(|ttl| Utc::now().checked_add_signed(ttl))
+            .ok_or_else(|| PolluxError::UnexpectedError(format!("lease ttl {ttl:?} overflows")))?;
+        let lease = RefreshLease {
+            provider,
+            credential_id,
+            holder: holder.to_string(),
+            expires_at,
+        };
+        ractor::call!(self.actor, DbActorMessage::AcquireRefreshLease, lease).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor AcquireRefreshLease RPC failed: {e}"))
+        })?
+    }
+
+    /// Sent once the refreshed tokens are stored, so waiting instances find them.
+    pub fn release_refresh_lease(&self, provider: &'static str, credential_id: i64, holder: &str) {
+        if let Err(e) = self.actor.cast(DbActorMessage::ReleaseRefreshLease(
+            provider,
+            credential_id,
+            holder.to_string(),
+        )) {
+            warn!("DbActor ReleaseRefreshLease cast failed: {e}");
+        }
+    }
+
+    /// Stop the actor once it has drained its mailbox, closing the connection pool. Another
+    /// `DbActor` can be spawned in this process afterwards.
+    pub async fn stop(self) {
+        if let Err(e) = self.actor.stop_and_wait(None, None).await {
+            warn!("DbActor stop failed: {e}");
+        }
+    }
 }
 
 struct DbActorState {
-    pool: SqlitePool,
+    store: Box<dyn Store>,
+    cipher: TokenCipher,
 }
 
 struct DbActor;
@@ -73,31 +247,23 @@ struct DbActor;
 impl Actor for DbActor {
     type Msg = DbActorMessage;
     type State = DbActorState;
-    type Arguments = String;
+    type Arguments = (String, Option<TokenKeyring>);
 
     async fn pre_start(
         &self,
         _myself: ActorRef<Self::Msg>,
-        database_url: Self::Arguments,
+        (database_url, keyring): Self::Arguments,
     ) -> Result<Self::State, ActorProcessingErr> {
-        let connect_opts = SqliteConnectOptions::from_str(database_url.as_str())
-            .map_err(|e| ActorProcessingErr::from(format!("invalid database url: {e}")))?
-            .create_if_missing(true)
-            .busy_timeout(Duration::from_secs(5))
-            .journal_mode(SqliteJournalMode::Wal)
-            .synchronous(SqliteSynchronous::Normal);
-
-        let pool = SqlitePoolOptions::new()
-            .connect_with(connect_opts)
+        let store = store::connect(&database_url)
             .await
-            .map_err(|e| ActorProcessingErr::from(format!("db connect failed: {e}")))?;
+            .map_err(|e| ActorProcessingErr::from(format!("db init failed: {e}")))?;
 
-        apply_schema(&pool)
+        let cipher = crypto::init(store.as_ref(), keyring.as_ref())
             .await
-            .map_err(|e| ActorProcessingErr::from(format!("db schema init failed: {e}")))?;
+            .map_err(|e| ActorProcessingErr::from(format!("token encryption init failed: {e}")))?;
 
         info!("DbActor initialized");
-        Ok(DbActorState { pool })
+        Ok(DbActorState { store, cipher })
     }
 
     async fn handle(
@@ -106,184 +272,288 @@ impl Actor for DbActor {
         message: Self::Msg,
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
+        let DbActorState { store, cipher } = state;
+        let store = store.as_ref();
         match message {
             DbActorMessage::Create(create, reply) => {
-                let res = self.create_provider(&state.pool, create).await;
+                let res = store.create(seal_create(cipher, create)).await;
                 let _ = reply.send(res);
             }
             DbActorMessage::Patch(patch, reply) => {
-                let res = patch.apply_patch(&state.pool).await;
+                let res = match seal_patch(store, cipher, patch).await {
+                    Ok(patch) => patch.apply_patch(store).await,
+                    Err(e) => Err(e),
+                };
                 let _ = reply.send(res);
             }
             DbActorMessage::ListActiveGeminiCli(reply) => {
-                let res = self.list_active_geminicli(&state.pool).await;
+                let res = store.list_active_geminicli().await.and_then(|rows| {
+                    rows.into_iter()
+                        .map(|row| open_geminicli(cipher, row))
+                        .collect()
+                });
                 let _ = reply.send(res);
             }
             DbActorMessage::ListActiveCodex(reply) => {
-                let res = self.list_active_codex(&state.pool).await;
+                let res = store.list_active_codex().await.and_then(|rows| {
+                    rows.into_iter()
+                        .map(|row| open_codex(cipher, row))
+                        .collect()
+                });
                 let _ = reply.send(res);
             }
             DbActorMessage::GetCodexById(id, reply) => {
-                let res = self.get_codex_by_id(&state.pool, id).await;
+                let res = store.get_codex_by_id(id).await.and_then(|row| {
+                    let row = row.ok_or_else(|| {
+                        PolluxError::NotFound(format!("Codex credential id={id}"))
+                    })?;
+                    open_codex(cipher, row)
+                });
                 let _ = reply.send(res);
             }
+            DbActorMessage::ListGeminiCli(offset, limit, reply) => {
+                let res = store.list_geminicli(offset, limit).await.and_then(|page| {
+                    let items = page
+                        .items
+                        .into_iter()
+                        .map(|row| open_geminicli(cipher, row))
+                        .collect::<Result<_, _>>()?;
+                    Ok(DbPage {
+                        total: page.total,
+                        items,
+                    })
+                });
+                let _ = reply.send(res);
+            }
+            DbActorMessage::ListCodex(offset, limit, reply) => {
+                let res = store.list_codex(offset, limit).await.and_then(|page| {
+                    let items = page
+                        .items
+                        .into_iter()
+                        .map(|row| open_codex(cipher, row))
+                        .collect::<Result<_, _>>()?;
+                    Ok(DbPage {
+                        total: page.total,
+                        items,
+                    })
+                });
+                let _ = reply.send(res);
+            }
+            DbActorMessage::GetGeminiCliById(id, reply) => {
+                let res = store.get_geminicli_by_id(id).await.and_then(|row| {
+                    let row = row.ok_or_else(|| {
+                        PolluxError::NotFound(format!("GeminiCli credential id={id}"))
+                    })?;
+                    open_geminicli(cipher, row)
+                });
+                let _ = reply.send(res);
+            }
+            DbActorMessage::DeleteGeminiCli(id, reply) => {
+                let res = delete_credential(store, "gemini_cli", "geminicli", id).await;
+                let _ = reply.send(res);
+            }
+            DbActorMessage::DeleteCodex(id, reply) => {
+                let res = delete_credential(store, "codex", "codex", id).await;
+                let _ = reply.send(res);
+            }
+            DbActorMessage::RecordUsage(usage) => {
+                if let Err(e) = store.record_usage(usage).await {
+                    warn!("Failed to record token usage: {e}");
+                }
+            }
+            DbActorMessage::QueryUsage(query, reply) => {
+                let res = store.query_usage(query).await;
+                let _ = reply.send(res);
+            }
+            DbActorMessage::SaveModelState(update) => {
+                if let Err(e) = store.save_model_state(update).await {
+                    warn!("Failed to save model state: {e}");
+                }
+            }
+            DbActorMessage::ListModelState(provider, reply) => {
+                let res = store.list_model_state(provider).await;
+                let _ = reply.send(res);
+            }
+            DbActorMessage::ClearModelState(provider, credential_id, reply) => {
+                let res = store.clear_model_state(provider, credential_id).await;
+                let _ = reply.send(res);
+            }
+            DbActorMessage::AcquireRefreshLease(lease, reply) => {
+                let res = store.acquire_refresh_lease(lease).await;
+                let _ = reply.send(res);
+            }
+            DbActorMessage::ReleaseRefreshLease(provider, credential_id, holder) => {
+                if let Err(e) = store
+                    .release_refresh_lease(provider, credential_id, &holder)
+                    .await
+                {
+                    warn!("Failed to release refresh lease: {e}");
+                }
+            }
         }
         Ok(())
     }
 }
 
-impl DbActor {
-    async fn create_provider(
-        &self,
-        pool: &SqlitePool,
-        create: ProviderCreate,
-    ) -> Result<i64, PolluxError> {
-        match create {
-            ProviderCreate::GeminiCli(c) => {
-                let now = Utc::now();
-                let id: i64 = sqlx::query_scalar(
-                    r#"
-                INSERT INTO gemini_cli (
-                    email, sub, project_id, refresh_token, access_token, expiry, status, created_at, updated_at
-                )
-                VALUES (?, ?, ?, ?, ?, ?, 1, ?, ?)
-                ON CONFLICT(sub, project_id) DO UPDATE SET
-                    email=excluded.email,
-                    refresh_token=excluded.refresh_token,
-                    access_token=excluded.access_token,
-                    expiry=excluded.expiry,
-                    status=1,
-                    updated_at=excluded.updated_at
-                RETURNING id
-                "#,
-                )
-                .bind(c.email)
-                .bind(c.sub)
-                .bind(c.project_id)
-                .bind(c.refresh_token)
-                .bind(c.access_token)
-                .bind(c.expiry)
-                .bind(now)
-                .bind(now)
-                .fetch_one(pool)
-                .await?;
-
-                Ok(id)
-            }
+/// `table` is always one of our own table names, never user input.
+async fn delete_credential(
+    store: &dyn Store,
+    table: &'static str,
+    provider: &'static str,
+    id: i64,
+) -> Result<(), PolluxError> {
+    if store.delete_credential(table, provider, id).await? == 0 {
+        return Err(PolluxError::NotFound(format!("{table} credential id={id}")));
+    }
+    Ok(())
+}
 
-            ProviderCreate::Codex(c) => {
-                let now = Utc::now();
-
-                let id: i64 = sqlx::query_scalar(
-                    r#"
-                INSERT INTO codex (
-                    email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, status, created_at, updated_at
-                )
-                VALUES (?, ?, ?, ?, ?, ?, ?, 1, ?, ?)
-                ON CONFLICT(sub, account_id) DO UPDATE SET
-                    email = COALESCE(excluded.email, email),
-                    refresh_token = excluded.refresh_token,
-                    access_token = excluded.access_token,
-                    expiry = excluded.expiry,
-                    chatgpt_plan_type = COALESCE(excluded.chatgpt_plan_type, chatgpt_plan_type),
-                    status = 1,
-                    updated_at = excluded.updated_at
-                RETURNING id
-                "#,
-                )
-                .bind(c.email)
-                .bind(c.sub)
-                .bind(c.account_id)
-                .bind(c.refresh_token)
-                .bind(c.access_token)
-                .bind(c.expiry)
-                .bind(c.chatgpt_plan_type)
-                .bind(now)
-                .bind(now)
-                .fetch_one(pool)
-                .await?;
-
-                Ok(id)
-            }
+/// Creates are upserts keyed by `sub` and `project_id` / `account_id`, so that is the row the
+/// tokens are sealed for.
+fn seal_create(cipher: &TokenCipher, create: ProviderCreate) -> ProviderCreate {
+    match create {
+        ProviderCreate::GeminiCli(mut c) => {
+            let row = TokenRow::geminicli(&c.sub, &c.project_id);
+            c.refresh_token = cipher.seal(&row, REFRESH_TOKEN, &c.refresh_token);
+            c.access_token = c
+                .access_token
+                .map(|token| cipher.seal(&row, ACCESS_TOKEN, &token));
+            ProviderCreate::GeminiCli(c)
+        }
+        ProviderCreate::Codex(mut c) => {
+            let row = TokenRow::codex(&c.sub, &c.account_id);
+            c.refresh_token = cipher.seal(&row, REFRESH_TOKEN, &c.refresh_token);
+            c.access_token = cipher.seal(&row, ACCESS_TOKEN, &c.access_token);
+            ProviderCreate::Codex(c)
         }
     }
+}
 
-    async fn list_active_geminicli(
-        &self,
-        pool: &SqlitePool,
-    ) -> Result<Vec<DbGeminiCliResource>, PolluxError> {
-        let rows = sqlx::query_as::<_, DbGeminiCliResource>(
-            r#"
-        SELECT id, email, sub, project_id, refresh_token, access_token, expiry, status, created_at, updated_at
-        FROM gemini_cli
-        WHERE status = 1
-        ORDER BY id
-        "#,
-        )
-        .fetch_all(pool)
-        .await?;
-
-        Ok(rows)
+/// Seal the tokens of a patch for the row it updates. A Codex patch that changes `sub` or
+/// `account_id` changes what the row's tokens are bound to, so it carries both tokens,
+/// re-sealed.
+///
+/// A missing row is left to `apply_patch` to report.
+async fn seal_patch(
+    store: &dyn Store,
+    cipher: &TokenCipher,
+    mut patch: ProviderPatch,
+) -> Result<ProviderPatch, PolluxError> {
+    if !cipher.is_enabled() {
+        return Ok(patch);
     }
+    match &mut patch {
+        ProviderPatch::GeminiCli { id, patch } => {
+            if (patch.refresh_token.is_some() || patch.access_token.is_some())
+                && let Some(stored) = stored_geminicli(store, *id).await?
+            {
+                let row = TokenRow::geminicli(&stored.sub, &stored.project_id);
+                patch.refresh_token = patch
+                    .refresh_token
+                    .take()
+                    .map(|token| cipher.seal(&row, REFRESH_TOKEN, &token));
+                patch.access_token = patch
+                    .access_token
+                    .take()
+                    .map(|token| cipher.seal(&row, ACCESS_TOKEN, &token));
+            }
+        }
+        ProviderPatch::Codex { id, patch } => {
+            let moves = patch.sub.is_some() || patch.account_id.is_some();
+            if (moves || patch.refresh_token.is_some() || patch.access_token.is_some())
+                && let Some(stored) = stored_codex(store, *id).await?
+            {
+                if moves {
+                    let old = TokenRow::codex(&stored.sub, &stored.account_id);
+                    if patch.refresh_token.is_none() {
+                        patch.refresh_token =
+                            Some(cipher.open(&old, REFRESH_TOKEN, &stored.refresh_token)?);
+                    }
+                    if patch.access_token.is_none() {
+                        patch.access_token =
+                            Some(cipher.open(&old, ACCESS_TOKEN, &stored.access_token)?);
+                    }
+                }
+                let sub = patch.sub.as_deref().unwrap_or(&stored.sub);
+                let account_id = patch.account_id.as_deref().unwrap_or(&stored.account_id);
+                let row = TokenRow::codex(sub, account_id);
+                let refresh_token = patch
+                    .refresh_token
+                    .as_deref()
+                    .map(|token| cipher.seal(&row, REFRESH_TOKEN, token));
+                let access_token = patch
+                    .access_token
+                    .as_deref()
+                    .map(|token| cipher.seal(&row, ACCESS_TOKEN, token));
+                patch.refresh_token = refresh_token;
+                patch.access_token = access_token;
+            }
+        }
+    }
+    Ok(patch)
+}
 
-    async fn list_active_codex(
-        &self,
-        pool: &SqlitePool,
-    ) -> Result<Vec<DbCodexResource>, PolluxError> {
-        let rows = sqlx::query_as::<_, DbCodexResource>(
-            r#"
-        SELECT id, email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, status, created_at, updated_at
-        FROM codex
-        WHERE status = 1
-        ORDER BY id
-        "#,
-        )
-        .fetch_all(pool)
-        .await?;
+async fn stored_geminicli(
+    store: &dyn Store,
+    id: u64,
+) -> Result<Option<DbGeminiCliResource>, PolluxError> {
+    match i64::try_from(id) {
+        Ok(id) => store.get_geminicli_by_id(id).await,
+        Err(_) => Ok(None),
+    }
+}
 
-        Ok(rows)
+async fn stored_codex(store: &dyn Store, id: u64) -> Result<Option<DbCodexResource>, PolluxError> {
+    match i64::try_from(id) {
+        Ok(id) => store.get_codex_by_id(id).await,
+        Err(_) => Ok(None),
     }
+}
 
-    async fn get_codex_by_id(
-        &self,
-        pool: &SqlitePool,
-        id: i64,
-    ) -> Result<DbCodexResource, PolluxError> {
-        let row = sqlx::query_as::<_, DbCodexResource>(
-            r#"
-        SELECT id, email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, status, created_at, updated_at
-        FROM codex
-        WHERE id = ?
-        "#,
-        )
-        .bind(id)
-        .fetch_one(pool)
-        .await?;
+fn open_geminicli(
+    cipher: &TokenCipher,
+    mut row: DbGeminiCliResource,
+) -> Result<DbGeminiCliResource, PolluxError> {
+    let slot = TokenRow::geminicli(&row.sub, &row.project_id);
+    let refresh_token = cipher.open(&slot, REFRESH_TOKEN, &row.refresh_token)?;
+    let access_token = row
+        .access_token
+        .as_deref()
+        .map(|token| cipher.open(&slot, ACCESS_TOKEN, token))
+        .transpose()?;
+    row.refresh_token = refresh_token;
+    row.access_token = access_token;
+    Ok(row)
+}
 
-        Ok(row)
-    }
+fn open_codex(
+    cipher: &TokenCipher,
+    mut row: DbCodexResource,
+) -> Result<DbCodexResource, PolluxError> {
+    let slot = TokenRow::codex(&row.sub, &row.account_id);
+    let refresh_token = cipher.open(&slot, REFRESH_TOKEN, &row.refresh_token)?;
+    let access_token = cipher.open(&slot, ACCESS_TOKEN, &row.access_token)?;
+    row.refresh_token = refresh_token;
+    row.access_token = access_token;
+    Ok(row)
 }
 
-/// Spawn the database actor and return a cloneable handle.
+/// Spawn the database actor and return a cloneable handle. Tokens are stored in plaintext.
+///
+/// The backend follows the scheme of `database_url`: `sqlite:` or `postgres://`.
 pub async fn spawn(database_url: &str) -> DbActorHandle {
+    spawn_with(database_url, None).await
+}
+
+/// Like [`spawn`], but with tokens encrypted at rest under `keyring` when one is given.
+pub async fn spawn_with(database_url: &str, keyring: Option<TokenKeyring>) -> DbActorHandle {
     let (actor, _jh) = ractor::Actor::spawn(
         Some("DbActor".to_string()),
         DbActor,
-        database_url.to_string(),
+        (database_url.to_string(), keyring),
     )
     .await
     .expect("failed to spawn DbActor");
 
     DbActorHandle { actor }
 }
-
-async fn apply_schema(pool: &SqlitePool) -> Result<(), PolluxError> {
-    for stmt in SQLITE_INIT.split(';') {
-        let s = stmt.trim();
-        if s.is_empty() {
-            continue;
-        }
-        sqlx::query(s).execute(pool).await?;
-    }
-    Ok(())
-}
@@ -0,0 +1,469 @@
+//! Envelope encryption of the OAuth token columns.
+//!
+//! Tokens are sealed with AES-256-GCM under a random data key. The data key lives in the
+//! `token_keys` table, itself sealed under the master key from config, so rotating the master
+//! key only re-seals that one row. Sealed values start with [`SEALED_PREFIX`]; anything else is
+//! plaintext written before encryption was switched on, which startup seals in one pass.
+//!
+//! Each value is bound to its table, row and column through the AES-GCM associated data, so
+//! someone who can write to the database cannot move a sealed token to another credential.
+//! Rows are identified by their unique key (`sub` plus `project_id` or `account_id`), which
+//! is known before an insert picks the id.
+
+use aes_gcm::{
+    Aes256Gcm, Key, KeyInit, Nonce,
+    aead::{Aead, Payload},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use tracing::info;
+
+use crate::db::store::{Store, StoredTokens};
+use crate::error::PolluxError;
+
+/// Marks a column value as sealed; the rest is base64 of nonce + ciphertext.
+pub const SEALED_PREFIX: &str = "enc:v1:";
+
+/// Token column names, part of the associated data.
+pub(crate) const REFRESH_TOKEN: &str = "refresh_token";
+pub(crate) const ACCESS_TOKEN: &str = "access_token";
+
+const DATA_KEY_AAD: &[u8] = b"token_keys.wrapped_key";
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+/// Decode a base64 master key, which must be 32 bytes (e.g. `openssl rand -base64 32`).
+pub fn parse_key(encoded: &str) -> Result<[u8; KEY_LEN], String> {
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| format!("token encryption key is not valid base64: {e}"))?;
+    <[u8; KEY_LEN]>::try_from(bytes.as_slice()).map_err(|_| {
+        format!(
+            "token encryption key must be {KEY_LEN} bytes, got {}",
+            bytes.len()
+        )
+    })
+}
+
+/// Master keys from config. The current key seals the data key; previous keys are only tried
+/// when it does not open, after which the data key is re-sealed under the current one.
+#[derive(Clone)]
+pub struct TokenKeyring {
+    current: [u8; KEY_LEN],
+    previous: Vec<[u8; KEY_LEN]>,
+}
+
+impl TokenKeyring {
+    pub fn new(current: &str, previous: &[String]) -> Result<Self, String> {
+        Ok(Self {
+            current: parse_key(current)?,
+            previous: previous
+                .iter()
+                .map(|key| parse_key(key))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl std::fmt::Debug for TokenKeyring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenKeyring")
+            .field("previous", &self.previous.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The credential row a sealed value belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TokenRow<'a> {
+    table: &'static str,
+    sub: &'a str,
+    /// `project_id` for Gemini CLI, `account_id` for Codex.
+    key: &'a str,
+}
+
+impl<'a> TokenRow<'a> {
+    pub(crate) fn geminicli(sub: &'a str, project_id: &'a str) -> Self {
+        Self {
+            table: "gemini_cli",
+            sub,
+            key: project_id,
+        }
+    }
+
+    pub(crate) fn codex(sub: &'a str, account_id: &'a str) -> Self {
+        Self {
+            table: "codex",
+            sub,
+            key: account_id,
+        }
+    }
+
+    fn of(tokens: &'a StoredTokens) -> Self {
+        Self {
+            table: tokens.table,
+            sub: &tokens.sub,
+            key: &tokens.key,
+        }
+    }
+
+    /// JSON keeps the parts apart even when an id contains a separator.
+    fn aad(&self, column: &str) -> Vec<u8> {
+        serde_json::json!([self.table, self.sub, self.key, column])
+            .to_string()
+            .into_bytes()
+    }
+}
+
+/// Seals and opens token column values. Without a data key it stores plaintext, and refuses
+/// to open sealed values.
+#[derive(Clone, Default)]
+pub struct TokenCipher {
+    data_key: Option<Aes256Gcm>,
+}
+
+impl std::fmt::Debug for TokenCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenCipher")
+            .field("enabled", &self.is_enabled())
+            .finish()
+    }
+}
+
+impl TokenCipher {
+    fn with_data_key(data_key: &[u8; KEY_LEN]) -> Self {
+        Self {
+            data_key: Some(aes(data_key)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.data_key.is_some()
+    }
+
+    /// Value to store in `column` of `row`.
+    pub(crate) fn seal(&self, row: &TokenRow<'_>, column: &str, plaintext: &str) -> String {
+        match &self.data_key {
+            Some(cipher) => format!(
+                "{SEALED_PREFIX}{}",
+                seal_bytes(cipher, &row.aad(column), plaintext.as_bytes())
+            ),
+            None => plaintext.to_string(),
+        }
+    }
+
+    /// Plaintext of a value read from `column` of `row`; plaintext values pass through
+    /// unchanged. A value sealed for another row does not open.
+    pub(crate) fn open(
+        &self,
+        row: &TokenRow<'_>,
+        column: &str,
+        stored: &str,
+    ) -> Result<String, PolluxError> {
+        if !is_sealed(stored) {
+            return Ok(stored.to_string());
+        }
+        let cipher = self.data_key.as_ref().ok_or_else(|| {
+            PolluxError::UnexpectedError(format!(
+                "{column} is encrypted but no token encryption key is configured"
+            ))
+        })?;
+        stored
+            .strip_prefix(SEALED_PREFIX)
+            .and_then(|sealed| open_bytes(cipher, &row.aad(column), sealed))
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| {
+                PolluxError::UnexpectedError(format!(
+                    "failed to decrypt {column} of {} credential {}",
+                    row.table, row.key
+                ))
+            })
+    }
+}
+
+/// Set up token encryption for an opened database.
+///
+/// With a keyring, loads the data key (creating it on first use, re-sealing it when a previous
+/// master key opened it) and seals every token still stored in plaintext. Without one, fails
+/// if the database already holds sealed tokens rather than serving them undecrypted.
+pub(crate) async fn init(
+    store: &dyn Store,
+    keyring: Option<&TokenKeyring>,
+) -> Result<TokenCipher, PolluxError> {
+    let wrapped = store.data_key().await?;
+
+    let Some(keyring) = keyring else {
+        let sealed_tokens = store.list_tokens().await?.iter().any(|row| {
+            is_sealed(&row.refresh_token) || row.access_token.as_deref().is_some_and(is_sealed)
+        });
+        if wrapped.is_some() || sealed_tokens {
+            return Err(PolluxError::UnexpectedError(
+                "stored tokens are encrypted; set basic.token_encryption_key".to_string(),
+            ));
+        }
+        return Ok(TokenCipher::default());
+    };
+
+    let wrapped = match wrapped {
+        Some(wrapped) => wrapped,
+        None => {
+            let data_key: [u8; KEY_LEN] = rand::random();
+            if store
+                .insert_data_key(&wrap_data_key(&keyring.current, &data_key))
+                .await?
+            {
+                info!("Token encryption enabled, generated a new data key");
+            }
+            // Another instance may have stored its key first; everyone uses the stored one.
+            store.data_key().await?.ok_or_else(|| {
+                PolluxError::UnexpectedError("token data key vanished after insert".to_string())
+            })?
+        }
+    };
+
+    let data_key = match open_data_key(&aes(&keyring.current), &wrapped) {
+        Some(data_key) => data_key,
+        None => {
+            let data_key = keyring
+                .previous
+                .iter()
+                .find_map(|key| open_data_key(&aes(key), &wrapped))
+                .ok_or_else(|| {
+                    PolluxError::UnexpectedError(
+                        "no configured token encryption key opens the stored data key".to_string(),
+                    )
+                })?;
+            store
+                .update_data_key(&wrap_data_key(&keyring.current, &data_key))
+                .await?;
+            info!("Token data key re-sealed under the current master key");
+            data_key
+        }
+    };
+
+    let cipher = TokenCipher::with_data_key(&data_key);
+    let sealed = seal_plaintext_rows(store, &cipher).await?;
+    if sealed > 0 {
+        info!("Encrypted the tokens of {sealed} existing credential(s)");
+    }
+    Ok(cipher)
+}
+
+fn aes(key: &[u8; KEY_LEN]) -> Aes256Gcm {
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+}
+
+fn seal_bytes(cipher: &Aes256Gcm, aad: &[u8], plaintext: &[u8]) -> String {
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .expect("AES-GCM encryption of an in-memory buffer does not fail");
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    STANDARD.encode(sealed)
+}
+
+fn open_bytes(cipher: &Aes256Gcm, aad: &[u8], encoded: &str) -> Option<Vec<u8>> {
+    let sealed = STANDARD.decode(encoded).ok()?;
+    if sealed.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    cipher
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .ok()
+}
+
+fn open_data_key(master: &Aes256Gcm, wrapped: &str) -> Option<[u8; KEY_LEN]> {
+    let bytes = open_bytes(master, DATA_KEY_AAD, wrapped)?;
+    <[u8; KEY_LEN]>::try_from(bytes.as_slice()).ok()
+}
+
+fn wrap_data_key(master: &[u8; KEY_LEN], data_key: &[u8; KEY_LEN]) -> String {
+    seal_bytes(&aes(master), DATA_KEY_AAD, data_key)
+}
+
+fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+/// Seal every token column still holding plaintext, in one transaction. Returns the number
+/// of rows changed.
+async fn seal_plaintext_rows(
+    store: &dyn Store,
+    cipher: &TokenCipher,
+) -> Result<usize, PolluxError> {
+    let seal = |row: &TokenRow<'_>, column, value: &str| {
+        if is_sealed(value) {
+            value.to_string()
+        } else {
+            cipher.seal(row, column, value)
+        }
+    };
+    let rows: Vec<StoredTokens> = store
+        .list_tokens()
+        .await?
+        .into_iter()
+        .filter(|tokens| {
+            !is_sealed(&tokens.refresh_token)
+                || tokens
+                    .access_token
+                    .as_deref()
+                    .is_some_and(|token| !is_sealed(token))
+        })
+        .map(|tokens| {
+            let row = TokenRow::of(&tokens);
+            let refresh_token = seal(&row, REFRESH_TOKEN, &tokens.refresh_token);
+            let access_token = tokens
+                .access_token
+                .as_deref()
+                .map(|token| seal(&row, ACCESS_TOKEN, token));
+            StoredTokens {
+                refresh_token,
+                access_token,
+                ..tokens
+            }
+        })
+        .collect();
+    let changed = rows.len();
+    if changed > 0 {
+        store.replace_tokens(rows).await?;
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::migrate;
+    use crate::db::sqlite::SqliteStore;
+    use sqlx::SqlitePool;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn key(byte: u8) -> String {
+        STANDARD.encode([byte; KEY_LEN])
+    }
+
+    async fn memory_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("in-memory sqlite");
+        migrate(&pool).await.expect("schema");
+        pool
+    }
+
+    async fn stored_refresh_token(pool: &SqlitePool) -> String {
+        sqlx::query_scalar("SELECT refresh_token FROM codex WHERE id = 1")
+            .fetch_one(pool)
+            .await
+            .expect("row")
+    }
+
+    #[test]
+    fn parse_key_wants_32_base64_bytes() {
+        assert!(parse_key(&key(7)).is_ok());
+        assert!(parse_key("not base64!").is_err());
+        assert!(parse_key(&STANDARD.encode([0u8; 16])).is_err());
+    }
+
+    #[test]
+    fn sealed_values_are_bound_to_their_row_and_column() {
+        let cipher = TokenCipher::with_data_key(&[1; KEY_LEN]);
+        let row = TokenRow::codex("sub", "acct");
+        let sealed = cipher.seal(&row, REFRESH_TOKEN, "rt-secret");
+        assert!(sealed.starts_with(SEALED_PREFIX));
+        assert!(!sealed.contains("rt-secret"));
+        assert_ne!(
+            sealed,
+            cipher.seal(&row, REFRESH_TOKEN, "rt-secret"),
+            "fresh nonce"
+        );
+
+        assert_eq!(
+            cipher.open(&row, REFRESH_TOKEN, &sealed).unwrap(),
+            "rt-secret"
+        );
+        assert!(cipher.open(&row, ACCESS_TOKEN, &sealed).is_err());
+        assert!(
+            cipher
+                .open(&TokenRow::codex("sub", "acct-2"), REFRESH_TOKEN, &sealed)
+                .is_err()
+        );
+        assert!(
+            cipher
+                .open(&TokenRow::geminicli("sub", "acct"), REFRESH_TOKEN, &sealed)
+                .is_err()
+        );
+        assert_eq!(
+            cipher.open(&row, REFRESH_TOKEN, "legacy").unwrap(),
+            "legacy"
+        );
+        assert!(
+            TokenCipher::default()
+                .open(&row, REFRESH_TOKEN, &sealed)
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn init_seals_old_rows_and_rotates_the_master_key() {
+        let pool = memory_pool().await;
+        let store = SqliteStore::new(pool.clone());
+        sqlx::query(
+            "INSERT INTO codex (id, sub, account_id, refresh_token, access_token, expiry, \
+             created_at, updated_at) VALUES (1, 's', 'a', 'rt-plain', 'at-plain', '', '', '')",
+        )
+        .execute(&pool)
+        .await
+        .expect("plaintext row");
+
+        let row = TokenRow::codex("s", "a");
+        let first = TokenKeyring::new(&key(1), &[]).unwrap();
+        let cipher = init(&store, Some(&first)).await.expect("enable");
+        let sealed = stored_refresh_token(&pool).await;
+        assert_eq!(
+            cipher.open(&row, REFRESH_TOKEN, &sealed).unwrap(),
+            "rt-plain"
+        );
+
+        // Without the old key in `previous`, a new key cannot open the data key.
+        let second = TokenKeyring::new(&key(2), &[]).unwrap();
+        assert!(init(&store, Some(&second)).await.is_err());
+        assert!(
+            init(&store, None).await.is_err(),
+            "sealed tokens need a key"
+        );
+
+        let rotating = TokenKeyring::new(&key(2), &[key(1)]).unwrap();
+        let cipher = init(&store, Some(&rotating)).await.expect("rotate");
+        assert_eq!(
+            stored_refresh_token(&pool).await,
+            sealed,
+            "rows are untouched"
+        );
+        assert_eq!(
+            cipher.open(&row, REFRESH_TOKEN, &sealed).unwrap(),
+            "rt-plain"
+        );
+
+        // From now on the new key alone is enough.
+        let cipher = init(&store, Some(&second)).await.expect("rotated");
+        assert_eq!(
+            cipher.open(&row, REFRESH_TOKEN, &sealed).unwrap(),
+            "rt-plain"
+        );
+    }
+}
@@ -2,20 +2,33 @@
 //!
 //! Layout:
 //! - `models.rs`: Rust structs mirroring DB rows
-//! - `schema.rs`: SQL DDL for initializing the database (SQLite-first)
+//! - `crypto.rs`: encryption of the stored OAuth tokens
+//! - `schema.rs`: versioned migrations that create and upgrade the database
+//! - `store.rs`: the storage trait, implemented by `sqlite.rs` and `postgres.rs`
 
 pub mod actor;
+pub mod crypto;
 pub mod models;
 pub mod patch;
 pub mod schema;
+pub mod store;
 pub mod traits;
 
 mod patch_impl;
+mod postgres;
+mod sqlite;
 
-pub use models::{DbCodexResource, DbGeminiCliResource};
+pub use models::{
+    DbCodexResource, DbGeminiCliResource, DbModelState, DbPage, DbUsageSummary, ModelStateUpdate,
+    RefreshLease, UsageCreate, UsageQuery,
+};
 pub use patch::{
     CodexCreate, CodexPatch, GeminiCliCreate, GeminiCliPatch, ProviderCreate, ProviderPatch,
 };
-pub use schema::SQLITE_INIT;
+pub use schema::{
+    MIGRATIONS, POSTGRES_MIGRATIONS, POSTGRES_SCHEMA_VERSION, SCHEMA_VERSION, migrate,
+    migrate_postgres,
+};
 
-pub use actor::{DbActorHandle, spawn};
+pub use actor::{DbActorHandle, spawn, spawn_with};
+pub use crypto::TokenKeyring;
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
@@ -11,6 +11,7 @@ pub struct DbGeminiCliResource {
     pub refresh_token: String,
     pub access_token: Option<String>,
     pub expiry: DateTime<Utc>,
+    pub tier: Option<String>,
     pub status: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -30,3 +31,77 @@ pub struct DbCodexResource {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+/// One page of rows plus the total row count of the table (all statuses).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DbPage<T> {
+    pub total: i64,
+    pub items: Vec<T>,
+}
+
+/// Token counts of one completed upstream call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageCreate {
+    pub key_name: String,
+    pub provider: &'static str,
+    pub model: String,
+    pub credential_id: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cached_tokens: i64,
+    pub reasoning_tokens: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Scheduler state of one credential for one model, as saved for the next start.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, FromRow)]
+pub struct DbModelState {
+    pub credential_id: i64,
+    pub model: String,
+    /// Upstream reported the model unsupported for this credential.
+    pub unsupported: bool,
+    pub cooldown_until: Option<DateTime<Utc>>,
+}
+
+/// Merged into the stored state: `unsupported` is sticky, a `cooldown_until` replaces the
+/// previous deadline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelStateUpdate {
+    pub provider: &'static str,
+    pub credential_id: i64,
+    pub model: String,
+    pub unsupported: bool,
+    pub cooldown_until: Option<DateTime<Utc>>,
+}
+
+/// Claim of one instance to refresh a credential's tokens until `expires_at`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefreshLease {
+    pub provider: &'static str,
+    pub credential_id: i64,
+    pub holder: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Filters for usage aggregation; `from` and `to` are inclusive UTC days.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct UsageQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub key: Option<String>,
+    pub model: Option<String>,
+}
+
+/// Usage summed per UTC day, key, provider and model.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, FromRow)]
+pub struct DbUsageSummary {
+    pub day: String,
+    pub key_name: String,
+    pub provider: String,
+    pub model: String,
+    pub requests: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cached_tokens: i64,
+    pub reasoning_tokens: i64,
+}
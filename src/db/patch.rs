@@ -14,6 +14,7 @@ pub struct GeminiCliCreate {
     pub refresh_token: String,
     pub access_token: Option<String>,
     pub expiry: DateTime<Utc>,
+    pub tier: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
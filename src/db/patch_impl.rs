@@ -1,73 +1,34 @@
 //! ProviderPatch -> DbPatchable implementation.
 //!
-//! This sits in the `db` module because it contains SQL/table knowledge.
+//! The SQL lives with each [`Store`] backend; this validates the id, logs what changed and
+//! turns a missing row into an error.
 
 use async_trait::async_trait;
-use chrono::Utc;
-use sqlx::SqlitePool;
 use tracing::debug;
 
+use crate::db::store::Store;
 use crate::error::PolluxError;
-use crate::patches::{CodexPatch, DbPatchable, GeminiCliPatch, ProviderPatch};
+use crate::patches::{DbPatchable, ProviderPatch};
 
 #[async_trait]
 impl DbPatchable for ProviderPatch {
-    async fn apply_patch(&self, pool: &SqlitePool) -> Result<(), PolluxError> {
+    async fn apply_patch(&self, store: &dyn Store) -> Result<(), PolluxError> {
         match self {
             ProviderPatch::GeminiCli { id, patch } => {
                 let id = i64::try_from(*id).map_err(|_| {
                     PolluxError::UnexpectedError(format!("Invalid GeminiCli id {id}"))
                 })?;
 
-                let GeminiCliPatch {
-                    email,
-                    refresh_token,
-                    access_token,
-                    expiry,
-                    status,
-                } = patch.clone();
-
-                let email_set = email.is_some();
-                let refresh_token_set = refresh_token.is_some();
-                let access_token_set = access_token.is_some();
-                let expiry_set = expiry.is_some();
-                let status_set = status.is_some();
-                let updated_at = Utc::now();
-
-                let res = sqlx::query!(
-                    r#"
-                    UPDATE gemini_cli
-                    SET
-                        email = COALESCE(?, email),
-                        refresh_token = COALESCE(?, refresh_token),
-                        access_token = COALESCE(?, access_token),
-                        expiry = COALESCE(?, expiry),
-                        status = COALESCE(?, status),
-                        updated_at = ?
-                    WHERE id = ?
-                    "#,
-                    email,
-                    refresh_token,
-                    access_token,
-                    expiry,
-                    status,
-                    updated_at,
-                    id,
-                )
-                .execute(pool)
-                .await?;
-
-                let affected = res.rows_affected();
+                let affected = store.patch_geminicli(id, patch.clone()).await?;
                 debug!(
                     provider = "gemini_cli",
                     id,
                     affected,
-                    updated_at = %updated_at,
-                    email_set,
-                    refresh_token_set,
-                    access_token_set,
-                    expiry_set,
-                    status_set,
+                    email_set = patch.email.is_some(),
+                    refresh_token_set = patch.refresh_token.is_some(),
+                    access_token_set = patch.access_token.is_some(),
+                    expiry_set = patch.expiry.is_some(),
+                    status_set = patch.status.is_some(),
                     "db patch applied"
                 );
 
@@ -84,71 +45,19 @@ impl DbPatchable for ProviderPatch {
                 let id = i64::try_from(*id)
                     .map_err(|_| PolluxError::UnexpectedError(format!("Invalid Codex id {id}")))?;
 
-                let CodexPatch {
-                    email,
-                    account_id,
-                    sub,
-                    refresh_token,
-                    access_token,
-                    expiry,
-                    chatgpt_plan_type,
-                    status,
-                } = patch.clone();
-
-                let email_set = email.is_some();
-                let account_id_set = account_id.is_some();
-                let sub_set = sub.is_some();
-                let refresh_token_set = refresh_token.is_some();
-                let access_token_set = access_token.is_some();
-                let expiry_set = expiry.is_some();
-                let chatgpt_plan_type_set = chatgpt_plan_type.is_some();
-                let status_set = status.is_some();
-                let updated_at = Utc::now();
-
-                // Use the non-macro query API so we don't have to keep SQLx's offline cache in sync.
-                let res = sqlx::query(
-                    r#"
-                    UPDATE codex
-                    SET
-                        email = COALESCE(?, email),
-                        account_id = COALESCE(?, account_id),
-                        sub = COALESCE(?, sub),
-                        refresh_token = COALESCE(?, refresh_token),
-                        access_token = COALESCE(?, access_token),
-                        expiry = COALESCE(?, expiry),
-                        chatgpt_plan_type = COALESCE(?, chatgpt_plan_type),
-                        status = COALESCE(?, status),
-                        updated_at = ?
-                    WHERE id = ?
-                    "#,
-                )
-                .bind(email)
-                .bind(account_id)
-                .bind(sub)
-                .bind(refresh_token)
-                .bind(access_token)
-                .bind(expiry)
-                .bind(chatgpt_plan_type)
-                .bind(status)
-                .bind(updated_at)
-                .bind(id)
-                .execute(pool)
-                .await?;
-
-                let affected = res.rows_affected();
+                let affected = store.patch_codex(id, patch.clone()).await?;
                 debug!(
                     provider = "codex",
                     id,
                     affected,
-                    updated_at = %updated_at,
-                    email_set,
-                    account_id_set,
-                    sub_set,
-                    refresh_token_set,
-                    access_token_set,
-                    expiry_set,
-                    chatgpt_plan_type_set,
-                    status_set,
+                    email_set = patch.email.is_some(),
+                    account_id_set = patch.account_id.is_some(),
+                    sub_set = patch.sub.is_some(),
+                    refresh_token_set = patch.refresh_token.is_some(),
+                    access_token_set = patch.access_token.is_some(),
+                    expiry_set = patch.expiry.is_some(),
+                    chatgpt_plan_type_set = patch.chatgpt_plan_type.is_some(),
+                    status_set = patch.status.is_some(),
                     "db patch applied"
                 );
 
@@ -0,0 +1,537 @@
+//! PostgreSQL storage backend, for several Pollux instances sharing one credential store.
+//!
+//! Same tables as SQLite, with native types: `BOOLEAN` flags and `TIMESTAMPTZ` times.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+
+use crate::db::models::{
+    DbCodexResource, DbGeminiCliResource, DbModelState, DbPage, DbUsageSummary, ModelStateUpdate,
+    RefreshLease, UsageCreate, UsageQuery,
+};
+use crate::db::patch::{CodexPatch, GeminiCliPatch, ProviderCreate};
+use crate::db::schema;
+use crate::db::store::{CREDENTIAL_TABLES, Store, StoredTokens};
+use crate::error::PolluxError;
+
+pub(crate) struct PostgresStore {
+    pool: PgPool,
+}
+
+impl PostgresStore {
+    pub(crate) async fn connect(database_url: &str) -> Result<Self, PolluxError> {
+        let pool = PgPoolOptions::new().connect(database_url).await?;
+        schema::migrate_postgres(&pool).await?;
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl Store for PostgresStore {
+    async fn create(&self, create: ProviderCreate) -> Result<i64, PolluxError> {
+        let now = Utc::now();
+        let id = match create {
+            ProviderCreate::GeminiCli(c) => {
+                sqlx::query_scalar(
+                    r#"
+                INSERT INTO gemini_cli (
+                    email, sub, project_id, refresh_token, access_token, expiry, tier, status, created_at, updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, TRUE, $8, $8)
+                ON CONFLICT(sub, project_id) DO UPDATE SET
+                    email = excluded.email,
+                    refresh_token = excluded.refresh_token,
+                    access_token = excluded.access_token,
+                    expiry = excluded.expiry,
+                    tier = COALESCE(excluded.tier, gemini_cli.tier),
+                    status = TRUE,
+                    updated_at = excluded.updated_at
+                RETURNING id
+                "#,
+                )
+                .bind(c.email)
+                .bind(c.sub)
+                .bind(c.project_id)
+                .bind(c.refresh_token)
+                .bind(c.access_token)
+                .bind(c.expiry)
+                .bind(c.tier)
+                .bind(now)
+                .fetch_one(&self.pool)
+                .await?
+            }
+
+            ProviderCreate::Codex(c) => {
+                sqlx::query_scalar(
+                    r#"
+                INSERT INTO codex (
+                    email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, status, created_at, updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, TRUE, $8, $8)
+                ON CONFLICT(sub, account_id) DO UPDATE SET
+                    email = COALESCE(excluded.email, codex.email),
+                    -- An older copy of the tokens (say, a re-imported export) must not
+                    -- replace ones refreshed since: the stored refresh token may be the only
+                    -- one left that works.
+                    refresh_token = CASE WHEN excluded.expiry >= codex.expiry
+                        THEN excluded.refresh_token ELSE codex.refresh_token END,
+                    access_token = CASE WHEN excluded.expiry >= codex.expiry
+                        THEN excluded.access_token ELSE codex.access_token END,
+                    expiry = CASE WHEN excluded.expiry >= codex.expiry
+                        THEN excluded.expiry ELSE codex.expiry END,
+                    chatgpt_plan_type = COALESCE(excluded.chatgpt_plan_type, codex.chatgpt_plan_type),
+                    status = TRUE,
+                    updated_at = excluded.updated_at
+                RETURNING id
+                "#,
+                )
+                .bind(c.email)
+                .bind(c.sub)
+                .bind(c.account_id)
+                .bind(c.refresh_token)
+                .bind(c.access_token)
+                .bind(c.expiry)
+                .bind(c.chatgpt_plan_type)
+                .bind(now)
+                .fetch_one(&self.pool)
+                .await?
+            }
+        };
+        Ok(id)
+    }
+
+    async fn patch_geminicli(&self, id: i64, patch: GeminiCliPatch) -> Result<u64, PolluxError> {
+        let res = sqlx::query(
+            r#"
+            UPDATE gemini_cli
+            SET
+                email = COALESCE($1, email),
+                refresh_token = COALESCE($2, refresh_token),
+                access_token = COALESCE($3, access_token),
+                expiry = COALESCE($4, expiry),
+                status = COALESCE($5, status),
+                updated_at = $6
+            WHERE id = $7
+            "#,
+        )
+        .bind(patch.email)
+        .bind(patch.refresh_token)
+        .bind(patch.access_token)
+        .bind(patch.expiry)
+        .bind(patch.status)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    async fn patch_codex(&self, id: i64, patch: CodexPatch) -> Result<u64, PolluxError> {
+        let res = sqlx::query(
+            r#"
+            UPDATE codex
+            SET
+                email = COALESCE($1, email),
+                account_id = COALESCE($2, account_id),
+                sub = COALESCE($3, sub),
+                refresh_token = COALESCE($4, refresh_token),
+                access_token = COALESCE($5, access_token),
+                expiry = COALESCE($6, expiry),
+                chatgpt_plan_type = COALESCE($7, chatgpt_plan_type),
+                status = COALESCE($8, status),
+                updated_at = $9
+            WHERE id = $10
+            "#,
+        )
+        .bind(patch.email)
+        .bind(patch.account_id)
+        .bind(patch.sub)
+        .bind(patch.refresh_token)
+        .bind(patch.access_token)
+        .bind(patch.expiry)
+        .bind(patch.chatgpt_plan_type)
+        .bind(patch.status)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    async fn list_active_geminicli(&self) -> Result<Vec<DbGeminiCliResource>, PolluxError> {
+        let rows = sqlx::query_as::<_, DbGeminiCliResource>(
+            r#"
+        SELECT id, email, sub, project_id, refresh_token, access_token, expiry, tier, status, created_at, updated_at
+        FROM gemini_cli
+        WHERE status
+        ORDER BY id
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn list_active_codex(&self) -> Result<Vec<DbCodexResource>, PolluxError> {
+        let rows = sqlx::query_as::<_, DbCodexResource>(
+            r#"
+        SELECT id, email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, status, created_at, updated_at
+        FROM codex
+        WHERE status
+        ORDER BY id
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn get_geminicli_by_id(
+        &self,
+        id: i64,
+    ) -> Result<Option<DbGeminiCliResource>, PolluxError> {
+        let row = sqlx::query_as::<_, DbGeminiCliResource>(
+            r#"
+        SELECT id, email, sub, project_id, refresh_token, access_token, expiry, tier, status, created_at, updated_at
+        FROM gemini_cli
+        WHERE id = $1
+        "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn get_codex_by_id(&self, id: i64) -> Result<Option<DbCodexResource>, PolluxError> {
+        let row = sqlx::query_as::<_, DbCodexResource>(
+            r#"
+        SELECT id, email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, status, created_at, updated_at
+        FROM codex
+        WHERE id = $1
+        "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list_geminicli(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<DbPage<DbGeminiCliResource>, PolluxError> {
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM gemini_cli")
+            .fetch_one(&self.pool)
+            .await?;
+
+        let items = sqlx::query_as::<_, DbGeminiCliResource>(
+            r#"
+        SELECT id, email, sub, project_id, refresh_token, access_token, expiry, tier, status, created_at, updated_at
+        FROM gemini_cli
+        ORDER BY id
+        LIMIT $1 OFFSET $2
+        "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(DbPage { total, items })
+    }
+
+    async fn list_codex(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<DbPage<DbCodexResource>, PolluxError> {
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM codex")
+            .fetch_one(&self.pool)
+            .await?;
+
+        let items = sqlx::query_as::<_, DbCodexResource>(
+            r#"
+        SELECT id, email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, status, created_at, updated_at
+        FROM codex
+        ORDER BY id
+        LIMIT $1 OFFSET $2
+        "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(DbPage { total, items })
+    }
+
+    /// `table` is always one of our own table names, never user input.
+    async fn delete_credential(
+        &self,
+        table: &'static str,
+        provider: &'static str,
+        id: i64,
+    ) -> Result<u64, PolluxError> {
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query(&format!("DELETE FROM {table} WHERE id = $1"))
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "DELETE FROM credential_model_state WHERE provider = $1 AND credential_id = $2",
+        )
+        .bind(provider)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn record_usage(&self, usage: UsageCreate) -> Result<(), PolluxError> {
+        sqlx::query(
+            r#"
+        INSERT INTO token_usage (
+            key_name, provider, model, credential_id,
+            input_tokens, output_tokens, cached_tokens, reasoning_tokens, created_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+        )
+        .bind(usage.key_name)
+        .bind(usage.provider)
+        .bind(usage.model)
+        .bind(usage.credential_id)
+        .bind(usage.input_tokens)
+        .bind(usage.output_tokens)
+        .bind(usage.cached_tokens)
+        .bind(usage.reasoning_tokens)
+        .bind(usage.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `SUM` of a `BIGINT` is a `NUMERIC` in Postgres, hence the casts.
+    async fn query_usage(&self, query: UsageQuery) -> Result<Vec<DbUsageSummary>, PolluxError> {
+        let rows = sqlx::query_as::<_, DbUsageSummary>(
+            r#"
+        SELECT
+            to_char(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD') AS day,
+            key_name,
+            provider,
+            model,
+            COUNT(*) AS requests,
+            SUM(input_tokens)::BIGINT AS input_tokens,
+            SUM(output_tokens)::BIGINT AS output_tokens,
+            SUM(cached_tokens)::BIGINT AS cached_tokens,
+            SUM(reasoning_tokens)::BIGINT AS reasoning_tokens
+        FROM token_usage
+        WHERE ($1::TEXT IS NULL OR to_char(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD') >= $1)
+          AND ($2::TEXT IS NULL OR to_char(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD') <= $2)
+          AND ($3::TEXT IS NULL OR key_name = $3)
+          AND ($4::TEXT IS NULL OR model = $4)
+        GROUP BY day, key_name, provider, model
+        ORDER BY day, key_name, provider, model
+        "#,
+        )
+        .bind(query.from.map(|day| day.to_string()))
+        .bind(query.to.map(|day| day.to_string()))
+        .bind(query.key)
+        .bind(query.model)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn save_model_state(&self, update: ModelStateUpdate) -> Result<(), PolluxError> {
+        sqlx::query(
+            r#"
+        INSERT INTO credential_model_state (
+            provider, credential_id, model, unsupported, cooldown_until, updated_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT(provider, credential_id, model) DO UPDATE SET
+            unsupported = credential_model_state.unsupported OR excluded.unsupported,
+            cooldown_until = COALESCE(excluded.cooldown_until, credential_model_state.cooldown_until),
+            updated_at = excluded.updated_at
+        "#,
+        )
+        .bind(update.provider)
+        .bind(update.credential_id)
+        .bind(update.model)
+        .bind(update.unsupported)
+        .bind(update.cooldown_until)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn list_model_state(
+        &self,
+        provider: &'static str,
+    ) -> Result<Vec<DbModelState>, PolluxError> {
+        sqlx::query(
+            r#"
+        DELETE FROM credential_model_state
+        WHERE provider = $1
+          AND NOT unsupported
+          AND (cooldown_until IS NULL OR cooldown_until <= $2)
+        "#,
+        )
+        .bind(provider)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        let rows = sqlx::query_as::<_, DbModelState>(
+            r#"
+        SELECT credential_id, model, unsupported, cooldown_until
+        FROM credential_model_state
+        WHERE provider = $1
+        ORDER BY credential_id, model
+        "#,
+        )
+        .bind(provider)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn clear_model_state(
+        &self,
+        provider: &'static str,
+        credential_id: i64,
+    ) -> Result<(), PolluxError> {
+        sqlx::query(
+            "DELETE FROM credential_model_state WHERE provider = $1 AND credential_id = $2",
+        )
+        .bind(provider)
+        .bind(credential_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn acquire_refresh_lease(&self, lease: RefreshLease) -> Result<bool, PolluxError> {
+        let res = sqlx::query(
+            r#"
+        INSERT INTO refresh_lease (provider, credential_id, holder, expires_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT(provider, credential_id) DO UPDATE SET
+            holder = excluded.holder,
+            expires_at = excluded.expires_at
+        WHERE refresh_lease.holder = excluded.holder OR refresh_lease.expires_at <= $5
+        "#,
+        )
+        .bind(lease.provider)
+        .bind(lease.credential_id)
+        .bind(lease.holder)
+        .bind(lease.expires_at)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn release_refresh_lease(
+        &self,
+        provider: &'static str,
+        credential_id: i64,
+        holder: &str,
+    ) -> Result<(), PolluxError> {
+        sqlx::query(
+            "DELETE FROM refresh_lease WHERE provider = $1 AND credential_id = $2 AND holder = $3",
+        )
+        .bind(provider)
+        .bind(credential_id)
+        .bind(holder)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn data_key(&self) -> Result<Option<String>, PolluxError> {
+        let wrapped = sqlx::query_scalar("SELECT wrapped_key FROM token_keys WHERE id = 1")
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(wrapped)
+    }
+
+    async fn insert_data_key(&self, wrapped_key: &str) -> Result<bool, PolluxError> {
+        let res = sqlx::query(
+            "INSERT INTO token_keys (id, wrapped_key, updated_at) VALUES (1, $1, $2) \
+             ON CONFLICT(id) DO NOTHING",
+        )
+        .bind(wrapped_key)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn update_data_key(&self, wrapped_key: &str) -> Result<(), PolluxError> {
+        sqlx::query("UPDATE token_keys SET wrapped_key = $1, updated_at = $2 WHERE id = 1")
+            .bind(wrapped_key)
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn list_tokens(&self) -> Result<Vec<StoredTokens>, PolluxError> {
+        let mut tokens = Vec::new();
+        for (table, key_column) in CREDENTIAL_TABLES {
+            let rows: Vec<(i64, String, String, String, Option<String>)> =
+                sqlx::query_as(&format!(
+                    "SELECT id, sub, {key_column}, refresh_token, access_token FROM {table} \
+                     ORDER BY id"
+                ))
+                .fetch_all(&self.pool)
+                .await?;
+            tokens.extend(
+                rows.into_iter()
+                    .map(|(id, sub, key, refresh_token, access_token)| StoredTokens {
+                        table,
+                        id,
+                        sub,
+                        key,
+                        refresh_token,
+                        access_token,
+                    }),
+            );
+        }
+        Ok(tokens)
+    }
+
+    async fn replace_tokens(&self, tokens: Vec<StoredTokens>) -> Result<(), PolluxError> {
+        let mut tx = self.pool.begin().await?;
+        for row in tokens {
+            sqlx::query(&format!(
+                "UPDATE {} SET refresh_token = $1, access_token = $2 WHERE id = $3",
+                row.table
+            ))
+            .bind(row.refresh_token)
+            .bind(row.access_token)
+            .bind(row.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
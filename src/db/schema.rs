@@ -1,12 +1,47 @@
-//! SQL DDL for initializing the database schema.
-//! SQLite-first design; can be adapted for other RDBMS.
+//! Versioned SQL migrations and the runners that apply them, one list per backend.
+//!
+//! The `schema_version` table records every migration a database has been through. Startup
+//! applies the ones it is missing, in order, each in its own transaction. Databases created
+//! before versioning (v0.4.0 and earlier) have no `schema_version` and start from version 0,
+//! which is why every step is idempotent: tables and indexes use `IF NOT EXISTS`, and columns
+//! are only added when missing. PostgreSQL support came later, so its list starts from the
+//! current layout.
 
-/// SQLite schema includes:
-/// - `gemini_cli` table (Gemini CLI provider, one (sub, project_id) per row)
-/// - `codex` table (Codex provider, one (sub, account_id) per row)
-pub const SQLITE_INIT: &str = r#"
+use chrono::Utc;
+use sqlx::{PgPool, SqlitePool};
+use tracing::info;
+
+use crate::error::PolluxError;
+
+/// One change to the schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationStep {
+    /// `;`-separated statements, run in order. Keep `;` out of comments.
+    Sql(&'static str),
+    /// `ALTER TABLE .. ADD COLUMN`, skipped when the column already exists.
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub steps: &'static [MigrationStep],
+}
+
+/// Every SQLite migration, oldest first. Append only: a shipped migration never changes.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "provider tables (v0.4.0 layout)",
+        steps: &[MigrationStep::Sql(
+            r#"
 -- ---------------------------------------------------------------------------
--- Gemini CLI provider
+-- Gemini CLI provider (one (sub, project_id) per row)
 -- ---------------------------------------------------------------------------
 CREATE TABLE IF NOT EXISTS gemini_cli (
     id INTEGER PRIMARY KEY NOT NULL,
@@ -43,4 +78,357 @@ CREATE TABLE IF NOT EXISTS codex (
 );
 
 CREATE INDEX IF NOT EXISTS idx_codex_status ON codex(status);
-"#;
+"#,
+        )],
+    },
+    Migration {
+        version: 2,
+        description: "token usage",
+        steps: &[MigrationStep::Sql(
+            r#"
+-- One row per completed upstream call.
+CREATE TABLE IF NOT EXISTS token_usage (
+    id INTEGER PRIMARY KEY NOT NULL,
+    key_name TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    credential_id INTEGER NOT NULL,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    cached_tokens INTEGER NOT NULL,
+    reasoning_tokens INTEGER NOT NULL,
+    created_at TEXT NOT NULL -- RFC3339
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_usage_created_at ON token_usage(created_at);
+"#,
+        )],
+    },
+    Migration {
+        version: 3,
+        description: "per-credential model state",
+        steps: &[MigrationStep::Sql(
+            r#"
+-- Scheduler state per (credential, model), restored on startup.
+-- Keyed by model name so it survives reordering `model_list`.
+CREATE TABLE IF NOT EXISTS credential_model_state (
+    provider TEXT NOT NULL,
+    credential_id INTEGER NOT NULL,
+    model TEXT NOT NULL,
+    unsupported INTEGER NOT NULL DEFAULT 0,
+    cooldown_until TEXT NULL, -- RFC3339
+    updated_at TEXT NOT NULL, -- RFC3339
+    PRIMARY KEY (provider, credential_id, model)
+);
+"#,
+        )],
+    },
+    Migration {
+        version: 4,
+        description: "gemini_cli tier",
+        steps: &[MigrationStep::AddColumn {
+            table: "gemini_cli",
+            column: "tier",
+            definition: "TEXT NULL",
+        }],
+    },
+    Migration {
+        version: 5,
+        description: "token encryption keys",
+        steps: &[MigrationStep::Sql(
+            r#"
+-- Data key for token encryption, sealed under the configured master key.
+-- At most one row (id = 1), absent while encryption is off.
+CREATE TABLE IF NOT EXISTS token_keys (
+    id INTEGER PRIMARY KEY NOT NULL,
+    wrapped_key TEXT NOT NULL,
+    updated_at TEXT NOT NULL -- RFC3339
+);
+"#,
+        )],
+    },
+    Migration {
+        version: 6,
+        description: "refresh leases",
+        steps: &[MigrationStep::Sql(
+            r#"
+-- Which instance is refreshing a credential's tokens, until when.
+-- Rows outlive their lease, an expired one is free to take.
+CREATE TABLE IF NOT EXISTS refresh_lease (
+    provider TEXT NOT NULL,
+    credential_id INTEGER NOT NULL,
+    holder TEXT NOT NULL,
+    expires_at TEXT NOT NULL, -- RFC3339
+    PRIMARY KEY (provider, credential_id)
+);
+"#,
+        )],
+    },
+];
+
+/// Schema version this binary brings a SQLite database up to.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+/// Every PostgreSQL migration, oldest first. Append only, like [`MIGRATIONS`].
+pub const POSTGRES_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        steps: &[MigrationStep::Sql(
+            r#"
+CREATE TABLE IF NOT EXISTS gemini_cli (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    email TEXT NULL,
+    sub TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NULL,
+    expiry TIMESTAMPTZ NOT NULL,
+    tier TEXT NULL,
+    status BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE(sub, project_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_gemini_cli_status ON gemini_cli(status);
+
+CREATE TABLE IF NOT EXISTS codex (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    email TEXT NULL,
+    sub TEXT NOT NULL,
+    account_id TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NOT NULL,
+    expiry TIMESTAMPTZ NOT NULL,
+    chatgpt_plan_type TEXT NULL,
+    status BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE(sub, account_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_codex_status ON codex(status);
+
+CREATE TABLE IF NOT EXISTS token_usage (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    key_name TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    credential_id BIGINT NOT NULL,
+    input_tokens BIGINT NOT NULL,
+    output_tokens BIGINT NOT NULL,
+    cached_tokens BIGINT NOT NULL,
+    reasoning_tokens BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_usage_created_at ON token_usage(created_at);
+
+CREATE TABLE IF NOT EXISTS credential_model_state (
+    provider TEXT NOT NULL,
+    credential_id BIGINT NOT NULL,
+    model TEXT NOT NULL,
+    unsupported BOOLEAN NOT NULL DEFAULT FALSE,
+    cooldown_until TIMESTAMPTZ NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (provider, credential_id, model)
+);
+
+CREATE TABLE IF NOT EXISTS token_keys (
+    id BIGINT PRIMARY KEY,
+    wrapped_key TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+"#,
+        )],
+    },
+    Migration {
+        version: 2,
+        description: "refresh leases",
+        steps: &[MigrationStep::Sql(
+            r#"
+CREATE TABLE IF NOT EXISTS refresh_lease (
+    provider TEXT NOT NULL,
+    credential_id BIGINT NOT NULL,
+    holder TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (provider, credential_id)
+);
+"#,
+        )],
+    },
+];
+
+/// Schema version this binary brings a PostgreSQL database up to.
+pub const POSTGRES_SCHEMA_VERSION: u32 = POSTGRES_MIGRATIONS[POSTGRES_MIGRATIONS.len() - 1].version;
+
+/// Advisory lock key that serializes migrations of instances starting together.
+const POSTGRES_MIGRATION_LOCK: i64 = 0x706f_6c6c_7578;
+
+/// Bring `pool` up to [`SCHEMA_VERSION`] and return the version it was at before.
+///
+/// Fails without touching anything if the database was migrated by a newer binary.
+pub async fn migrate(pool: &SqlitePool) -> Result<u32, PolluxError> {
+    sqlx::query(
+        r#"
+    CREATE TABLE IF NOT EXISTS schema_version (
+        version INTEGER PRIMARY KEY NOT NULL,
+        description TEXT NOT NULL,
+        applied_at TEXT NOT NULL -- RFC3339
+    )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    let current: u32 = sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_version")
+        .fetch_one(pool)
+        .await?;
+    if current > SCHEMA_VERSION {
+        return Err(newer_schema(current, SCHEMA_VERSION));
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let mut tx = pool.begin().await?;
+        for step in migration.steps {
+            match *step {
+                MigrationStep::Sql(script) => {
+                    for stmt in script.split(';').map(str::trim) {
+                        if !stmt.is_empty() {
+                            sqlx::query(stmt).execute(&mut *tx).await?;
+                        }
+                    }
+                }
+                MigrationStep::AddColumn {
+                    table,
+                    column,
+                    definition,
+                } => {
+                    let present: bool = sqlx::query_scalar(
+                        "SELECT EXISTS(SELECT 1 FROM pragma_table_info(?) WHERE name = ?)",
+                    )
+                    .bind(table)
+                    .bind(column)
+                    .fetch_one(&mut *tx)
+                    .await?;
+                    if !present {
+                        sqlx::query(&format!(
+                            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+                        ))
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+            }
+        }
+        sqlx::query(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?, ?, ?)",
+        )
+        .bind(migration.version)
+        .bind(migration.description)
+        .bind(Utc::now())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        info!(
+            "Applied schema migration {}: {}",
+            migration.version, migration.description
+        );
+    }
+    Ok(current)
+}
+
+/// Bring `pool` up to [`POSTGRES_SCHEMA_VERSION`] and return the version it was at before.
+///
+/// Holds an advisory lock throughout, so of several instances starting at once one migrates
+/// and the others find the work done.
+pub async fn migrate_postgres(pool: &PgPool) -> Result<u32, PolluxError> {
+    let mut conn = pool.acquire().await?;
+    sqlx::query("SELECT pg_advisory_lock($1)")
+        .bind(POSTGRES_MIGRATION_LOCK)
+        .execute(&mut *conn)
+        .await?;
+    let res = migrate_postgres_locked(&mut conn).await;
+    sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(POSTGRES_MIGRATION_LOCK)
+        .execute(&mut *conn)
+        .await?;
+    res
+}
+
+async fn migrate_postgres_locked(conn: &mut sqlx::PgConnection) -> Result<u32, PolluxError> {
+    sqlx::query(
+        r#"
+    CREATE TABLE IF NOT EXISTS schema_version (
+        version INTEGER PRIMARY KEY,
+        description TEXT NOT NULL,
+        applied_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let current: i32 = sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_version")
+        .fetch_one(&mut *conn)
+        .await?;
+    let current = u32::try_from(current).unwrap_or_default();
+    if current > POSTGRES_SCHEMA_VERSION {
+        return Err(newer_schema(current, POSTGRES_SCHEMA_VERSION));
+    }
+
+    for migration in POSTGRES_MIGRATIONS.iter().filter(|m| m.version > current) {
+        let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+        for step in migration.steps {
+            let sql = match *step {
+                MigrationStep::Sql(script) => script.to_string(),
+                MigrationStep::AddColumn {
+                    table,
+                    column,
+                    definition,
+                } => format!("ALTER TABLE {table} ADD COLUMN IF NOT EXISTS {column} {definition}"),
+            };
+            for stmt in sql.split(';').map(str::trim) {
+                if !stmt.is_empty() {
+                    sqlx::query(stmt).execute(&mut *tx).await?;
+                }
+            }
+        }
+        sqlx::query(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES ($1, $2, $3)",
+        )
+        .bind(i32::try_from(migration.version).unwrap_or(i32::MAX))
+        .bind(migration.description)
+        .bind(Utc::now())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        info!(
+            "Applied schema migration {}: {}",
+            migration.version, migration.description
+        );
+    }
+    Ok(current)
+}
+
+fn newer_schema(current: u32, supported: u32) -> PolluxError {
+    PolluxError::UnexpectedError(format!(
+        "database schema version {current} is newer than this build supports \
+         ({supported}); upgrade Pollux or point it at another database"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_are_numbered_in_order() {
+        for migrations in [MIGRATIONS, POSTGRES_MIGRATIONS] {
+            for (position, migration) in migrations.iter().enumerate() {
+                assert_eq!(migration.version as usize, position + 1, "{migration:?}");
+            }
+        }
+    }
+}
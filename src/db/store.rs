@@ -0,0 +1,147 @@
+//! Storage backends behind the `DbActor`.
+//!
+//! [`Store`] holds the SQL for one database engine; the actor on top of it owns encryption and
+//! error mapping, so both backends behave the same. [`connect`] picks the backend from the
+//! scheme of `basic.database_url`.
+
+use async_trait::async_trait;
+
+use crate::db::models::{
+    DbCodexResource, DbGeminiCliResource, DbModelState, DbPage, DbUsageSummary, ModelStateUpdate,
+    RefreshLease, UsageCreate, UsageQuery,
+};
+use crate::db::patch::{CodexPatch, GeminiCliPatch, ProviderCreate};
+use crate::db::{postgres::PostgresStore, sqlite::SqliteStore};
+use crate::error::PolluxError;
+
+/// Credential tables, named the way the SQL knows them, each with the column that identifies
+/// a row together with `sub`.
+pub const CREDENTIAL_TABLES: [(&str, &str); 2] =
+    [("gemini_cli", "project_id"), ("codex", "account_id")];
+
+/// Token columns of one credential row, as stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredTokens {
+    /// One of [`CREDENTIAL_TABLES`].
+    pub table: &'static str,
+    pub id: i64,
+    pub sub: String,
+    /// `project_id` or `account_id`, whichever the table has.
+    pub key: String,
+    pub refresh_token: String,
+    pub access_token: Option<String>,
+}
+
+/// Everything the `DbActor` reads and writes. Implementations apply their migrations while
+/// connecting and store values as given: tokens arrive already sealed.
+#[async_trait]
+pub trait Store: Send + Sync {
+    /// Create (or upsert) a credential and return its id. A Codex upsert keeps the stored
+    /// tokens when they expire later than the given ones.
+    async fn create(&self, create: ProviderCreate) -> Result<i64, PolluxError>;
+
+    /// Update the set fields of a Gemini CLI credential; returns the rows affected.
+    async fn patch_geminicli(&self, id: i64, patch: GeminiCliPatch) -> Result<u64, PolluxError>;
+
+    /// Update the set fields of a Codex credential; returns the rows affected.
+    async fn patch_codex(&self, id: i64, patch: CodexPatch) -> Result<u64, PolluxError>;
+
+    async fn list_active_geminicli(&self) -> Result<Vec<DbGeminiCliResource>, PolluxError>;
+
+    async fn list_active_codex(&self) -> Result<Vec<DbCodexResource>, PolluxError>;
+
+    async fn get_geminicli_by_id(
+        &self,
+        id: i64,
+    ) -> Result<Option<DbGeminiCliResource>, PolluxError>;
+
+    async fn get_codex_by_id(&self, id: i64) -> Result<Option<DbCodexResource>, PolluxError>;
+
+    async fn list_geminicli(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<DbPage<DbGeminiCliResource>, PolluxError>;
+
+    async fn list_codex(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<DbPage<DbCodexResource>, PolluxError>;
+
+    /// Delete a row of one of [`CREDENTIAL_TABLES`] together with the model state saved for
+    /// it under `provider`, in one transaction; returns the credential rows affected.
+    async fn delete_credential(
+        &self,
+        table: &'static str,
+        provider: &'static str,
+        id: i64,
+    ) -> Result<u64, PolluxError>;
+
+    async fn record_usage(&self, usage: UsageCreate) -> Result<(), PolluxError>;
+
+    /// Sum token usage per UTC day, key, provider and model.
+    async fn query_usage(&self, query: UsageQuery) -> Result<Vec<DbUsageSummary>, PolluxError>;
+
+    /// Merge one (credential, model) state: `unsupported` is sticky, a cooldown replaces the
+    /// stored one.
+    async fn save_model_state(&self, update: ModelStateUpdate) -> Result<(), PolluxError>;
+
+    /// Drop expired state of a provider, then list what is left.
+    async fn list_model_state(
+        &self,
+        provider: &'static str,
+    ) -> Result<Vec<DbModelState>, PolluxError>;
+
+    async fn clear_model_state(
+        &self,
+        provider: &'static str,
+        credential_id: i64,
+    ) -> Result<(), PolluxError>;
+
+    /// Take the refresh lease of a credential when it is free, expired or already held by
+    /// `lease.holder`, and return whether this holder has it now.
+    async fn acquire_refresh_lease(&self, lease: RefreshLease) -> Result<bool, PolluxError>;
+
+    /// Give up a refresh lease; a lease some other holder took over is left alone.
+    async fn release_refresh_lease(
+        &self,
+        provider: &'static str,
+        credential_id: i64,
+        holder: &str,
+    ) -> Result<(), PolluxError>;
+
+    /// The sealed token data key, if one was stored.
+    async fn data_key(&self) -> Result<Option<String>, PolluxError>;
+
+    /// Store the first data key. Returns `false`, leaving the stored key alone, when another
+    /// process got there first.
+    async fn insert_data_key(&self, wrapped_key: &str) -> Result<bool, PolluxError>;
+
+    /// Replace the stored data key, e.g. re-sealed under a new master key.
+    async fn update_data_key(&self, wrapped_key: &str) -> Result<(), PolluxError>;
+
+    /// Token columns of every credential.
+    async fn list_tokens(&self) -> Result<Vec<StoredTokens>, PolluxError>;
+
+    /// Overwrite the token columns of the given rows, in one transaction.
+    async fn replace_tokens(&self, tokens: Vec<StoredTokens>) -> Result<(), PolluxError>;
+}
+
+/// Open the database at `database_url` and bring its schema up to date.
+///
+/// `sqlite:` URLs open (or create) a SQLite file; `postgres://` and `postgresql://` URLs
+/// connect to an existing PostgreSQL database.
+pub async fn connect(database_url: &str) -> Result<Box<dyn Store>, PolluxError> {
+    if database_url.starts_with("sqlite:") {
+        Ok(Box::new(SqliteStore::connect(database_url).await?))
+    } else if database_url.starts_with("postgres://") || database_url.starts_with("postgresql://") {
+        Ok(Box::new(PostgresStore::connect(database_url).await?))
+    } else {
+        // Only the scheme: the rest of a Postgres URL may hold a password.
+        let scheme = database_url.split(':').next().unwrap_or_default();
+        Err(PolluxError::UnexpectedError(format!(
+            "unsupported database url scheme {scheme:?}: expected sqlite: or postgres://"
+        )))
+    }
+}
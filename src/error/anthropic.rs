@@ -0,0 +1,160 @@
+use axum::{
+    Json,
+    extract::rejection::JsonRejection,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use pollux_schema::anthropic::{AnthropicErrorBody, AnthropicErrorObject};
+use std::time::Duration;
+use thiserror::Error as ThisError;
+
+use super::{CodexError, GeminiCliError, with_pool_status, with_retry_after};
+use crate::providers::snapshot::PoolStatus;
+
+/// Error for the Anthropic Messages route.
+///
+/// Upstream failures keep the status and message the serving provider would have returned;
+/// only the envelope changes to Anthropic's `{"type":"error","error":{...}}` shape.
+#[derive(Debug, ThisError)]
+pub(crate) enum AnthropicError {
+    #[error("Request rejected: {message}")]
+    RequestRejected {
+        status: StatusCode,
+        message: String,
+        debug_message: Option<String>,
+    },
+
+    #[error(transparent)]
+    GeminiCli(#[from] GeminiCliError),
+
+    #[error(transparent)]
+    Codex(#[from] CodexError),
+}
+
+impl AnthropicError {
+    pub(crate) fn invalid_request(message: impl Into<String>) -> Self {
+        AnthropicError::RequestRejected {
+            status: StatusCode::BAD_REQUEST,
+            message: message.into(),
+            debug_message: None,
+        }
+    }
+
+    /// Whether the serving pool ran out of capacity for the model, so a `[models.fallbacks]`
+    /// entry on the other pool may be tried.
+    pub(crate) fn allows_fallback(&self) -> bool {
+        match self {
+            AnthropicError::RequestRejected { .. } => false,
+            AnthropicError::GeminiCli(e) => e.allows_fallback(),
+            AnthropicError::Codex(e) => e.allows_fallback(),
+        }
+    }
+
+    /// How long the client should wait before retrying, sent as `Retry-After`.
+    pub(crate) fn retry_after(&self) -> Option<Duration> {
+        match self {
+            AnthropicError::RequestRejected { .. } => None,
+            AnthropicError::GeminiCli(e) => e.retry_after(),
+            AnthropicError::Codex(e) => e.retry_after(),
+        }
+    }
+
+    /// State of the serving pool when no credential was available.
+    pub(crate) fn pool_status(&self) -> Option<PoolStatus> {
+        match self {
+            AnthropicError::RequestRejected { .. } => None,
+            AnthropicError::GeminiCli(e) => e.pool_status(),
+            AnthropicError::Codex(e) => e.pool_status(),
+        }
+    }
+
+    /// Log the error and resolve it to the client-facing status and error object.
+    pub(crate) fn into_parts(self) -> (StatusCode, AnthropicErrorObject) {
+        let (status, message) = match self {
+            AnthropicError::RequestRejected {
+                status,
+                message,
+                debug_message,
+            } => {
+                tracing::warn!(
+                    status = %status,
+                    message = %message,
+                    debug_message = debug_message.as_deref().unwrap_or_default(),
+                    "Anthropic request rejected"
+                );
+                (status, message)
+            }
+            AnthropicError::GeminiCli(e) => {
+                let (status, body) = e.into_parts();
+                (status, body.message)
+            }
+            AnthropicError::Codex(e) => {
+                let (status, body) = e.into_parts();
+                (status, body.message)
+            }
+        };
+        (
+            status,
+            AnthropicErrorObject::for_status(status.as_u16(), message),
+        )
+    }
+}
+
+impl From<JsonRejection> for AnthropicError {
+    fn from(rejection: JsonRejection) -> Self {
+        let debug_message = Some(rejection.to_string());
+        let (status, message) = match rejection {
+            JsonRejection::BytesRejection(_) => {
+                (StatusCode::PAYLOAD_TOO_LARGE, "request body too large")
+            }
+            JsonRejection::JsonSyntaxError(_) => (StatusCode::BAD_REQUEST, "invalid JSON"),
+            _ => (StatusCode::BAD_REQUEST, "invalid request"),
+        };
+        AnthropicError::RequestRejected {
+            status,
+            message: message.to_string(),
+            debug_message,
+        }
+    }
+}
+
+impl IntoResponse for AnthropicError {
+    fn into_response(self) -> Response {
+        let retry_after = self.retry_after();
+        let pool = self.pool_status();
+        let (status, error) = self.into_parts();
+        let response = with_retry_after(
+            (status, Json(AnthropicErrorBody::new(error))).into_response(),
+            retry_after,
+        );
+        with_pool_status(response, pool)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_capacity_errors_allow_cross_pool_fallback() {
+        assert!(AnthropicError::from(CodexError::NoAvailableCredential(None)).allows_fallback());
+        assert!(
+            AnthropicError::from(GeminiCliError::NoAvailableCredential(None)).allows_fallback()
+        );
+        assert!(
+            AnthropicError::from(GeminiCliError::UpstreamFallbackError {
+                status: StatusCode::TOO_MANY_REQUESTS,
+                body: String::new(),
+            })
+            .allows_fallback()
+        );
+        assert!(
+            !AnthropicError::from(CodexError::UpstreamFallbackError {
+                status: StatusCode::BAD_GATEWAY,
+                body: String::new(),
+            })
+            .allows_fallback()
+        );
+        assert!(!AnthropicError::invalid_request("bad").allows_fallback());
+    }
+}
@@ -4,10 +4,13 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use std::time::Duration;
 use thiserror::Error as ThisError;
 
-use super::IsRetryable;
+use super::pollux::QUEUE_FULL_MESSAGE;
+use super::{IsRetryable, with_pool_status, with_retry_after};
 use crate::providers::UPSTREAM_BODY_PREVIEW_CHARS;
+use crate::providers::snapshot::PoolStatus;
 use pollux_schema::{CodexErrorBody, OpenaiResponsesErrorBody, OpenaiResponsesErrorObject};
 
 #[derive(Debug, ThisError)]
@@ -19,9 +22,14 @@ pub(crate) enum CodexError {
         debug_message: Option<String>,
     },
 
-    /// No usable credential is currently available.
+    /// No usable credential is currently available. Carries the state of the pool when the
+    /// provider could report it.
     #[error("No available credential")]
-    NoAvailableCredential,
+    NoAvailableCredential(Option<PoolStatus>),
+
+    /// Every credential is taken and the wait queue is full.
+    #[error("Credential wait queue full")]
+    QueueFull { retry_after: Duration },
 
     /// Upstream error that matched a provider mapping rule.
     #[error("Upstream mapped error: status={status}, body={body:?}")]
@@ -87,9 +95,40 @@ impl From<JsonRejection> for CodexError {
     }
 }
 
-impl IntoResponse for CodexError {
-    fn into_response(self) -> Response {
-        let (status, error_body) = match self {
+impl CodexError {
+    /// Whether this pool ran out of capacity for the model, so a `[models.fallbacks]` entry on
+    /// the other pool may be tried.
+    pub(crate) fn allows_fallback(&self) -> bool {
+        match self {
+            CodexError::NoAvailableCredential(_) | CodexError::QueueFull { .. } => true,
+            CodexError::UpstreamMappedError { status, .. }
+            | CodexError::UpstreamFallbackError { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+
+    /// How long the client should wait before retrying, sent as `Retry-After`.
+    pub(crate) fn retry_after(&self) -> Option<Duration> {
+        match self {
+            CodexError::QueueFull { retry_after } => Some(*retry_after),
+            CodexError::NoAvailableCredential(pool) => pool.and_then(|pool| pool.retry_after),
+            _ => None,
+        }
+    }
+
+    /// State of the pool when no credential was available.
+    pub(crate) fn pool_status(&self) -> Option<PoolStatus> {
+        match self {
+            CodexError::NoAvailableCredential(pool) => *pool,
+            _ => None,
+        }
+    }
+
+    /// Log the error and resolve it to the client-facing status and error object.
+    pub(crate) fn into_parts(self) -> (StatusCode, OpenaiResponsesErrorObject) {
+        match self {
             CodexError::RequestRejected {
                 status,
                 body,
@@ -144,7 +183,7 @@ impl IntoResponse for CodexError {
                 (status, error_body)
             }
 
-            CodexError::NoAvailableCredential => (
+            CodexError::NoAvailableCredential(_) => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 OpenaiResponsesErrorObject {
                     code: Some("NO_CREDENTIAL".to_string()),
@@ -154,6 +193,16 @@ impl IntoResponse for CodexError {
                 },
             ),
 
+            CodexError::QueueFull { .. } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                OpenaiResponsesErrorObject {
+                    code: Some("QUEUE_FULL".to_string()),
+                    message: QUEUE_FULL_MESSAGE.to_string(),
+                    r#type: "QUEUE_FULL".to_string(),
+                    param: None,
+                },
+            ),
+
             CodexError::Reqwest(e) => {
                 tracing::warn!(error = %e, status = ?e.status(), "Codex reqwest error");
                 (
@@ -192,17 +241,26 @@ impl IntoResponse for CodexError {
                     },
                 )
             }
-        };
+        }
+    }
+}
 
+impl IntoResponse for CodexError {
+    fn into_response(self) -> Response {
+        let retry_after = self.retry_after();
+        let pool = self.pool_status();
+        let (status, error_body) = self.into_parts();
         let resp_json = OpenaiResponsesErrorBody { inner: error_body };
-        (status, Json(resp_json)).into_response()
+        let response = with_retry_after((status, Json(resp_json)).into_response(), retry_after);
+        with_pool_status(response, pool)
     }
 }
 
 impl From<crate::PolluxError> for CodexError {
     fn from(err: crate::PolluxError) -> Self {
         match err {
-            crate::PolluxError::NoAvailableCredential => CodexError::NoAvailableCredential,
+            crate::PolluxError::NoAvailableCredential => CodexError::NoAvailableCredential(None),
+            crate::PolluxError::QueueFull { retry_after } => CodexError::QueueFull { retry_after },
             crate::PolluxError::ReqwestError(e) => CodexError::Reqwest(e),
             crate::PolluxError::StreamProtocolError(s) => CodexError::StreamProtocolError(s),
             other => CodexError::Internal(other.to_string()),
@@ -258,4 +316,31 @@ mod tests {
 
         assert!(error.is_retryable());
     }
+
+    #[test]
+    fn exhausted_pool_response_carries_retry_and_capacity_headers() {
+        let error = CodexError::NoAvailableCredential(Some(PoolStatus {
+            total: 3,
+            available: 0,
+            retry_after: Some(Duration::from_millis(12_300)),
+        }));
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let header = |name: &str| response.headers().get(name).and_then(|v| v.to_str().ok());
+        assert_eq!(header("retry-after"), Some("13"));
+        assert_eq!(header("x-ratelimit-reset"), Some("13"));
+        assert_eq!(header("x-pollux-credentials-total"), Some("3"));
+        assert_eq!(header("x-pollux-credentials-available"), Some("0"));
+
+        // Without a running cooldown there is nothing to wait for.
+        let response = CodexError::NoAvailableCredential(Some(PoolStatus {
+            total: 2,
+            available: 0,
+            retry_after: None,
+        }))
+        .into_response();
+        assert!(response.headers().get("retry-after").is_none());
+        assert!(response.headers().get("x-ratelimit-reset").is_none());
+        assert_eq!(response.headers()["x-pollux-credentials-total"], "2");
+    }
 }
@@ -1,4 +1,5 @@
-use super::IsRetryable;
+use super::pollux::QUEUE_FULL_MESSAGE;
+use super::{IsRetryable, with_pool_status, with_retry_after};
 use axum::{
     Json,
     extract::rejection::JsonRejection,
@@ -12,6 +13,7 @@ use std::collections::BTreeMap;
 use std::time::Duration;
 use thiserror::Error as ThisError;
 
+use crate::providers::snapshot::PoolStatus;
 use crate::providers::{ActionForError, MappingAction, UPSTREAM_BODY_PREVIEW_CHARS};
 
 #[derive(Debug, ThisError)]
@@ -23,9 +25,14 @@ pub enum GeminiCliError {
         debug_message: Option<String>,
     },
 
-    /// No usable credential is currently available.
+    /// No usable credential is currently available. Carries the state of the pool when the
+    /// provider could report it.
     #[error("No available credential")]
-    NoAvailableCredential,
+    NoAvailableCredential(Option<PoolStatus>),
+
+    /// Every credential is taken and the wait queue is full.
+    #[error("Credential wait queue full")]
+    QueueFull { retry_after: Duration },
 
     /// Upstream error that matched a provider mapping rule.
     #[error("Upstream mapped error: status={status} body={body:?}")]
@@ -88,9 +95,40 @@ impl From<JsonRejection> for GeminiCliError {
     }
 }
 
-impl IntoResponse for GeminiCliError {
-    fn into_response(self) -> Response {
-        let (status, error_body) = match self {
+impl GeminiCliError {
+    /// Whether this pool ran out of capacity for the model, so a `[models.fallbacks]` entry on
+    /// the other pool may be tried.
+    pub(crate) fn allows_fallback(&self) -> bool {
+        match self {
+            GeminiCliError::NoAvailableCredential(_) | GeminiCliError::QueueFull { .. } => true,
+            GeminiCliError::UpstreamMappedError { status, .. }
+            | GeminiCliError::UpstreamFallbackError { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+
+    /// How long the client should wait before retrying, sent as `Retry-After`.
+    pub(crate) fn retry_after(&self) -> Option<Duration> {
+        match self {
+            GeminiCliError::QueueFull { retry_after } => Some(*retry_after),
+            GeminiCliError::NoAvailableCredential(pool) => pool.and_then(|pool| pool.retry_after),
+            _ => None,
+        }
+    }
+
+    /// State of the pool when no credential was available.
+    pub(crate) fn pool_status(&self) -> Option<PoolStatus> {
+        match self {
+            GeminiCliError::NoAvailableCredential(pool) => *pool,
+            _ => None,
+        }
+    }
+
+    /// Log the error and resolve it to the client-facing status and error object.
+    pub(crate) fn into_parts(self) -> (StatusCode, GeminiErrorObject) {
+        match self {
             GeminiCliError::RequestRejected {
                 status,
                 body,
@@ -152,7 +190,7 @@ impl IntoResponse for GeminiCliError {
                 )
             }
 
-            GeminiCliError::NoAvailableCredential => (
+            GeminiCliError::NoAvailableCredential(_) => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 GeminiErrorObject::for_status(
                     StatusCode::SERVICE_UNAVAILABLE,
@@ -161,6 +199,15 @@ impl IntoResponse for GeminiCliError {
                 ),
             ),
 
+            GeminiCliError::QueueFull { .. } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                GeminiErrorObject::for_status(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "UNAVAILABLE",
+                    QUEUE_FULL_MESSAGE,
+                ),
+            ),
+
             GeminiCliError::Reqwest(e) => {
                 tracing::warn!(error = %e, status = ?e.status(), "Gemini reqwest error");
                 (
@@ -196,17 +243,30 @@ impl IntoResponse for GeminiCliError {
                     ),
                 )
             }
-        };
+        }
+    }
+}
 
+impl IntoResponse for GeminiCliError {
+    fn into_response(self) -> Response {
+        let retry_after = self.retry_after();
+        let pool = self.pool_status();
+        let (status, error_body) = self.into_parts();
         let resp_json = GeminiErrorBody { inner: error_body };
-        (status, Json(resp_json)).into_response()
+        let response = with_retry_after((status, Json(resp_json)).into_response(), retry_after);
+        with_pool_status(response, pool)
     }
 }
 
 impl From<crate::PolluxError> for GeminiCliError {
     fn from(err: crate::PolluxError) -> Self {
         match err {
-            crate::PolluxError::NoAvailableCredential => GeminiCliError::NoAvailableCredential,
+            crate::PolluxError::NoAvailableCredential => {
+                GeminiCliError::NoAvailableCredential(None)
+            }
+            crate::PolluxError::QueueFull { retry_after } => {
+                GeminiCliError::QueueFull { retry_after }
+            }
             crate::PolluxError::ReqwestError(e) => GeminiCliError::Reqwest(e),
             crate::PolluxError::StreamProtocolError(s) => GeminiCliError::StreamProtocolError(s),
             other => GeminiCliError::Internal(other.to_string()),
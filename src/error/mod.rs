@@ -1,8 +1,10 @@
+mod anthropic;
 mod codex;
 mod gemini;
 mod oauth;
 mod pollux;
 
+pub(crate) use anthropic::AnthropicError;
 pub(crate) use codex::CodexError;
 pub use gemini::{
     GeminiCliError, GeminiCliErrorBody, GeminiCliErrorObject, GeminiErrorBody, GeminiErrorObject,
@@ -10,6 +12,53 @@ pub use gemini::{
 pub use oauth::OauthError;
 pub use pollux::{ApiErrorBody, ApiErrorObject, PolluxError};
 
+use crate::providers::snapshot::PoolStatus;
+use axum::{
+    http::{HeaderName, HeaderValue, header::RETRY_AFTER},
+    response::Response,
+};
+use std::time::Duration;
+
+/// Seconds until the earliest cooldown on the requested models ends.
+const RATELIMIT_RESET: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+/// Credentials able to serve the requested models.
+const CREDENTIALS_TOTAL: HeaderName = HeaderName::from_static("x-pollux-credentials-total");
+/// Credentials that could have taken the request.
+const CREDENTIALS_AVAILABLE: HeaderName = HeaderName::from_static("x-pollux-credentials-available");
+
 pub trait IsRetryable {
     fn is_retryable(&self) -> bool;
 }
+
+/// `Retry-After` value in whole seconds, rounded up so clients never retry too early.
+pub(crate) fn retry_after_secs(retry_after: Duration) -> String {
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    secs.max(1).to_string()
+}
+
+/// Add a `Retry-After` header to an error response when the error carries a hint.
+pub(crate) fn with_retry_after(mut response: Response, retry_after: Option<Duration>) -> Response {
+    if let Some(retry_after) = retry_after
+        && let Ok(value) = retry_after_secs(retry_after).parse()
+    {
+        response.headers_mut().insert(RETRY_AFTER, value);
+    }
+    response
+}
+
+/// Describe an exhausted pool on an error response: credential counts, plus
+/// `x-ratelimit-reset` when a cooldown is running.
+pub(crate) fn with_pool_status(mut response: Response, pool: Option<PoolStatus>) -> Response {
+    let Some(pool) = pool else {
+        return response;
+    };
+    let headers = response.headers_mut();
+    headers.insert(CREDENTIALS_TOTAL, HeaderValue::from(pool.total));
+    headers.insert(CREDENTIALS_AVAILABLE, HeaderValue::from(pool.available));
+    if let Some(reset) = pool.retry_after
+        && let Ok(value) = retry_after_secs(reset).parse()
+    {
+        headers.insert(RATELIMIT_RESET, value);
+    }
+    response
+}
@@ -1,10 +1,19 @@
-use axum::{Json, http::StatusCode, response::IntoResponse};
+use axum::{
+    Json,
+    http::{StatusCode, header::RETRY_AFTER},
+    response::IntoResponse,
+};
 use serde::Serialize;
 use serde_json::Value;
+use std::time::Duration;
 use thiserror::Error as ThisError;
 
-use super::IsRetryable;
 use super::oauth::OauthError;
+use super::{IsRetryable, retry_after_secs};
+
+/// Client-facing message when a provider sheds a request because its wait queue is full.
+pub(crate) const QUEUE_FULL_MESSAGE: &str =
+    "Too many requests are waiting for a credential; retry later.";
 
 #[derive(Debug, ThisError)]
 pub enum PolluxError {
@@ -41,6 +50,19 @@ pub enum PolluxError {
     #[error("No available credential")]
     NoAvailableCredential,
 
+    /// Every credential is taken and the provider's wait queue is full.
+    #[error("Credential wait queue full; retry after {retry_after:?}")]
+    QueueFull { retry_after: Duration },
+
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
+
+    #[error("Invalid import: {0}")]
+    InvalidImport(String),
+
+    #[error("{0} not found")]
+    NotFound(String),
+
     #[error("Ractor error: {0}")]
     RactorError(String),
 
@@ -117,6 +139,50 @@ impl IntoResponse for PolluxError {
                 (status, body)
             }
 
+            PolluxError::QueueFull { retry_after } => {
+                let body = ApiErrorObject {
+                    code: "QUEUE_FULL".to_string(),
+                    message: QUEUE_FULL_MESSAGE.to_string(),
+                    details: None,
+                };
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    [(RETRY_AFTER, retry_after_secs(retry_after))],
+                    Json(ApiErrorBody { inner: body }),
+                )
+                    .into_response();
+            }
+
+            PolluxError::InvalidConfig(reason) => {
+                let status = StatusCode::BAD_REQUEST;
+                let body = ApiErrorObject {
+                    code: "INVALID_CONFIG".to_string(),
+                    message: reason,
+                    details: None,
+                };
+                (status, body)
+            }
+
+            PolluxError::InvalidImport(reason) => {
+                let status = StatusCode::BAD_REQUEST;
+                let body = ApiErrorObject {
+                    code: "INVALID_IMPORT".to_string(),
+                    message: reason,
+                    details: None,
+                };
+                (status, body)
+            }
+
+            PolluxError::NotFound(what) => {
+                let status = StatusCode::NOT_FOUND;
+                let body = ApiErrorObject {
+                    code: "NOT_FOUND".to_string(),
+                    message: format!("{what} not found."),
+                    details: None,
+                };
+                (status, body)
+            }
+
             PolluxError::UpstreamStatus(code)
             | PolluxError::Oauth(OauthError::UpstreamStatus(code)) => {
                 let (err_code, msg) = match code {
@@ -1,11 +1,13 @@
 pub mod config;
 pub mod db;
 pub mod error;
+pub mod metrics;
 pub mod model_catalog;
 pub(crate) mod oauth_utils;
 mod patches;
 pub mod providers;
 pub mod server;
+pub mod usage;
 pub(crate) mod utils;
 
 pub use error::PolluxError;
@@ -1,19 +1,47 @@
 use mimalloc::MiMalloc;
-use std::net::SocketAddr;
+use pollux::config::Config;
+use pollux::providers::manifest::ProviderKind;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::sync::Arc;
 use tokio::{net::TcpListener, signal};
-use tracing::info;
+use tracing::{error, info};
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
+const USAGE: &str = "\
+Usage:
+  pollux                                     run the server
+  pollux export <file.tar> [geminicli|codex] write enabled credentials to a tar archive
+  pollux import <file>...                    send credential files or archives to the running server";
+
+type CliResult = Result<(), Box<dyn std::error::Error>>;
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // The server binary requires a real config file with a non-empty pollux_key.
+async fn main() -> CliResult {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    // The binary requires a real config file with a non-empty pollux_key.
     // (Library code uses `config::CONFIG` which is best-effort and does not validate.)
-    let cfg = pollux::config::Config::from_toml();
+    let cfg = Config::from_toml();
+    init_tracing(&cfg);
+
+    match args.as_slice() {
+        [] => serve(cfg).await,
+        ["export", path] => export(&cfg, path, None).await,
+        ["export", path, "geminicli"] => export(&cfg, path, Some(ProviderKind::GeminiCli)).await,
+        ["export", path, "codex"] => export(&cfg, path, Some(ProviderKind::Codex)).await,
+        ["import", files @ ..] if !files.is_empty() => import(&cfg, files).await,
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+}
 
+fn init_tracing(cfg: &Config) {
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new(cfg.basic.loglevel.clone()));
 
@@ -26,13 +54,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .with_target(false),
         )
         .init();
+}
 
-    let db = pollux::db::spawn(cfg.basic.database_url.as_str()).await;
+async fn serve(cfg: Config) -> CliResult {
+    let keyring = cfg.basic.token_keyring()?;
+    let db = pollux::db::spawn_with(cfg.basic.database_url.as_str(), keyring).await;
     let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
     // Build axum router and serve
     let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
     let state =
-        pollux::server::router::PolluxState::new(providers, pollux_key, cfg.basic.insecure_cookie);
+        pollux::server::router::PolluxState::new(providers, pollux_key, cfg.basic.insecure_cookie)
+            .with_api_keys(&cfg.basic.pollux_key, &cfg.api_keys);
+    #[cfg(unix)]
+    tokio::spawn(reload_on_sighup(state.clone()));
     let app = pollux::server::router::pollux_router(state);
 
     let addr = SocketAddr::from((cfg.basic.listen_addr, cfg.basic.listen_port));
@@ -44,6 +78,75 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Server has shut down gracefully.");
     Ok(())
 }
+
+/// `pollux export`: read the database directly, so the server need not be running.
+async fn export(cfg: &Config, path: &str, provider: Option<ProviderKind>) -> CliResult {
+    let keyring = cfg.basic.token_keyring()?;
+    let db = pollux::db::spawn_with(cfg.basic.database_url.as_str(), keyring).await;
+    let (archive, count) = pollux::providers::transfer::export_archive(&db, provider).await?;
+    db.stop().await;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // The archive holds live tokens.
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    std::io::Write::write_all(&mut options.open(path)?, &archive)?;
+    println!("Exported {count} credentials to {path}");
+    Ok(())
+}
+
+/// `pollux import`: onboarding needs the provider actors, so post the files to the running
+/// server's admin endpoint.
+async fn import(cfg: &Config, files: &[&str]) -> CliResult {
+    let host = match cfg.basic.listen_addr {
+        IpAddr::V4(addr) if addr.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(addr) if addr.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        addr => addr,
+    };
+    let url = format!(
+        "http://{}/admin/credentials/import",
+        SocketAddr::from((host, cfg.basic.listen_port))
+    );
+    let client = reqwest::Client::new();
+    for file in files {
+        let resp = client
+            .post(&url)
+            .bearer_auth(&cfg.basic.pollux_key)
+            .body(std::fs::read(file)?)
+            .send()
+            .await?;
+        let status = resp.status();
+        let body = resp.text().await?;
+        if !status.is_success() {
+            return Err(format!("{file}: {status} {body}").into());
+        }
+        println!("{file}: {body}");
+    }
+    Ok(())
+}
+
+/// `kill -HUP <pid>` re-reads `config.toml`, the same as `POST /admin/reload`.
+#[cfg(unix)]
+async fn reload_on_sighup(state: pollux::server::router::PolluxState) {
+    let mut hangup = signal::unix::signal(signal::unix::SignalKind::hangup())
+        .expect("failed to install SIGHUP handler");
+    while hangup.recv().await.is_some() {
+        info!("SIGHUP received, reloading config");
+        let result = match pollux::config::Config::try_from_toml() {
+            Ok(cfg) => state
+                .reload(&cfg)
+                .await
+                .map(drop)
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!("Config reload failed, keeping the running config: {}", e);
+        }
+    }
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
@@ -0,0 +1,264 @@
+//! Minimal metric families rendered in the Prometheus text exposition format.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+
+/// Monotonic counter keyed by a fixed set of label values.
+pub struct CounterVec<const N: usize> {
+    name: &'static str,
+    help: &'static str,
+    label_names: [&'static str; N],
+    values: Mutex<BTreeMap<[String; N], u64>>,
+}
+
+impl<const N: usize> CounterVec<N> {
+    pub fn new(name: &'static str, help: &'static str, label_names: [&'static str; N]) -> Self {
+        Self {
+            name,
+            help,
+            label_names,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn inc(&self, labels: [&str; N]) {
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        *values.entry(labels.map(str::to_string)).or_insert(0) += 1;
+    }
+
+    pub fn get(&self, labels: [&str; N]) -> u64 {
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        values
+            .get(&labels.map(str::to_string))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn render(&self, out: &mut String) {
+        write_header(out, self.name, self.help, "counter");
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        for (labels, value) in values.iter() {
+            write_sample(
+                out,
+                self.name,
+                &self.label_names,
+                labels,
+                None,
+                *value as f64,
+            );
+        }
+    }
+}
+
+#[derive(Clone)]
+struct HistogramState {
+    /// Per-bucket (non-cumulative) counts; cumulated at render time.
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+/// Fixed-bucket histogram keyed by a fixed set of label values.
+pub struct HistogramVec<const N: usize> {
+    name: &'static str,
+    help: &'static str,
+    label_names: [&'static str; N],
+    bounds: &'static [f64],
+    values: Mutex<BTreeMap<[String; N], HistogramState>>,
+}
+
+impl<const N: usize> HistogramVec<N> {
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: [&'static str; N],
+        bounds: &'static [f64],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            label_names,
+            bounds,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn observe(&self, labels: [&str; N], value: f64) {
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        let state = values
+            .entry(labels.map(str::to_string))
+            .or_insert_with(|| HistogramState {
+                buckets: vec![0; self.bounds.len()],
+                sum: 0.0,
+                count: 0,
+            });
+        if let Some(index) = self.bounds.iter().position(|bound| value <= *bound) {
+            state.buckets[index] += 1;
+        }
+        state.sum += value;
+        state.count += 1;
+    }
+
+    pub fn count(&self, labels: [&str; N]) -> u64 {
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        values
+            .get(&labels.map(str::to_string))
+            .map(|state| state.count)
+            .unwrap_or(0)
+    }
+
+    pub fn render(&self, out: &mut String) {
+        write_header(out, self.name, self.help, "histogram");
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket_name = format!("{}_bucket", self.name);
+        let sum_name = format!("{}_sum", self.name);
+        let count_name = format!("{}_count", self.name);
+
+        for (labels, state) in values.iter() {
+            let mut cumulative = 0;
+            for (bound, count) in self.bounds.iter().zip(&state.buckets) {
+                cumulative += count;
+                let le = format_value(*bound);
+                write_sample(
+                    out,
+                    &bucket_name,
+                    &self.label_names,
+                    labels,
+                    Some(&le),
+                    cumulative as f64,
+                );
+            }
+            write_sample(
+                out,
+                &bucket_name,
+                &self.label_names,
+                labels,
+                Some("+Inf"),
+                state.count as f64,
+            );
+            write_sample(out, &sum_name, &self.label_names, labels, None, state.sum);
+            write_sample(
+                out,
+                &count_name,
+                &self.label_names,
+                labels,
+                None,
+                state.count as f64,
+            );
+        }
+    }
+}
+
+/// Render a gauge family whose samples are computed at scrape time.
+pub fn render_gauge<const N: usize>(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    label_names: [&str; N],
+    samples: &[([String; N], f64)],
+) {
+    write_header(out, name, help, "gauge");
+    for (labels, value) in samples {
+        write_sample(out, name, &label_names, labels, None, *value);
+    }
+}
+
+fn write_header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn write_sample(
+    out: &mut String,
+    name: &str,
+    label_names: &[&str],
+    label_values: &[String],
+    le: Option<&str>,
+    value: f64,
+) {
+    out.push_str(name);
+    if !label_names.is_empty() || le.is_some() {
+        out.push('{');
+        let mut first = true;
+        let pairs = label_names
+            .iter()
+            .copied()
+            .zip(label_values.iter().map(String::as_str))
+            .chain(le.map(|le| ("le", le)));
+        for (label, value) in pairs {
+            if !first {
+                out.push(',');
+            }
+            first = false;
+            let _ = write!(out, "{label}=\"{}\"", escape_label_value(value));
+        }
+        out.push('}');
+    }
+    let _ = writeln!(out, " {}", format_value(value));
+}
+
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_finite() && value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{value}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counter_renders_labels_in_sorted_order() {
+        let counter = CounterVec::new("t_total", "Test counter.", ["provider", "status"]);
+        counter.inc(["codex", "200"]);
+        counter.inc(["codex", "200"]);
+        counter.inc(["codex", "429"]);
+
+        let mut out = String::new();
+        counter.render(&mut out);
+        assert_eq!(
+            out,
+            "# HELP t_total Test counter.\n\
+             # TYPE t_total counter\n\
+             t_total{provider=\"codex\",status=\"200\"} 2\n\
+             t_total{provider=\"codex\",status=\"429\"} 1\n"
+        );
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let histogram = HistogramVec::new("t_seconds", "Test histogram.", ["p"], &[0.1, 1.0]);
+        histogram.observe(["a"], 0.05);
+        histogram.observe(["a"], 0.5);
+        histogram.observe(["a"], 5.0);
+
+        let mut out = String::new();
+        histogram.render(&mut out);
+        assert!(out.contains("t_seconds_bucket{p=\"a\",le=\"0.1\"} 1\n"));
+        assert!(out.contains("t_seconds_bucket{p=\"a\",le=\"1\"} 2\n"));
+        assert!(out.contains("t_seconds_bucket{p=\"a\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("t_seconds_sum{p=\"a\"} 5.55\n"));
+        assert!(out.contains("t_seconds_count{p=\"a\"} 3\n"));
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(escape_label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
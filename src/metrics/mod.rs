@@ -0,0 +1,191 @@
+//! Process-wide Prometheus metrics.
+//!
+//! Hot paths record into `METRICS` directly; `/metrics` renders the text exposition
+//! format on scrape. Scheduler gauges are not stored here: they are computed from
+//! actor snapshots at scrape time so they can never drift from the real queues.
+
+mod family;
+
+pub use family::{CounterVec, HistogramVec, render_gauge};
+
+use crate::providers::ActionForError;
+use crate::providers::snapshot::SchedulerSnapshot;
+use std::sync::{Arc, LazyLock, OnceLock};
+use std::time::Duration;
+
+/// Actor RPCs are normally microseconds; the upper buckets catch a stalled actor.
+const LEASE_WAIT_BUCKETS: &[f64] = &[
+    0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0,
+];
+
+const UPSTREAM_TTFB_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub struct Metrics {
+    pub requests: CounterVec<3>,
+    pub key_requests: CounterVec<2>,
+    pub lease_wait: HistogramVec<2>,
+    pub upstream_ttfb: HistogramVec<2>,
+    pub upstream_actions: CounterVec<3>,
+    pub refreshes: CounterVec<3>,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        Self {
+            requests: CounterVec::new(
+                "pollux_requests_total",
+                "Proxy requests by provider, model and response status.",
+                ["provider", "model", "status"],
+            ),
+            key_requests: CounterVec::new(
+                "pollux_key_requests_total",
+                "Requests by API key name and response status.",
+                ["key", "status"],
+            ),
+            lease_wait: HistogramVec::new(
+                "pollux_lease_wait_seconds",
+                "Time spent waiting for a credential lease from the provider actor.",
+                ["provider", "model"],
+                LEASE_WAIT_BUCKETS,
+            ),
+            upstream_ttfb: HistogramVec::new(
+                "pollux_upstream_ttfb_seconds",
+                "Time from sending an upstream request to receiving its response headers.",
+                ["provider", "model"],
+                UPSTREAM_TTFB_BUCKETS,
+            ),
+            upstream_actions: CounterVec::new(
+                "pollux_upstream_error_actions_total",
+                "Upstream errors by the scheduler action they triggered.",
+                ["provider", "model", "action"],
+            ),
+            refreshes: CounterVec::new(
+                "pollux_refresh_total",
+                "Refresher outcomes by job kind and result.",
+                ["provider", "kind", "result"],
+            ),
+        }
+    }
+
+    pub fn observe_lease_wait(&self, provider: &str, model: &str, waited: Duration) {
+        self.lease_wait
+            .observe([provider, model], waited.as_secs_f64());
+    }
+
+    pub fn observe_upstream_ttfb(&self, provider: &str, model: &str, took: Duration) {
+        self.upstream_ttfb
+            .observe([provider, model], took.as_secs_f64());
+    }
+
+    pub fn record_action(&self, provider: &str, model: &str, action: &ActionForError) {
+        self.upstream_actions
+            .inc([provider, model, action.as_label()]);
+    }
+
+    pub fn record_refresh(&self, provider: &str, kind: &str, ok: bool) {
+        let result = if ok { "success" } else { "failure" };
+        self.refreshes.inc([provider, kind, result]);
+    }
+
+    /// Render all stored families plus gauges derived from `snapshots`.
+    pub fn render(&self, snapshots: &[(&str, &SchedulerSnapshot)]) -> String {
+        let mut out = String::new();
+        self.requests.render(&mut out);
+        self.key_requests.render(&mut out);
+        self.lease_wait.render(&mut out);
+        self.upstream_ttfb.render(&mut out);
+        self.upstream_actions.render(&mut out);
+        self.refreshes.render(&mut out);
+        render_scheduler_gauges(&mut out, snapshots);
+        out
+    }
+}
+
+fn render_scheduler_gauges(out: &mut String, snapshots: &[(&str, &SchedulerSnapshot)]) {
+    let mut pool = Vec::new();
+    let mut available = Vec::new();
+    let mut cooling = Vec::new();
+    let mut refreshing = Vec::new();
+
+    for (provider, snapshot) in snapshots {
+        for model in &snapshot.models {
+            let labels = [provider.to_string(), model.model.clone()];
+            let serving = snapshot
+                .credentials
+                .iter()
+                .filter(|cred| cred.models.contains(&model.model));
+            let (total, busy) = serving.fold((0usize, 0usize), |(total, busy), cred| {
+                (total + 1, busy + usize::from(cred.refreshing))
+            });
+
+            pool.push((labels.clone(), total as f64));
+            available.push((labels.clone(), model.available as f64));
+            cooling.push((labels.clone(), model.cooling as f64));
+            refreshing.push((labels, busy as f64));
+        }
+    }
+
+    let label_names = ["provider", "model"];
+    render_gauge(
+        out,
+        "pollux_pool_credentials",
+        "Credentials in memory whose capabilities include the model.",
+        label_names,
+        &pool,
+    );
+    render_gauge(
+        out,
+        "pollux_available_credentials",
+        "Queued credentials that can be leased for the model right now.",
+        label_names,
+        &available,
+    );
+    render_gauge(
+        out,
+        "pollux_cooling_credentials",
+        "Credentials in a rate-limit cooldown for the model.",
+        label_names,
+        &cooling,
+    );
+    render_gauge(
+        out,
+        "pollux_refreshing_credentials",
+        "Credentials serving the model with a token refresh in flight.",
+        label_names,
+        &refreshing,
+    );
+}
+
+/// Write-once slots for the request counters' labels.
+///
+/// `access_log` puts empty slots into the request extensions; route extractors fill the
+/// provider/model slot once the model has been validated, so unknown model names never
+/// become label values, and the auth guard fills the key slot.
+#[derive(Clone, Default)]
+pub(crate) struct RequestLabels {
+    route: Arc<OnceLock<(&'static str, String)>>,
+    key: Arc<OnceLock<Arc<str>>>,
+}
+
+impl RequestLabels {
+    pub(crate) fn set(&self, provider: &'static str, model: &str) {
+        let _ = self.route.set((provider, model.to_string()));
+    }
+
+    pub(crate) fn get(&self) -> Option<(&'static str, &str)> {
+        self.route
+            .get()
+            .map(|(provider, model)| (*provider, model.as_str()))
+    }
+
+    pub(crate) fn set_key(&self, name: &Arc<str>) {
+        let _ = self.key.set(name.clone());
+    }
+
+    /// Name of the API key that authenticated the request.
+    pub(crate) fn key(&self) -> Option<&str> {
+        self.key.get().map(AsRef::as_ref)
+    }
+}
@@ -4,27 +4,142 @@ pub mod registry;
 pub use capabilities::ModelCapabilities;
 pub use registry::ModelRegistry;
 
-use crate::config::{CONFIG, Config};
+use crate::config::{CONFIG, Config, Reloadable};
 use std::collections::HashSet;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 
-pub static MODEL_REGISTRY: LazyLock<ModelRegistry> = LazyLock::new(|| {
+static MODEL_REGISTRY: LazyLock<Reloadable<ModelRegistry>> = LazyLock::new(|| {
     let cfg = &*CONFIG;
     let models = collect_global_model_names(cfg);
-    ModelRegistry::new(&models)
+    Reloadable::new(build_registry(&models, cfg))
 });
 
-pub static MODEL_MASK_ALL: LazyLock<u64> = LazyLock::new(|| {
-    let model_count = MODEL_REGISTRY.len();
+fn build_registry(models: &[String], cfg: &Config) -> ModelRegistry {
+    ModelRegistry::new(models)
+        .with_aliases(&cfg.models.aliases)
+        .with_fallbacks(&cfg.models.fallbacks)
+}
+
+/// Current model registry.
+pub fn registry() -> Arc<ModelRegistry> {
+    MODEL_REGISTRY.load()
+}
+
+/// Build the registry for a reloaded config; [`install`] puts it in place.
+///
+/// Known models keep their index, so capability bits held by the schedulers stay valid. New
+/// models are appended; removed ones keep their slot and simply stop being served.
+pub fn reloaded_registry(cfg: &Config) -> Result<ModelRegistry, String> {
+    let current = registry();
+    let mut models = current.names().to_vec();
+    models.extend(
+        collect_global_model_names(cfg)
+            .into_iter()
+            .filter(|name| current.get_index(name).is_none()),
+    );
+    if models.len() > 64 {
+        return Err(format!(
+            "reload would register {} models; at most 64 fit until the next restart",
+            models.len()
+        ));
+    }
+    Ok(build_registry(&models, cfg))
+}
+
+/// Replace the current registry.
+pub fn install(registry: ModelRegistry) {
+    MODEL_REGISTRY.store(registry);
+}
+
+/// Mask with a bit for every registered model.
+pub fn model_mask_all() -> u64 {
+    let model_count = registry().len();
     if model_count >= 64 {
         u64::MAX
     } else {
         (1u64 << model_count) - 1
     }
-});
+}
 
+/// Models one provider serves: its `model_list` (deduplicated, in order) and their mask.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderModels {
+    pub names: Vec<String>,
+    pub mask: u64,
+}
+
+impl ProviderModels {
+    pub fn new(model_list: Vec<String>) -> Self {
+        let mut seen = HashSet::<String>::new();
+        let names: Vec<String> = model_list
+            .into_iter()
+            .filter(|name| seen.insert(name.clone()))
+            .collect();
+        let mask = names
+            .iter()
+            .filter_map(|name| mask(name))
+            .fold(0, |mask, bit| mask | bit);
+        Self { names, mask }
+    }
+}
+
+/// Capability bit of a model name or alias.
 pub fn mask(name: &str) -> Option<u64> {
-    MODEL_REGISTRY.get_index(name).map(|idx| 1u64 << idx)
+    registry().resolve_index(name).map(|idx| 1u64 << idx)
+}
+
+/// Configured model name for a model name or alias; this is what gets sent upstream.
+pub fn resolve(name: &str) -> Option<String> {
+    let registry = registry();
+    registry
+        .resolve_index(name)
+        .map(|idx| registry.get_name(idx).to_string())
+}
+
+/// Rewrite an alias in place to its configured model; other names are left untouched.
+pub fn resolve_alias(model: &mut String) {
+    if let Some(resolved) = resolve(model) {
+        *model = resolved;
+    }
+}
+
+/// A model a request may be served by: its configured name and capability bit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelRoute {
+    pub name: String,
+    pub mask: u64,
+}
+
+/// `[models.fallbacks]` chain of `name`, in order, keeping only models in `served`.
+///
+/// `served` is whatever the caller can lease from, e.g. one provider's model mask narrowed by
+/// the client key's allowlist.
+pub fn fallbacks(name: &str, served: u64) -> Vec<ModelRoute> {
+    let registry = registry();
+    let Some(idx) = registry.get_index(name) else {
+        return Vec::new();
+    };
+    let served = ModelCapabilities::from_bits(served);
+    registry
+        .fallbacks_of(idx)
+        .iter()
+        .map(|&target| ModelRoute {
+            name: registry.get_name(target).to_string(),
+            mask: 1u64 << target,
+        })
+        .filter(|route| served.intersects(ModelCapabilities::from_bits(route.mask)))
+        .collect()
+}
+
+/// `models` followed by the exact aliases that point at them, for `/models` listings.
+pub fn names_with_aliases(models: &[String]) -> Vec<String> {
+    let registry = registry();
+    let aliases = models
+        .iter()
+        .filter_map(|name| registry.get_index(name))
+        .flat_map(|idx| registry.aliases_of(idx))
+        .map(str::to_string);
+    models.iter().cloned().chain(aliases).collect()
 }
 
 /// Resolve a bitmask into a list of model names (best-effort).
@@ -32,11 +147,12 @@ pub fn mask(name: &str) -> Option<u64> {
 /// Unknown bits (outside the registry) are ignored here; use `format_model_mask` if you want
 /// those shown explicitly in logs.
 pub fn model_names_from_mask(model_mask: u64) -> Vec<String> {
+    let registry = registry();
     let mut names = Vec::new();
-    for idx in 0..MODEL_REGISTRY.len() {
+    for idx in 0..registry.len() {
         let bit = 1u64 << idx;
         if (model_mask & bit) != 0 {
-            names.push(MODEL_REGISTRY.get_name(idx).to_string());
+            names.push(registry.get_name(idx).to_string());
         }
     }
     names
@@ -49,7 +165,7 @@ pub fn format_model_mask(model_mask: u64) -> String {
     }
 
     let names = model_names_from_mask(model_mask);
-    let unknown_bits = model_mask & !*MODEL_MASK_ALL;
+    let unknown_bits = model_mask & !model_mask_all();
 
     if unknown_bits != 0 {
         format!(
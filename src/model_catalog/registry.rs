@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use tracing::warn;
 
 /// Immutable registry of model names and indices.
 /// Maintains a bidirectional mapping between `Model Name (String)` and
@@ -9,6 +10,12 @@ pub struct ModelRegistry {
     name_to_index: HashMap<String, usize>,
     /// Index-to-name lookup for logs and diagnostics.
     index_to_name: Vec<String>,
+    /// Exact alias name to the index of its target model.
+    aliases: HashMap<String, usize>,
+    /// `prefix*` aliases, longest prefix first.
+    wildcards: Vec<(String, usize)>,
+    /// Model index to the indices it falls back to, in order.
+    fallbacks: HashMap<usize, Vec<usize>>,
 }
 
 impl ModelRegistry {
@@ -37,9 +44,55 @@ impl ModelRegistry {
         Self {
             name_to_index,
             index_to_name,
+            aliases: HashMap::new(),
+            wildcards: Vec::new(),
+            fallbacks: HashMap::new(),
         }
     }
 
+    /// Adds alias names (`[models.aliases]`). Keys ending in `*` are prefix patterns.
+    /// Aliases whose target is not a registered model are skipped.
+    pub fn with_aliases(mut self, aliases: &BTreeMap<String, String>) -> Self {
+        for (alias, target) in aliases {
+            let Some(idx) = self.get_index(target) else {
+                warn!(alias = %alias, target = %target, "Ignoring alias for unknown model");
+                continue;
+            };
+            match alias.strip_suffix('*') {
+                Some(prefix) => self.wildcards.push((prefix.to_string(), idx)),
+                None => {
+                    self.aliases.insert(alias.clone(), idx);
+                }
+            }
+        }
+        self.wildcards
+            .sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        self
+    }
+
+    /// Adds fallback chains (`[models.fallbacks]`). Unknown models and self-references are
+    /// skipped.
+    pub fn with_fallbacks(mut self, fallbacks: &BTreeMap<String, Vec<String>>) -> Self {
+        for (model, chain) in fallbacks {
+            let Some(idx) = self.get_index(model) else {
+                warn!(model = %model, "Ignoring fallback chain for unknown model");
+                continue;
+            };
+            let targets = chain
+                .iter()
+                .filter_map(|target| match self.get_index(target) {
+                    Some(target_idx) if target_idx != idx => Some(target_idx),
+                    _ => {
+                        warn!(model = %model, target = %target, "Ignoring fallback entry");
+                        None
+                    }
+                })
+                .collect();
+            self.fallbacks.insert(idx, targets);
+        }
+        self
+    }
+
     /// Dictionary lookup: get the index for a model name (0..63).
     ///
     /// Used by: bitmask computation (`1 << index`) and manager queue operations.
@@ -47,6 +100,42 @@ impl ModelRegistry {
         self.name_to_index.get(name).copied()
     }
 
+    /// Like `get_index`, but also accepts aliases: exact name, then exact alias, then the
+    /// longest matching `prefix*` alias.
+    ///
+    /// Used by: request routing, so clients may use friendly or legacy names.
+    pub fn resolve_index(&self, name: &str) -> Option<usize> {
+        self.get_index(name)
+            .or_else(|| self.aliases.get(name).copied())
+            .or_else(|| {
+                self.wildcards
+                    .iter()
+                    .find(|(prefix, _)| name.starts_with(prefix.as_str()))
+                    .map(|(_, idx)| *idx)
+            })
+    }
+
+    /// Exact (non-wildcard) aliases of the model at `index`, sorted.
+    ///
+    /// Used by: `/models` listings.
+    pub fn aliases_of(&self, index: usize) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .aliases
+            .iter()
+            .filter(|(_, idx)| **idx == index)
+            .map(|(alias, _)| alias.as_str())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Indices the model at `index` falls back to, in configured order.
+    ///
+    /// Used by: request routing when the requested model cannot be served.
+    pub fn fallbacks_of(&self, index: usize) -> &[usize] {
+        self.fallbacks.get(&index).map_or(&[], Vec::as_slice)
+    }
+
     /// Reverse lookup: get the model name for an index.
     ///
     /// Used by: logging and error messages.
@@ -58,6 +147,13 @@ impl ModelRegistry {
             .unwrap_or("UNKNOWN_MODEL")
     }
 
+    /// Registered model names in index order.
+    ///
+    /// Used by: config reloads, which must keep every existing index.
+    pub fn names(&self) -> &[String] {
+        &self.index_to_name
+    }
+
     /// Returns the total number of models in the registry.
     ///
     /// Used by: sizing the manager queue vectors.
@@ -70,3 +166,55 @@ impl ModelRegistry {
         self.index_to_name.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_index_prefers_exact_names_then_aliases_then_longest_wildcard() {
+        let models = ["gemini-2.5-pro", "gemini-2.5-flash", "gpt-5.2"].map(String::from);
+        let aliases = BTreeMap::from(
+            [
+                ("gemini-pro", "gemini-2.5-pro"),
+                ("gemini-*", "gemini-2.5-flash"),
+                ("gemini-2.5-pro-*", "gemini-2.5-pro"),
+                ("gpt-5-codex-latest", "gpt-5.2"),
+                ("ghost", "no-such-model"),
+            ]
+            .map(|(alias, target)| (alias.to_string(), target.to_string())),
+        );
+        let registry = ModelRegistry::new(&models).with_aliases(&aliases);
+
+        assert_eq!(registry.resolve_index("gemini-2.5-pro"), Some(0));
+        assert_eq!(registry.resolve_index("gemini-pro"), Some(0));
+        assert_eq!(registry.resolve_index("gemini-2.5-pro-preview"), Some(0));
+        assert_eq!(registry.resolve_index("gemini-exp"), Some(1));
+        assert_eq!(registry.resolve_index("gpt-5-codex-latest"), Some(2));
+        assert_eq!(registry.resolve_index("ghost"), None);
+        assert_eq!(registry.resolve_index("gpt-4"), None);
+        // Aliases never change what exact lookups see.
+        assert_eq!(registry.get_index("gemini-pro"), None);
+
+        assert_eq!(registry.aliases_of(0), vec!["gemini-pro"]);
+        assert!(registry.aliases_of(1).is_empty());
+    }
+
+    #[test]
+    fn fallbacks_keep_configured_order_and_drop_unknown_entries() {
+        let models = ["gpt-5.3-codex", "gpt-5.2-codex", "gpt-5.2"].map(String::from);
+        let fallbacks = BTreeMap::from([
+            (
+                "gpt-5.3-codex".to_string(),
+                ["gpt-5.2", "gpt-5.3-codex", "gpt-4", "gpt-5.2-codex"]
+                    .map(String::from)
+                    .to_vec(),
+            ),
+            ("ghost".to_string(), vec!["gpt-5.2".to_string()]),
+        ]);
+        let registry = ModelRegistry::new(&models).with_fallbacks(&fallbacks);
+
+        assert_eq!(registry.fallbacks_of(0), &[2, 1]);
+        assert!(registry.fallbacks_of(1).is_empty());
+    }
+}
@@ -6,7 +6,8 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
+
+use crate::db::store::Store;
 
 /// Abstraction for applying a patch payload/envelope to the database.
 ///
@@ -14,7 +15,7 @@ use sqlx::SqlitePool;
 /// providers, and higher-level orchestrators can share the same contract.
 #[async_trait]
 pub trait DbPatchable {
-    async fn apply_patch(&self, pool: &SqlitePool) -> Result<(), crate::error::PolluxError>;
+    async fn apply_patch(&self, store: &dyn Store) -> Result<(), crate::error::PolluxError>;
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -1,4 +1,4 @@
-use crate::config::{CodexResolvedConfig, Config, GeminiCliResolvedConfig};
+use crate::config::{CodexResolvedConfig, Config, GeminiCliResolvedConfig, Reloadable};
 use crate::db::DbActorHandle;
 use crate::providers::codex::CodexActorHandle;
 use crate::providers::geminicli::GeminiCliActorHandle;
@@ -12,9 +12,11 @@ use tracing::info;
 #[derive(Clone)]
 pub struct Providers {
     pub geminicli: GeminiCliActorHandle,
-    pub geminicli_cfg: Arc<GeminiCliResolvedConfig>,
+    pub geminicli_cfg: Arc<Reloadable<GeminiCliResolvedConfig>>,
     pub codex: CodexActorHandle,
-    pub codex_cfg: Arc<CodexResolvedConfig>,
+    pub codex_cfg: Arc<Reloadable<CodexResolvedConfig>>,
+    /// Shared storage handle; read-only admin views query it directly.
+    pub db: DbActorHandle,
 }
 
 impl Providers {
@@ -44,19 +46,43 @@ impl Providers {
             codex_enable_multiplexing = codex_cfg.enable_multiplexing,
             codex_retry_max_times = codex_cfg.retry_max_times,
             codex_oauth_tps = codex_cfg.oauth_tps,
-            codex_responses_url = %crate::providers::codex::CODEX_RESPONSES_URL.as_str(),
+            codex_responses_url = %codex_cfg.responses_url,
             codex_model_list = ?codex_cfg.model_list,
             "Codex config (effective)"
         );
 
         let geminicli = crate::providers::geminicli::spawn(db.clone(), geminicli_cfg.clone()).await;
-        let codex = crate::providers::codex::spawn(db, codex_cfg.clone()).await;
+        let codex = crate::providers::codex::spawn(db.clone(), codex_cfg.clone()).await;
 
         Self {
             geminicli,
-            geminicli_cfg,
+            geminicli_cfg: Arc::new(Reloadable::new(Arc::unwrap_or_clone(geminicli_cfg))),
             codex,
-            codex_cfg,
+            codex_cfg: Arc::new(Reloadable::new(Arc::unwrap_or_clone(codex_cfg))),
+            db,
         }
     }
+
+    /// Swap in the provider settings of a reloaded config and let each actor resize its
+    /// queues and restart its refresh pipeline.
+    ///
+    /// Run after the model registry and provider model lists were reloaded.
+    pub fn reload(&self, cfg: &Config) {
+        let geminicli_cfg = cfg.geminicli();
+        let codex_cfg = cfg.codex();
+        info!(
+            geminicli_retry_max_times = geminicli_cfg.retry_max_times,
+            geminicli_oauth_tps = geminicli_cfg.oauth_tps,
+            geminicli_model_list = ?geminicli_cfg.model_list,
+            codex_retry_max_times = codex_cfg.retry_max_times,
+            codex_oauth_tps = codex_cfg.oauth_tps,
+            codex_model_list = ?codex_cfg.model_list,
+            "Provider config reloaded"
+        );
+
+        self.geminicli.reload(Arc::new(geminicli_cfg.clone()));
+        self.codex.reload(Arc::new(codex_cfg.clone()));
+        self.geminicli_cfg.store(geminicli_cfg);
+        self.codex_cfg.store(codex_cfg);
+    }
 }
@@ -1,12 +1,17 @@
 use crate::config::CodexResolvedConfig;
 use crate::error::{CodexError, IsRetryable};
-use crate::providers::codex::{CODEX_RESPONSES_URL, CodexActorHandle};
-use crate::providers::{ActionForError, policy::classify_upstream_error};
+use crate::metrics::METRICS;
+use crate::model_catalog::ModelRoute;
+use crate::providers::codex::{CodexActorHandle, CodexContext};
+use crate::providers::{
+    ActionForError, UpstreamResponse, counts_against_health, policy::classify_upstream_error,
+};
 use backon::{ExponentialBuilder, Retryable};
 use pollux_schema::{CodexErrorBody, CodexRequestBody};
 
 use std::time::{Duration, Instant};
 use tracing::info;
+use url::Url;
 
 use super::api::CodexApi;
 
@@ -17,6 +22,7 @@ use super::api::CodexApi;
 /// - OAuth/token refresh is intentionally left as future work (placeholders in config).
 pub(crate) struct CodexClient {
     client: reqwest::Client,
+    responses_url: Url,
     retry_policy: ExponentialBuilder,
 }
 
@@ -31,134 +37,206 @@ impl CodexClient {
 
         Self {
             client,
+            responses_url: cfg.responses_url.clone(),
             retry_policy,
         }
     }
 
+    /// Post `body` for `ctx.model`, walking `ctx.fallbacks` in order when no credential serves
+    /// the current model or upstream rate-limits it or reports it unsupported.
+    ///
+    /// Each retry attempt starts again from `ctx.model`, so the requested model is preferred as
+    /// soon as one of its credentials frees up.
     pub(crate) async fn call_codex(
         &self,
         handle: &CodexActorHandle,
-        model: &str,
-        model_mask: u64,
-        client_stream: bool,
+        ctx: &CodexContext,
         body: &CodexRequestBody,
-    ) -> Result<reqwest::Response, CodexError> {
+    ) -> Result<UpstreamResponse, CodexError> {
         let handle = handle.clone();
         let client = self.client.clone();
-        let responses_url = CODEX_RESPONSES_URL.clone();
+        let responses_url = self.responses_url.clone();
         let retry_policy_inner = self.retry_policy;
         let body = body.clone();
-        let model = model.to_string();
+        let client_stream = ctx.stream;
+        let session = ctx.session.clone();
+        let chain: Vec<ModelRoute> = std::iter::once(ModelRoute {
+            name: ctx.model.clone(),
+            mask: ctx.model_mask,
+        })
+        .chain(ctx.fallbacks.iter().cloned())
+        .collect();
 
         let op = move || {
             let handle = handle.clone();
             let client = client.clone();
             let responses_url = responses_url.clone();
             let body = body.clone();
-            let model = model.clone();
+            let chain = chain.clone();
+            let session = session.clone();
             async move {
-                let start = Instant::now();
-                let lease = handle
-                    .get_credential(model_mask)
-                    .await?
-                    .ok_or(CodexError::NoAvailableCredential)?;
-
-                let actor_took = start.elapsed();
-                info!(
-                    channel = "codex",
-                    lease.id = lease.id,
-                    lease.waited_us = actor_took.as_micros() as u64,
-                    req.model = %model,
-                    req.stream = client_stream,
-
-                    "[Codex] [ID: {}] [{:?}] Post responses -> {}",
-                    lease.id,
-                    actor_took,
-                    model
-                );
-
-                let resp = CodexApi::try_post_codex(
-                    client.clone(),
-                    responses_url.clone(),
-                    &lease,
-                    &body,
-                    retry_policy_inner,
-                )
-                .await?;
-
-                if resp.status().is_success() {
-                    return Ok(resp);
-                }
-
-                let status = resp.status();
-                let (action, final_error) = classify_upstream_error(
-                    resp,
-                    |json: CodexErrorBody| CodexError::UpstreamMappedError { status, body: json },
-                    |status, body| CodexError::UpstreamFallbackError { status, body },
-                )
-                .await;
-
-                match &action {
-                    ActionForError::RateLimit(duration) => {
-                        handle
-                            .report_rate_limit(lease.id, model_mask, *duration)
-                            .await;
-                        // Optionally, could add a log here about when to retry
-                    }
-                    ActionForError::Ban => {
-                        handle.report_baned(lease.id).await;
-                    }
-                    ActionForError::ModelUnsupported => {
-                        handle.report_model_unsupported(lease.id, model_mask).await;
+                let mut fallback_error = None;
+                for (step, route) in chain.iter().enumerate() {
+                    let model = &route.name;
+                    let model_mask = route.mask;
+                    let has_fallback = step + 1 < chain.len();
+
+                    let start = Instant::now();
+                    let Some((lease, guard)) = handle
+                        .get_credential(model_mask, session.clone(), !has_fallback)
+                        .await?
+                    else {
+                        if has_fallback {
+                            info!(
+                                "[Codex] No credential for {}, falling back to {}",
+                                model,
+                                chain[step + 1].name
+                            );
+                        }
+                        continue;
+                    };
+
+                    let actor_took = start.elapsed();
+                    METRICS.observe_lease_wait("codex", model, actor_took);
+                    info!(
+                        channel = "codex",
+                        lease.id = lease.id,
+                        lease.waited_us = actor_took.as_micros() as u64,
+                        req.model = %model,
+                        req.stream = client_stream,
+
+                        "[Codex] [ID: {}] [{:?}] Post responses -> {}",
+                        lease.id,
+                        actor_took,
+                        model
+                    );
+
+                    let mut body = body.clone();
+                    body.model.clone_from(model);
+                    let sent = Instant::now();
+                    let resp = match CodexApi::try_post_codex(
+                        client.clone(),
+                        responses_url.clone(),
+                        &lease,
+                        &body,
+                        retry_policy_inner,
+                    )
+                    .await
+                    {
+                        Ok(resp) => resp,
+                        Err(e) => {
+                            // Transport errors and 5xx that outlasted the retries.
+                            handle.report_failure(lease.id).await;
+                            return Err(e.into());
+                        }
+                    };
+                    METRICS.observe_upstream_ttfb("codex", model, sent.elapsed());
+
+                    if resp.status().is_success() {
+                        handle.report_success(lease.id).await;
+                        return Ok(UpstreamResponse {
+                            credential_id: lease.id,
+                            model: model.clone(),
+                            resp,
+                            lease: guard,
+                        });
                     }
-                    ActionForError::Invalid => {
-                        handle.report_invalid(lease.id).await;
-                    }
-                    ActionForError::None => {
-                        // Do nothing
-                    }
-                }
 
-                match &final_error {
-                    CodexError::UpstreamMappedError { status, .. } => {
-                        tracing::warn!(
-                            lease_id = lease.id,
-                            model = %model,
-                            status = %status,
-                            action = ?action,
-                            "[Codex] Upstream mapped error"
-                        );
-                    }
-                    CodexError::UpstreamFallbackError { status, .. } => {
-                        tracing::warn!(
-                            lease_id = lease.id,
-                            model = %model,
-                            status = %status,
-                            action = ?action,
-                            "[Codex] Upstream fallback error"
-                        );
+                    let status = resp.status();
+                    let (action, final_error) = classify_upstream_error(
+                        resp,
+                        |json: CodexErrorBody| CodexError::UpstreamMappedError {
+                            status,
+                            body: json,
+                        },
+                        |status, body| CodexError::UpstreamFallbackError { status, body },
+                    )
+                    .await;
+
+                    METRICS.record_action("codex", model, &action);
+                    match &action {
+                        ActionForError::RateLimit(duration) => {
+                            handle
+                                .report_rate_limit(lease.id, model_mask, *duration)
+                                .await;
+                            // Optionally, could add a log here about when to retry
+                        }
+                        ActionForError::Ban => {
+                            handle.report_baned(lease.id).await;
+                        }
+                        ActionForError::ModelUnsupported => {
+                            handle.report_model_unsupported(lease.id, model_mask).await;
+                        }
+                        ActionForError::Invalid => {
+                            handle.report_invalid(lease.id).await;
+                        }
+                        ActionForError::None => {
+                            if counts_against_health(status) {
+                                handle.report_failure(lease.id).await;
+                            }
+                        }
                     }
-                    CodexError::Reqwest(error) => {
-                        tracing::warn!(
-                            lease_id = lease.id,
-                            model = %model,
-                            status = ?error.status(),
-                            action = ?action,
-                            "[Codex] Upstream reqwest error"
-                        );
+
+                    match &final_error {
+                        CodexError::UpstreamMappedError { status, .. } => {
+                            tracing::warn!(
+                                lease_id = lease.id,
+                                model = %model,
+                                status = %status,
+                                action = ?action,
+                                "[Codex] Upstream mapped error"
+                            );
+                        }
+                        CodexError::UpstreamFallbackError { status, .. } => {
+                            tracing::warn!(
+                                lease_id = lease.id,
+                                model = %model,
+                                status = %status,
+                                action = ?action,
+                                "[Codex] Upstream fallback error"
+                            );
+                        }
+                        CodexError::Reqwest(error) => {
+                            tracing::warn!(
+                                lease_id = lease.id,
+                                model = %model,
+                                status = ?error.status(),
+                                action = ?action,
+                                "[Codex] Upstream reqwest error"
+                            );
+                        }
+                        _ => {
+                            tracing::warn!(
+                                lease_id = lease.id,
+                                model = %model,
+                                status = "N/A",
+                                action = ?action,
+                                "[Codex] Upstream other error"
+                            );
+                        }
                     }
-                    _ => {
-                        tracing::warn!(
-                            lease_id = lease.id,
-                            model = %model,
-                            status = "N/A",
-                            action = ?action,
-                            "[Codex] Upstream other error"
+
+                    if has_fallback && action.allows_fallback() {
+                        info!(
+                            "[Codex] {} unavailable ({:?}), falling back to {}",
+                            model,
+                            action,
+                            chain[step + 1].name
                         );
+                        fallback_error.get_or_insert(final_error);
+                        continue;
                     }
+                    return Err(final_error);
                 }
 
-                Err(final_error)
+                // Surface the first upstream error so it can still be retried.
+                if let Some(error) = fallback_error {
+                    return Err(error);
+                }
+                let chain_mask = chain.iter().fold(0, |mask, route| mask | route.mask);
+                Err(CodexError::NoAvailableCredential(
+                    handle.pool_status(chain_mask).await.ok(),
+                ))
             }
         };
 
@@ -0,0 +1,13 @@
+use crate::model_catalog::ModelRoute;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct CodexContext {
+    pub model: String,
+    pub stream: bool,
+    pub model_mask: u64,
+    /// `[models.fallbacks]` entries this pool may serve in place of `model`.
+    pub fallbacks: Vec<ModelRoute>,
+    /// Conversation key for `session_affinity`, if the client sent one.
+    pub session: Option<Arc<str>>,
+}
@@ -1,4 +1,5 @@
 pub mod client;
+mod context;
 mod errors;
 mod identity;
 mod manager;
@@ -12,9 +13,13 @@ use std::sync::LazyLock;
 use url::Url;
 use workers::{CodexRefresherHandle, RefreshOutcome};
 
+pub use context::CodexContext;
+pub(crate) use identity::identity_from_id_token;
 pub use manager::CodexActorHandle;
 pub(in crate::providers) use manager::spawn;
-pub(crate) use model_mask::{SUPPORTED_MODEL_MASK, SUPPORTED_MODEL_NAMES, model_mask};
+pub(crate) use model_mask::{
+    fallbacks, model_mask, reload_supported_models, supported_model_mask, supported_model_names,
+};
 pub(crate) use submission::CodexRefreshTokenSeed;
 
 pub(crate) static CODEX_RESPONSES_URL: LazyLock<Url> = LazyLock::new(|| {
@@ -7,6 +7,7 @@ use super::super::{
 };
 use crate::config::CodexResolvedConfig;
 use crate::error::{IsRetryable, OauthError, PolluxError};
+use crate::metrics::METRICS;
 use backon::{ExponentialBuilder, Retryable};
 use futures::stream::StreamExt;
 use governor::{Quota, RateLimiter};
@@ -32,6 +33,16 @@ pub(in crate::providers::codex) enum RefreshOutcome {
     },
 }
 
+impl RefreshOutcome {
+    fn record_metric(&self) {
+        let (kind, ok) = match self {
+            Self::RefreshCredential { result, .. } => ("refresh", result.is_ok()),
+            Self::InitialOauthTokenResponse { result, .. } => ("initial", result.is_ok()),
+        };
+        METRICS.record_refresh("codex", kind, ok);
+    }
+}
+
 #[derive(Debug)]
 enum CodexRefresherMessage {
     RefreshCredential {
@@ -41,6 +52,8 @@ enum CodexRefresherMessage {
     InitialRefreshCredential {
         seed: CodexRefreshTokenSeed,
     },
+    /// Replace the refresh pipeline with one built from a reloaded config.
+    Reconfigure(Arc<CodexResolvedConfig>),
 }
 
 /// Handle for submitting refresh requests to the Codex refresher actor.
@@ -64,6 +77,12 @@ impl CodexRefresherHandle {
         Ok(Self { actor })
     }
 
+    /// Rebuild the OAuth client and rate limit from `cfg`; queued jobs finish on the old ones.
+    pub fn reconfigure(&self, cfg: Arc<CodexResolvedConfig>) -> Result<(), PolluxError> {
+        ractor::cast!(self.actor, CodexRefresherMessage::Reconfigure(cfg))
+            .map_err(|e| PolluxError::RactorError(format!("CodexRefresherActor cast failed: {e}")))
+    }
+
     pub fn submit_refresh(&self, id: CredentialId, cred: CodexResource) -> Result<(), PolluxError> {
         ractor::cast!(
             self.actor,
@@ -116,6 +135,84 @@ impl RefreshTask {
     }
 }
 
+/// Build the HTTP client and rate limiter from `cfg` and start a refresh pipeline.
+///
+/// The pipeline stops once every sender is dropped and queued jobs are done, which is how a
+/// reconfigure retires the previous one.
+fn start_pipeline(
+    handle: CodexActorHandle,
+    cfg: &CodexResolvedConfig,
+) -> mpsc::Sender<RefreshTask> {
+    let mut headers = HeaderMap::new();
+    let mut builder = reqwest::Client::builder()
+        .user_agent("codex-oauth/1.0".to_string())
+        .connect_timeout(Duration::from_secs(5))
+        .timeout(Duration::from_secs(30));
+
+    if let Some(proxy_url) = cfg.proxy.clone() {
+        let proxy =
+            reqwest::Proxy::all(proxy_url.as_str()).expect("invalid proxy url for reqwest client");
+        builder = builder.proxy(proxy);
+    }
+
+    if !cfg.enable_multiplexing {
+        headers.insert(CONNECTION, HeaderValue::from_static("close"));
+
+        builder = builder
+            .http1_only()
+            .pool_max_idle_per_host(0)
+            .pool_idle_timeout(Duration::from_secs(0));
+    } else {
+        builder = builder.http2_adaptive_window(true);
+    }
+
+    let client = builder
+        .default_headers(headers)
+        .build()
+        .expect("FATAL: initialize codex refresh HTTP client failed");
+
+    let oauth_tps = cfg.oauth_tps.max(1);
+    let oauth_tps_u32 = u32::try_from(oauth_tps).unwrap_or(u32::MAX);
+    let burst_u32 = u32::try_from(oauth_tps.saturating_mul(2)).unwrap_or(u32::MAX);
+    let limiter = Arc::new(RateLimiter::direct(
+        Quota::per_second(std::num::NonZeroU32::new(oauth_tps_u32).unwrap())
+            .allow_burst(std::num::NonZeroU32::new(burst_u32).unwrap()),
+    ));
+
+    let (job_tx, job_rx) = mpsc::channel::<RefreshTask>(1000);
+    // Spawn background refresh worker using buffer_unordered semantics.
+    let buffer_unordered = oauth_tps.saturating_mul(2).max(1);
+    tokio::spawn(async move {
+        info!(
+            "Codex Refresh Pipeline Started: BufferUnordered={}, RateLimit={}/s, Burst={}",
+            buffer_unordered, oauth_tps_u32, burst_u32
+        );
+
+        let mut pipeline = ReceiverStream::new(job_rx)
+            .map(|task| {
+                let lim = limiter.clone();
+                let http = client.clone();
+                async move {
+                    lim.until_ready().await;
+                    task.execute(http).await
+                }
+            })
+            .buffer_unordered(buffer_unordered);
+
+        while let Some(outcome) = pipeline.next().await {
+            outcome.record_metric();
+            if let Err(e) = handle.send_refresh_complete(outcome) {
+                warn!("Actor unreachable (channel closed), worker stopping: {}", e);
+                break;
+            }
+        }
+
+        info!("Codex Refresh Pipeline Stopped");
+    });
+
+    job_tx
+}
+
 struct CodexRefresherActorState {
     job_tx: mpsc::Sender<RefreshTask>,
     handle: CodexActorHandle,
@@ -134,74 +231,7 @@ impl Actor for CodexRefresherActor {
         _myself: ActorRef<Self::Msg>,
         (handle, cfg): Self::Arguments,
     ) -> Result<Self::State, ActorProcessingErr> {
-        let mut headers = HeaderMap::new();
-        let mut builder = reqwest::Client::builder()
-            .user_agent("codex-oauth/1.0".to_string())
-            .connect_timeout(Duration::from_secs(5))
-            .timeout(Duration::from_secs(30));
-
-        if let Some(proxy_url) = cfg.proxy.clone() {
-            let proxy = reqwest::Proxy::all(proxy_url.as_str())
-                .expect("invalid proxy url for reqwest client");
-            builder = builder.proxy(proxy);
-        }
-
-        if !cfg.enable_multiplexing {
-            headers.insert(CONNECTION, HeaderValue::from_static("close"));
-
-            builder = builder
-                .http1_only()
-                .pool_max_idle_per_host(0)
-                .pool_idle_timeout(Duration::from_secs(0));
-        } else {
-            builder = builder.http2_adaptive_window(true);
-        }
-
-        let client = builder
-            .default_headers(headers)
-            .build()
-            .expect("FATAL: initialize codex refresh HTTP client failed");
-
-        let oauth_tps = cfg.oauth_tps.max(1);
-        let oauth_tps_u32 = u32::try_from(oauth_tps).unwrap_or(u32::MAX);
-        let burst_u32 = u32::try_from(oauth_tps.saturating_mul(2)).unwrap_or(u32::MAX);
-        let limiter = Arc::new(RateLimiter::direct(
-            Quota::per_second(std::num::NonZeroU32::new(oauth_tps_u32).unwrap())
-                .allow_burst(std::num::NonZeroU32::new(burst_u32).unwrap()),
-        ));
-
-        let (job_tx, job_rx) = mpsc::channel::<RefreshTask>(1000);
-        let pipeline_handle = handle.clone();
-
-        // Spawn background refresh worker using buffer_unordered semantics.
-        let buffer_unordered = oauth_tps.saturating_mul(2).max(1);
-        tokio::spawn(async move {
-            info!(
-                "Codex Refresh Pipeline Started: BufferUnordered={}, RateLimit={}/s, Burst={}",
-                buffer_unordered, oauth_tps_u32, burst_u32
-            );
-
-            let mut pipeline = ReceiverStream::new(job_rx)
-                .map(|task| {
-                    let lim = limiter.clone();
-                    let http = client.clone();
-                    async move {
-                        lim.until_ready().await;
-                        task.execute(http).await
-                    }
-                })
-                .buffer_unordered(buffer_unordered);
-
-            while let Some(outcome) = pipeline.next().await {
-                if let Err(e) = pipeline_handle.send_refresh_complete(outcome) {
-                    warn!("Actor unreachable (channel closed), worker stopping: {}", e);
-                    break;
-                }
-            }
-
-            info!("Codex Refresh Pipeline Stopped");
-        });
-
+        let job_tx = start_pipeline(handle.clone(), &cfg);
         info!(
             proxy = %cfg.proxy.as_ref().map(|u| u.as_str()).unwrap_or("<none>"),
             enable_multiplexing = cfg.enable_multiplexing,
@@ -219,6 +249,15 @@ impl Actor for CodexRefresherActor {
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
         match message {
+            CodexRefresherMessage::Reconfigure(cfg) => {
+                state.job_tx = start_pipeline(state.handle.clone(), &cfg);
+                info!(
+                    proxy = %cfg.proxy.as_ref().map(|u| u.as_str()).unwrap_or("<none>"),
+                    enable_multiplexing = cfg.enable_multiplexing,
+                    oauth_tps = cfg.oauth_tps,
+                    "CodexRefresher reconfigured"
+                );
+            }
             CodexRefresherMessage::RefreshCredential { id, cred } => {
                 let tx = state.job_tx.clone();
                 let handle = state.handle.clone();
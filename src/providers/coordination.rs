@@ -0,0 +1,142 @@
+//! Coordination between Pollux instances that share one database.
+//!
+//! Every instance schedules from its own in-memory pool; the database is where they meet:
+//! - a refresh lease per credential lets one instance refresh its tokens while the others wait
+//!   for the stored result, so a rotating refresh token is never spent twice;
+//! - every [`STORE_SYNC_INTERVAL`] the provider actors re-read the active credentials and the
+//!   saved model state, picking up what other instances added, disabled, banned or refreshed,
+//!   and the cooldowns and unsupported models they reported.
+
+use super::policy::{REFRESH_LEASE_POLL, REFRESH_LEASE_TTL, STORE_SYNC_INTERVAL};
+use crate::db::DbActorHandle;
+use crate::error::PolluxError;
+use std::{
+    collections::HashMap,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+
+static INSTANCE_ID: LazyLock<String> = LazyLock::new(|| {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "pollux".to_string());
+    format!(
+        "{host}-{}-{:08x}",
+        std::process::id(),
+        rand::random::<u32>()
+    )
+});
+
+/// Names this process as the holder of refresh leases. Unique per start, so a restarted
+/// instance does not mistake the lease of its previous run for its own.
+pub(crate) fn instance_id() -> &'static str {
+    &INSTANCE_ID
+}
+
+/// What waiting for a credential's refresh lease ended with.
+#[derive(Debug)]
+pub(crate) enum RefreshClaim<C> {
+    /// This instance holds the lease and refreshes the credential.
+    Claimed,
+    /// Another instance refreshed it meanwhile; these are the stored credentials.
+    Refreshed(C),
+}
+
+/// Wait until this instance may refresh credential `id`, or another instance already has.
+///
+/// `refreshed` re-reads the credential and returns it when the stored tokens are newer than
+/// the ones about to be refreshed. Storage errors let the refresh go ahead unguarded: a token
+/// refreshed twice beats a credential that is never refreshed.
+pub(crate) async fn claim_refresh<C, F, Fut>(
+    db: &DbActorHandle,
+    provider: &'static str,
+    id: u64,
+    mut refreshed: F,
+) -> RefreshClaim<C>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<C>, PolluxError>>,
+{
+    let Ok(db_id) = i64::try_from(id) else {
+        return RefreshClaim::Claimed;
+    };
+    loop {
+        let attempt = async {
+            if let Some(cred) = refreshed().await? {
+                return Ok(Some(RefreshClaim::Refreshed(cred)));
+            }
+            let claimed = db
+                .acquire_refresh_lease(provider, db_id, instance_id(), REFRESH_LEASE_TTL)
+                .await?;
+            Ok::<_, PolluxError>(claimed.then_some(RefreshClaim::Claimed))
+        }
+        .await;
+
+        match attempt {
+            Ok(Some(claim)) => return claim,
+            Ok(None) => {
+                debug!("ID: {id} is being refreshed by another instance, waiting");
+                tokio::time::sleep(REFRESH_LEASE_POLL).await;
+            }
+            Err(e) => {
+                warn!("ID: {id} refresh lease unavailable, refreshing anyway: {e}");
+                return RefreshClaim::Claimed;
+            }
+        }
+    }
+}
+
+/// Credentials this instance added or removed lately.
+///
+/// A sync may have read the database before those writes landed, so it leaves such
+/// credentials alone until the next one.
+#[derive(Debug, Default)]
+pub(crate) struct RecentChanges {
+    changed: HashMap<u64, Instant>,
+}
+
+impl RecentChanges {
+    pub fn record(&mut self, id: u64) {
+        self.changed.insert(id, Instant::now());
+    }
+
+    /// Start a sync that read the database at `read_at`: forget changes from before the
+    /// previous sync and return the cut-off for [`Self::changed_since`].
+    pub fn begin_sync(&mut self, read_at: Instant) -> Instant {
+        let since = read_at.checked_sub(STORE_SYNC_INTERVAL).unwrap_or(read_at);
+        self.changed.retain(|_, at| *at >= since);
+        since
+    }
+
+    pub fn changed_since(&self, id: u64, since: Instant) -> bool {
+        self.changed.get(&id).is_some_and(|at| *at >= since)
+    }
+}
+
+/// Time left until `until`, or `None` once it has passed.
+pub(crate) fn remaining(until: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    (until - chrono::Utc::now())
+        .to_std()
+        .ok()
+        .filter(|remaining| !remaining.is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_changes_cover_one_sync_interval() {
+        let mut recent = RecentChanges::default();
+        recent.record(1);
+        let now = Instant::now();
+
+        let since = recent.begin_sync(now);
+        assert!(recent.changed_since(1, since));
+        assert!(!recent.changed_since(2, since));
+
+        // A sync reading one interval later no longer protects the change.
+        let since = recent.begin_sync(now + STORE_SYNC_INTERVAL + Duration::from_secs(1));
+        assert!(!recent.changed_since(1, since));
+        assert!(recent.changed.is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::providers::geminicli::GeminiRpc;
 use backon::{ExponentialBuilder, Retryable};
 use tracing::error;
 
@@ -6,22 +7,23 @@ pub struct GeminiApi;
 const GEMINI_GENERATE_URL: &str = "https://cloudcode-pa.googleapis.com/v1internal:generateContent";
 const GEMINI_STREAM_URL: &str =
     "https://cloudcode-pa.googleapis.com/v1internal:streamGenerateContent?alt=sse";
+const GEMINI_COUNT_TOKENS_URL: &str = "https://cloudcode-pa.googleapis.com/v1internal:countTokens";
 
 impl GeminiApi {
     pub async fn try_post_cli<T>(
         client: reqwest::Client,
         token: impl AsRef<str>,
-        stream: bool,
+        rpc: GeminiRpc,
         retry_policy: ExponentialBuilder,
         body: &T,
     ) -> Result<reqwest::Response, reqwest::Error>
     where
         T: serde::Serialize,
     {
-        let url = if stream {
-            GEMINI_STREAM_URL
-        } else {
-            GEMINI_GENERATE_URL
+        let url = match rpc {
+            GeminiRpc::GenerateContent => GEMINI_GENERATE_URL,
+            GeminiRpc::StreamGenerateContent => GEMINI_STREAM_URL,
+            GeminiRpc::CountTokens => GEMINI_COUNT_TOKENS_URL,
         };
 
         (|| async {
@@ -1,10 +1,14 @@
 use crate::config::GeminiCliResolvedConfig;
 use crate::error::{GeminiCliError, GeminiCliErrorBody, IsRetryable};
-use crate::providers::geminicli::{GeminiCliActorHandle, GeminiContext};
+use crate::metrics::METRICS;
+use crate::model_catalog::ModelRoute;
+use crate::providers::geminicli::{GeminiCliActorHandle, GeminiContext, GeminiRpc};
 use crate::providers::policy::classify_upstream_error;
+use crate::providers::{UpstreamResponse, counts_against_health};
 use backon::{ExponentialBuilder, Retryable};
 use pollux_schema::gemini::GeminiRequestBody;
 use serde::Serialize;
+use serde_json::Value;
 use std::time::{Duration, Instant};
 use tracing::{error, info, warn};
 
@@ -22,6 +26,36 @@ struct CliPostFormatBody {
     request: GeminiRequestBody,
 }
 
+/// `v1internal:countTokens` takes no project; the model travels inside the request.
+#[derive(Clone, Serialize)]
+struct CliCountTokensBody {
+    request: CliCountTokensRequest,
+}
+
+#[derive(Clone, Serialize)]
+struct CliCountTokensRequest {
+    model: String,
+    contents: Value,
+}
+
+impl CliCountTokensBody {
+    /// Accepts both public `countTokens` shapes: top-level `contents`, or a full
+    /// `generateContentRequest` whose contents are counted.
+    fn new(model: &str, body: &GeminiRequestBody) -> Self {
+        let contents = body
+            .get("contents")
+            .or_else(|| body.pointer("/generateContentRequest/contents"))
+            .cloned()
+            .unwrap_or_else(|| Value::Array(Vec::new()));
+        Self {
+            request: CliCountTokensRequest {
+                model: format!("models/{model}"),
+                contents,
+            },
+        }
+    }
+}
+
 impl GeminiClient {
     pub fn new(cfg: &GeminiCliResolvedConfig, client: reqwest::Client) -> Self {
         let retry_policy = ExponentialBuilder::default()
@@ -35,144 +69,214 @@ impl GeminiClient {
         }
     }
 
+    /// Post `body` for `ctx.model`, walking `ctx.fallbacks` in order when no credential serves
+    /// the current model or upstream rate-limits it or reports it unsupported.
     pub async fn call_gemini_cli(
         &self,
         handle: &GeminiCliActorHandle,
         ctx: &GeminiContext,
         body: &GeminiRequestBody,
-    ) -> Result<reqwest::Response, GeminiCliError> {
-        let base_payload = CliPostFormatBody {
-            model: ctx.model.clone(),
-            project: String::new(),
-            request: body.clone(),
-        };
+    ) -> Result<UpstreamResponse, GeminiCliError> {
+        let chain: Vec<ModelRoute> = std::iter::once(ModelRoute {
+            name: ctx.model.clone(),
+            mask: ctx.model_mask,
+        })
+        .chain(ctx.fallbacks.iter().cloned())
+        .collect();
 
         let handle = handle.clone();
         let client = self.client.clone();
         let stream = ctx.stream;
         let retry_policy_inner = self.retry_policy;
 
-        let op = {
-            let base_payload = base_payload.clone();
-            move || {
-                let handle = handle.clone();
-                let client = client.clone();
-                let base_payload = base_payload.clone();
-                async move {
+        let op = move || {
+            let handle = handle.clone();
+            let client = client.clone();
+            let chain = chain.clone();
+            async move {
+                let mut fallback_error = None;
+                for (step, route) in chain.iter().enumerate() {
+                    let model = &route.name;
+                    let model_mask = route.mask;
+                    let has_fallback = step + 1 < chain.len();
+
                     let start = Instant::now();
-                    let assigned = handle
-                        .get_credential(ctx.model_mask)
+                    let Some((assigned, guard)) = handle
+                        .get_credential(model_mask, ctx.session.clone(), !has_fallback)
                         .await?
-                        .ok_or(GeminiCliError::NoAvailableCredential)?;
+                    else {
+                        if has_fallback {
+                            info!(
+                                "[GeminiCli] No credential for {}, falling back to {}",
+                                model,
+                                chain[step + 1].name
+                            );
+                        }
+                        continue;
+                    };
 
                     let actor_took = start.elapsed();
+                    METRICS.observe_lease_wait("geminicli", model, actor_took);
                     info!(
                         channel = "geminicli",
                         lease.id = assigned.id,
                         lease.waited_us = actor_took.as_micros() as u64,
-                        req.model = %ctx.model,
+                        req.model = %model,
                         req.stream = stream,
 
                         "[GeminiCli] [ID: {}] [{:?}] Post responses -> {}",
                         assigned.id,
                         actor_took,
-                        ctx.model
+                        model
                     );
 
-                    let mut payload = base_payload.clone();
-                    payload.project = assigned.project_id.clone();
+                    let sent = Instant::now();
+                    let sent_result = if ctx.rpc == GeminiRpc::CountTokens {
+                        GeminiApi::try_post_cli(
+                            client.clone(),
+                            assigned.access_token,
+                            ctx.rpc,
+                            retry_policy_inner,
+                            &CliCountTokensBody::new(model, body),
+                        )
+                        .await
+                    } else {
+                        let payload = CliPostFormatBody {
+                            model: model.clone(),
+                            project: assigned.project_id.clone(),
+                            request: body.clone(),
+                        };
+                        GeminiApi::try_post_cli(
+                            client.clone(),
+                            assigned.access_token,
+                            ctx.rpc,
+                            retry_policy_inner,
+                            &payload,
+                        )
+                        .await
+                    };
+                    let resp = match sent_result {
+                        Ok(resp) => resp,
+                        Err(e) => {
+                            // Transport errors and 5xx that outlasted the retries.
+                            handle.report_failure(assigned.id).await;
+                            return Err(e.into());
+                        }
+                    };
+                    METRICS.observe_upstream_ttfb("geminicli", model, sent.elapsed());
+                    if resp.status().is_success() {
+                        handle.report_success(assigned.id).await;
+                        return Ok(UpstreamResponse {
+                            credential_id: assigned.id,
+                            model: model.clone(),
+                            resp,
+                            lease: guard,
+                        });
+                    }
 
-                    let resp = GeminiApi::try_post_cli(
-                        client.clone(),
-                        assigned.access_token,
-                        stream,
-                        retry_policy_inner,
-                        &payload,
+                    let status = resp.status();
+                    let (action, final_error) = classify_upstream_error(
+                        resp,
+                        |json: GeminiCliErrorBody| GeminiCliError::UpstreamMappedError {
+                            status,
+                            body: json,
+                        },
+                        |status, body| GeminiCliError::UpstreamFallbackError { status, body },
                     )
-                    .await?;
-                    if !resp.status().is_success() {
-                        let status = resp.status();
+                    .await;
 
-                        let (action, final_error) = classify_upstream_error(
-                            resp,
-                            |json: GeminiCliErrorBody| GeminiCliError::UpstreamMappedError {
-                                status,
-                                body: json,
-                            },
-                            |status, body| GeminiCliError::UpstreamFallbackError { status, body },
-                        )
-                        .await;
-
-                        match &action {
-                            crate::providers::ActionForError::RateLimit(duration) => {
-                                handle
-                                    .report_rate_limit(assigned.id, ctx.model_mask, *duration)
-                                    .await;
-                                info!(
-                                    "Project: {}, rate limited, retry in {:?}",
-                                    assigned.project_id, duration
-                                );
-                            }
-                            crate::providers::ActionForError::Ban => {
-                                handle.report_baned(assigned.id).await;
-                                info!("Project: {}, banned", assigned.project_id);
-                            }
-                            crate::providers::ActionForError::ModelUnsupported => {
-                                handle
-                                    .report_model_unsupported(assigned.id, ctx.model_mask)
-                                    .await;
-                                info!("Project: {}, model unsupported", assigned.project_id);
-                            }
-                            crate::providers::ActionForError::Invalid => {
-                                handle.report_invalid(assigned.id).await;
-                                info!("Project: {}, invalid", assigned.project_id);
+                    METRICS.record_action("geminicli", model, &action);
+                    match &action {
+                        crate::providers::ActionForError::RateLimit(duration) => {
+                            handle
+                                .report_rate_limit(assigned.id, model_mask, *duration)
+                                .await;
+                            info!(
+                                "Project: {}, rate limited, retry in {:?}",
+                                assigned.project_id, duration
+                            );
+                        }
+                        crate::providers::ActionForError::Ban => {
+                            handle.report_baned(assigned.id).await;
+                            info!("Project: {}, banned", assigned.project_id);
+                        }
+                        crate::providers::ActionForError::ModelUnsupported => {
+                            handle
+                                .report_model_unsupported(assigned.id, model_mask)
+                                .await;
+                            info!("Project: {}, model unsupported", assigned.project_id);
+                        }
+                        crate::providers::ActionForError::Invalid => {
+                            handle.report_invalid(assigned.id).await;
+                            info!("Project: {}, invalid", assigned.project_id);
+                        }
+                        crate::providers::ActionForError::None => {
+                            if counts_against_health(status) {
+                                handle.report_failure(assigned.id).await;
                             }
-                            crate::providers::ActionForError::None => {}
                         }
+                    }
 
-                        match &final_error {
-                            GeminiCliError::UpstreamMappedError { status, .. } => {
-                                warn!(
-                                    lease_id = assigned.id,
-                                    model = %ctx.model,
-                                    status = %status,
-                                    action = ?action,
-                                    "[GeminiCli] Upstream mapped error"
-                                );
-                            }
-                            GeminiCliError::UpstreamFallbackError { status, .. } => {
-                                warn!(
-                                    lease_id = assigned.id,
-                                    model = %ctx.model,
-                                    status = %status,
-                                    action = ?action,
-                                    "[GeminiCli] Upstream fallback error"
-                                );
-                            }
-                            GeminiCliError::Reqwest(error) => {
-                                warn!(
-                                    lease_id = assigned.id,
-                                    model = %ctx.model,
-                                    status = ?error.status(),
-                                    action = ?action,
-                                    "[GeminiCli] Upstream reqwest error"
-                                );
-                            }
-                            _ => {
-                                warn!(
-                                    lease_id = assigned.id,
-                                    model = %ctx.model,
-                                    status = "N/A",
-                                    action = ?action,
-                                    "[GeminiCli] Upstream other error"
-                                );
-                            }
+                    match &final_error {
+                        GeminiCliError::UpstreamMappedError { status, .. } => {
+                            warn!(
+                                lease_id = assigned.id,
+                                model = %model,
+                                status = %status,
+                                action = ?action,
+                                "[GeminiCli] Upstream mapped error"
+                            );
+                        }
+                        GeminiCliError::UpstreamFallbackError { status, .. } => {
+                            warn!(
+                                lease_id = assigned.id,
+                                model = %model,
+                                status = %status,
+                                action = ?action,
+                                "[GeminiCli] Upstream fallback error"
+                            );
                         }
+                        GeminiCliError::Reqwest(error) => {
+                            warn!(
+                                lease_id = assigned.id,
+                                model = %model,
+                                status = ?error.status(),
+                                action = ?action,
+                                "[GeminiCli] Upstream reqwest error"
+                            );
+                        }
+                        _ => {
+                            warn!(
+                                lease_id = assigned.id,
+                                model = %model,
+                                status = "N/A",
+                                action = ?action,
+                                "[GeminiCli] Upstream other error"
+                            );
+                        }
+                    }
 
-                        return Err(final_error);
+                    if has_fallback && action.allows_fallback() {
+                        info!(
+                            "[GeminiCli] {} unavailable ({:?}), falling back to {}",
+                            model,
+                            action,
+                            chain[step + 1].name
+                        );
+                        fallback_error.get_or_insert(final_error);
+                        continue;
                     }
-                    Ok(resp)
+                    return Err(final_error);
                 }
+
+                // Surface the first upstream error so it can still be retried.
+                if let Some(error) = fallback_error {
+                    return Err(error);
+                }
+                let chain_mask = chain.iter().fold(0, |mask, route| mask | route.mask);
+                Err(GeminiCliError::NoAvailableCredential(
+                    handle.pool_status(chain_mask).await.ok(),
+                ))
             }
         };
 
@@ -188,3 +292,26 @@ impl GeminiClient {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn count_tokens_body_reads_either_public_request_shape() {
+        let contents = json!([{ "role": "user", "parts": [{ "text": "hi" }] }]);
+        let expected = json!({
+            "request": { "model": "models/gemini-2.5-pro", "contents": contents }
+        });
+
+        let direct = CliCountTokensBody::new("gemini-2.5-pro", &json!({ "contents": contents }));
+        assert_eq!(serde_json::to_value(direct).unwrap(), expected);
+
+        let wrapped = CliCountTokensBody::new(
+            "gemini-2.5-pro",
+            &json!({ "generateContentRequest": { "model": "models/x", "contents": contents } }),
+        );
+        assert_eq!(serde_json::to_value(wrapped).unwrap(), expected);
+    }
+}
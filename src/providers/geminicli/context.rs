@@ -1,7 +1,66 @@
+use crate::model_catalog::ModelRoute;
+use std::sync::Arc;
+
 #[derive(Debug, Clone)]
 pub struct GeminiContext {
     pub model: String,
     pub stream: bool,
     pub path: String,
     pub model_mask: u64,
+    pub rpc: GeminiRpc,
+    /// `[models.fallbacks]` entries this pool may serve in place of `model`.
+    pub fallbacks: Vec<ModelRoute>,
+    /// Conversation key for `session_affinity`, if the client sent one.
+    pub session: Option<Arc<str>>,
+}
+
+/// Code Assist `v1internal` method a request is forwarded to.
+///
+/// The set mirrors what Gemini CLI itself calls on `cloudcode-pa`; embeddings are not
+/// served there, so `embedContent` has no variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeminiRpc {
+    GenerateContent,
+    StreamGenerateContent,
+    CountTokens,
+}
+
+impl GeminiRpc {
+    /// Parse the verb after `{model}:` in a v1beta path.
+    pub fn from_verb(verb: &str) -> Option<Self> {
+        match verb {
+            "generateContent" => Some(GeminiRpc::GenerateContent),
+            "streamGenerateContent" => Some(GeminiRpc::StreamGenerateContent),
+            "countTokens" => Some(GeminiRpc::CountTokens),
+            _ => None,
+        }
+    }
+
+    /// Generation method for routes that only choose between unary and streaming.
+    pub fn generate(stream: bool) -> Self {
+        if stream {
+            GeminiRpc::StreamGenerateContent
+        } else {
+            GeminiRpc::GenerateContent
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_verb_accepts_known_methods_only() {
+        assert_eq!(
+            GeminiRpc::from_verb("streamGenerateContent"),
+            Some(GeminiRpc::StreamGenerateContent)
+        );
+        assert_eq!(
+            GeminiRpc::from_verb("countTokens"),
+            Some(GeminiRpc::CountTokens)
+        );
+        assert_eq!(GeminiRpc::from_verb("embedContent"), None);
+        assert_eq!(GeminiRpc::from_verb("generatecontent"), None);
+    }
 }
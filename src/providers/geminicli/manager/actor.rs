@@ -4,17 +4,28 @@ use super::{
     scheduler::{CredentialId, CredentialManager},
 };
 use crate::config::GeminiCliResolvedConfig;
-use crate::db::GeminiCliPatch;
+use crate::db::{DbModelState, GeminiCliPatch};
 use crate::error::{OauthError, PolluxError};
-use crate::model_catalog::MODEL_REGISTRY;
+use crate::model_catalog;
+use crate::providers::coordination::{RecentChanges, RefreshClaim, remaining};
 use crate::providers::geminicli::client::oauth::endpoints::GoogleTokenResponse;
 use crate::providers::geminicli::client::oauth::utils::attach_email_from_id_token;
 use crate::providers::geminicli::resource::GeminiCliResource;
-use crate::providers::geminicli::{SUPPORTED_MODEL_MASK, SUPPORTED_MODEL_NAMES};
+use crate::providers::geminicli::{supported_model_mask, supported_model_names};
+use crate::providers::health::BreakerChange;
 use crate::providers::manifest::{GeminiCliLease, GeminiCliProfile};
+use crate::providers::policy::STORE_SYNC_INTERVAL;
+use crate::providers::snapshot::{PoolStatus, SchedulerSnapshot};
+use crate::providers::wait_queue::{CredentialReply, WaitQueue};
+use crate::providers::{LeaseGuard, REFRESH_AHEAD, REFRESH_SWEEP_INTERVAL, refresh_sweep_budget};
+use chrono::Utc;
 use ractor::{Actor, ActorProcessingErr, ActorRef, RpcReplyPort};
 use serde_json::json;
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tracing::{debug, error, info, warn};
 
 #[derive(Debug, Clone)]
@@ -36,7 +47,20 @@ impl GeminiCliRefreshTokenSeed {
 #[derive(Debug)]
 pub enum GeminiCliActorMessage {
     /// Request one available credential for the given model mask. Err if none available.
-    GetCredential(u64, RpcReplyPort<Option<GeminiCliLease>>),
+    /// With a session key and `session_affinity` on, the session's previous credential is
+    /// preferred while it can serve the model.
+    /// With `wait` set and `queue_timeout_secs` configured, a caller that finds nothing waits
+    /// in the queue instead; `QueueFull` when that is full too.
+    GetCredential(
+        u64,
+        Option<Arc<str>>,
+        bool,
+        CredentialReply<(GeminiCliLease, LeaseGuard)>,
+    ),
+    /// A lease from `GetCredential` has ended (its `LeaseGuard` was dropped).
+    ReleaseLease(CredentialId),
+    /// Capacity of the models in the mask, for a caller that got no credential.
+    GetPoolStatus(u64, RpcReplyPort<PoolStatus>),
     /// Report rate limiting for a model mask; start cooldown with lazy re-enqueue.
     ReportRateLimit {
         id: CredentialId,
@@ -49,6 +73,10 @@ pub enum GeminiCliActorMessage {
     ReportInvalid { id: CredentialId },
     /// Report a credential as banned/unusable; remove from queues and storage.
     ReportBaned { id: CredentialId },
+    /// Report a successful upstream response; closes a half-open breaker.
+    ReportSuccess { id: CredentialId },
+    /// Report a failure no mapping rule explained (5xx, transport error, unmapped 403).
+    ReportFailure { id: CredentialId },
 
     /// Submit a batch of credentials and trigger one refresh pass for each.
     SubmitCredentials(Vec<GeminiCliProfile>),
@@ -57,14 +85,42 @@ pub enum GeminiCliActorMessage {
     /// Submit refresh tokens as 0-trust seeds. The actor will refresh, onboard, then persist+activate.
     SubmitUntrustedSeeds(Vec<GeminiCliRefreshTokenSeed>),
 
+    /// Admin: return a point-in-time view of the scheduler state.
+    GetSnapshot(RpcReplyPort<SchedulerSnapshot>),
+    /// Admin: apply a reloaded config (model list, refresher client and rate limit).
+    Reload(Arc<GeminiCliResolvedConfig>),
+    /// Admin: enable or disable a credential, keeping DB status and queues in sync.
+    SetStatus(CredentialId, bool, RpcReplyPort<Result<(), PolluxError>>),
+    /// Admin: drop a credential from queues and delete its DB row.
+    DeleteCredential(CredentialId, RpcReplyPort<Result<(), PolluxError>>),
+    /// Re-read credentials and model state that other instances wrote; also sent every
+    /// `STORE_SYNC_INTERVAL`. Replies once the changes are applied.
+    SyncStore(Option<RpcReplyPort<()>>),
+
     // Internal messages (sent by the actor itself)
+    /// Look at the wait queue again: a waiter's deadline or a cooldown end has come.
+    ServeWaiters,
     /// Token refresh has completed; update stored credential and re-enqueue if ok.
     RefreshComplete { outcome: RefreshOutcome },
+    /// Periodic tick: refresh credentials nearing expiry before a request finds them expired.
+    RefreshSweep,
     /// A credential has been refreshed and stored; activate it in memory queues.
     ActivateCredential {
         id: CredentialId,
         credential: GeminiCliResource,
     },
+    /// Another instance refreshed a credential this one was about to; take its tokens.
+    AdoptRefreshed {
+        id: CredentialId,
+        credential: GeminiCliResource,
+    },
+    /// What a store sync read, starting at `read_at`.
+    StoreSynced {
+        read_at: Instant,
+        credentials: Vec<(CredentialId, GeminiCliResource)>,
+        model_state: Vec<(CredentialId, DbModelState)>,
+        reply: Option<RpcReplyPort<()>>,
+    },
 }
 
 /// Handle for interacting with the Gemini CLI actor.
@@ -74,13 +130,22 @@ pub struct GeminiCliActorHandle {
 }
 
 impl GeminiCliActorHandle {
-    /// Request a credential based on target model mask. Returns error if none available.
+    /// Request a credential based on target model mask. Returns `None` if none available.
+    /// With `wait`, the caller first waits in the queue when `queue_timeout_secs` is set.
     pub async fn get_credential(
         &self,
         model_mask: u64,
-    ) -> Result<Option<GeminiCliLease>, PolluxError> {
-        ractor::call!(self.actor, GeminiCliActorMessage::GetCredential, model_mask)
-            .map_err(|e| PolluxError::RactorError(format!("GetCredential RPC failed:: {e}")))
+        session: Option<Arc<str>>,
+        wait: bool,
+    ) -> Result<Option<(GeminiCliLease, LeaseGuard)>, PolluxError> {
+        ractor::call!(
+            self.actor,
+            GeminiCliActorMessage::GetCredential,
+            model_mask,
+            session,
+            wait
+        )
+        .map_err(|e| PolluxError::RactorError(format!("GetCredential RPC failed: {e}")))?
     }
 
     /// Report rate limit; the actor will cool down this credential before reuse.
@@ -113,6 +178,14 @@ impl GeminiCliActorHandle {
         let _ = ractor::cast!(self.actor, GeminiCliActorMessage::ReportBaned { id });
     }
 
+    pub async fn report_success(&self, id: CredentialId) {
+        let _ = ractor::cast!(self.actor, GeminiCliActorMessage::ReportSuccess { id });
+    }
+
+    pub async fn report_failure(&self, id: CredentialId) {
+        let _ = ractor::cast!(self.actor, GeminiCliActorMessage::ReportFailure { id });
+    }
+
     /// Submit new credentials to the actor and trigger refresh for each.
     pub async fn submit_credentials(&self, creds: Vec<GeminiCliProfile>) {
         let _ = ractor::cast!(self.actor, GeminiCliActorMessage::SubmitCredentials(creds));
@@ -143,6 +216,52 @@ impl GeminiCliActorHandle {
         );
     }
 
+    /// Total and available credentials for the models in `model_mask`, and when the earliest
+    /// cooldown on them ends.
+    pub async fn pool_status(&self, model_mask: u64) -> Result<PoolStatus, PolluxError> {
+        ractor::call!(self.actor, GeminiCliActorMessage::GetPoolStatus, model_mask)
+            .map_err(|e| PolluxError::RactorError(format!("GetPoolStatus RPC failed: {e}")))
+    }
+
+    /// Apply a reloaded config: resize the per-model queues to the current model registry and
+    /// restart the refresh pipeline. Leases already handed out are unaffected.
+    ///
+    /// Cast rather than called, so a reload cannot fail halfway; requests sent after it are
+    /// handled after it.
+    pub fn reload(&self, cfg: Arc<GeminiCliResolvedConfig>) {
+        if let Err(e) = ractor::cast!(self.actor, GeminiCliActorMessage::Reload(cfg)) {
+            warn!("Reload cast failed: {}", e);
+        }
+    }
+
+    /// Snapshot queues, cooldowns, refreshing ids and per-credential caps.
+    pub async fn snapshot(&self) -> Result<SchedulerSnapshot, PolluxError> {
+        ractor::call!(self.actor, GeminiCliActorMessage::GetSnapshot)
+            .map_err(|e| PolluxError::RactorError(format!("GetSnapshot RPC failed: {e}")))
+    }
+
+    /// Enable or disable a credential. Disabling takes it out of rotation immediately;
+    /// enabling reloads it from the DB and puts it back into the queues.
+    pub async fn set_status(&self, id: CredentialId, enabled: bool) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, GeminiCliActorMessage::SetStatus, id, enabled)
+            .map_err(|e| PolluxError::RactorError(format!("SetStatus RPC failed: {e}")))?
+    }
+
+    /// Remove a credential from rotation and delete it from storage.
+    pub async fn delete_credential(&self, id: CredentialId) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, GeminiCliActorMessage::DeleteCredential, id)
+            .map_err(|e| PolluxError::RactorError(format!("DeleteCredential RPC failed: {e}")))?
+    }
+
+    /// Pick up what other instances sharing the database changed, without waiting for the
+    /// next periodic sync.
+    pub async fn sync_store(&self) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, |reply| GeminiCliActorMessage::SyncStore(Some(
+            reply
+        )))
+        .map_err(|e| PolluxError::RactorError(format!("SyncStore RPC failed: {e}")))
+    }
+
     pub(in crate::providers::geminicli) fn send_refresh_complete(
         &self,
         outcome: RefreshOutcome,
@@ -161,6 +280,12 @@ struct GeminiCliActorState {
     manager: CredentialManager,
     model_caps_all: u64,
     refresh_handle: GeminiCliRefresherHandle,
+    /// Sizes each refresh sweep; follows `oauth_tps` across reloads.
+    oauth_tps: usize,
+    session_affinity: bool,
+    waiters: WaitQueue<(GeminiCliLease, LeaseGuard)>,
+    /// Credentials added or removed here lately, which a store sync leaves alone.
+    recent: RecentChanges,
 }
 
 /// ractor-based Gemini CLI actor.
@@ -186,12 +311,16 @@ impl Actor for GeminiCliActor {
         )
         .await?;
 
-        let model_count = MODEL_REGISTRY.len();
-        let model_caps_all = *SUPPORTED_MODEL_MASK;
+        let model_count = model_catalog::registry().len();
+        let model_caps_all = supported_model_mask();
 
         let mut manager = CredentialManager::new(model_count);
+        manager.set_scheduling(cfg.scheduling, cfg.weights.clone());
+        manager.set_max_concurrent(cfg.max_concurrent_per_credential);
+        let mut waiters = WaitQueue::default();
+        waiters.configure(cfg.queue_timeout_secs, cfg.queue_max_depth);
 
-        let model_names = (*SUPPORTED_MODEL_NAMES).clone();
+        let model_names = supported_model_names();
         info!(
             "GeminiCliActor initializing with supported models: {:?}",
             model_names
@@ -205,6 +334,18 @@ impl Actor for GeminiCliActor {
         for (id, cred) in rows {
             manager.add_credential(id, cred, model_caps_all);
         }
+        match ops.load_model_state().await {
+            Ok(saved) => {
+                let (unsupported, cooling) = apply_model_state(&mut manager, saved);
+                if unsupported + cooling > 0 {
+                    info!(
+                        "GeminiCliActor restored {} unsupported models and {} cooldowns from DB",
+                        unsupported, cooling
+                    );
+                }
+            }
+            Err(e) => warn!("GeminiCliActor could not restore saved model state: {}", e),
+        }
 
         info!(
             "GeminiCliActor started from DB: {} active creds loaded into {} queues",
@@ -216,6 +357,11 @@ impl Actor for GeminiCliActor {
             proxy = %cfg.proxy.as_ref().map(|u| u.as_str()).unwrap_or("<none>"),
             enable_multiplexing = cfg.enable_multiplexing,
             oauth_tps = cfg.oauth_tps,
+            scheduling = ?cfg.scheduling,
+            session_affinity = cfg.session_affinity,
+            max_concurrent_per_credential = ?cfg.max_concurrent_per_credential,
+            queue_timeout_secs = ?cfg.queue_timeout_secs,
+            queue_max_depth = cfg.queue_max_depth,
             "GeminiCliActor runtime config loaded"
         );
 
@@ -224,9 +370,29 @@ impl Actor for GeminiCliActor {
             manager,
             model_caps_all,
             refresh_handle,
+            oauth_tps: cfg.oauth_tps,
+            session_affinity: cfg.session_affinity,
+            waiters,
+            recent: RecentChanges::default(),
         })
     }
 
+    async fn post_start(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        // Credentials loaded from the DB may already be close to expiry.
+        self.handle_refresh_sweep(myself.clone(), state);
+        myself.send_interval(REFRESH_SWEEP_INTERVAL, || {
+            GeminiCliActorMessage::RefreshSweep
+        });
+        myself.send_interval(STORE_SYNC_INTERVAL, || {
+            GeminiCliActorMessage::SyncStore(None)
+        });
+        Ok(())
+    }
+
     async fn handle(
         &self,
         myself: ActorRef<Self::Msg>,
@@ -234,11 +400,23 @@ impl Actor for GeminiCliActor {
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
         match message {
-            GeminiCliActorMessage::GetCredential(model_mask, rp) => {
-                self.handle_get_credential(myself.clone(), state, rp, model_mask)
+            GeminiCliActorMessage::GetCredential(model_mask, session, wait, rp) => {
+                self.handle_get_credential(myself.clone(), state, rp, model_mask, session, wait)
                     .await;
             }
 
+            GeminiCliActorMessage::ReleaseLease(id) => {
+                state.manager.release(id);
+            }
+
+            GeminiCliActorMessage::GetPoolStatus(model_mask, reply) => {
+                let _ = reply.send(state.manager.pool_status(model_mask));
+            }
+
+            GeminiCliActorMessage::ServeWaiters => {
+                state.waiters.woke();
+            }
+
             GeminiCliActorMessage::ReportRateLimit {
                 id,
                 cooldown,
@@ -257,6 +435,12 @@ impl Actor for GeminiCliActor {
             GeminiCliActorMessage::ReportBaned { id } => {
                 self.handle_report_baned(state, id).await;
             }
+            GeminiCliActorMessage::ReportSuccess { id } => {
+                self.handle_report_outcome(state, id, true);
+            }
+            GeminiCliActorMessage::ReportFailure { id } => {
+                self.handle_report_outcome(state, id, false);
+            }
             GeminiCliActorMessage::SubmitCredentials(creds_vec) => {
                 self.handle_submit_credentials(state, creds_vec).await;
             }
@@ -267,23 +451,184 @@ impl Actor for GeminiCliActor {
             GeminiCliActorMessage::SubmitUntrustedSeeds(seeds) => {
                 self.handle_submit_untrusted_seeds(state, seeds).await;
             }
+            GeminiCliActorMessage::GetSnapshot(reply) => {
+                let mut snapshot = state.manager.snapshot(state.model_caps_all);
+                snapshot.waiting = state.waiters.len();
+                let _ = reply.send(snapshot);
+            }
+            GeminiCliActorMessage::Reload(cfg) => self.handle_reload(state, cfg),
+            GeminiCliActorMessage::SetStatus(id, enabled, reply) => {
+                self.handle_set_status(myself.clone(), state, id, enabled, reply);
+            }
+            GeminiCliActorMessage::DeleteCredential(id, reply) => {
+                self.handle_delete_credential(state, id, reply);
+            }
             GeminiCliActorMessage::RefreshComplete { outcome } => {
                 self.handle_refresh_complete(myself.clone(), state, outcome)
                     .await;
             }
+            GeminiCliActorMessage::RefreshSweep => {
+                self.handle_refresh_sweep(myself.clone(), state);
+            }
             GeminiCliActorMessage::ActivateCredential { id, credential } => {
                 let project = credential.project_id().to_string();
                 state
                     .manager
                     .add_credential(id, credential, state.model_caps_all);
+                state.recent.record(id);
                 info!("ID: {id}, Project: {project}, submitted and activated");
             }
+            GeminiCliActorMessage::AdoptRefreshed { id, credential } => {
+                if state.manager.is_refreshing(id) {
+                    state
+                        .manager
+                        .add_credential(id, credential, state.model_caps_all);
+                    info!("ID: {id} refreshed by another instance, using its tokens");
+                }
+            }
+            GeminiCliActorMessage::SyncStore(reply) => {
+                self.handle_sync_store(myself.clone(), state, reply);
+            }
+            GeminiCliActorMessage::StoreSynced {
+                read_at,
+                credentials,
+                model_state,
+                reply,
+            } => {
+                self.handle_store_synced(state, read_at, credentials, model_state);
+                if let Some(reply) = reply {
+                    let _ = reply.send(());
+                }
+            }
+        }
+        // Anything above may have freed capacity for callers in the wait queue.
+        if !state.waiters.is_empty() {
+            self.serve_waiters(myself, state).await;
         }
         Ok(())
     }
 }
 
 impl GeminiCliActor {
+    fn handle_reload(&self, state: &mut GeminiCliActorState, cfg: Arc<GeminiCliResolvedConfig>) {
+        let served = supported_model_mask();
+        let model_count = model_catalog::registry().len();
+        state
+            .manager
+            .set_served_models(model_count, state.model_caps_all, served);
+        state.model_caps_all = served;
+        state.oauth_tps = cfg.oauth_tps;
+        state.session_affinity = cfg.session_affinity;
+        if !cfg.session_affinity {
+            state.manager.clear_sessions();
+        }
+        state
+            .manager
+            .set_scheduling(cfg.scheduling, cfg.weights.clone());
+        state
+            .manager
+            .set_max_concurrent(cfg.max_concurrent_per_credential);
+        state
+            .waiters
+            .configure(cfg.queue_timeout_secs, cfg.queue_max_depth);
+
+        if let Err(e) = state.refresh_handle.reconfigure(cfg) {
+            warn!(
+                "GeminiCliActor reload could not reconfigure the refresher: {}",
+                e
+            );
+        }
+        info!(
+            "GeminiCliActor reloaded: serving {:?} across {} queues",
+            supported_model_names(),
+            model_count
+        );
+    }
+
+    /// Read the active credentials and saved model state off the actor loop, then come back
+    /// with them as `StoreSynced`.
+    fn handle_sync_store(
+        &self,
+        myself: ActorRef<GeminiCliActorMessage>,
+        state: &GeminiCliActorState,
+        reply: Option<RpcReplyPort<()>>,
+    ) {
+        let ops = state.ops.clone();
+        tokio::spawn(async move {
+            let read_at = Instant::now();
+            let read = async {
+                let credentials = ops.load_active().await?;
+                let model_state = ops.load_model_state().await?;
+                Ok::<_, PolluxError>((credentials, model_state))
+            }
+            .await;
+            match read {
+                Ok((credentials, model_state)) => {
+                    let _ = myself.cast(GeminiCliActorMessage::StoreSynced {
+                        read_at,
+                        credentials,
+                        model_state,
+                        reply,
+                    });
+                }
+                Err(e) => {
+                    warn!("GeminiCliActor store sync failed: {}", e);
+                    if let Some(reply) = reply {
+                        let _ = reply.send(());
+                    }
+                }
+            }
+        });
+    }
+
+    /// Bring the pool in line with the store: add credentials other instances onboarded or
+    /// re-enabled, drop the ones they disabled, banned or deleted, take tokens they refreshed,
+    /// and apply the cooldowns and unsupported models they reported.
+    fn handle_store_synced(
+        &self,
+        state: &mut GeminiCliActorState,
+        read_at: Instant,
+        credentials: Vec<(CredentialId, GeminiCliResource)>,
+        model_state: Vec<(CredentialId, DbModelState)>,
+    ) {
+        let since = state.recent.begin_sync(read_at);
+        let active: HashSet<CredentialId> = credentials.iter().map(|(id, _)| *id).collect();
+        let (mut added, mut updated, mut removed) = (0, 0, 0);
+
+        for id in state.manager.credential_ids() {
+            if !active.contains(&id) && !state.recent.changed_since(id, since) {
+                state.manager.delete_credential(id);
+                removed += 1;
+            }
+        }
+        for (id, credential) in credentials {
+            if state.recent.changed_since(id, since) {
+                continue;
+            }
+            match state.manager.expiry_of(id) {
+                None => added += 1,
+                // Newer tokens, refreshed elsewhere. A local refresh settles it on its own.
+                Some(expiry)
+                    if credential.expiry() > expiry && !state.manager.is_refreshing(id) =>
+                {
+                    updated += 1
+                }
+                Some(_) => continue,
+            }
+            state
+                .manager
+                .add_credential(id, credential, state.model_caps_all);
+        }
+        let (unsupported, cooling) = apply_model_state(&mut state.manager, model_state);
+
+        if added + updated + removed + unsupported + cooling > 0 {
+            info!(
+                "GeminiCliActor synced from DB: {} added, {} refreshed, {} removed, {} models unsupported, {} cooldowns",
+                added, updated, removed, unsupported, cooling
+            );
+        }
+    }
+
     fn handle_report_model_unsupported(
         &self,
         state: &mut GeminiCliActorState,
@@ -307,6 +652,7 @@ impl GeminiCliActor {
         if before_bits == after_bits {
             return;
         }
+        state.ops.save_unsupported(id, model_mask);
 
         let disabled_names = crate::model_catalog::format_model_mask(model_mask);
         if after_bits == 0 {
@@ -326,25 +672,35 @@ impl GeminiCliActor {
         &self,
         myself: ActorRef<GeminiCliActorMessage>,
         state: &mut GeminiCliActorState,
-        reply_port: RpcReplyPort<Option<GeminiCliLease>>,
+        reply_port: CredentialReply<(GeminiCliLease, LeaseGuard)>,
         model_mask: u64,
+        session: Option<Arc<str>>,
+        wait: bool,
     ) {
-        let assignment = state.manager.get_assigned(model_mask);
-
-        if !assignment.refresh_ids.is_empty() {
-            self.handle_report_invalid(myself, state, assignment.refresh_ids)
-                .await;
+        let session = session.filter(|_| state.session_affinity);
+        let wait = wait && state.waiters.is_enabled();
+        // Callers already waiting get freed capacity first.
+        if !state.waiters.is_empty() {
+            self.serve_waiters(myself.clone(), state).await;
+        }
+        if wait && !state.waiters.is_empty() {
+            self.enqueue_waiter(state, model_mask, session, reply_port);
+            return;
         }
 
-        if let Some(assigned) = assignment.assigned {
-            info!(
-                "Get credential: ID: {}, Project: {}, model_mask=0x{:016x}, queue_len={}",
-                assigned.id,
-                assigned.project_id,
-                model_mask,
-                state.manager.queue_len(model_mask)
-            );
-            let _ = reply_port.send(Some(assigned));
+        let mut refresh_ids = Vec::new();
+        let assigned = assign(
+            &mut state.manager,
+            &myself,
+            model_mask,
+            session.clone(),
+            &mut refresh_ids,
+        );
+        if !refresh_ids.is_empty() {
+            self.handle_report_invalid(myself, state, refresh_ids).await;
+        }
+        if assigned.is_some() {
+            let _ = reply_port.send(Ok(assigned));
             return;
         }
 
@@ -355,7 +711,60 @@ impl GeminiCliActor {
             state.manager.cooldown_len(),
             state.manager.refreshing_len()
         );
-        let _ = reply_port.send(None);
+        if wait {
+            self.enqueue_waiter(state, model_mask, session, reply_port);
+        } else {
+            let _ = reply_port.send(Ok(None));
+        }
+    }
+
+    /// Park the caller until a credential frees up, or shed it when the queue is full.
+    fn enqueue_waiter(
+        &self,
+        state: &mut GeminiCliActorState,
+        model_mask: u64,
+        session: Option<Arc<str>>,
+        reply_port: CredentialReply<(GeminiCliLease, LeaseGuard)>,
+    ) {
+        let Err(reply_port) = state
+            .waiters
+            .push(model_mask, session, reply_port, Instant::now())
+        else {
+            return;
+        };
+        let retry_after = state
+            .manager
+            .cooldown_remaining(model_mask)
+            .unwrap_or_else(|| state.waiters.timeout());
+        warn!(
+            "Wait queue full ({} waiting), shedding model_mask=0x{:016x}, retry after {:?}",
+            state.waiters.len(),
+            model_mask,
+            retry_after
+        );
+        let _ = reply_port.send(Err(PolluxError::QueueFull { retry_after }));
+    }
+
+    /// Offer capacity to waiting callers in arrival order, then schedule the next look.
+    async fn serve_waiters(
+        &self,
+        myself: ActorRef<GeminiCliActorMessage>,
+        state: &mut GeminiCliActorState,
+    ) {
+        let mut refresh_ids = Vec::new();
+        let manager = &mut state.manager;
+        state.waiters.serve(Instant::now(), |model_mask, session| {
+            assign(manager, &myself, model_mask, session, &mut refresh_ids)
+        });
+        if !refresh_ids.is_empty() {
+            self.handle_report_invalid(myself.clone(), state, refresh_ids)
+                .await;
+        }
+        if let Some(at) = state.waiters.next_wake(state.manager.next_capacity_at()) {
+            myself.send_after(at.saturating_duration_since(Instant::now()), || {
+                GeminiCliActorMessage::ServeWaiters
+            });
+        }
     }
 
     fn handle_report_rate_limit(
@@ -369,6 +778,7 @@ impl GeminiCliActor {
             return;
         }
         state.manager.report_rate_limit(id, model_mask, cooldown);
+        state.ops.save_cooldown(id, model_mask, cooldown);
 
         info!(
             "ID: {id}, Credential starting cooldown for model_mask=0x{:016x}, lazy re-enqueue after {} secs",
@@ -387,6 +797,8 @@ impl GeminiCliActor {
         let mut jobs_to_send = Vec::new();
         for id in ids {
             if state.manager.is_refreshing(id) {
+                // A refresh ahead of expiry may be in flight; keep it out of rotation now.
+                state.manager.mark_refreshing(id);
                 debug!("ID: {id} in batch already refreshing, skipping.");
                 continue;
             }
@@ -405,9 +817,56 @@ impl GeminiCliActor {
         if jobs_to_send.is_empty() {
             return;
         }
-        let refresh_handle = state.refresh_handle.clone();
-        tokio::spawn(async move {
-            for (id, cred) in jobs_to_send {
+        self.submit_refresh_jobs(myself, state, jobs_to_send);
+    }
+
+    /// Refresh credentials ahead of expiry, soonest first, capped so the refresher keeps up
+    /// under `oauth_tps`. They stay in rotation while their current token is still valid.
+    fn handle_refresh_sweep(
+        &self,
+        myself: ActorRef<GeminiCliActorMessage>,
+        state: &mut GeminiCliActorState,
+    ) {
+        let due = state.manager.due_for_refresh(
+            Utc::now() + REFRESH_AHEAD,
+            refresh_sweep_budget(state.oauth_tps),
+        );
+        if due.is_empty() {
+            return;
+        }
+
+        let mut jobs_to_send = Vec::with_capacity(due.len());
+        for id in due {
+            if let Some(current) = state.manager.get_full_credential_copy(id) {
+                state.manager.mark_renewing(id);
+                jobs_to_send.push((id, current));
+            }
+        }
+        info!(
+            "GeminiCliActor refresh sweep: {} credentials expire within {} minutes, refreshing ahead",
+            jobs_to_send.len(),
+            REFRESH_AHEAD.num_minutes()
+        );
+        self.submit_refresh_jobs(myself, state, jobs_to_send);
+    }
+
+    /// Hand credentials to the refresher once this instance holds their refresh lease. While
+    /// another instance holds it, wait for the tokens it stores instead.
+    fn submit_refresh_jobs(
+        &self,
+        myself: ActorRef<GeminiCliActorMessage>,
+        state: &GeminiCliActorState,
+        jobs_to_send: Vec<(CredentialId, GeminiCliResource)>,
+    ) {
+        for (id, cred) in jobs_to_send {
+            let ops = state.ops.clone();
+            let refresh_handle = state.refresh_handle.clone();
+            let myself = myself.clone();
+            tokio::spawn(async move {
+                if let RefreshClaim::Refreshed(credential) = ops.claim_refresh(id, &cred).await {
+                    let _ = myself.cast(GeminiCliActorMessage::AdoptRefreshed { id, credential });
+                    return;
+                }
                 if let Err(e) = refresh_handle.submit_refresh(id, cred.clone()) {
                     warn!("ID: {id} Batch refresh enqueue failed. Rolling back.");
 
@@ -421,8 +880,54 @@ impl GeminiCliActor {
                 } else {
                     debug!("ID: {id} Batch refresh enqueued.");
                 }
+            });
+        }
+    }
+
+    /// Feed an upstream outcome into the credential's breaker and log what it did.
+    fn handle_report_outcome(&self, state: &mut GeminiCliActorState, id: CredentialId, ok: bool) {
+        let change = if ok {
+            state.manager.report_success(id)
+        } else {
+            state.manager.report_failure(id)
+        };
+        let Some(health) = state.manager.health_of(id) else {
+            return;
+        };
+        let project = state
+            .manager
+            .project_id_of(id)
+            .unwrap_or_else(|| "-".to_string());
+        match change {
+            Some(BreakerChange::Tripped { park, probe }) => {
+                warn!(
+                    credential.id = id,
+                    health.score = health.score(),
+                    health.failure_streak = health.failure_streak(),
+                    "ID: {id} ({project}), {}, parked for {} secs (score {:.2})",
+                    if probe {
+                        "probe failed"
+                    } else {
+                        "breaker opened"
+                    },
+                    park.as_secs(),
+                    health.score()
+                );
             }
-        });
+            Some(BreakerChange::Recovered) => {
+                info!("ID: {id} ({project}), probe succeeded, breaker closed");
+            }
+            None if !ok => {
+                debug!(
+                    credential.id = id,
+                    health.score = health.score(),
+                    health.failure_streak = health.failure_streak(),
+                    "ID: {id} ({project}), unexplained failure (score {:.2})",
+                    health.score()
+                );
+            }
+            None => {}
+        }
     }
 
     async fn handle_report_baned(&self, state: &mut GeminiCliActorState, id: CredentialId) {
@@ -433,6 +938,7 @@ impl GeminiCliActor {
         let removed_cred = state.manager.contains(id);
 
         state.manager.delete_credential(id);
+        state.recent.record(id);
 
         let ops = state.ops.clone();
         let project_for_db = project.clone();
@@ -531,6 +1037,77 @@ impl GeminiCliActor {
         });
     }
 
+    fn handle_set_status(
+        &self,
+        myself: ActorRef<GeminiCliActorMessage>,
+        state: &mut GeminiCliActorState,
+        id: CredentialId,
+        enabled: bool,
+        reply: RpcReplyPort<Result<(), PolluxError>>,
+    ) {
+        let ops = state.ops.clone();
+
+        if !enabled {
+            let removed = state.manager.contains(id);
+            state.manager.delete_credential(id);
+            state.recent.record(id);
+            info!("ID: {id}, disabled by admin. removed_from_mem={}", removed);
+            tokio::spawn(async move {
+                let _ = reply.send(ops.set_status(id, false).await);
+            });
+            return;
+        }
+
+        tokio::spawn(async move {
+            let loaded = async {
+                ops.set_status(id, true).await?;
+                // Re-enabling starts over: saved cooldowns and unsupported models are dropped.
+                ops.clear_model_state(id).await?;
+                ops.load_by_id(id).await
+            }
+            .await;
+            match loaded {
+                Ok(credential) => {
+                    let res = myself
+                        .cast(GeminiCliActorMessage::ActivateCredential { id, credential })
+                        .map_err(|e| {
+                            PolluxError::RactorError(format!("ActivateCredential cast failed: {e}"))
+                        });
+                    let _ = reply.send(res);
+                }
+                Err(e) => {
+                    warn!("ID: {id} admin enable failed: {}", e);
+                    let _ = reply.send(Err(e));
+                }
+            }
+        });
+    }
+
+    fn handle_delete_credential(
+        &self,
+        state: &mut GeminiCliActorState,
+        id: CredentialId,
+        reply: RpcReplyPort<Result<(), PolluxError>>,
+    ) {
+        let project = state
+            .manager
+            .project_id_of(id)
+            .unwrap_or_else(|| "-".to_string());
+        let removed = state.manager.contains(id);
+
+        state.manager.delete_credential(id);
+        state.recent.record(id);
+        info!(
+            "ID: {id}, Project: {project}, deleted by admin. removed_from_mem={}",
+            removed
+        );
+
+        let ops = state.ops.clone();
+        tokio::spawn(async move {
+            let _ = reply.send(ops.delete(id).await);
+        });
+    }
+
     async fn handle_refresh_complete(
         &self,
         myself: ActorRef<GeminiCliActorMessage>,
@@ -542,6 +1119,7 @@ impl GeminiCliActor {
                 Ok(()) => {
                     if !state.manager.is_refreshing(id) {
                         debug!("ID: {id} Refresh completed after removal; skipping.");
+                        state.ops.release_refresh(id);
                         return;
                     }
                     debug!("ID: {id} Refresh success. Updating manager and persisting.");
@@ -559,11 +1137,13 @@ impl GeminiCliActor {
                         if let Err(e) = ops.update_by_id(id, patch).await {
                             warn!("ID: {id} DB update failed: {}", e);
                         }
+                        ops.release_refresh(id);
                     });
                 }
                 Err(err) => {
                     if !state.manager.is_refreshing(id) {
                         debug!("ID: {id} Refresh failed after removal; skipping.");
+                        state.ops.release_refresh(id);
                         return;
                     }
                     match err {
@@ -571,11 +1151,13 @@ impl GeminiCliActor {
                             error!("ID: {id} Refresh failed: {}. Removing.", err);
 
                             state.manager.delete_credential(id);
+                            state.recent.record(id);
                             let ops = state.ops.clone();
                             tokio::spawn(async move {
                                 if let Err(e) = ops.set_status(id, false).await {
                                     warn!("ID: {id} DB set_status failed: {}", e);
                                 }
+                                ops.release_refresh(id);
                             });
                         }
                         _ => {
@@ -584,6 +1166,7 @@ impl GeminiCliActor {
                                 err
                             );
                             state.manager.add_credential(id, cred, state.model_caps_all);
+                            state.ops.release_refresh(id);
                         }
                     }
                 }
@@ -625,6 +1208,38 @@ impl GeminiCliActor {
     }
 }
 
+/// Apply unsupported models and unexpired cooldowns saved by this or another instance, and
+/// return how many of each changed the pool. Models that are no longer configured are skipped.
+fn apply_model_state(
+    manager: &mut CredentialManager,
+    saved: Vec<(CredentialId, DbModelState)>,
+) -> (usize, usize) {
+    let (mut unsupported, mut cooling) = (0, 0);
+    for (id, state) in saved {
+        let Some(model_mask) = model_catalog::mask(&state.model) else {
+            continue;
+        };
+        if !manager.contains(id) {
+            continue;
+        }
+        if state.unsupported
+            && manager
+                .mark_model_unsupported(id, model_mask)
+                .is_some_and(|(before, after)| before != after)
+        {
+            unsupported += 1;
+        }
+        if state
+            .cooldown_until
+            .and_then(remaining)
+            .is_some_and(|remaining| manager.adopt_cooldown(id, model_mask, remaining))
+        {
+            cooling += 1;
+        }
+    }
+    (unsupported, cooling)
+}
+
 /// Async spawn of the Gemini CLI actor and return a handle.
 pub(in crate::providers) async fn spawn(
     db: crate::db::DbActorHandle,
@@ -641,3 +1256,36 @@ pub(in crate::providers) async fn spawn(
     .expect("failed to spawn GeminiCliActor");
     GeminiCliActorHandle { actor }
 }
+
+/// Schedule one lease for `model_mask`, collecting expired credentials met on the way.
+///
+/// The guard travels with the reply, so a caller that has already given up still releases
+/// the lease when the undelivered reply is dropped.
+fn assign(
+    manager: &mut CredentialManager,
+    myself: &ActorRef<GeminiCliActorMessage>,
+    model_mask: u64,
+    session: Option<Arc<str>>,
+    refresh_ids: &mut Vec<CredentialId>,
+) -> Option<(GeminiCliLease, LeaseGuard)> {
+    let assignment = match session {
+        Some(session) => manager.get_assigned_for_session(model_mask, session),
+        None => manager.get_assigned(model_mask),
+    };
+    refresh_ids.extend(assignment.refresh_ids);
+
+    let lease = assignment.assigned?;
+    info!(
+        "Get credential: ID: {}, Project: {}, model_mask=0x{:016x}, queue_len={}",
+        lease.id,
+        lease.project_id,
+        model_mask,
+        manager.queue_len(model_mask)
+    );
+    let actor = myself.clone();
+    let id = lease.id;
+    let guard = LeaseGuard::new(move || {
+        let _ = ractor::cast!(actor, GeminiCliActorMessage::ReleaseLease(id));
+    });
+    Some((lease, guard))
+}
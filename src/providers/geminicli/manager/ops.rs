@@ -1,7 +1,14 @@
 use super::scheduler::CredentialId;
-use crate::db::{DbActorHandle, GeminiCliCreate, GeminiCliPatch, ProviderCreate, ProviderPatch};
+use crate::db::{
+    DbActorHandle, DbModelState, GeminiCliCreate, GeminiCliPatch, ModelStateUpdate, ProviderCreate,
+    ProviderPatch,
+};
 use crate::error::PolluxError;
+use crate::model_catalog;
+use crate::providers::coordination::{self, RefreshClaim};
 use crate::providers::geminicli::resource::GeminiCliResource;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct CredentialOps {
@@ -25,6 +32,13 @@ impl CredentialOps {
         Ok(result)
     }
 
+    pub async fn load_by_id(&self, id: CredentialId) -> Result<GeminiCliResource, PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {}", id)))?;
+        let row = self.db.get_geminicli_by_id(db_id).await?;
+        Ok(row.into())
+    }
+
     pub async fn upsert(&self, cred: GeminiCliResource) -> Result<CredentialId, PolluxError> {
         if cred.sub().is_empty() {
             return Err(PolluxError::UnexpectedError(
@@ -63,4 +77,80 @@ impl CredentialOps {
         };
         self.db.patch(ProviderPatch::GeminiCli { id, patch }).await
     }
+
+    pub async fn delete(&self, id: CredentialId) -> Result<(), PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {}", id)))?;
+        self.db.delete_geminicli(db_id).await
+    }
+
+    /// Wait for the refresh lease of `cred`, or for another instance to store newer tokens.
+    pub async fn claim_refresh(
+        &self,
+        id: CredentialId,
+        cred: &GeminiCliResource,
+    ) -> RefreshClaim<GeminiCliResource> {
+        let expiry = cred.expiry();
+        coordination::claim_refresh(&self.db, "geminicli", id, || async move {
+            let stored = self.load_by_id(id).await?;
+            Ok((stored.expiry() > expiry).then_some(stored))
+        })
+        .await
+    }
+
+    /// Call once the refreshed tokens are written, so instances waiting on the lease find them.
+    pub fn release_refresh(&self, id: CredentialId) {
+        let Ok(db_id) = i64::try_from(id) else {
+            return;
+        };
+        self.db
+            .release_refresh_lease("geminicli", db_id, coordination::instance_id());
+    }
+
+    /// Unsupported models and unexpired cooldowns, as saved by any instance.
+    pub async fn load_model_state(&self) -> Result<Vec<(CredentialId, DbModelState)>, PolluxError> {
+        let rows = self.db.list_model_state("geminicli").await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| Some((u64::try_from(row.credential_id).ok()?, row)))
+            .collect())
+    }
+
+    pub fn save_unsupported(&self, id: CredentialId, model_mask: u64) {
+        self.save_model_state(id, model_mask, true, None);
+    }
+
+    pub fn save_cooldown(&self, id: CredentialId, model_mask: u64, cooldown: Duration) {
+        let until = chrono::Duration::from_std(cooldown)
+            .ok()
+            .and_then(|cooldown| Utc::now().checked_add_signed(cooldown));
+        self.save_model_state(id, model_mask, false, until);
+    }
+
+    fn save_model_state(
+        &self,
+        id: CredentialId,
+        model_mask: u64,
+        unsupported: bool,
+        cooldown_until: Option<DateTime<Utc>>,
+    ) {
+        let Ok(credential_id) = i64::try_from(id) else {
+            return;
+        };
+        for model in model_catalog::model_names_from_mask(model_mask) {
+            self.db.save_model_state(ModelStateUpdate {
+                provider: "geminicli",
+                credential_id,
+                model,
+                unsupported,
+                cooldown_until,
+            });
+        }
+    }
+
+    pub async fn clear_model_state(&self, id: CredentialId) -> Result<(), PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {}", id)))?;
+        self.db.clear_model_state("geminicli", db_id).await
+    }
 }
@@ -1,9 +1,20 @@
-use crate::model_catalog::ModelCapabilities;
+use crate::config::SchedulingStrategy;
+use crate::model_catalog::{ModelCapabilities, model_names_from_mask};
 use crate::providers::geminicli::resource::GeminiCliResource;
+use crate::providers::health::{BreakerChange, BreakerState, Health};
 use crate::providers::manifest::GeminiCliLease;
+use crate::providers::policy::COOLDOWN_SYNC_SLACK;
+use crate::providers::scheduling::{SelectionStats, Selector, SessionPins};
+use crate::providers::snapshot::{
+    CooldownSnapshot, CredentialSnapshot, ModelQueueSnapshot, PoolStatus, SchedulerSnapshot,
+    model_name,
+};
+use chrono::{DateTime, Utc};
 use std::{
     cmp::Reverse,
-    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque},
+    num::NonZeroU32,
+    sync::Arc,
     time::{Duration, Instant},
 };
 /// Runtime credential = base data + dynamic capabilities.
@@ -14,6 +25,12 @@ pub struct RuntimeCredential {
 
     // Dynamic capability bitset (runtime-only unless persisted elsewhere).
     pub caps: ModelCapabilities,
+
+    // Selection bookkeeping for the configured strategy (runtime-only).
+    pub stats: SelectionStats,
+
+    // Circuit breaker fed by unexplained upstream failures (runtime-only).
+    pub health: Health,
 }
 
 impl RuntimeCredential {
@@ -23,6 +40,8 @@ impl RuntimeCredential {
         Self {
             inner,
             caps: initial_caps,
+            stats: SelectionStats::default(),
+            health: Health::default(),
         }
     }
 
@@ -42,6 +61,17 @@ pub struct AssignmentResult {
     pub refresh_ids: Vec<CredentialId>,
 }
 
+/// Whether a queued credential can serve the model it is queued for.
+enum Eligibility {
+    /// Unsupported, cooling or refreshing: drop it from the queue.
+    Skip,
+    /// Expired or without an access token: drop it and ask for a refresh.
+    Refresh,
+    /// At its concurrency limit or parked by its breaker: keep it queued but pass over it.
+    Busy,
+    Ready,
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 struct CooldownTicket(Reverse<Instant>, CredentialId, ModelIndex);
 
@@ -52,6 +82,13 @@ pub struct CredentialManager {
     waiting_room: BinaryHeap<CooldownTicket>,
     cooldown_map: HashMap<(CredentialId, ModelIndex), Instant>,
     refreshing: HashSet<CredentialId>,
+    /// Refreshed ahead of expiry; still valid, so they keep serving meanwhile.
+    renewing: HashSet<CredentialId>,
+    selector: Selector,
+    sessions: SessionPins,
+    /// Leases handed out and not yet released, per credential.
+    in_flight: HashMap<CredentialId, u32>,
+    max_in_flight: Option<NonZeroU32>,
 }
 
 impl Default for CredentialManager {
@@ -68,6 +105,38 @@ impl CredentialManager {
             waiting_room: BinaryHeap::new(),
             cooldown_map: HashMap::new(),
             refreshing: HashSet::new(),
+            renewing: HashSet::new(),
+            selector: Selector::default(),
+            sessions: SessionPins::default(),
+            in_flight: HashMap::new(),
+            max_in_flight: None,
+        }
+    }
+
+    /// Switch the selection strategy; weights are keyed by user tier (`standard-tier`, ...).
+    pub fn set_scheduling(
+        &mut self,
+        strategy: SchedulingStrategy,
+        weights: BTreeMap<String, NonZeroU32>,
+    ) {
+        self.selector.configure(strategy, weights);
+        for cred in self.creds.values_mut() {
+            self.selector.classify(&mut cred.stats, cred.inner.tier());
+        }
+    }
+
+    /// Cap the leases each credential may have in flight; `None` removes the cap.
+    pub fn set_max_concurrent(&mut self, limit: Option<NonZeroU32>) {
+        self.max_in_flight = limit;
+    }
+
+    /// End one lease of `id`, making room under its concurrency limit.
+    pub fn release(&mut self, id: CredentialId) {
+        if let Some(count) = self.in_flight.get_mut(&id) {
+            *count -= 1;
+            if *count == 0 {
+                self.in_flight.remove(&id);
+            }
         }
     }
 
@@ -78,14 +147,20 @@ impl CredentialManager {
         initial_caps_bits: u64,
     ) {
         let initial_caps = ModelCapabilities::from_bits(initial_caps_bits);
-        let caps = self
+        let (caps, stats, health) = self
             .creds
             .get(&id)
-            .map(|cred| cred.caps)
-            .unwrap_or(initial_caps);
-
-        self.creds.insert(id, RuntimeCredential::new(cred, caps));
+            .map(|cred| (cred.caps, cred.stats, cred.health))
+            .unwrap_or((initial_caps, SelectionStats::default(), Health::default()));
+
+        let mut runtime = RuntimeCredential::new(cred, caps);
+        runtime.stats = stats;
+        runtime.health = health;
+        self.selector
+            .classify(&mut runtime.stats, runtime.inner.tier());
+        self.creds.insert(id, runtime);
         self.refreshing.remove(&id);
+        self.renewing.remove(&id);
 
         for (index, queue) in self.queues.iter_mut().enumerate() {
             if !caps.supports(index) {
@@ -110,10 +185,30 @@ impl CredentialManager {
     }
 
     pub fn mark_refreshing(&mut self, id: CredentialId) {
+        self.renewing.remove(&id);
         self.refreshing.insert(id);
         self.clear_cooldowns_for(id);
     }
 
+    /// Like `mark_refreshing`, but the credential stays in rotation until the refresh lands.
+    pub fn mark_renewing(&mut self, id: CredentialId) {
+        self.renewing.insert(id);
+    }
+
+    /// Up to `limit` credentials expiring before `deadline` that are not being refreshed yet,
+    /// soonest first.
+    pub fn due_for_refresh(&self, deadline: DateTime<Utc>, limit: usize) -> Vec<CredentialId> {
+        let mut due: Vec<(DateTime<Utc>, CredentialId)> = self
+            .creds
+            .iter()
+            .filter(|(id, _)| !self.is_refreshing(**id))
+            .map(|(id, cred)| (cred.inner.expiry(), *id))
+            .filter(|(expiry, _)| *expiry < deadline)
+            .collect();
+        due.sort_unstable();
+        due.into_iter().take(limit).map(|(_, id)| id).collect()
+    }
+
     pub fn mark_model_unsupported(
         &mut self,
         id: CredentialId,
@@ -129,23 +224,107 @@ impl CredentialManager {
         Some((before, after))
     }
 
+    /// Apply a reloaded model list: grow the per-model queues to `model_count`, give every
+    /// credential the models `served` gained since `previous`, and drop the ones it lost.
+    pub fn set_served_models(&mut self, model_count: usize, previous: u64, served: u64) {
+        if self.queues.len() < model_count {
+            self.queues.resize_with(model_count, VecDeque::new);
+        }
+        let added = served & !previous;
+        let removed = previous & !served;
+
+        for (index, queue) in self.queues.iter_mut().enumerate() {
+            if removed & (1u64 << index) != 0 {
+                queue.clear();
+            }
+        }
+        for (&id, cred) in self.creds.iter_mut() {
+            cred.caps = ModelCapabilities::from_bits((cred.caps.bits() | added) & !removed);
+            for (index, queue) in self.queues.iter_mut().enumerate() {
+                if added & (1u64 << index) != 0 && !queue.contains(&id) {
+                    queue.push_back(id);
+                }
+            }
+        }
+    }
+
     pub fn delete_credential(&mut self, id: CredentialId) {
         self.creds.remove(&id);
         self.refreshing.remove(&id);
+        self.renewing.remove(&id);
         self.clear_cooldowns_for(id);
     }
 
+    /// Record a request `id` served; a successful probe closes its breaker.
+    pub fn report_success(&mut self, id: CredentialId) -> Option<BreakerChange> {
+        self.creds
+            .get_mut(&id)?
+            .health
+            .record_success(Instant::now())
+    }
+
+    /// Record an unexplained failure of `id`; enough of them park it.
+    pub fn report_failure(&mut self, id: CredentialId) -> Option<BreakerChange> {
+        self.creds
+            .get_mut(&id)?
+            .health
+            .record_failure(Instant::now())
+    }
+
+    pub fn health_of(&self, id: CredentialId) -> Option<Health> {
+        self.creds.get(&id).map(|cred| cred.health)
+    }
+
     pub fn report_rate_limit(&mut self, id: CredentialId, model_mask: u64, cooldown: Duration) {
         let Some(model_index) = self.index_from_mask(model_mask) else {
             return;
         };
-        let deadline = Instant::now() + cooldown;
+        let now = Instant::now();
+        let deadline = now + cooldown;
+        if let Some(cred) = self.creds.get_mut(&id) {
+            cred.stats.record_rate_limit(now);
+        }
 
         self.cooldown_map.insert((id, model_index), deadline);
         self.waiting_room
             .push(CooldownTicket(Reverse(deadline), id, model_index));
     }
 
+    /// Apply a cooldown another instance saved, unless the local one ends about as late.
+    /// Returns whether it was applied.
+    pub fn adopt_cooldown(
+        &mut self,
+        id: CredentialId,
+        model_mask: u64,
+        remaining: Duration,
+    ) -> bool {
+        let Some(model_index) = self.index_from_mask(model_mask) else {
+            return false;
+        };
+        if !self.creds.contains_key(&id) {
+            return false;
+        }
+        let deadline = Instant::now() + remaining;
+        if self
+            .cooldown_map
+            .get(&(id, model_index))
+            .is_some_and(|local| *local + COOLDOWN_SYNC_SLACK >= deadline)
+        {
+            return false;
+        }
+        self.report_rate_limit(id, model_mask, remaining);
+        true
+    }
+
+    /// Ids of every credential in the pool, refreshing ones included.
+    pub fn credential_ids(&self) -> Vec<CredentialId> {
+        self.creds.keys().copied().collect()
+    }
+
+    pub fn expiry_of(&self, id: CredentialId) -> Option<DateTime<Utc>> {
+        self.creds.get(&id).map(|cred| cred.inner.expiry())
+    }
+
     pub fn get_full_credential_copy(&self, id: CredentialId) -> Option<GeminiCliResource> {
         self.creds.get(&id).map(|cred| cred.inner.clone())
     }
@@ -169,41 +348,161 @@ impl CredentialManager {
             return result;
         };
 
-        while let Some(id) = self.queues.get_mut(model_index).and_then(|q| q.pop_front()) {
-            let Some(cred) = self.creds.get(&id) else {
-                continue;
+        let picked = match self.selector.strategy() {
+            SchedulingStrategy::RoundRobin => {
+                self.next_in_rotation(model_index, &mut result.refresh_ids)
+            }
+            _ => self.best_in_queue(model_index, &mut result.refresh_ids),
+        };
+        result.assigned = picked.and_then(|id| self.lease(id, Instant::now()));
+        result
+    }
+
+    /// Like `get_assigned`, but keeps `session` on the credential that served it last while
+    /// that credential can serve the model. Otherwise it schedules normally and re-pins the
+    /// session to whichever credential that picks.
+    pub fn get_assigned_for_session(
+        &mut self,
+        model_mask: u64,
+        session: Arc<str>,
+    ) -> AssignmentResult {
+        let now = Instant::now();
+        if let Some(model_index) = self.index_from_mask(model_mask)
+            && let Some(id) = self.sessions.get(&session, now)
+            && matches!(self.eligibility(id, model_index), Eligibility::Ready)
+            && let Some(lease) = self.lease(id, now)
+        {
+            self.sessions.pin(session, id, now);
+            return AssignmentResult {
+                assigned: Some(lease),
+                refresh_ids: Vec::new(),
             };
+        }
 
-            if !cred.caps.supports(model_index) {
-                continue;
-            }
+        let result = self.get_assigned(model_mask);
+        if let Some(lease) = &result.assigned {
+            self.sessions.pin(session, lease.id, now);
+        }
+        result
+    }
 
-            if self.refreshing.contains(&id) || self.is_model_cooling(id, model_index) {
-                continue;
-            }
+    /// Forget every sticky session, e.g. when affinity is switched off.
+    pub fn clear_sessions(&mut self) {
+        self.sessions.clear();
+    }
 
-            let Some(token) = cred
-                .inner
-                .access_token()
-                .filter(|_| !cred.is_expired())
-                .map(str::to_owned)
-            else {
-                result.refresh_ids.push(id);
-                continue;
+    /// Round robin: the first servable credential in queue order, moved to the back.
+    fn next_in_rotation(
+        &mut self,
+        model_index: ModelIndex,
+        refresh_ids: &mut Vec<CredentialId>,
+    ) -> Option<CredentialId> {
+        let queued = self.queues.get(model_index)?.len();
+        // Busy credentials go back in the queue, so look at each queued id at most once.
+        for _ in 0..queued {
+            let Some(id) = self.queues[model_index].pop_front() else {
+                break;
             };
+            match self.eligibility(id, model_index) {
+                Eligibility::Skip => continue,
+                Eligibility::Refresh => refresh_ids.push(id),
+                Eligibility::Busy => self.queues[model_index].push_back(id),
+                Eligibility::Ready => {
+                    self.queues[model_index].push_back(id);
+                    return Some(id);
+                }
+            }
+        }
+        None
+    }
 
-            if let Some(queue) = self.queues.get_mut(model_index) {
-                queue.push_back(id);
+    /// Any other strategy: drop every credential that cannot serve, let the selector rank
+    /// the ready ones, and move its choice to the back of the queue.
+    fn best_in_queue(
+        &mut self,
+        model_index: ModelIndex,
+        refresh_ids: &mut Vec<CredentialId>,
+    ) -> Option<CredentialId> {
+        let mut queue = std::mem::take(self.queues.get_mut(model_index)?);
+        let mut ready = Vec::with_capacity(queue.len());
+        queue.retain(|&id| match self.eligibility(id, model_index) {
+            Eligibility::Skip => false,
+            Eligibility::Refresh => {
+                refresh_ids.push(id);
+                false
+            }
+            Eligibility::Busy => true,
+            Eligibility::Ready => {
+                ready.push(id);
+                true
             }
+        });
+
+        // Every ready id was just found in `creds`.
+        let position = self
+            .selector
+            .pick(ready.iter().map(|id| &self.creds[id].stats));
+        let picked = position.map(|position| ready[position]);
+        if let Some(id) = picked {
+            queue.retain(|&queued| queued != id);
+            queue.push_back(id);
+        }
+        self.queues[model_index] = queue;
+        picked
+    }
 
-            result.assigned = Some(GeminiCliLease {
-                id,
-                project_id: cred.inner.project_id().to_string(),
-                access_token: token,
-            });
-            return result;
+    /// Hand out `id` and account for the use.
+    fn lease(&mut self, id: CredentialId, now: Instant) -> Option<GeminiCliLease> {
+        let cred = self.creds.get_mut(&id)?;
+        let access_token = cred.inner.access_token()?.to_owned();
+        self.selector.record_use(&mut cred.stats, now);
+        *self.in_flight.entry(id).or_default() += 1;
+        Some(GeminiCliLease {
+            id,
+            project_id: cred.inner.project_id().to_string(),
+            access_token,
+        })
+    }
+
+    fn eligibility(&self, id: CredentialId, model_index: ModelIndex) -> Eligibility {
+        let Some(cred) = self.creds.get(&id) else {
+            return Eligibility::Skip;
+        };
+        if !cred.caps.supports(model_index)
+            || self.refreshing.contains(&id)
+            || self.is_model_cooling(id, model_index)
+        {
+            return Eligibility::Skip;
         }
-        result
+        if cred.inner.access_token().is_none() || cred.is_expired() {
+            return Eligibility::Refresh;
+        }
+        if self.is_busy(id) {
+            return Eligibility::Busy;
+        }
+        Eligibility::Ready
+    }
+
+    /// At its concurrency limit, parked, or half-open with its probe request in flight.
+    fn is_busy(&self, id: CredentialId) -> bool {
+        let in_flight = self.in_flight_of(id);
+        if self
+            .max_in_flight
+            .is_some_and(|limit| in_flight >= limit.get())
+        {
+            return true;
+        }
+        self.creds
+            .get(&id)
+            .is_some_and(|cred| match cred.health.state(Instant::now()) {
+                BreakerState::Closed => false,
+                BreakerState::Open => true,
+                BreakerState::HalfOpen => in_flight > 0,
+            })
+    }
+
+    pub fn in_flight_of(&self, id: CredentialId) -> u32 {
+        self.in_flight.get(&id).copied().unwrap_or(0)
     }
 
     fn process_waiting_room(&mut self) {
@@ -238,17 +537,151 @@ impl CredentialManager {
     }
 
     pub fn refreshing_len(&self) -> usize {
-        self.refreshing.len()
+        self.refreshing.len() + self.renewing.len()
     }
 
     pub fn is_refreshing(&self, id: CredentialId) -> bool {
-        self.refreshing.contains(&id)
+        self.refreshing.contains(&id) || self.renewing.contains(&id)
     }
 
     pub fn cooldown_len(&self) -> usize {
         self.cooldown_map.len()
     }
 
+    /// Point-in-time view for admin introspection, limited to models in `model_mask`.
+    pub fn snapshot(&self, model_mask: u64) -> SchedulerSnapshot {
+        let now = Instant::now();
+
+        let models = self
+            .queues
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| model_mask & (1u64 << index) != 0)
+            .map(|(index, queue)| {
+                let available = queue
+                    .iter()
+                    .filter(|id| self.is_available(**id, index))
+                    .count();
+                let cooling = self
+                    .cooldown_map
+                    .iter()
+                    .filter(|((_, model_index), deadline)| {
+                        *model_index == index && now < **deadline
+                    })
+                    .count();
+                ModelQueueSnapshot {
+                    model: model_name(index),
+                    queue_len: queue.len(),
+                    available,
+                    cooling,
+                }
+            })
+            .collect();
+
+        let mut credentials: Vec<CredentialSnapshot> = self
+            .creds
+            .iter()
+            .map(|(id, cred)| {
+                let mut cooldowns: Vec<CooldownSnapshot> = self
+                    .cooldown_map
+                    .iter()
+                    .filter(|((cid, _), deadline)| cid == id && now < **deadline)
+                    .map(|((_, model_index), deadline)| {
+                        CooldownSnapshot::new(*model_index, *deadline, now)
+                    })
+                    .collect();
+                cooldowns.sort_by_key(|c| c.until);
+
+                CredentialSnapshot {
+                    id: *id,
+                    label: cred.inner.project_id().to_string(),
+                    caps: format!("0x{:016x}", cred.caps.bits()),
+                    models: model_names_from_mask(cred.caps.bits() & model_mask),
+                    expired: cred.is_expired(),
+                    refreshing: self.is_refreshing(*id),
+                    in_flight: self.in_flight_of(*id),
+                    health: cred.health.snapshot(now),
+                    cooldowns,
+                }
+            })
+            .collect();
+        credentials.sort_by_key(|c| c.id);
+
+        let mut refreshing: Vec<CredentialId> =
+            self.refreshing.union(&self.renewing).copied().collect();
+        refreshing.sort_unstable();
+
+        SchedulerSnapshot {
+            total_creds: self.creds.len(),
+            sessions: self.sessions.len(),
+            // The wait queue lives in the actor, which fills this in.
+            waiting: 0,
+            refreshing,
+            models,
+            credentials,
+        }
+    }
+
+    /// Time until the earliest running cooldown on one of the models in `model_mask` ends.
+    pub fn cooldown_remaining(&self, model_mask: u64) -> Option<Duration> {
+        let now = Instant::now();
+        self.cooldown_map
+            .iter()
+            .filter(|((_, index), deadline)| model_mask & (1u64 << index) != 0 && now < **deadline)
+            .map(|(_, deadline)| deadline.duration_since(now))
+            .min()
+    }
+
+    /// How many credentials serve the models in `model_mask`, how many of them could take a
+    /// request now, and when the earliest cooldown on them ends.
+    pub fn pool_status(&self, model_mask: u64) -> PoolStatus {
+        let indices: Vec<ModelIndex> = (0..self.queues.len())
+            .filter(|index| model_mask & (1u64 << index) != 0)
+            .collect();
+        let serving: Vec<CredentialId> = self
+            .creds
+            .iter()
+            .filter(|(_, cred)| indices.iter().any(|index| cred.caps.supports(*index)))
+            .map(|(id, _)| *id)
+            .collect();
+        let available = serving
+            .iter()
+            .filter(|id| indices.iter().any(|index| self.is_available(**id, *index)))
+            .count();
+        PoolStatus {
+            total: serving.len(),
+            available,
+            retry_after: self.cooldown_remaining(model_mask),
+        }
+    }
+
+    /// When capacity may come back next: the earliest end of a running cooldown, on any
+    /// model, or of a breaker park.
+    pub fn next_capacity_at(&self) -> Option<Instant> {
+        let now = Instant::now();
+        let parks = self
+            .creds
+            .values()
+            .filter_map(|cred| cred.health.parked_until(now));
+        self.cooldown_map
+            .values()
+            .copied()
+            .filter(|deadline| now < *deadline)
+            .chain(parks)
+            .min()
+    }
+
+    /// Supports the model and is neither refreshing, cooling, parked nor at its concurrency
+    /// limit.
+    fn is_available(&self, id: CredentialId, model_index: ModelIndex) -> bool {
+        self.creds
+            .get(&id)
+            .is_some_and(|cred| cred.caps.supports(model_index))
+            && !self.refreshing.contains(&id)
+            && !self.is_model_cooling(id, model_index)
+            && !self.is_busy(id)
+    }
+
     fn is_model_cooling(&self, id: CredentialId, model_index: ModelIndex) -> bool {
         match self.cooldown_map.get(&(id, model_index)) {
             Some(deadline) => Instant::now() < *deadline,
@@ -264,6 +697,7 @@ impl CredentialManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::policy::BREAKER_FAILURE_STREAK;
     use chrono::{Duration, Utc};
     use serde_json::json;
 
@@ -348,6 +782,25 @@ mod tests {
         assert_eq!(assigned_after.project_id, "p1");
     }
 
+    #[test]
+    fn adopt_cooldown_keeps_the_later_deadline() {
+        let mut manager = CredentialManager::new(1);
+        manager.add_credential(1, make_credential("p1"), mask(0));
+        manager.report_rate_limit(1, mask(0), std::time::Duration::from_secs(60));
+
+        // Another instance reporting the same or an earlier limit changes nothing.
+        assert!(!manager.adopt_cooldown(1, mask(0), std::time::Duration::from_secs(60)));
+        assert!(!manager.adopt_cooldown(1, mask(0), std::time::Duration::from_secs(5)));
+        assert!(manager.adopt_cooldown(1, mask(0), std::time::Duration::from_secs(300)));
+        let until = manager.cooldown_map[&(1, 0)];
+        assert!(until > Instant::now() + std::time::Duration::from_secs(250));
+
+        // Unknown credentials and models are ignored.
+        assert!(!manager.adopt_cooldown(2, mask(0), std::time::Duration::from_secs(60)));
+        assert!(!manager.adopt_cooldown(1, mask(5), std::time::Duration::from_secs(60)));
+        assert!(manager.get_assigned(mask(0)).assigned.is_none());
+    }
+
     #[test]
     fn expired_token_triggers_refresh_request() {
         let mut manager = CredentialManager::new(1);
@@ -419,4 +872,278 @@ mod tests {
         assert_eq!(first.id, 1);
         assert_eq!(second.id, 2);
     }
+
+    #[test]
+    fn snapshot_reports_cooling_and_refreshing_credentials() {
+        let mut manager = CredentialManager::new(2);
+
+        manager.add_credential(1, make_credential("proj1"), mask(0) | mask(1));
+        manager.add_credential(2, make_credential("proj2"), mask(0));
+
+        manager.report_rate_limit(1, mask(0), std::time::Duration::from_secs(60));
+        manager.mark_refreshing(2);
+
+        let snapshot = manager.snapshot(mask(0) | mask(1));
+        assert_eq!(snapshot.total_creds, 2);
+        assert_eq!(snapshot.refreshing, vec![2]);
+
+        assert_eq!(snapshot.models.len(), 2);
+        assert_eq!(snapshot.models[0].queue_len, 2);
+        assert_eq!(snapshot.models[0].available, 0);
+        assert_eq!(snapshot.models[0].cooling, 1);
+        assert_eq!(snapshot.models[1].available, 1);
+
+        let first = &snapshot.credentials[0];
+        assert_eq!(first.id, 1);
+        assert_eq!(first.label, "proj1");
+        assert_eq!(first.caps, format!("0x{:016x}", mask(0) | mask(1)));
+        assert_eq!(first.cooldowns.len(), 1);
+        assert!(first.cooldowns[0].remaining_secs <= 60);
+        assert!(snapshot.credentials[1].refreshing);
+
+        // Models outside the provider mask are left out.
+        assert_eq!(manager.snapshot(mask(1)).models.len(), 1);
+    }
+
+    #[test]
+    fn set_served_models_grows_queues_and_applies_model_changes() {
+        let mut manager = CredentialManager::new(2);
+        manager.add_credential(1, make_credential("p1"), mask(0) | mask(1));
+
+        // Reload: model 1 dropped, model 2 appended.
+        manager.set_served_models(3, mask(0) | mask(1), mask(0) | mask(2));
+
+        assert!(manager.get_assigned(mask(0)).assigned.is_some());
+        assert!(manager.get_assigned(mask(1)).assigned.is_none());
+        assert!(manager.get_assigned(mask(2)).assigned.is_some());
+    }
+
+    #[test]
+    fn due_for_refresh_orders_by_expiry_and_renewing_keeps_serving() {
+        let mut manager = CredentialManager::new(1);
+        let with_expiry = |name: &str, minutes: i64| {
+            let mut cred = make_credential(name);
+            cred.update_credential(json!({"expiry": Utc::now() + Duration::minutes(minutes)}))
+                .expect("valid expiry update");
+            cred
+        };
+        manager.add_credential(1, with_expiry("p1", 10), mask(0));
+        manager.add_credential(2, with_expiry("p2", 60), mask(0));
+        manager.add_credential(3, with_expiry("p3", -10), mask(0));
+        manager.add_credential(4, with_expiry("p4", 5), mask(0));
+        manager.mark_refreshing(4);
+
+        let deadline = Utc::now() + Duration::minutes(15);
+        assert_eq!(manager.due_for_refresh(deadline, 10), vec![3, 1]);
+        assert_eq!(manager.due_for_refresh(deadline, 1), vec![3]);
+
+        // A credential refreshed ahead of expiry is not picked again and still serves.
+        manager.mark_renewing(1);
+        assert_eq!(manager.due_for_refresh(deadline, 10), vec![3]);
+        assert!(manager.is_refreshing(1));
+        let ids: Vec<_> = (0..3)
+            .filter_map(|_| manager.get_assigned(mask(0)).assigned)
+            .map(|lease| lease.id)
+            .collect();
+        assert!(ids.contains(&1), "{ids:?}");
+
+        // Reported invalid meanwhile: out of rotation until the refresh lands.
+        manager.mark_refreshing(1);
+        for _ in 0..3 {
+            assert_ne!(
+                manager.get_assigned(mask(0)).assigned.map(|l| l.id),
+                Some(1)
+            );
+        }
+        manager.add_credential(1, with_expiry("p1", 60), mask(0));
+        assert!(!manager.is_refreshing(1));
+    }
+
+    #[test]
+    fn weighted_strategy_shares_by_tier_and_applies_on_reconfigure() {
+        let mut manager = CredentialManager::new(1);
+        let with_tier = |name: &str, tier: &str| {
+            let mut cred = make_credential(name);
+            cred.update_credential(json!({"tier": tier}))
+                .expect("valid tier update");
+            cred
+        };
+        manager.add_credential(1, with_tier("p1", "standard-tier"), mask(0));
+        manager.add_credential(2, with_tier("p2", "free-tier"), mask(0));
+
+        let picks = |manager: &mut CredentialManager| -> Vec<CredentialId> {
+            (0..8)
+                .filter_map(|_| manager.get_assigned(mask(0)).assigned)
+                .map(|lease| lease.id)
+                .collect()
+        };
+        assert_eq!(picks(&mut manager), vec![1, 2, 1, 2, 1, 2, 1, 2]);
+
+        let weights = BTreeMap::from([("standard-tier".to_string(), NonZeroU32::new(3).unwrap())]);
+        manager.set_scheduling(SchedulingStrategy::Weighted, weights);
+        let ids = picks(&mut manager);
+        assert_eq!(ids.iter().filter(|&&id| id == 1).count(), 6, "{ids:?}");
+    }
+
+    #[test]
+    fn least_recently_rate_limited_strategy_avoids_recent_limits() {
+        let mut manager = CredentialManager::new(1);
+        manager.set_scheduling(
+            SchedulingStrategy::LeastRecentlyRateLimited,
+            BTreeMap::new(),
+        );
+        for id in 1..=3 {
+            manager.add_credential(id, make_credential(&format!("p{id}")), mask(0));
+        }
+        let short = std::time::Duration::from_millis(1);
+        manager.report_rate_limit(2, mask(0), short);
+        manager.report_rate_limit(1, mask(0), short);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        let next = |manager: &mut CredentialManager| {
+            manager.get_assigned(mask(0)).assigned.map(|lease| lease.id)
+        };
+        assert_eq!(next(&mut manager), Some(3));
+        assert_eq!(next(&mut manager), Some(3), "never limited beats limited");
+
+        manager.report_rate_limit(3, mask(0), short);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert_eq!(next(&mut manager), Some(2), "oldest limit first");
+    }
+
+    #[test]
+    fn sessions_stick_to_a_healthy_credential_and_move_when_it_cools() {
+        let mut manager = CredentialManager::new(1);
+        manager.add_credential(1, make_credential("p1"), mask(0));
+        manager.add_credential(2, make_credential("p2"), mask(0));
+        let session: Arc<str> = Arc::from("conversation-1");
+
+        let next = |manager: &mut CredentialManager| {
+            manager
+                .get_assigned_for_session(mask(0), session.clone())
+                .assigned
+                .map(|lease| lease.id)
+        };
+        let first = next(&mut manager).expect("assigned");
+        // Round robin alone would alternate; the session stays put.
+        assert_eq!(next(&mut manager), Some(first));
+        assert_eq!(next(&mut manager), Some(first));
+        assert_eq!(manager.snapshot(mask(0)).sessions, 1);
+
+        // Rate limited: the session moves over and stays on the new credential.
+        manager.report_rate_limit(first, mask(0), std::time::Duration::from_secs(60));
+        let moved = next(&mut manager).expect("assigned");
+        assert_ne!(moved, first);
+        assert_eq!(next(&mut manager), Some(moved));
+
+        manager.clear_sessions();
+        assert_eq!(manager.snapshot(mask(0)).sessions, 0);
+    }
+
+    #[test]
+    fn concurrency_limit_skips_busy_credentials_until_released() {
+        for strategy in [
+            SchedulingStrategy::RoundRobin,
+            SchedulingStrategy::LeastRecentlyUsed,
+        ] {
+            let mut manager = CredentialManager::new(1);
+            manager.set_scheduling(strategy, BTreeMap::new());
+            manager.set_max_concurrent(NonZeroU32::new(1));
+            manager.add_credential(1, make_credential("p1"), mask(0));
+            manager.add_credential(2, make_credential("p2"), mask(0));
+
+            let next = |manager: &mut CredentialManager| {
+                manager.get_assigned(mask(0)).assigned.map(|lease| lease.id)
+            };
+            let first = next(&mut manager).expect("assigned");
+            let second = next(&mut manager).expect("assigned");
+            assert_ne!(first, second, "{strategy:?}");
+            assert_eq!(next(&mut manager), None, "{strategy:?}: both at the limit");
+
+            let snapshot = manager.snapshot(mask(0));
+            assert_eq!(
+                snapshot.models[0].queue_len, 2,
+                "busy credentials stay queued"
+            );
+            assert_eq!(snapshot.models[0].available, 0);
+            assert_eq!(snapshot.credentials[0].in_flight, 1);
+
+            manager.release(second);
+            assert_eq!(next(&mut manager), Some(second), "{strategy:?}");
+
+            // Lifting the limit makes busy credentials available again.
+            manager.set_max_concurrent(None);
+            assert!(next(&mut manager).is_some(), "{strategy:?}");
+            assert_eq!(
+                manager.in_flight_of(first) + manager.in_flight_of(second),
+                3
+            );
+        }
+    }
+
+    #[test]
+    fn pool_status_counts_serving_credentials_and_the_earliest_cooldown() {
+        let mut manager = CredentialManager::new(2);
+        manager.set_max_concurrent(NonZeroU32::new(1));
+        manager.add_credential(1, make_credential("p1"), mask(0));
+        manager.add_credential(2, make_credential("p2"), mask(0));
+        manager.add_credential(3, make_credential("p3"), mask(0) | mask(1));
+        manager.add_credential(4, make_credential("p4"), mask(1));
+
+        manager.report_rate_limit(1, mask(0), std::time::Duration::from_secs(60));
+        manager.report_rate_limit(2, mask(0), std::time::Duration::from_secs(30));
+        manager.mark_refreshing(4);
+        assert!(
+            manager.get_assigned(mask(0)).assigned.is_some(),
+            "3 is now busy"
+        );
+
+        let status = manager.pool_status(mask(0));
+        assert_eq!((status.total, status.available), (3, 0));
+        let retry_after = status.retry_after.expect("cooling");
+        assert!(retry_after > std::time::Duration::from_secs(29));
+        assert!(retry_after <= std::time::Duration::from_secs(30));
+
+        // A fallback chain counts every credential serving one of its models.
+        let status = manager.pool_status(mask(0) | mask(1));
+        assert_eq!((status.total, status.available), (4, 0));
+
+        manager.release(3);
+        let status = manager.pool_status(mask(1));
+        assert_eq!((status.total, status.available), (2, 1));
+        assert_eq!(status.retry_after, None);
+    }
+
+    #[test]
+    fn breaker_parks_a_failing_credential_but_keeps_it_queued() {
+        let mut manager = CredentialManager::new(1);
+        manager.add_credential(1, make_credential("p1"), mask(0));
+        manager.add_credential(2, make_credential("p2"), mask(0));
+
+        let tripped = (0..BREAKER_FAILURE_STREAK)
+            .filter_map(|_| manager.report_failure(1))
+            .last();
+        assert!(matches!(
+            tripped,
+            Some(BreakerChange::Tripped { probe: false, .. })
+        ));
+        for _ in 0..3 {
+            let assigned = manager.get_assigned(mask(0)).assigned;
+            assert_eq!(assigned.map(|lease| lease.id), Some(2));
+        }
+
+        let snapshot = manager.snapshot(mask(0));
+        assert_eq!(
+            snapshot.models[0].queue_len, 2,
+            "parked credentials stay queued"
+        );
+        assert_eq!(snapshot.models[0].available, 1);
+        assert_eq!(snapshot.credentials[0].health.state, BreakerState::Open);
+        assert!(snapshot.credentials[0].health.parked_until.is_some());
+        assert!(manager.next_capacity_at().is_some());
+
+        // Requests that were already in flight do not close an open breaker.
+        assert_eq!(manager.report_success(1), None);
+        assert_eq!(manager.pool_status(mask(0)).available, 1);
+    }
 }
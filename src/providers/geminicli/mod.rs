@@ -5,10 +5,12 @@ mod model_mask;
 mod resource;
 mod workers;
 
-pub use context::GeminiContext;
+pub use context::{GeminiContext, GeminiRpc};
 pub use manager::GeminiCliActorHandle;
 pub(in crate::providers) use manager::spawn;
-pub(crate) use model_mask::{SUPPORTED_MODEL_MASK, SUPPORTED_MODEL_NAMES, model_mask};
+pub(crate) use model_mask::{
+    fallbacks, model_mask, reload_supported_models, supported_model_mask, supported_model_names,
+};
 use workers::{GeminiCliRefresherHandle, RefreshOutcome};
 
 use crate::config::CONFIG;
@@ -34,7 +36,7 @@ static OAUTH_CALLBACK_URL: LazyLock<RedirectUrl> = LazyLock::new(|| {
     .expect("valid OAuth callback URL bound to localhost with configured port")
 });
 
-static GEMINICLI_SCOPES: LazyLock<Vec<Scope>> = LazyLock::new(|| {
+pub(in crate::providers) static GEMINICLI_SCOPES: LazyLock<Vec<Scope>> = LazyLock::new(|| {
     vec![
         Scope::new("https://www.googleapis.com/auth/cloud-platform".to_string()),
         Scope::new("https://www.googleapis.com/auth/userinfo.email".to_string()),
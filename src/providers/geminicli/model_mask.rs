@@ -1,33 +1,33 @@
-use crate::config::CONFIG;
-use crate::model_catalog;
-use std::collections::HashSet;
+use crate::config::{CONFIG, Config, Reloadable};
+use crate::model_catalog::{self, ModelRoute, ProviderModels};
 use std::sync::LazyLock;
 
-pub(crate) static SUPPORTED_MODEL_NAMES: LazyLock<Vec<String>> = LazyLock::new(|| {
-    let cfg = CONFIG.geminicli();
+static SUPPORTED_MODELS: LazyLock<Reloadable<ProviderModels>> =
+    LazyLock::new(|| Reloadable::new(ProviderModels::new(CONFIG.geminicli().model_list)));
 
-    let mut seen = HashSet::<String>::new();
-    cfg.model_list
-        .into_iter()
-        .filter(|name| seen.insert(name.clone()))
-        .collect()
-});
+pub(crate) fn supported_model_names() -> Vec<String> {
+    SUPPORTED_MODELS.load().names.clone()
+}
 
-pub(crate) static SUPPORTED_MODEL_MASK: LazyLock<u64> = LazyLock::new(|| {
-    let mut mask = 0u64;
-    for name in SUPPORTED_MODEL_NAMES.iter() {
-        if let Some(bit) = model_catalog::mask(name) {
-            mask |= bit;
-        }
-    }
-    mask
-});
+pub(crate) fn supported_model_mask() -> u64 {
+    SUPPORTED_MODELS.load().mask
+}
+
+/// Swap in the `model_list` of a reloaded config; run after `model_catalog::install`.
+pub(crate) fn reload_supported_models(cfg: &Config) {
+    SUPPORTED_MODELS.store(ProviderModels::new(cfg.geminicli().model_list));
+}
 
 pub(crate) fn model_mask(name: &str) -> Option<u64> {
     let bit = model_catalog::mask(name)?;
-    if (*SUPPORTED_MODEL_MASK & bit) != 0 {
+    if (supported_model_mask() & bit) != 0 {
         Some(bit)
     } else {
         None
     }
 }
+
+/// `[models.fallbacks]` entries of `name` this pool serves, narrowed to the `allowed` mask.
+pub(crate) fn fallbacks(name: &str, allowed: u64) -> Vec<ModelRoute> {
+    model_catalog::fallbacks(name, supported_model_mask() & allowed)
+}
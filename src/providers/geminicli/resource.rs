@@ -13,6 +13,8 @@ pub(super) struct GeminiCliResource {
     refresh_token: String,
     access_token: Option<String>,
     expiry: DateTime<Utc>,
+    /// Cloud Code user tier (`free-tier`, `standard-tier`, ...), resolved during onboarding.
+    tier: Option<String>,
 }
 
 impl Default for GeminiCliResource {
@@ -24,6 +26,7 @@ impl Default for GeminiCliResource {
             refresh_token: String::new(),
             access_token: None,
             expiry: Utc::now(),
+            tier: None,
         }
     }
 }
@@ -57,6 +60,14 @@ impl GeminiCliResource {
         self.sub = sub;
     }
 
+    pub fn tier(&self) -> Option<&str> {
+        self.tier.as_deref()
+    }
+
+    pub fn set_tier(&mut self, tier: String) {
+        self.tier = Some(tier);
+    }
+
     pub fn refresh_token(&self) -> &str {
         &self.refresh_token
     }
@@ -65,7 +76,6 @@ impl GeminiCliResource {
         self.access_token.as_deref()
     }
 
-    #[allow(dead_code)]
     pub fn expiry(&self) -> DateTime<Utc> {
         self.expiry
     }
@@ -85,6 +95,7 @@ impl GeminiCliResource {
             access_token: Option<String>,
             expiry: Option<DateTime<Utc>>,
             expires_in: Option<i64>,
+            tier: Option<String>,
         }
 
         let value = serde_json::to_value(payload)?;
@@ -110,6 +121,7 @@ impl GeminiCliResource {
         set_plain!(project_id);
         set_plain!(refresh_token);
         set_opt!(access_token);
+        set_opt!(tier);
 
         if let Some(secs) = patch.expires_in {
             self.expiry = Utc::now() + Duration::seconds(secs);
@@ -154,6 +166,7 @@ impl From<DbGeminiCliResource> for GeminiCliResource {
             refresh_token: d.refresh_token,
             access_token: d.access_token,
             expiry: d.expiry,
+            tier: d.tier,
         }
     }
 }
@@ -167,6 +180,7 @@ impl From<GeminiCliResource> for GeminiCliCreate {
             refresh_token: cred.refresh_token,
             access_token: cred.access_token,
             expiry: cred.expiry,
+            tier: cred.tier,
         }
     }
 }
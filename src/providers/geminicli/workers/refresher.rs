@@ -11,6 +11,7 @@ use super::super::{
 };
 use crate::config::GeminiCliResolvedConfig;
 use crate::error::{IsRetryable, OauthError, PolluxError};
+use crate::metrics::METRICS;
 use backon::{ExponentialBuilder, Retryable};
 use futures::stream::StreamExt;
 use governor::{Quota, RateLimiter};
@@ -36,6 +37,16 @@ pub(in crate::providers::geminicli) enum RefreshOutcome {
     },
 }
 
+impl RefreshOutcome {
+    fn record_metric(&self) {
+        let (kind, ok) = match self {
+            Self::RefreshCredential { result, .. } => ("refresh", result.is_ok()),
+            Self::OnboardCredential { result, .. } => ("onboard", result.is_ok()),
+        };
+        METRICS.record_refresh("geminicli", kind, ok);
+    }
+}
+
 #[derive(Debug)]
 enum GeminiCliRefresherMessage {
     RefreshCredential {
@@ -45,6 +56,8 @@ enum GeminiCliRefresherMessage {
     OnboardCredential {
         cred: GeminiCliResource,
     },
+    /// Replace the refresh pipeline with one built from a reloaded config.
+    Reconfigure(Arc<GeminiCliResolvedConfig>),
 }
 
 /// Handle for submitting refresh requests to the Gemini CLI refresher actor.
@@ -70,6 +83,13 @@ impl GeminiCliRefresherHandle {
         Ok(Self { actor })
     }
 
+    /// Rebuild the OAuth client and rate limit from `cfg`; queued jobs finish on the old ones.
+    pub fn reconfigure(&self, cfg: Arc<GeminiCliResolvedConfig>) -> Result<(), PolluxError> {
+        ractor::cast!(self.actor, GeminiCliRefresherMessage::Reconfigure(cfg)).map_err(|e| {
+            PolluxError::RactorError(format!("GeminiCliRefresherActor cast failed: {e}"))
+        })
+    }
+
     pub fn submit_refresh(
         &self,
         id: CredentialId,
@@ -128,8 +148,10 @@ impl RefreshTask {
                 let token_str = cred.access_token().ok_or_else(|| {
                     PolluxError::RactorError("Refresh success but token is None".to_string())
                 })?;
-                let project_id = ensure_companion_project(token_str, client.clone()).await?;
+                let (project_id, tier) =
+                    ensure_companion_project(token_str, client.clone()).await?;
                 cred.set_project_id(project_id);
+                cred.set_tier(tier.as_str().to_string());
             }
         }
         Ok(())
@@ -147,10 +169,11 @@ impl RefreshTask {
     }
 }
 
+/// Resolve (or provision) the companion project, returning it with the account's tier.
 async fn ensure_companion_project(
     access_token: &str,
     client: reqwest::Client,
-) -> Result<String, PolluxError> {
+) -> Result<(String, UserTier), PolluxError> {
     let load_json =
         GoogleOauthOps::load_code_assist_with_retry(access_token, client.clone()).await?;
     debug!(body = %load_json, "loadCodeAssist upstream body");
@@ -168,20 +191,20 @@ async fn ensure_companion_project(
             tier = %tier.as_str(),
             "loadCodeAssist resolved companion project id"
         );
-        return Ok(existing_project_id);
+        return Ok((existing_project_id, tier));
     }
 
     info!(
         tier = %tier.as_str(),
         "No existing companion project found; starting onboarding"
     );
-    let new_project_id = perform_onboarding(access_token, tier, client).await?;
+    let new_project_id = perform_onboarding(access_token, tier.clone(), client).await?;
 
     info!(
         project_id = %new_project_id,
         "Companion project provisioning completed"
     );
-    Ok(new_project_id)
+    Ok((new_project_id, tier))
 }
 
 async fn perform_onboarding(
@@ -239,6 +262,81 @@ async fn perform_onboarding(
     .into())
 }
 
+/// Build the HTTP client and rate limiter from `cfg` and start a refresh pipeline.
+///
+/// The pipeline stops once every sender is dropped and queued jobs are done, which is how a
+/// reconfigure retires the previous one.
+fn start_pipeline(
+    handle: GeminiCliActorHandle,
+    cfg: &GeminiCliResolvedConfig,
+) -> mpsc::Sender<RefreshTask> {
+    let mut headers = HeaderMap::new();
+    let mut builder = reqwest::Client::builder()
+        .user_agent("geminicli-oauth/1.0".to_string())
+        .connect_timeout(Duration::from_secs(5))
+        .timeout(Duration::from_secs(15));
+    if let Some(proxy_url) = cfg.proxy.clone() {
+        let proxy =
+            reqwest::Proxy::all(proxy_url.as_str()).expect("invalid proxy url for reqwest client");
+        builder = builder.proxy(proxy);
+    }
+    if !cfg.enable_multiplexing {
+        headers.insert(CONNECTION, HeaderValue::from_static("close"));
+
+        builder = builder
+            .http1_only()
+            .pool_max_idle_per_host(0)
+            .pool_idle_timeout(Duration::from_secs(0));
+    } else {
+        builder = builder.http2_adaptive_window(true);
+    }
+    let client = builder
+        .default_headers(headers)
+        .build()
+        .expect("FATAL: initialize refresh job HTTP client failed");
+    let oauth_tps = cfg.oauth_tps.max(1);
+    let oauth_tps_u32 = u32::try_from(oauth_tps).unwrap_or(u32::MAX);
+    let burst_u32 = u32::try_from(oauth_tps.saturating_mul(2)).unwrap_or(u32::MAX);
+    let limiter = Arc::new(RateLimiter::direct(
+        Quota::per_second(std::num::NonZeroU32::new(oauth_tps_u32).unwrap())
+            .allow_burst(std::num::NonZeroU32::new(burst_u32).unwrap()),
+    ));
+
+    let (job_tx, job_rx) = mpsc::channel::<RefreshTask>(1000);
+    // Spawn background refresh worker using buffer_unordered semantics.
+    let buffer_unordered = oauth_tps.saturating_mul(2).max(1);
+    tokio::spawn(async move {
+        info!(
+            "Refresh Pipeline Started: BufferUnordered={}, RateLimit={}/s, Burst={}",
+            buffer_unordered, oauth_tps_u32, burst_u32
+        );
+
+        let mut pipeline = ReceiverStream::new(job_rx)
+            .map(|mut task| {
+                let lim = limiter.clone();
+                let http = client.clone();
+                async move {
+                    lim.until_ready().await;
+
+                    let result = task.execute(http).await;
+                    task.into_outcome(result)
+                }
+            })
+            .buffer_unordered(buffer_unordered);
+
+        while let Some(outcome) = pipeline.next().await {
+            outcome.record_metric();
+            if let Err(e) = handle.send_refresh_complete(outcome) {
+                warn!("Actor unreachable (channel closed), worker stopping: {}", e);
+                break;
+            }
+        }
+        info!("Refresh Pipeline Stopped");
+    });
+
+    job_tx
+}
+
 struct GeminiCliRefresherActorState {
     job_tx: mpsc::Sender<RefreshTask>,
     handle: GeminiCliActorHandle,
@@ -257,71 +355,7 @@ impl Actor for GeminiCliRefresherActor {
         _myself: ActorRef<Self::Msg>,
         (handle, cfg): Self::Arguments,
     ) -> Result<Self::State, ActorProcessingErr> {
-        let mut headers = HeaderMap::new();
-        let mut builder = reqwest::Client::builder()
-            .user_agent("geminicli-oauth/1.0".to_string())
-            .connect_timeout(Duration::from_secs(5))
-            .timeout(Duration::from_secs(15));
-        if let Some(proxy_url) = cfg.proxy.clone() {
-            let proxy = reqwest::Proxy::all(proxy_url.as_str())
-                .expect("invalid proxy url for reqwest client");
-            builder = builder.proxy(proxy);
-        }
-        if !cfg.enable_multiplexing {
-            headers.insert(CONNECTION, HeaderValue::from_static("close"));
-
-            builder = builder
-                .http1_only()
-                .pool_max_idle_per_host(0)
-                .pool_idle_timeout(Duration::from_secs(0));
-        } else {
-            builder = builder.http2_adaptive_window(true);
-        }
-        let client = builder
-            .default_headers(headers)
-            .build()
-            .expect("FATAL: initialize refresh job HTTP client failed");
-        let oauth_tps = cfg.oauth_tps.max(1);
-        let oauth_tps_u32 = u32::try_from(oauth_tps).unwrap_or(u32::MAX);
-        let burst_u32 = u32::try_from(oauth_tps.saturating_mul(2)).unwrap_or(u32::MAX);
-        let limiter = Arc::new(RateLimiter::direct(
-            Quota::per_second(std::num::NonZeroU32::new(oauth_tps_u32).unwrap())
-                .allow_burst(std::num::NonZeroU32::new(burst_u32).unwrap()),
-        ));
-
-        let (job_tx, job_rx) = mpsc::channel::<RefreshTask>(1000);
-        let pipeline_handle = handle.clone();
-
-        // Spawn background refresh worker using buffer_unordered semantics.
-        let buffer_unordered = oauth_tps.saturating_mul(2).max(1);
-        tokio::spawn(async move {
-            info!(
-                "Refresh Pipeline Started: BufferUnordered={}, RateLimit={}/s, Burst={}",
-                buffer_unordered, oauth_tps_u32, burst_u32
-            );
-
-            let mut pipeline = ReceiverStream::new(job_rx)
-                .map(|mut task| {
-                    let lim = limiter.clone();
-                    let http = client.clone();
-                    async move {
-                        lim.until_ready().await;
-
-                        let result = task.execute(http).await;
-                        task.into_outcome(result)
-                    }
-                })
-                .buffer_unordered(buffer_unordered);
-
-            while let Some(outcome) = pipeline.next().await {
-                if let Err(e) = pipeline_handle.send_refresh_complete(outcome) {
-                    warn!("Actor unreachable (channel closed), worker stopping: {}", e);
-                    break;
-                }
-            }
-            info!("Refresh Pipeline Stopped");
-        });
-
+        let job_tx = start_pipeline(handle.clone(), &cfg);
         info!(
             proxy = %cfg.proxy.as_ref().map(|u| u.as_str()).unwrap_or("<none>"),
             enable_multiplexing = cfg.enable_multiplexing,
@@ -339,6 +373,15 @@ impl Actor for GeminiCliRefresherActor {
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
         match message {
+            GeminiCliRefresherMessage::Reconfigure(cfg) => {
+                state.job_tx = start_pipeline(state.handle.clone(), &cfg);
+                info!(
+                    proxy = %cfg.proxy.as_ref().map(|u| u.as_str()).unwrap_or("<none>"),
+                    enable_multiplexing = cfg.enable_multiplexing,
+                    oauth_tps = cfg.oauth_tps,
+                    "GeminiCliRefresher reconfigured"
+                );
+            }
             GeminiCliRefresherMessage::RefreshCredential { id, cred } => {
                 let tx = state.job_tx.clone();
                 let handle = state.handle.clone();
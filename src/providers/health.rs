@@ -0,0 +1,216 @@
+//! Per-credential health: a rolling success score and a circuit breaker.
+//!
+//! Rate limits, bans and invalid tokens have their own handling. The failures nothing explains
+//! (5xx after retries, transport errors, a WAF answering 403) feed [`Health`] instead. A streak
+//! of them, or a score that sinks too low, opens the breaker: the credential is parked. Once the
+//! park ends the breaker is half-open and the credential serves a single probe. A successful
+//! probe closes the breaker; a failed one parks it again, twice as long as before.
+
+use super::policy::{
+    BREAKER_BASE_PARK, BREAKER_FAILURE_STREAK, BREAKER_MAX_PARK, BREAKER_MIN_SAMPLES,
+    BREAKER_MIN_SCORE, HEALTH_WINDOW,
+};
+use super::snapshot::HealthSnapshot;
+use chrono::Utc;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Serving normally.
+    Closed,
+    /// Parked until the breaker turns half-open.
+    Open,
+    /// Park over: one probe request decides whether the breaker closes or opens again.
+    HalfOpen,
+}
+
+/// What an outcome did to the breaker, for the actor to log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerChange {
+    /// The breaker opened; the credential is parked for `park`.
+    Tripped { park: Duration, probe: bool },
+    /// A probe succeeded and the breaker closed.
+    Recovered,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Health {
+    /// Recent outcomes, newest in the lowest bit; a set bit is a failure.
+    outcomes: u32,
+    samples: u32,
+    failure_streak: u32,
+    /// Trips since the breaker last closed; each one doubles the park.
+    trips: u32,
+    /// Set while the breaker is open or half-open.
+    parked_until: Option<Instant>,
+}
+
+impl Health {
+    pub fn state(&self, now: Instant) -> BreakerState {
+        match self.parked_until {
+            None => BreakerState::Closed,
+            Some(until) if now < until => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    /// Share of recent outcomes that succeeded; 1.0 before the first one.
+    pub fn score(&self) -> f64 {
+        if self.samples == 0 {
+            return 1.0;
+        }
+        let failures = (self.outcomes & window_mask()).count_ones();
+        1.0 - f64::from(failures) / f64::from(self.samples)
+    }
+
+    pub fn failure_streak(&self) -> u32 {
+        self.failure_streak
+    }
+
+    /// When a parked credential turns half-open.
+    pub fn parked_until(&self, now: Instant) -> Option<Instant> {
+        self.parked_until.filter(|until| now < *until)
+    }
+
+    pub fn record_success(&mut self, now: Instant) -> Option<BreakerChange> {
+        self.push(false);
+        self.failure_streak = 0;
+        if self.state(now) != BreakerState::HalfOpen {
+            return None;
+        }
+        // A fresh start: the failures that tripped the breaker no longer say much.
+        *self = Self::default();
+        Some(BreakerChange::Recovered)
+    }
+
+    pub fn record_failure(&mut self, now: Instant) -> Option<BreakerChange> {
+        self.push(true);
+        self.failure_streak += 1;
+        let probe = match self.state(now) {
+            // Requests that started before the trip are still finishing.
+            BreakerState::Open => return None,
+            BreakerState::HalfOpen => true,
+            BreakerState::Closed => {
+                let sinking =
+                    self.samples >= BREAKER_MIN_SAMPLES && self.score() < BREAKER_MIN_SCORE;
+                if self.failure_streak < BREAKER_FAILURE_STREAK && !sinking {
+                    return None;
+                }
+                false
+            }
+        };
+        let park = BREAKER_BASE_PARK
+            .saturating_mul(1u32 << self.trips.min(16))
+            .min(BREAKER_MAX_PARK);
+        self.trips += 1;
+        self.parked_until = Some(now + park);
+        Some(BreakerChange::Tripped { park, probe })
+    }
+
+    pub fn snapshot(&self, now: Instant) -> HealthSnapshot {
+        HealthSnapshot {
+            state: self.state(now),
+            score: self.score(),
+            failure_streak: self.failure_streak,
+            parked_until: self.parked_until(now).map(|until| {
+                Utc::now()
+                    + chrono::Duration::from_std(until - now)
+                        .unwrap_or_else(|_| chrono::Duration::zero())
+            }),
+        }
+    }
+
+    fn push(&mut self, failed: bool) {
+        self.outcomes = (self.outcomes << 1) | u32::from(failed);
+        self.samples = (self.samples + 1).min(HEALTH_WINDOW);
+    }
+}
+
+fn window_mask() -> u32 {
+    u32::MAX >> (u32::BITS - HEALTH_WINDOW)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_failure_streak_parks_with_escalating_probes() {
+        let now = Instant::now();
+        let mut health = Health::default();
+        for _ in 1..BREAKER_FAILURE_STREAK {
+            assert_eq!(health.record_failure(now), None);
+        }
+        assert_eq!(
+            health.record_failure(now),
+            Some(BreakerChange::Tripped {
+                park: BREAKER_BASE_PARK,
+                probe: false
+            })
+        );
+        assert_eq!(health.state(now), BreakerState::Open);
+        assert_eq!(
+            health.record_failure(now),
+            None,
+            "stragglers do not extend the park"
+        );
+
+        // A failed probe doubles the park ...
+        let probe_at = now + BREAKER_BASE_PARK;
+        assert_eq!(health.state(probe_at), BreakerState::HalfOpen);
+        assert_eq!(
+            health.record_failure(probe_at),
+            Some(BreakerChange::Tripped {
+                park: BREAKER_BASE_PARK * 2,
+                probe: true
+            })
+        );
+
+        // ... and a successful one closes the breaker and starts over.
+        let probe_at = probe_at + BREAKER_BASE_PARK * 2;
+        assert_eq!(
+            health.record_success(probe_at),
+            Some(BreakerChange::Recovered)
+        );
+        assert_eq!(health.state(probe_at), BreakerState::Closed);
+        assert_eq!(health.score(), 1.0);
+    }
+
+    #[test]
+    fn a_sinking_score_trips_without_a_streak() {
+        let now = Instant::now();
+        let mut health = Health::default();
+        // Never three failures in a row, but two of every three requests fail.
+        let tripped = (0..HEALTH_WINDOW).find_map(|_| {
+            health.record_success(now);
+            health
+                .record_failure(now)
+                .or_else(|| health.record_failure(now))
+        });
+        assert!(health.score() < BREAKER_MIN_SCORE);
+        assert!(health.failure_streak() < BREAKER_FAILURE_STREAK);
+        assert!(matches!(
+            tripped,
+            Some(BreakerChange::Tripped { probe: false, .. })
+        ));
+    }
+
+    #[test]
+    fn parks_are_capped() {
+        let mut now = Instant::now();
+        let mut health = Health::default();
+        let mut last = Duration::ZERO;
+        for _ in 0..40 {
+            for _ in 0..BREAKER_FAILURE_STREAK {
+                if let Some(BreakerChange::Tripped { park, .. }) = health.record_failure(now) {
+                    last = park;
+                    now += park;
+                    break;
+                }
+            }
+        }
+        assert_eq!(last, BREAKER_MAX_PARK);
+    }
+}
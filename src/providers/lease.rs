@@ -0,0 +1,56 @@
+//! In-flight accounting for handed-out credentials.
+
+use std::fmt;
+
+/// One in-flight use of a credential, counted against its provider's
+/// `max_concurrent_per_credential`.
+///
+/// Dropping the guard tells the scheduler the use has ended, so response paths keep it alive
+/// until the JSON body is built or the stream finishes.
+pub struct LeaseGuard {
+    release: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl LeaseGuard {
+    pub(crate) fn new(release: impl FnOnce() + Send + 'static) -> Self {
+        Self {
+            release: Some(Box::new(release)),
+        }
+    }
+}
+
+impl Drop for LeaseGuard {
+    fn drop(&mut self) {
+        if let Some(release) = self.release.take() {
+            release();
+        }
+    }
+}
+
+impl fmt::Debug for LeaseGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LeaseGuard").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    #[test]
+    fn dropping_the_guard_releases_once() {
+        let released = Arc::new(AtomicUsize::new(0));
+        let counter = released.clone();
+        let guard = LeaseGuard::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        assert_eq!(released.load(Ordering::SeqCst), 0);
+
+        drop(guard);
+        assert_eq!(released.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,9 +1,30 @@
 pub mod codex;
 pub mod geminicli;
 pub mod manifest;
+pub mod snapshot;
+pub mod transfer;
 
 mod bootstrap;
+mod coordination;
+mod health;
+mod lease;
 mod policy;
+mod scheduling;
+mod wait_queue;
 
 pub use bootstrap::Providers;
+pub use lease::LeaseGuard;
 pub use policy::{ActionForError, MappingAction, UPSTREAM_BODY_PREVIEW_CHARS};
+pub(crate) use policy::{
+    REFRESH_AHEAD, REFRESH_SWEEP_INTERVAL, counts_against_health, refresh_sweep_budget,
+};
+
+/// A successful upstream response together with the credential and model that served it.
+pub struct UpstreamResponse {
+    pub credential_id: u64,
+    /// Requested model, or the `[models.fallbacks]` entry used in its place.
+    pub model: String,
+    pub resp: reqwest::Response,
+    /// Keeps the credential's lease until the response has been relayed.
+    pub lease: LeaseGuard,
+}
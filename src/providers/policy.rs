@@ -4,6 +4,63 @@ use std::time::Duration;
 
 pub const UPSTREAM_BODY_PREVIEW_CHARS: usize = 200;
 
+/// How often each provider actor looks for credentials to refresh ahead of expiry.
+pub const REFRESH_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Credentials expiring within this window are refreshed by the sweep. It is well above the
+/// 5-minute buffer of `is_expired`, so requests do not run into expired tokens.
+pub const REFRESH_AHEAD: chrono::TimeDelta = chrono::TimeDelta::minutes(15);
+
+/// How often each provider actor re-reads credentials and model state written by other
+/// instances sharing the database.
+pub const STORE_SYNC_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long a refresh lease keeps other instances from refreshing the same credential. It
+/// covers the refresher's retries; a lease left by a crashed instance frees up after it.
+pub const REFRESH_LEASE_TTL: Duration = Duration::from_secs(120);
+
+/// How often an instance that lost the lease looks for the refreshed tokens.
+pub const REFRESH_LEASE_POLL: Duration = Duration::from_secs(2);
+
+/// A cooldown read back from the database is ignored when the local one ends no more than
+/// this much earlier, so an instance's own reports do not restart its cooldowns.
+pub const COOLDOWN_SYNC_SLACK: Duration = Duration::from_secs(1);
+
+/// Refreshes one sweep may submit: as many as `oauth_tps` lets through before the next one.
+pub fn refresh_sweep_budget(oauth_tps: usize) -> usize {
+    oauth_tps
+        .max(1)
+        .saturating_mul(REFRESH_SWEEP_INTERVAL.as_secs() as usize)
+}
+
+/// A sticky session that sends nothing for this long goes back to normal scheduling.
+pub const SESSION_IDLE_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Sticky sessions remembered per provider; the least recently seen is dropped beyond this.
+pub const SESSION_CAPACITY: usize = 10_000;
+
+/// Recent outcomes each credential's health score is computed over (at most 32).
+pub const HEALTH_WINDOW: u32 = 20;
+
+/// Unexplained failures in a row that open a credential's breaker.
+pub const BREAKER_FAILURE_STREAK: u32 = 3;
+
+/// With at least `BREAKER_MIN_SAMPLES` outcomes recorded, a score below `BREAKER_MIN_SCORE`
+/// opens the breaker too, catching credentials that fail often but not in a row.
+pub const BREAKER_MIN_SAMPLES: u32 = 10;
+pub const BREAKER_MIN_SCORE: f64 = 0.5;
+
+/// First park after the breaker opens; each failed probe doubles it, up to `BREAKER_MAX_PARK`.
+pub const BREAKER_BASE_PARK: Duration = Duration::from_secs(30);
+pub const BREAKER_MAX_PARK: Duration = Duration::from_secs(30 * 60);
+
+/// Whether an upstream status no mapping rule explained counts against the credential's
+/// health: server errors and refusals do, complaints about the request itself do not.
+pub fn counts_against_health(status: StatusCode) -> bool {
+    status.is_server_error()
+        || matches!(status, StatusCode::FORBIDDEN | StatusCode::REQUEST_TIMEOUT)
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum ActionForError {
     RateLimit(Duration),
@@ -13,6 +70,28 @@ pub enum ActionForError {
     None,
 }
 
+impl ActionForError {
+    /// Stable label for metrics.
+    pub fn as_label(&self) -> &'static str {
+        match self {
+            ActionForError::RateLimit(_) => "rate_limit",
+            ActionForError::Ban => "ban",
+            ActionForError::Invalid => "invalid",
+            ActionForError::ModelUnsupported => "model_unsupported",
+            ActionForError::None => "none",
+        }
+    }
+
+    /// Whether the failure is specific to the requested model, so a `[models.fallbacks]`
+    /// entry may serve the request instead.
+    pub fn allows_fallback(&self) -> bool {
+        matches!(
+            self,
+            ActionForError::RateLimit(_) | ActionForError::ModelUnsupported
+        )
+    }
+}
+
 pub trait MappingAction: std::fmt::Debug + DeserializeOwned {
     fn try_match_rule(&self, status: StatusCode) -> Option<ActionForError>;
 
@@ -0,0 +1,286 @@
+//! Credential selection strategies, shared by the provider schedulers.
+//!
+//! A scheduler keeps one [`SelectionStats`] per credential and asks its [`Selector`] which of
+//! the credentials able to serve a request goes next. Candidates are passed in queue order,
+//! so ties fall back to round robin. [`SessionPins`] lets a conversation skip the selector and
+//! return to the credential that served it last.
+
+use super::policy::{SESSION_CAPACITY, SESSION_IDLE_TTL};
+use crate::config::SchedulingStrategy;
+use std::{
+    collections::{BTreeMap, HashMap},
+    num::NonZeroU32,
+    sync::Arc,
+    time::Instant,
+};
+
+/// Stride scheduling numerator: each pick advances a credential's pass by `STRIDE / weight`.
+const STRIDE: u64 = 1 << 20;
+
+/// Per-credential bookkeeping the strategies rank candidates by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectionStats {
+    weight: u32,
+    pass: u64,
+    last_used: Option<Instant>,
+    last_rate_limited: Option<Instant>,
+}
+
+impl Default for SelectionStats {
+    fn default() -> Self {
+        Self {
+            weight: 1,
+            pass: 0,
+            last_used: None,
+            last_rate_limited: None,
+        }
+    }
+}
+
+impl SelectionStats {
+    pub fn record_rate_limit(&mut self, now: Instant) {
+        self.last_rate_limited = Some(now);
+    }
+}
+
+/// The configured strategy plus the state it needs across picks.
+#[derive(Debug, Default)]
+pub struct Selector {
+    strategy: SchedulingStrategy,
+    weights: BTreeMap<String, NonZeroU32>,
+    /// Pass of the last weighted pick. Credentials that sat out (cooling, refreshing, newly
+    /// added) are ranked from here rather than from their stale pass, so they do not get a
+    /// burst of consecutive picks when they return.
+    virtual_time: u64,
+}
+
+impl Selector {
+    /// Apply a (re)loaded strategy. Callers re-`classify` every credential afterwards.
+    pub fn configure(
+        &mut self,
+        strategy: SchedulingStrategy,
+        weights: BTreeMap<String, NonZeroU32>,
+    ) {
+        self.strategy = strategy;
+        self.weights = weights;
+    }
+
+    pub fn strategy(&self) -> SchedulingStrategy {
+        self.strategy
+    }
+
+    /// Refresh `stats` for a credential of the given plan/tier; unlisted classes weigh 1.
+    pub fn classify(&self, stats: &mut SelectionStats, class: Option<&str>) {
+        stats.weight = class
+            .and_then(|class| self.weights.get(class))
+            .map_or(1, |weight| weight.get());
+    }
+
+    /// Position of the candidate to serve next, or `None` when there are none.
+    pub fn pick<'a>(
+        &self,
+        candidates: impl IntoIterator<Item = &'a SelectionStats>,
+    ) -> Option<usize> {
+        let mut candidates = candidates.into_iter().enumerate();
+        let best = match self.strategy {
+            SchedulingStrategy::RoundRobin => candidates.next(),
+            SchedulingStrategy::Weighted => {
+                candidates.min_by_key(|(_, stats)| stats.pass.max(self.virtual_time))
+            }
+            SchedulingStrategy::LeastRecentlyUsed => {
+                candidates.min_by_key(|(_, stats)| stats.last_used)
+            }
+            SchedulingStrategy::LeastRecentlyRateLimited => {
+                candidates.min_by_key(|(_, stats)| (stats.last_rate_limited, stats.last_used))
+            }
+        };
+        best.map(|(position, _)| position)
+    }
+
+    /// Account for `stats` having just been handed out.
+    pub fn record_use(&mut self, stats: &mut SelectionStats, now: Instant) {
+        let pass = stats.pass.max(self.virtual_time);
+        if self.strategy == SchedulingStrategy::Weighted {
+            self.virtual_time = pass;
+        }
+        stats.pass = pass + STRIDE / u64::from(stats.weight.max(1));
+        stats.last_used = Some(now);
+    }
+}
+
+/// Sticky sessions: the credential that last served each session key.
+#[derive(Debug)]
+pub struct SessionPins {
+    pins: HashMap<Arc<str>, (u64, Instant)>,
+    capacity: usize,
+}
+
+impl Default for SessionPins {
+    fn default() -> Self {
+        Self::with_capacity(SESSION_CAPACITY)
+    }
+}
+
+impl SessionPins {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            pins: HashMap::new(),
+            capacity,
+        }
+    }
+
+    /// Credential `session` is pinned to, unless the pin has idled out.
+    pub fn get(&self, session: &str, now: Instant) -> Option<u64> {
+        self.pins
+            .get(session)
+            .filter(|(_, seen)| now.saturating_duration_since(*seen) < SESSION_IDLE_TTL)
+            .map(|(id, _)| *id)
+    }
+
+    /// Pin `session` to `id`. At capacity, idle pins go first, then the least recently seen.
+    pub fn pin(&mut self, session: Arc<str>, id: u64, now: Instant) {
+        if !self.pins.contains_key(&session) && self.pins.len() >= self.capacity {
+            self.pins
+                .retain(|_, (_, seen)| now.saturating_duration_since(*seen) < SESSION_IDLE_TTL);
+            if self.pins.len() >= self.capacity
+                && let Some(oldest) = self
+                    .pins
+                    .iter()
+                    .min_by_key(|(_, (_, seen))| *seen)
+                    .map(|(key, _)| key.clone())
+            {
+                self.pins.remove(&oldest);
+            }
+        }
+        self.pins.insert(session, (id, now));
+    }
+
+    pub fn clear(&mut self) {
+        self.pins.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.pins.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn selector(strategy: SchedulingStrategy) -> Selector {
+        let weights = BTreeMap::from([
+            ("pro".to_string(), NonZeroU32::new(3).unwrap()),
+            ("plus".to_string(), NonZeroU32::new(1).unwrap()),
+        ]);
+        let mut selector = Selector::default();
+        selector.configure(strategy, weights);
+        selector
+    }
+
+    /// Pick `rounds` times from `pool`, one millisecond apart from `start`, recording each
+    /// use; returns the picked positions.
+    fn run(
+        selector: &mut Selector,
+        pool: &mut [SelectionStats],
+        start: Instant,
+        rounds: usize,
+    ) -> Vec<usize> {
+        (0..rounds)
+            .map(|round| {
+                let position = selector.pick(pool.iter()).expect("non-empty pool");
+                let now = start + Duration::from_millis(round as u64);
+                selector.record_use(&mut pool[position], now);
+                position
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_robin_takes_the_queue_head() {
+        let mut selector = selector(SchedulingStrategy::RoundRobin);
+        let mut pool = [SelectionStats::default(); 3];
+        assert_eq!(
+            run(&mut selector, &mut pool, Instant::now(), 3),
+            vec![0, 0, 0]
+        );
+        assert_eq!(selector.pick(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn weighted_shares_picks_by_class() {
+        let mut selector = selector(SchedulingStrategy::Weighted);
+        let mut pool = [SelectionStats::default(); 3];
+        selector.classify(&mut pool[0], Some("pro"));
+        selector.classify(&mut pool[1], Some("plus"));
+        selector.classify(&mut pool[2], None);
+        assert_eq!(
+            pool.map(|stats| stats.weight),
+            [3, 1, 1],
+            "unlisted classes weigh 1"
+        );
+
+        let picks = run(&mut selector, &mut pool, Instant::now(), 50);
+        let count = |position| picks.iter().filter(|&&p| p == position).count();
+        assert_eq!((count(0), count(1), count(2)), (30, 10, 10));
+    }
+
+    #[test]
+    fn weighted_does_not_burst_a_returning_credential() {
+        let mut selector = selector(SchedulingStrategy::Weighted);
+        let mut pool = [SelectionStats::default(); 2];
+        run(&mut selector, &mut pool, Instant::now(), 20);
+
+        // A credential that sat out (or was just added) starts from the current pass.
+        let mut pool = [pool[0], pool[1], SelectionStats::default()];
+        let picks = run(&mut selector, &mut pool, Instant::now(), 6);
+        assert_eq!(picks.iter().filter(|&&p| p == 2).count(), 2, "{picks:?}");
+    }
+
+    #[test]
+    fn least_recently_used_prefers_idle_credentials() {
+        let mut selector = selector(SchedulingStrategy::LeastRecentlyUsed);
+        let mut pool = [SelectionStats::default(); 3];
+        let now = Instant::now();
+        selector.record_use(&mut pool[0], now);
+        selector.record_use(&mut pool[2], now + Duration::from_millis(1));
+
+        // Never used first, then oldest use.
+        let later = now + Duration::from_secs(1);
+        assert_eq!(run(&mut selector, &mut pool, later, 3), vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn least_recently_rate_limited_prefers_unlimited_then_oldest_limit() {
+        let selector = selector(SchedulingStrategy::LeastRecentlyRateLimited);
+        let mut pool = [SelectionStats::default(); 3];
+        let now = Instant::now();
+        pool[0].record_rate_limit(now + Duration::from_secs(1));
+        pool[1].record_rate_limit(now);
+
+        assert_eq!(selector.pick(pool.iter()), Some(2));
+        pool[2].record_rate_limit(now + Duration::from_secs(2));
+        assert_eq!(selector.pick(pool.iter()), Some(1));
+    }
+
+    #[test]
+    fn session_pins_expire_and_evict_the_least_recently_seen() {
+        let mut pins = SessionPins::with_capacity(2);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        pins.pin(Arc::from("a"), 1, at(0));
+        pins.pin(Arc::from("b"), 2, at(1));
+        assert_eq!(pins.get("a", at(2)), Some(1));
+        assert_eq!(pins.get("a", at(0) + SESSION_IDLE_TTL), None, "idled out");
+
+        // Re-pinning refreshes "a", so "b" is the one evicted.
+        pins.pin(Arc::from("a"), 3, at(2));
+        pins.pin(Arc::from("c"), 4, at(3));
+        assert_eq!(pins.len(), 2);
+        assert_eq!(pins.get("a", at(4)), Some(3));
+        assert_eq!(pins.get("b", at(4)), None);
+        assert_eq!(pins.get("c", at(4)), Some(4));
+    }
+}
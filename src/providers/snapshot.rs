@@ -0,0 +1,103 @@
+//! Read-only scheduler snapshots, shared by all provider actors.
+//!
+//! Snapshots are built inside the actor (so they are consistent with a single
+//! point in time) and only carry ids/labels, never tokens.
+
+pub use super::health::BreakerState;
+
+use crate::model_catalog::model_names_from_mask;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SchedulerSnapshot {
+    /// Credentials currently held in memory.
+    pub total_creds: usize,
+    /// Ids with an in-flight token refresh.
+    pub refreshing: Vec<u64>,
+    /// Sticky sessions currently pinned to a credential.
+    pub sessions: usize,
+    /// Requests waiting in the queue for a credential to free up.
+    pub waiting: usize,
+    /// One entry per model this provider serves.
+    pub models: Vec<ModelQueueSnapshot>,
+    pub credentials: Vec<CredentialSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelQueueSnapshot {
+    pub model: String,
+    /// Raw queue length; may include ids that are cooling, refreshing or removed.
+    pub queue_len: usize,
+    /// Queued ids that `get_assigned` could hand out right now (expiry aside); excludes
+    /// credentials at their concurrency limit.
+    pub available: usize,
+    pub cooling: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialSnapshot {
+    pub id: u64,
+    /// Project id (Gemini CLI) or account id (Codex).
+    pub label: String,
+    /// Capability bitmask, hex-formatted.
+    pub caps: String,
+    pub models: Vec<String>,
+    pub expired: bool,
+    pub refreshing: bool,
+    /// Leases handed out and not yet released.
+    pub in_flight: u32,
+    pub health: HealthSnapshot,
+    pub cooldowns: Vec<CooldownSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthSnapshot {
+    pub state: BreakerState,
+    /// Share of recent requests that succeeded, from 0 to 1.
+    pub score: f64,
+    /// Unexplained failures since the last success.
+    pub failure_streak: u32,
+    /// When a parked credential gets its probe request.
+    pub parked_until: Option<DateTime<Utc>>,
+}
+
+/// Capacity of the models a request asked for, reported when it found no credential.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PoolStatus {
+    /// Credentials able to serve at least one of the models, whatever their state.
+    pub total: usize,
+    /// Of those, credentials that could take a request right now.
+    pub available: usize,
+    /// Time until the earliest running cooldown on one of the models ends.
+    pub retry_after: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CooldownSnapshot {
+    pub model: String,
+    pub until: DateTime<Utc>,
+    pub remaining_secs: u64,
+}
+
+impl CooldownSnapshot {
+    /// Converts a monotonic deadline into wall-clock time relative to `now`.
+    pub(crate) fn new(model_index: usize, deadline: Instant, now: Instant) -> Self {
+        let remaining = deadline.saturating_duration_since(now);
+        let until = Utc::now()
+            + chrono::Duration::from_std(remaining).unwrap_or_else(|_| chrono::Duration::zero());
+        Self {
+            model: model_name(model_index),
+            until,
+            remaining_secs: remaining.as_secs(),
+        }
+    }
+}
+
+/// Name of a model index, falling back to `#<index>` outside the registry.
+pub(crate) fn model_name(index: usize) -> String {
+    model_names_from_mask(1u64 << index)
+        .pop()
+        .unwrap_or_else(|| format!("#{index}"))
+}
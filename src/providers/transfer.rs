@@ -0,0 +1,540 @@
+//! Moving credentials in and out of Pollux as the files the upstream CLIs keep.
+//!
+//! - Gemini CLI reads its OAuth tokens from `~/.gemini/oauth_creds.json`.
+//! - Codex CLI reads them from `~/.codex/auth.json`.
+//!
+//! An export archive is an uncompressed tar holding one such file per credential, under
+//! `geminicli/<id>-<project_id>/` and `codex/<id>-<account_id>/`. Imports take those files,
+//! the archive, or `GeminiCliProfile` / `CodexProfile` JSON, and hand them to the provider
+//! actors: profiles and Codex files with a readable identity take the trusted path, everything
+//! else is onboarded from its refresh token like a `resource:add` seed.
+
+use crate::db::{DbActorHandle, DbCodexResource, DbGeminiCliResource};
+use crate::error::PolluxError;
+use crate::providers::Providers;
+use crate::providers::codex::identity_from_id_token;
+use crate::providers::geminicli::GEMINICLI_SCOPES;
+use crate::providers::manifest::{CodexProfile, GeminiCliProfile, ProviderKind};
+use crate::utils::jwt::decode_jwt_claims;
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::HashSet;
+use std::io::Read;
+use tracing::info;
+
+/// File name Gemini CLI keeps its tokens under.
+pub const GEMINICLI_CREDS_FILE: &str = "oauth_creds.json";
+/// File name Codex CLI keeps its tokens under.
+pub const CODEX_AUTH_FILE: &str = "auth.json";
+
+/// `~/.gemini/oauth_creds.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiCliCredsFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+    pub refresh_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+    /// Access token expiry in Unix milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry_date: Option<i64>,
+}
+
+impl From<DbGeminiCliResource> for GeminiCliCredsFile {
+    fn from(row: DbGeminiCliResource) -> Self {
+        let scope = GEMINICLI_SCOPES
+            .iter()
+            .map(|scope| scope.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Self {
+            expiry_date: row
+                .access_token
+                .as_ref()
+                .map(|_| row.expiry.timestamp_millis()),
+            access_token: row.access_token,
+            refresh_token: row.refresh_token,
+            scope: Some(scope),
+            token_type: Some("Bearer".to_string()),
+            id_token: None,
+        }
+    }
+}
+
+/// `~/.codex/auth.json`, signed in with ChatGPT.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodexAuthFile {
+    #[serde(rename = "OPENAI_API_KEY", default)]
+    pub openai_api_key: Option<String>,
+    pub tokens: CodexAuthTokens,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_refresh: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodexAuthTokens {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+    pub refresh_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+}
+
+impl From<DbCodexResource> for CodexAuthFile {
+    fn from(row: DbCodexResource) -> Self {
+        Self {
+            openai_api_key: None,
+            tokens: CodexAuthTokens {
+                id_token: Some(unsigned_id_token(&row)),
+                access_token: Some(row.access_token),
+                refresh_token: row.refresh_token,
+                account_id: Some(row.account_id),
+            },
+            last_refresh: Some(row.updated_at),
+        }
+    }
+}
+
+/// Pollux does not keep the id_token OpenAI issued, and Codex CLI only reads its claims, so
+/// export rebuilds one from the stored identity. It is not signed; the next refresh replaces
+/// it with a real one.
+fn unsigned_id_token(row: &DbCodexResource) -> String {
+    let mut auth = Map::new();
+    auth.insert("chatgpt_account_id".into(), json!(row.account_id));
+    if let Some(plan) = &row.chatgpt_plan_type {
+        auth.insert("chatgpt_plan_type".into(), json!(plan));
+    }
+    let mut claims = Map::new();
+    claims.insert("sub".into(), json!(row.sub));
+    if let Some(email) = &row.email {
+        claims.insert("email".into(), json!(email));
+    }
+    claims.insert("https://api.openai.com/auth".into(), Value::Object(auth));
+
+    let encode = |value: Value| {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value.to_string().as_bytes())
+    };
+    format!(
+        "{}.{}.unsigned",
+        encode(json!({ "alg": "none", "typ": "JWT" })),
+        encode(Value::Object(claims))
+    )
+}
+
+/// Build a tar archive of every enabled credential of `provider`, or of both providers, and
+/// return it with the number of credentials it holds.
+pub async fn export_archive(
+    db: &DbActorHandle,
+    provider: Option<ProviderKind>,
+) -> Result<(Vec<u8>, usize), PolluxError> {
+    let mut archive = tar::Builder::new(Vec::new());
+    let mut count = 0;
+    if provider.is_none_or(|p| p == ProviderKind::GeminiCli) {
+        for row in db.list_active_geminicli().await? {
+            let path = format!(
+                "geminicli/{}-{}/{GEMINICLI_CREDS_FILE}",
+                row.id,
+                path_label(&row.project_id)
+            );
+            let mtime = row.updated_at;
+            append_json(&mut archive, &path, mtime, &GeminiCliCredsFile::from(row))?;
+            count += 1;
+        }
+    }
+    if provider.is_none_or(|p| p == ProviderKind::Codex) {
+        for row in db.list_active_codex().await? {
+            let path = format!(
+                "codex/{}-{}/{CODEX_AUTH_FILE}",
+                row.id,
+                path_label(&row.account_id)
+            );
+            let mtime = row.updated_at;
+            append_json(&mut archive, &path, mtime, &CodexAuthFile::from(row))?;
+            count += 1;
+        }
+    }
+    Ok((archive.into_inner()?, count))
+}
+
+fn append_json(
+    archive: &mut tar::Builder<Vec<u8>>,
+    path: &str,
+    mtime: DateTime<Utc>,
+    file: &impl Serialize,
+) -> Result<(), PolluxError> {
+    let data = serde_json::to_vec_pretty(file)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    // The files hold live tokens.
+    header.set_mode(0o600);
+    header.set_mtime(u64::try_from(mtime.timestamp()).unwrap_or_default());
+    archive.append_data(&mut header, path, data.as_slice())?;
+    Ok(())
+}
+
+/// Keep ids from upstream out of archive paths unless they are plain names.
+fn path_label(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// A file or entry that could not be imported, and why.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportRejection {
+    /// Archive path, or `body` (with an array index) for JSON posted directly.
+    pub source: String,
+    pub reason: String,
+}
+
+/// What an import handed to the provider actors. Onboarding continues in the background.
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub geminicli: usize,
+    pub codex: usize,
+    pub rejected: Vec<ImportRejection>,
+}
+
+/// Credentials read from an import, sorted by the submission path they take.
+#[derive(Debug, Default)]
+pub struct ImportBatch {
+    geminicli_profiles: Vec<GeminiCliProfile>,
+    geminicli_refresh_tokens: Vec<String>,
+    codex_profiles: Vec<CodexProfile>,
+    codex_refresh_tokens: Vec<String>,
+    rejected: Vec<ImportRejection>,
+    seen: HashSet<String>,
+}
+
+enum Imported {
+    GeminiCliProfile(GeminiCliProfile),
+    GeminiCliRefreshToken(String),
+    CodexProfile(CodexProfile),
+    CodexRefreshToken(String),
+}
+
+impl ImportBatch {
+    /// Read an import body: a tar archive, or one JSON document that is a credential file, a
+    /// profile, or an array of them.
+    pub fn parse(body: &[u8]) -> Result<Self, PolluxError> {
+        let mut batch = Self::default();
+        match body.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') | Some(b'[') => {
+                let value: Value = serde_json::from_slice(body)
+                    .map_err(|e| PolluxError::InvalidImport(format!("invalid JSON: {e}")))?;
+                match value {
+                    Value::Array(items) => {
+                        for (index, item) in items.into_iter().enumerate() {
+                            batch.add(&format!("body[{index}]"), item);
+                        }
+                    }
+                    value => batch.add("body", value),
+                }
+            }
+            Some(_) => batch.add_archive(body)?,
+            None => return Err(PolluxError::InvalidImport("empty body".to_string())),
+        }
+        Ok(batch)
+    }
+
+    fn add_archive(&mut self, body: &[u8]) -> Result<(), PolluxError> {
+        let invalid = |e: std::io::Error| PolluxError::InvalidImport(format!("invalid tar: {e}"));
+        let mut archive = tar::Archive::new(body);
+        let mut entries = 0;
+        for entry in archive.entries().map_err(invalid)? {
+            let mut entry = entry.map_err(invalid)?;
+            entries += 1;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let source = entry.path().map_err(invalid)?.display().to_string();
+            if !source.ends_with(".json") {
+                self.reject(&source, "not a .json file");
+                continue;
+            }
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).map_err(invalid)?;
+            match serde_json::from_slice(&data) {
+                Ok(value) => self.add(&source, value),
+                Err(e) => self.reject(&source, &format!("invalid JSON: {e}")),
+            }
+        }
+        if entries == 0 {
+            return Err(PolluxError::InvalidImport(
+                "expected JSON or a tar archive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn add(&mut self, source: &str, value: Value) {
+        let imported = match classify(value) {
+            Ok(imported) => imported,
+            Err(reason) => return self.reject(source, &reason),
+        };
+        let refresh_token = match &imported {
+            Imported::GeminiCliProfile(profile) => &profile.refresh_token,
+            Imported::CodexProfile(profile) => &profile.refresh_token,
+            Imported::GeminiCliRefreshToken(token) | Imported::CodexRefreshToken(token) => token,
+        };
+        // The same credential twice would only be onboarded twice.
+        if !self.seen.insert(refresh_token.clone()) {
+            return self.reject(source, "duplicate refresh_token");
+        }
+        match imported {
+            Imported::GeminiCliProfile(profile) => self.geminicli_profiles.push(profile),
+            Imported::GeminiCliRefreshToken(token) => self.geminicli_refresh_tokens.push(token),
+            Imported::CodexProfile(profile) => self.codex_profiles.push(profile),
+            Imported::CodexRefreshToken(token) => self.codex_refresh_tokens.push(token),
+        }
+    }
+
+    fn reject(&mut self, source: &str, reason: &str) {
+        self.rejected.push(ImportRejection {
+            source: source.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Hand the credentials to the provider actors.
+    pub async fn submit(self, providers: &Providers) -> ImportReport {
+        let report = ImportReport {
+            geminicli: self.geminicli_profiles.len() + self.geminicli_refresh_tokens.len(),
+            codex: self.codex_profiles.len() + self.codex_refresh_tokens.len(),
+            rejected: self.rejected,
+        };
+        info!(
+            geminicli = report.geminicli,
+            codex = report.codex,
+            rejected = report.rejected.len(),
+            "Credential import received"
+        );
+        if !self.geminicli_profiles.is_empty() {
+            providers
+                .geminicli
+                .submit_credentials(self.geminicli_profiles)
+                .await;
+        }
+        if !self.geminicli_refresh_tokens.is_empty() {
+            providers
+                .geminicli
+                .submit_refresh_tokens(self.geminicli_refresh_tokens)
+                .await;
+        }
+        if !self.codex_profiles.is_empty() {
+            providers
+                .codex
+                .submit_credentials(self.codex_profiles)
+                .await;
+        }
+        if !self.codex_refresh_tokens.is_empty() {
+            providers
+                .codex
+                .submit_refresh_tokens(self.codex_refresh_tokens)
+                .await;
+        }
+        report
+    }
+}
+
+/// Tell the formats apart by their fields.
+fn classify(value: Value) -> Result<Imported, String> {
+    let Some(object) = value.as_object() else {
+        return Err("expected a JSON object".to_string());
+    };
+    let has = |field: &str| object.contains_key(field);
+    let parse_error = |e: serde_json::Error| e.to_string();
+
+    let imported = if has("tokens") {
+        let file: CodexAuthFile = serde_json::from_value(value).map_err(parse_error)?;
+        match codex_profile(&file.tokens) {
+            Some(profile) => Imported::CodexProfile(profile),
+            None => Imported::CodexRefreshToken(file.tokens.refresh_token),
+        }
+    } else if has("project_id") {
+        Imported::GeminiCliProfile(serde_json::from_value(value).map_err(parse_error)?)
+    } else if has("account_id") {
+        Imported::CodexProfile(serde_json::from_value(value).map_err(parse_error)?)
+    } else if has("refresh_token") && (has("expiry_date") || has("scope") || has("token_type")) {
+        let file: GeminiCliCredsFile = serde_json::from_value(value).map_err(parse_error)?;
+        Imported::GeminiCliRefreshToken(file.refresh_token)
+    } else {
+        return Err("not a Gemini CLI or Codex CLI credential".to_string());
+    };
+
+    let (refresh_token, identity) = match &imported {
+        Imported::GeminiCliProfile(p) => (&p.refresh_token, vec![&p.project_id]),
+        Imported::CodexProfile(p) => (&p.refresh_token, vec![&p.account_id, &p.sub]),
+        Imported::GeminiCliRefreshToken(t) | Imported::CodexRefreshToken(t) => (t, vec![]),
+    };
+    if refresh_token.trim().is_empty() || identity.iter().any(|id| id.trim().is_empty()) {
+        return Err("missing refresh_token or account".to_string());
+    }
+    Ok(imported)
+}
+
+/// The identity and access token of an `auth.json`, when its id_token can be read. The
+/// expiry comes from the access token's `exp` claim; without it the profile is refreshed
+/// before use.
+fn codex_profile(tokens: &CodexAuthTokens) -> Option<CodexProfile> {
+    let identity = identity_from_id_token(tokens.id_token.as_deref()?).ok()?;
+    let expiry = tokens
+        .access_token
+        .as_deref()
+        .and_then(decode_jwt_claims)
+        .and_then(|claims| claims.get("exp").and_then(Value::as_i64))
+        .and_then(|exp| DateTime::from_timestamp(exp, 0));
+    Some(CodexProfile {
+        account_id: identity.account_id,
+        sub: identity.sub,
+        refresh_token: tokens.refresh_token.trim().to_string(),
+        access_token: tokens.access_token.clone(),
+        email: identity.email,
+        chatgpt_plan_type: identity.chatgpt_plan_type,
+        expiry,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn codex_row() -> DbCodexResource {
+        let now = Utc::now();
+        DbCodexResource {
+            id: 7,
+            email: Some("user@example.com".to_string()),
+            sub: "auth0|subject".to_string(),
+            account_id: "acct-7".to_string(),
+            refresh_token: "rt-7".to_string(),
+            access_token: "at-7".to_string(),
+            expiry: now + Duration::hours(1),
+            chatgpt_plan_type: Some("plus".to_string()),
+            status: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn codex_auth_file_round_trips_through_the_trusted_path() {
+        let file = CodexAuthFile::from(codex_row());
+        let json = serde_json::to_value(&file).unwrap();
+        assert_eq!(json["OPENAI_API_KEY"], Value::Null);
+        assert_eq!(json["tokens"]["account_id"], "acct-7");
+
+        let Ok(Imported::CodexProfile(profile)) = classify(json) else {
+            panic!("auth.json with an id_token is a profile");
+        };
+        assert_eq!(profile.account_id, "acct-7");
+        assert_eq!(profile.sub, "auth0|subject");
+        assert_eq!(profile.email.as_deref(), Some("user@example.com"));
+        assert_eq!(profile.chatgpt_plan_type.as_deref(), Some("plus"));
+        assert_eq!(profile.refresh_token, "rt-7");
+        // "at-7" is not a JWT, so the expiry is unknown.
+        assert_eq!(profile.expiry, None);
+
+        // Without an id_token only the refresh token can be trusted.
+        let bare = json!({ "tokens": { "refresh_token": "rt-8" } });
+        assert!(matches!(classify(bare), Ok(Imported::CodexRefreshToken(t)) if t == "rt-8"));
+    }
+
+    #[test]
+    fn formats_are_told_apart_by_their_fields() {
+        let gemini_file = json!({
+            "access_token": "ya29.a",
+            "refresh_token": "1//r",
+            "token_type": "Bearer",
+            "expiry_date": 1_760_000_000_000_i64,
+        });
+        assert!(matches!(
+            classify(gemini_file),
+            Ok(Imported::GeminiCliRefreshToken(t)) if t == "1//r"
+        ));
+        let gemini_profile = json!({ "refresh_token": "1//p", "project_id": "proj" });
+        assert!(matches!(
+            classify(gemini_profile),
+            Ok(Imported::GeminiCliProfile(p)) if p.project_id == "proj"
+        ));
+        let codex_profile = json!({ "account_id": "acct", "sub": "s", "refresh_token": "rt" });
+        assert!(matches!(
+            classify(codex_profile),
+            Ok(Imported::CodexProfile(p)) if p.account_id == "acct"
+        ));
+
+        assert!(classify(json!({ "refresh_token": "ambiguous" })).is_err());
+        assert!(classify(json!({ "refresh_token": " ", "project_id": "proj" })).is_err());
+        assert!(classify(json!(["not", "an", "object"])).is_err());
+    }
+
+    #[test]
+    fn gemini_creds_file_carries_expiry_in_millis() {
+        let now = Utc::now();
+        let file = GeminiCliCredsFile::from(DbGeminiCliResource {
+            id: 1,
+            email: None,
+            sub: "sub".to_string(),
+            project_id: "proj".to_string(),
+            refresh_token: "1//r".to_string(),
+            access_token: Some("ya29.a".to_string()),
+            expiry: now,
+            tier: None,
+            status: true,
+            created_at: now,
+            updated_at: now,
+        });
+        assert_eq!(file.expiry_date, Some(now.timestamp_millis()));
+        assert_eq!(file.token_type.as_deref(), Some("Bearer"));
+        assert!(file.scope.unwrap().contains("cloud-platform"));
+    }
+
+    #[test]
+    fn archives_and_arrays_are_parsed_entry_by_entry() {
+        let mut archive = tar::Builder::new(Vec::new());
+        let now = Utc::now();
+        append_json(
+            &mut archive,
+            "codex/7-acct-7/auth.json",
+            now,
+            &CodexAuthFile::from(codex_row()),
+        )
+        .unwrap();
+        append_json(&mut archive, "notes/readme.json", now, &json!({})).unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(2);
+        archive
+            .append_data(&mut header, "notes/readme.txt", b"hi".as_slice())
+            .unwrap();
+        let batch = ImportBatch::parse(&archive.into_inner().unwrap()).unwrap();
+        assert_eq!(batch.codex_profiles.len(), 1);
+        let sources: Vec<_> = batch.rejected.iter().map(|r| r.source.as_str()).collect();
+        assert_eq!(sources, ["notes/readme.json", "notes/readme.txt"]);
+
+        let body = json!([
+            { "refresh_token": "1//p", "project_id": "proj" },
+            { "refresh_token": "1//p", "project_id": "proj" },
+        ]);
+        let batch = ImportBatch::parse(body.to_string().as_bytes()).unwrap();
+        assert_eq!(batch.geminicli_profiles.len(), 1);
+        assert_eq!(batch.rejected[0].source, "body[1]");
+
+        assert!(ImportBatch::parse(b"  ").is_err());
+        assert!(ImportBatch::parse(b"neither JSON nor tar").is_err());
+        assert!(ImportBatch::parse(b"{ not json").is_err());
+    }
+}
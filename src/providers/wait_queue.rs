@@ -0,0 +1,219 @@
+//! Callers parked in a provider actor until a credential frees up.
+//!
+//! With `queue_timeout_secs` set, a `GetCredential` that finds no credential joins a bounded
+//! FIFO instead of failing at once. The actor retries the queue whenever capacity may have
+//! come back (a cooldown ends, a refresh lands, a lease is released) and answers `None` once
+//! a caller's deadline passes.
+
+use crate::error::PolluxError;
+use ractor::RpcReplyPort;
+use std::{
+    collections::VecDeque,
+    num::NonZeroU64,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Reply of a `GetCredential` call: a lease, nothing, or `QueueFull` when load was shed.
+pub type CredentialReply<L> = RpcReplyPort<Result<Option<L>, PolluxError>>;
+
+struct Waiter<L> {
+    model_mask: u64,
+    session: Option<Arc<str>>,
+    deadline: Instant,
+    reply: CredentialReply<L>,
+}
+
+pub struct WaitQueue<L> {
+    waiters: VecDeque<Waiter<L>>,
+    timeout: Option<Duration>,
+    max_depth: usize,
+    /// Earliest wake-up already scheduled with the actor.
+    wake_at: Option<Instant>,
+}
+
+impl<L> Default for WaitQueue<L> {
+    fn default() -> Self {
+        Self {
+            waiters: VecDeque::new(),
+            timeout: None,
+            max_depth: 0,
+            wake_at: None,
+        }
+    }
+}
+
+impl<L> WaitQueue<L> {
+    /// Apply a (re)loaded config. Callers already waiting keep their deadlines.
+    pub fn configure(&mut self, timeout_secs: Option<NonZeroU64>, max_depth: usize) {
+        self.timeout = timeout_secs.map(|secs| Duration::from_secs(secs.get()));
+        self.max_depth = max_depth;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.timeout.is_some() && self.max_depth > 0
+    }
+
+    /// How long a caller may wait; zero when waiting is disabled.
+    pub fn timeout(&self) -> Duration {
+        self.timeout.unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.waiters.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.waiters.len()
+    }
+
+    /// Park `reply` at the back of the queue, or hand it back when the queue is full.
+    pub fn push(
+        &mut self,
+        model_mask: u64,
+        session: Option<Arc<str>>,
+        reply: CredentialReply<L>,
+        now: Instant,
+    ) -> Result<(), CredentialReply<L>> {
+        if !self.is_enabled() || self.waiters.len() >= self.max_depth {
+            return Err(reply);
+        }
+        self.waiters.push_back(Waiter {
+            model_mask,
+            session,
+            deadline: now + self.timeout(),
+            reply,
+        });
+        Ok(())
+    }
+
+    /// Offer capacity to every waiter in arrival order. `assign` returns a lease or `None`;
+    /// once a model comes up empty, later waiters for it stay parked, so nobody overtakes an
+    /// earlier caller. Waiters past their deadline get `None`; ones that hung up are dropped.
+    pub fn serve(
+        &mut self,
+        now: Instant,
+        mut assign: impl FnMut(u64, Option<Arc<str>>) -> Option<L>,
+    ) {
+        let mut exhausted = 0u64;
+        for waiter in std::mem::take(&mut self.waiters) {
+            if waiter.reply.is_closed() {
+                continue;
+            }
+            if now >= waiter.deadline {
+                let _ = waiter.reply.send(Ok(None));
+                continue;
+            }
+            if waiter.model_mask & exhausted == 0 {
+                match assign(waiter.model_mask, waiter.session.clone()) {
+                    Some(lease) => {
+                        let _ = waiter.reply.send(Ok(Some(lease)));
+                        continue;
+                    }
+                    None => exhausted |= waiter.model_mask,
+                }
+            }
+            self.waiters.push_back(waiter);
+        }
+    }
+
+    /// When the actor should next look at the queue: the earliest waiter deadline or
+    /// `capacity_at`, whichever comes first. `None` if nothing is waiting or an early enough
+    /// wake-up is already scheduled.
+    pub fn next_wake(&mut self, capacity_at: Option<Instant>) -> Option<Instant> {
+        let deadline = self.waiters.iter().map(|waiter| waiter.deadline).min()?;
+        let at = capacity_at.map_or(deadline, |at| at.min(deadline));
+        if self.wake_at.is_some_and(|scheduled| scheduled <= at) {
+            return None;
+        }
+        self.wake_at = Some(at);
+        Some(at)
+    }
+
+    /// A scheduled wake-up has fired.
+    pub fn woke(&mut self) {
+        self.wake_at = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ractor::concurrency::{OneshotReceiver, oneshot};
+
+    fn port() -> (
+        CredentialReply<u64>,
+        OneshotReceiver<Result<Option<u64>, PolluxError>>,
+    ) {
+        let (tx, rx) = oneshot();
+        (tx.into(), rx)
+    }
+
+    fn queue(max_depth: usize) -> WaitQueue<u64> {
+        let mut queue = WaitQueue::default();
+        queue.configure(NonZeroU64::new(10), max_depth);
+        queue
+    }
+
+    #[test]
+    fn full_or_disabled_queues_hand_the_caller_back() {
+        let now = Instant::now();
+        let mut disabled = WaitQueue::<u64>::default();
+        assert!(disabled.push(1, None, port().0, now).is_err());
+
+        let mut queue = queue(1);
+        let (first, _rx) = port();
+        assert!(queue.push(1, None, first, now).is_ok());
+        assert!(queue.push(1, None, port().0, now).is_err());
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn serve_is_fifo_per_model_and_expires_waiters() {
+        let now = Instant::now();
+        let mut queue = queue(8);
+        let (a, mut rx_a) = port();
+        let (b, mut rx_b) = port();
+        let (c, mut rx_c) = port();
+        queue.push(0b01, None, a, now).unwrap();
+        queue.push(0b01, None, b, now).unwrap();
+        queue.push(0b10, None, c, now).unwrap();
+
+        // One lease for model 1 and none for model 2: only the first caller is served, and the
+        // failed attempt for model 2 does not stop later models from being tried.
+        let mut leases = vec![7];
+        let mut asked = Vec::new();
+        queue.serve(now, |mask, _| {
+            asked.push(mask);
+            if mask == 0b01 { leases.pop() } else { None }
+        });
+        assert_eq!(asked, vec![0b01, 0b01, 0b10]);
+        assert_eq!(rx_a.try_recv().expect("served").expect("ok"), Some(7));
+        assert!(rx_b.try_recv().is_err(), "still waiting");
+        assert_eq!(queue.len(), 2);
+
+        // Past the deadline everyone left gets `None`.
+        queue.serve(now + Duration::from_secs(10), |_, _| Some(9));
+        assert_eq!(rx_b.try_recv().expect("answered").expect("ok"), None);
+        assert_eq!(rx_c.try_recv().expect("answered").expect("ok"), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn next_wake_only_reschedules_for_an_earlier_time() {
+        let now = Instant::now();
+        let mut queue = queue(8);
+        assert_eq!(queue.next_wake(None), None, "nothing waiting");
+
+        let (reply, _rx) = port();
+        queue.push(1, None, reply, now).unwrap();
+        let deadline = now + Duration::from_secs(10);
+        assert_eq!(queue.next_wake(None), Some(deadline));
+        assert_eq!(queue.next_wake(None), None, "already scheduled");
+
+        let cooldown_end = now + Duration::from_secs(2);
+        assert_eq!(queue.next_wake(Some(cooldown_end)), Some(cooldown_end));
+        queue.woke();
+        assert_eq!(queue.next_wake(None), Some(deadline));
+    }
+}
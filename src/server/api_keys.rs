@@ -0,0 +1,207 @@
+//! Client API keys: the master `basic.pollux_key` plus named `[[api_keys]]` entries.
+
+use crate::config::ApiKeyConfig;
+use crate::model_catalog;
+use crate::providers::{codex, geminicli};
+use crate::server::guards::auth::AuthError;
+use governor::clock::{Clock, DefaultClock};
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use tracing::warn;
+
+/// Name the master key is reported under in logs and metrics.
+pub const MASTER_KEY_NAME: &str = "master";
+
+/// Identity of the key that authenticated a request; inserted into request extensions by
+/// `RequireKeyAuth`.
+#[derive(Debug, Clone)]
+pub struct ApiKeyIdentity {
+    pub name: Arc<str>,
+    /// Allowed models as a `ModelCapabilities` mask; `None` allows every model.
+    pub model_mask: Option<u64>,
+    /// Only the master key may use `/admin/*`.
+    pub admin: bool,
+}
+
+impl ApiKeyIdentity {
+    pub fn allows(&self, model_mask: u64) -> bool {
+        self.model_mask
+            .is_none_or(|allowed| allowed & model_mask != 0)
+    }
+
+    /// Models this key may use, as a mask; every bit is set when unrestricted.
+    pub fn allowed_mask(&self) -> u64 {
+        self.model_mask.unwrap_or(u64::MAX)
+    }
+
+    /// Client-facing rejection message when this key may not use `model`.
+    pub(crate) fn denies(&self, model: &str, model_mask: u64) -> Option<String> {
+        (!self.allows(model_mask)).then(|| {
+            format!(
+                "API key '{}' is not allowed to use model: {model}",
+                self.name
+            )
+        })
+    }
+}
+
+struct ApiKeyEntry {
+    secret: String,
+    identity: ApiKeyIdentity,
+    enabled: bool,
+    limiter: Option<DefaultDirectRateLimiter>,
+}
+
+pub struct ApiKeyStore {
+    entries: Vec<ApiKeyEntry>,
+}
+
+impl ApiKeyStore {
+    /// Store holding only the master key.
+    pub fn new(master_key: &str) -> Self {
+        Self {
+            entries: vec![ApiKeyEntry {
+                secret: master_key.to_string(),
+                identity: ApiKeyIdentity {
+                    name: Arc::from(MASTER_KEY_NAME),
+                    model_mask: None,
+                    admin: true,
+                },
+                enabled: true,
+                limiter: None,
+            }],
+        }
+    }
+
+    /// Store holding the master key plus every configured client key.
+    pub fn with_keys(master_key: &str, keys: &[ApiKeyConfig]) -> Self {
+        let mut store = Self::new(master_key);
+        store.entries.extend(keys.iter().map(|cfg| {
+            ApiKeyEntry {
+                secret: cfg.key.clone(),
+                identity: ApiKeyIdentity {
+                    name: Arc::from(cfg.name.as_str()),
+                    model_mask: cfg
+                        .models
+                        .as_deref()
+                        .map(|models| resolve_models(&cfg.name, models)),
+                    admin: false,
+                },
+                enabled: cfg.enabled,
+                limiter: cfg
+                    .rpm
+                    .map(|rpm| RateLimiter::direct(Quota::per_minute(rpm))),
+            }
+        }));
+        store
+    }
+
+    /// Match `token` against every key, then apply the enabled flag and rate limit.
+    ///
+    /// Every entry is compared so the time taken does not reveal which key (if any) matched.
+    pub fn authenticate(&self, token: &str) -> Result<ApiKeyIdentity, AuthError> {
+        let mut matched = None;
+        for entry in &self.entries {
+            if bool::from(entry.secret.as_bytes().ct_eq(token.as_bytes())) {
+                matched = Some(entry);
+            }
+        }
+        let entry = matched.ok_or(AuthError::InvalidKey)?;
+
+        if !entry.enabled {
+            return Err(AuthError::KeyDisabled(entry.identity.name.clone()));
+        }
+        if let Some(limiter) = &entry.limiter
+            && let Err(not_until) = limiter.check()
+        {
+            let retry_after = not_until.wait_time_from(DefaultClock::default().now());
+            return Err(AuthError::RateLimited(
+                entry.identity.name.clone(),
+                retry_after,
+            ));
+        }
+        Ok(entry.identity.clone())
+    }
+}
+
+/// Resolve an allowlist into a model mask. Unknown names are logged and ignored.
+fn resolve_models(key_name: &str, models: &[String]) -> u64 {
+    models.iter().fold(0, |mask, model| {
+        let bits = match model.as_str() {
+            "geminicli" => geminicli::supported_model_mask(),
+            "codex" => codex::supported_model_mask(),
+            name => model_catalog::mask(name).unwrap_or_else(|| {
+                warn!(
+                    key = key_name,
+                    model = name,
+                    "Ignoring unknown model in API key allowlist"
+                );
+                0
+            }),
+        };
+        mask | bits
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU32;
+
+    fn key(name: &str, enabled: bool, rpm: Option<u32>) -> ApiKeyConfig {
+        ApiKeyConfig {
+            name: name.to_string(),
+            key: format!("sk-{name}"),
+            enabled,
+            models: None,
+            rpm: rpm.and_then(NonZeroU32::new),
+        }
+    }
+
+    #[test]
+    fn authenticate_resolves_identity_and_applies_limits() {
+        let store = ApiKeyStore::with_keys(
+            "master-secret",
+            &[key("team-a", true, Some(1)), key("team-b", false, None)],
+        );
+
+        let master = store.authenticate("master-secret").expect("master key");
+        assert_eq!(master.name.as_ref(), MASTER_KEY_NAME);
+        assert!(master.admin);
+
+        let team_a = store.authenticate("sk-team-a").expect("team-a key");
+        assert_eq!(team_a.name.as_ref(), "team-a");
+        assert!(!team_a.admin);
+        assert!(matches!(
+            store.authenticate("sk-team-a"),
+            Err(AuthError::RateLimited(..))
+        ));
+
+        assert!(matches!(
+            store.authenticate("sk-team-b"),
+            Err(AuthError::KeyDisabled(_))
+        ));
+        assert!(matches!(
+            store.authenticate("nope"),
+            Err(AuthError::InvalidKey)
+        ));
+    }
+
+    #[test]
+    fn allows_checks_the_model_mask() {
+        let identity = ApiKeyIdentity {
+            name: Arc::from("scoped"),
+            model_mask: Some(0b010),
+            admin: false,
+        };
+        assert!(identity.allows(0b010));
+        assert!(!identity.allows(0b100));
+
+        let unrestricted = ApiKeyIdentity {
+            model_mask: None,
+            ..identity
+        };
+        assert!(unrestricted.allows(0b100));
+    }
+}
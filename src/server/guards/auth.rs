@@ -1,18 +1,26 @@
+use crate::error::retry_after_secs;
+use crate::metrics::RequestLabels;
+use crate::server::api_keys::ApiKeyIdentity;
 use crate::server::router::PolluxState;
 use axum::{
     Json,
     extract::FromRequestParts,
-    http::{StatusCode, request::Parts},
+    http::{StatusCode, header::RETRY_AFTER, request::Parts},
     response::{IntoResponse, Response},
 };
 use axum_extra::headers::{Authorization, HeaderMapExt, authorization::Bearer};
 use serde_json::json;
-use subtle::ConstantTimeEq;
+use std::sync::Arc;
+use std::time::Duration;
 
 fn extract_header_token(headers: &axum::http::HeaderMap) -> Option<String> {
     if let Some(k) = headers.get("x-goog-api-key").and_then(|v| v.to_str().ok()) {
         return Some(k.to_string());
     }
+    // Anthropic SDKs send the key here.
+    if let Some(k) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) {
+        return Some(k.to_string());
+    }
     headers
         .typed_get::<Authorization<Bearer>>()
         .map(|auth| auth.token().to_string())
@@ -36,26 +44,60 @@ impl FromRequestParts<PolluxState> for RequireKeyAuth {
         parts: &mut Parts,
         state: &PolluxState,
     ) -> Result<Self, Self::Rejection> {
-        let token =
-            extract_header_token(&parts.headers).or_else(|| extract_query_token(parts.uri.query()));
+        let token = extract_header_token(&parts.headers)
+            .or_else(|| extract_query_token(parts.uri.query()))
+            .ok_or(AuthError::MissingKey)?;
 
-        match token {
-            Some(key) => {
-                let expected = state.pollux_key.as_ref();
-                if key.as_bytes().ct_eq(expected.as_bytes()).into() {
-                    Ok(RequireKeyAuth)
-                } else {
-                    Err(AuthError::InvalidKey)
-                }
-            }
-            None => Err(AuthError::MissingKey),
+        let result = state.api_keys.load().authenticate(&token);
+        let key = match &result {
+            Ok(identity) => Some(&identity.name),
+            Err(err) => err.key(),
+        };
+        if let (Some(key), Some(labels)) = (key, parts.extensions.get::<RequestLabels>()) {
+            labels.set_key(key);
+        }
+        parts.extensions.insert(result?);
+        Ok(RequireKeyAuth)
+    }
+}
+
+/// `RequireKeyAuth` restricted to the master key.
+#[derive(Debug, Clone, Copy)]
+pub struct RequireAdminKey;
+
+impl FromRequestParts<PolluxState> for RequireAdminKey {
+    type Rejection = AuthError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &PolluxState,
+    ) -> Result<Self, Self::Rejection> {
+        RequireKeyAuth::from_request_parts(parts, state).await?;
+        match parts.extensions.get::<ApiKeyIdentity>() {
+            Some(identity) if identity.admin => Ok(RequireAdminKey),
+            _ => Err(AuthError::AdminOnly),
         }
     }
 }
 
+#[derive(Debug)]
 pub enum AuthError {
     MissingKey,
     InvalidKey,
+    KeyDisabled(Arc<str>),
+    AdminOnly,
+    /// Per-key requests-per-minute limit hit; carries the time until the next slot.
+    RateLimited(Arc<str>, Duration),
+}
+
+impl AuthError {
+    /// Name of the key that was recognised but refused, for logs and metrics.
+    pub fn key(&self) -> Option<&Arc<str>> {
+        match self {
+            AuthError::KeyDisabled(key) | AuthError::RateLimited(key, _) => Some(key),
+            _ => None,
+        }
+    }
 }
 
 impl IntoResponse for AuthError {
@@ -63,11 +105,25 @@ impl IntoResponse for AuthError {
         let (status, reason) = match self {
             AuthError::MissingKey => (StatusCode::UNAUTHORIZED, "Missing API key"),
             AuthError::InvalidKey => (StatusCode::UNAUTHORIZED, "Invalid API key"),
+            AuthError::KeyDisabled(_) => (StatusCode::FORBIDDEN, "API key disabled"),
+            AuthError::AdminOnly => (StatusCode::FORBIDDEN, "Admin key required"),
+            AuthError::RateLimited(_, retry_after) => {
+                let body = Json(
+                    json!({ "error": "rate_limited", "reason": "API key rate limit exceeded" }),
+                );
+                return (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(RETRY_AFTER, retry_after_secs(retry_after))],
+                    body,
+                )
+                    .into_response();
+            }
+        };
+        let error = if status == StatusCode::FORBIDDEN {
+            "forbidden"
+        } else {
+            "unauthorized"
         };
-        (
-            status,
-            Json(json!({ "error": "unauthorized", "reason": reason })),
-        )
-            .into_response()
+        (status, Json(json!({ "error": error, "reason": reason }))).into_response()
     }
 }
@@ -1,3 +1,5 @@
+pub mod api_keys;
 pub mod guards;
+pub mod reload;
 pub mod router;
 pub mod routes;
@@ -0,0 +1,58 @@
+//! Apply a re-read `config.toml` to a running server.
+//!
+//! Listen address/port, `database_url`, `loglevel`, `insecure_cookie` and the token
+//! encryption keys (`POLLUX_TOKEN_ENCRYPTION_KEY`, `token_encryption_key_file`,
+//! `token_encryption_key` and `previous_token_encryption_keys`) are read once at startup, so
+//! a reload never rotates the key. Everything else is swapped in place. Requests already in
+//! flight keep the snapshot they started with.
+
+use crate::config::Config;
+use crate::error::PolluxError;
+use crate::model_catalog;
+use crate::providers::{codex, geminicli};
+use crate::server::api_keys::ApiKeyStore;
+use crate::server::router::{PolluxState, UpstreamClients};
+use serde::Serialize;
+use tracing::info;
+
+/// Models each provider serves after a reload.
+#[derive(Debug, Serialize)]
+pub struct ReloadSummary {
+    pub geminicli: Vec<String>,
+    pub codex: Vec<String>,
+    pub api_keys: usize,
+}
+
+impl PolluxState {
+    /// Validate `cfg` and apply it: model registry, provider model lists, scheduler queues,
+    /// refresh pipelines, upstream HTTP clients and API keys.
+    ///
+    /// Everything that can fail runs before anything is swapped in, so a rejected config
+    /// changes nothing.
+    pub async fn reload(&self, cfg: &Config) -> Result<ReloadSummary, PolluxError> {
+        cfg.validate().map_err(PolluxError::InvalidConfig)?;
+        let registry = model_catalog::reloaded_registry(cfg).map_err(PolluxError::InvalidConfig)?;
+        let clients = UpstreamClients::new(&cfg.geminicli(), &cfg.codex())?;
+
+        model_catalog::install(registry);
+        geminicli::reload_supported_models(cfg);
+        codex::reload_supported_models(cfg);
+        self.providers.reload(cfg);
+        self.clients.store(clients);
+        self.api_keys
+            .store(ApiKeyStore::with_keys(&cfg.basic.pollux_key, &cfg.api_keys));
+
+        let summary = ReloadSummary {
+            geminicli: geminicli::supported_model_names(),
+            codex: codex::supported_model_names(),
+            api_keys: cfg.api_keys.len(),
+        };
+        info!(
+            geminicli = ?summary.geminicli,
+            codex = ?summary.codex,
+            api_keys = summary.api_keys,
+            "Config reloaded"
+        );
+        Ok(summary)
+    }
+}
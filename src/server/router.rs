@@ -1,10 +1,16 @@
+use crate::config::{ApiKeyConfig, CodexResolvedConfig, GeminiCliResolvedConfig, Reloadable};
+use crate::error::PolluxError;
+use crate::metrics::{METRICS, RequestLabels};
 use crate::providers::Providers;
 use crate::providers::codex::CODEX_USER_AGENT;
+use crate::providers::codex::client::CodexClient;
 use crate::providers::geminicli::GEMINICLI_USER_AGENT;
-use crate::server::guards::auth::RequireKeyAuth;
+use crate::providers::geminicli::client::GeminiClient;
+use crate::server::api_keys::ApiKeyStore;
+use crate::server::guards::auth::{RequireAdminKey, RequireKeyAuth};
 use crate::server::routes::codex::oauth::{codex_oauth_callback, codex_oauth_entry};
 use crate::server::routes::geminicli::oauth::{google_oauth_callback, google_oauth_entry};
-use crate::server::routes::{codex, geminicli};
+use crate::server::routes::{admin, anthropic, codex, geminicli, metrics};
 
 use axum::{
     Router,
@@ -46,25 +52,24 @@ fn format_http_version(version: Version) -> &'static str {
     }
 }
 
-#[derive(Clone)]
-pub struct PolluxState {
-    pub providers: Providers,
-    pub client: reqwest::Client,
-    pub codex_client: reqwest::Client,
-    pub pollux_key: Arc<str>,
-    pub insecure_cookie: bool,
+/// HTTP clients for upstream calls, rebuilt when a config reload changes proxy or
+/// multiplexing settings.
+pub struct UpstreamClients {
+    pub geminicli: reqwest::Client,
+    pub codex: reqwest::Client,
 }
 
-impl PolluxState {
-    pub fn new(providers: Providers, pollux_key: Arc<str>, insecure_cookie: bool) -> Self {
-        let geminicli_cfg = providers.geminicli_cfg.clone();
-        let codex_cfg = providers.codex_cfg.clone();
-
+impl UpstreamClients {
+    /// Fails with `InvalidConfig` when reqwest rejects a proxy URL or cannot build a client.
+    pub fn new(
+        geminicli_cfg: &GeminiCliResolvedConfig,
+        codex_cfg: &CodexResolvedConfig,
+    ) -> Result<Self, PolluxError> {
         fn build_client(
             user_agent: &str,
             proxy: Option<url::Url>,
             enable_multiplexing: bool,
-        ) -> reqwest::Client {
+        ) -> Result<reqwest::Client, PolluxError> {
             let mut headers = HeaderMap::new();
 
             let mut builder = reqwest::Client::builder()
@@ -74,8 +79,9 @@ impl PolluxState {
                 .timeout(Duration::from_secs(10 * 60));
 
             if let Some(proxy_url) = proxy {
-                let proxy = reqwest::Proxy::all(proxy_url.as_str())
-                    .expect("invalid proxy url for reqwest client");
+                let proxy = reqwest::Proxy::all(proxy_url.as_str()).map_err(|e| {
+                    PolluxError::InvalidConfig(format!("invalid proxy url {proxy_url}: {e}"))
+                })?;
                 builder = builder.proxy(proxy);
             }
 
@@ -90,30 +96,72 @@ impl PolluxState {
                 builder = builder.http2_adaptive_window(true);
             }
 
-            builder
-                .default_headers(headers)
-                .build()
-                .expect("failed to build reqwest client")
+            builder.default_headers(headers).build().map_err(|e| {
+                PolluxError::InvalidConfig(format!("failed to build reqwest client: {e}"))
+            })
         }
-        let client = build_client(
-            GEMINICLI_USER_AGENT,
-            geminicli_cfg.proxy.clone(),
-            geminicli_cfg.enable_multiplexing,
-        );
-        let codex_client = build_client(
-            CODEX_USER_AGENT,
-            codex_cfg.proxy.clone(),
-            codex_cfg.enable_multiplexing,
-        );
+        Ok(Self {
+            geminicli: build_client(
+                GEMINICLI_USER_AGENT,
+                geminicli_cfg.proxy.clone(),
+                geminicli_cfg.enable_multiplexing,
+            )?,
+            codex: build_client(
+                CODEX_USER_AGENT,
+                codex_cfg.proxy.clone(),
+                codex_cfg.enable_multiplexing,
+            )?,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct PolluxState {
+    pub providers: Providers,
+    pub clients: Arc<Reloadable<UpstreamClients>>,
+    pub api_keys: Arc<Reloadable<ApiKeyStore>>,
+    pub insecure_cookie: bool,
+}
+
+impl PolluxState {
+    /// # Panics
+    ///
+    /// At startup, when the upstream HTTP clients cannot be built from the provider config.
+    pub fn new(providers: Providers, pollux_key: Arc<str>, insecure_cookie: bool) -> Self {
+        let clients =
+            UpstreamClients::new(&providers.geminicli_cfg.load(), &providers.codex_cfg.load())
+                .unwrap_or_else(|e| panic!("{e}"));
 
         Self {
             providers,
-            client,
-            codex_client,
-            pollux_key,
+            clients: Arc::new(Reloadable::new(clients)),
+            api_keys: Arc::new(Reloadable::new(ApiKeyStore::new(&pollux_key))),
             insecure_cookie,
         }
     }
+
+    /// Replace the key store with the master key plus the configured client keys.
+    pub fn with_api_keys(self, pollux_key: &str, keys: &[ApiKeyConfig]) -> Self {
+        self.api_keys
+            .store(ApiKeyStore::with_keys(pollux_key, keys));
+        self
+    }
+
+    /// Gemini CLI caller using the current retry settings and HTTP client.
+    pub fn geminicli_caller(&self) -> GeminiClient {
+        GeminiClient::new(
+            &self.providers.geminicli_cfg.load(),
+            self.clients.load().geminicli.clone(),
+        )
+    }
+
+    /// Codex caller using the current retry settings and HTTP client.
+    pub(crate) fn codex_caller(&self) -> CodexClient {
+        CodexClient::new(
+            &self.providers.codex_cfg.load(),
+            self.clients.load().codex.clone(),
+        )
+    }
 }
 
 impl FromRef<PolluxState> for Key {
@@ -127,7 +175,7 @@ async fn not_found_handler() -> StatusCode {
     StatusCode::NOT_FOUND
 }
 
-async fn access_log(req: Request, next: Next) -> Response {
+async fn access_log(mut req: Request, next: Next) -> Response {
     // Capture request metadata before moving `req` into the handler stack.
     let method = req.method().clone();
     let uri = req.uri().clone();
@@ -148,6 +196,9 @@ async fn access_log(req: Request, next: Next) -> Response {
         .unwrap_or("-")
         .to_string();
 
+    let labels = RequestLabels::default();
+    req.extensions_mut().insert(labels.clone());
+
     let start = Instant::now();
     let mut resp = next.run(req).await;
 
@@ -160,40 +211,45 @@ async fn access_log(req: Request, next: Next) -> Response {
     let latency_ms = start.elapsed().as_millis() as u64;
     let path = uri.path();
     let protocol = format_http_version(version);
+    record_request(&labels, path, status);
+    let key = labels.key().unwrap_or("-");
 
     // Note: for SSE/streaming responses, `latency_ms` is time-to-first-byte (handler return),
     // not the full stream duration.
     if status.is_server_error() {
         error!(
-            "| {:>3} | {} | {:^7} | {:<8} | {} | {}ms | {}",
+            "| {:>3} | {} | {:^7} | {:<8} | {} | {}ms | {} | {}",
             status.as_u16(),
             request_id,
             method.as_str(),
             protocol,
             path,
             latency_ms,
+            key,
             user_agent
         );
     } else if status.is_client_error() {
         warn!(
-            "| {:>3} | {} | {:^7} | {:<8} | {} | {}ms | {}",
+            "| {:>3} | {} | {:^7} | {:<8} | {} | {}ms | {} | {}",
             status.as_u16(),
             request_id,
             method.as_str(),
             protocol,
             path,
             latency_ms,
+            key,
             user_agent
         );
     } else {
         info!(
-            "| {:>3} | {} | {:^7} | {:<8} | {} | {}ms | {}",
+            "| {:>3} | {} | {:^7} | {:<8} | {} | {}ms | {} | {}",
             status.as_u16(),
             request_id,
             method.as_str(),
             protocol,
             path,
             latency_ms,
+            key,
             user_agent
         );
     }
@@ -201,6 +257,22 @@ async fn access_log(req: Request, next: Next) -> Response {
     resp
 }
 
+/// Count proxy traffic only; requests rejected before model validation fall back to `model="-"`.
+/// The per-key counter covers every request from a recognised key, admin and refused included.
+fn record_request(labels: &RequestLabels, path: &str, status: StatusCode) {
+    if let Some(key) = labels.key() {
+        METRICS.key_requests.inc([key, status.as_str()]);
+    }
+    let (provider, model) = match labels.get() {
+        Some(labels) => labels,
+        None if path.starts_with("/geminicli/") => ("geminicli", "-"),
+        None if path.starts_with("/codex/") => ("codex", "-"),
+        None if path.starts_with("/anthropic/") => ("anthropic", "-"),
+        None => return,
+    };
+    METRICS.requests.inc([provider, model, status.as_str()]);
+}
+
 pub fn pollux_router(state: PolluxState) -> Router {
     let gemini = geminicli::router()
         .layer(middleware::from_extractor_with_state::<RequireKeyAuth, _>(
@@ -211,6 +283,21 @@ pub fn pollux_router(state: PolluxState) -> Router {
         state.clone(),
     ));
 
+    let anthropic = anthropic::router()
+        .layer(middleware::from_extractor_with_state::<RequireKeyAuth, _>(
+            state.clone(),
+        ));
+
+    let admin = admin::router().layer(middleware::from_extractor_with_state::<RequireAdminKey, _>(
+        state.clone(),
+    ));
+
+    // The per-key series name every client key, so only the master key may scrape them.
+    let metrics = metrics::router()
+        .layer(middleware::from_extractor_with_state::<RequireAdminKey, _>(
+            state.clone(),
+        ));
+
     let oauth = Router::new()
         // Oauth Redirect path
         .route("/geminicli/auth", get(google_oauth_entry))
@@ -224,6 +311,9 @@ pub fn pollux_router(state: PolluxState) -> Router {
         .merge(oauth)
         .merge(gemini)
         .merge(codex)
+        .merge(anthropic)
+        .merge(admin)
+        .merge(metrics)
         .fallback(not_found_handler)
         .with_state(state)
         // Set DefaultBodyLimit to 30 MiB for all routes
@@ -0,0 +1,229 @@
+use crate::db::{DbCodexResource, DbGeminiCliResource, DbPage};
+use crate::error::PolluxError;
+use crate::providers::manifest::ProviderKind;
+use crate::server::router::PolluxState;
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_PAGE_LIMIT: u32 = 50;
+const MAX_PAGE_LIMIT: u32 = 500;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PageQuery {
+    pub offset: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+impl PageQuery {
+    fn resolve(&self) -> (i64, i64) {
+        let offset = self.offset.unwrap_or(0);
+        let limit = self
+            .limit
+            .unwrap_or(DEFAULT_PAGE_LIMIT)
+            .clamp(1, MAX_PAGE_LIMIT);
+        (i64::from(offset), i64::from(limit))
+    }
+}
+
+/// Token-free view of a stored credential.
+///
+/// Refresh/access tokens are never part of this shape; keep it that way.
+#[derive(Debug, Serialize)]
+pub struct CredentialView {
+    pub id: i64,
+    pub provider: ProviderKind,
+    pub email: Option<String>,
+    pub sub: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    /// ChatGPT plan (Codex) or user tier (Gemini CLI).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<String>,
+    pub expiry: DateTime<Utc>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<DbGeminiCliResource> for CredentialView {
+    fn from(row: DbGeminiCliResource) -> Self {
+        Self {
+            id: row.id,
+            provider: ProviderKind::GeminiCli,
+            email: row.email,
+            sub: row.sub,
+            project_id: Some(row.project_id),
+            account_id: None,
+            plan: row.tier,
+            expiry: row.expiry,
+            enabled: row.status,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+impl From<DbCodexResource> for CredentialView {
+    fn from(row: DbCodexResource) -> Self {
+        Self {
+            id: row.id,
+            provider: ProviderKind::Codex,
+            email: row.email,
+            sub: row.sub,
+            project_id: None,
+            account_id: Some(row.account_id),
+            plan: row.chatgpt_plan_type,
+            expiry: row.expiry,
+            enabled: row.status,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CredentialPage {
+    pub total: i64,
+    pub offset: i64,
+    pub limit: i64,
+    pub items: Vec<CredentialView>,
+}
+
+impl CredentialPage {
+    fn from_db<T: Into<CredentialView>>(page: DbPage<T>, offset: i64, limit: i64) -> Self {
+        Self {
+            total: page.total,
+            offset,
+            limit,
+            items: page.items.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CredentialOverview {
+    pub geminicli: CredentialPage,
+    pub codex: CredentialPage,
+}
+
+async fn list_page(
+    state: &PolluxState,
+    provider: ProviderKind,
+    offset: i64,
+    limit: i64,
+) -> Result<CredentialPage, PolluxError> {
+    let db = &state.providers.db;
+    let page = match provider {
+        ProviderKind::GeminiCli => {
+            CredentialPage::from_db(db.list_geminicli(offset, limit).await?, offset, limit)
+        }
+        ProviderKind::Codex => {
+            CredentialPage::from_db(db.list_codex(offset, limit).await?, offset, limit)
+        }
+    };
+    Ok(page)
+}
+
+async fn load_view(
+    state: &PolluxState,
+    provider: ProviderKind,
+    id: u64,
+) -> Result<CredentialView, PolluxError> {
+    let db_id =
+        i64::try_from(id).map_err(|_| PolluxError::NotFound(format!("Credential id={id}")))?;
+    let db = &state.providers.db;
+    let view = match provider {
+        ProviderKind::GeminiCli => db.get_geminicli_by_id(db_id).await?.into(),
+        ProviderKind::Codex => db.get_codex_by_id(db_id).await?.into(),
+    };
+    Ok(view)
+}
+
+/// GET /admin/credentials
+///
+/// One page of each provider, using the same `offset`/`limit` for both.
+pub async fn admin_credentials_overview(
+    State(state): State<PolluxState>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<CredentialOverview>, PolluxError> {
+    let (offset, limit) = query.resolve();
+    Ok(Json(CredentialOverview {
+        geminicli: list_page(&state, ProviderKind::GeminiCli, offset, limit).await?,
+        codex: list_page(&state, ProviderKind::Codex, offset, limit).await?,
+    }))
+}
+
+/// GET /admin/credentials/{provider}
+///
+/// Pages through every stored credential of a provider, disabled rows included.
+pub async fn admin_credentials_list(
+    State(state): State<PolluxState>,
+    Path(provider): Path<ProviderKind>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<CredentialPage>, PolluxError> {
+    let (offset, limit) = query.resolve();
+    Ok(Json(list_page(&state, provider, offset, limit).await?))
+}
+
+/// GET /admin/credentials/{provider}/{id}
+pub async fn admin_credential_get(
+    State(state): State<PolluxState>,
+    Path((provider, id)): Path<(ProviderKind, u64)>,
+) -> Result<Json<CredentialView>, PolluxError> {
+    Ok(Json(load_view(&state, provider, id).await?))
+}
+
+/// POST /admin/credentials/{provider}/{id}/enable
+pub async fn admin_credential_enable(
+    State(state): State<PolluxState>,
+    Path((provider, id)): Path<(ProviderKind, u64)>,
+) -> Result<Json<CredentialView>, PolluxError> {
+    set_enabled(&state, provider, id, true).await
+}
+
+/// POST /admin/credentials/{provider}/{id}/disable
+pub async fn admin_credential_disable(
+    State(state): State<PolluxState>,
+    Path((provider, id)): Path<(ProviderKind, u64)>,
+) -> Result<Json<CredentialView>, PolluxError> {
+    set_enabled(&state, provider, id, false).await
+}
+
+async fn set_enabled(
+    state: &PolluxState,
+    provider: ProviderKind,
+    id: u64,
+    enabled: bool,
+) -> Result<Json<CredentialView>, PolluxError> {
+    // 404 before touching the actor, so unknown ids never reach the scheduler.
+    load_view(state, provider, id).await?;
+
+    match provider {
+        ProviderKind::GeminiCli => state.providers.geminicli.set_status(id, enabled).await?,
+        ProviderKind::Codex => state.providers.codex.set_status(id, enabled).await?,
+    }
+
+    Ok(Json(load_view(state, provider, id).await?))
+}
+
+/// DELETE /admin/credentials/{provider}/{id}
+pub async fn admin_credential_delete(
+    State(state): State<PolluxState>,
+    Path((provider, id)): Path<(ProviderKind, u64)>,
+) -> Result<StatusCode, PolluxError> {
+    load_view(&state, provider, id).await?;
+
+    match provider {
+        ProviderKind::GeminiCli => state.providers.geminicli.delete_credential(id).await?,
+        ProviderKind::Codex => state.providers.codex.delete_credential(id).await?,
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,54 @@
+use crate::server::router::PolluxState;
+use axum::{
+    Router,
+    routing::{get, post},
+};
+
+pub mod credentials;
+pub mod reload;
+pub mod scheduler;
+pub mod transfer;
+pub mod usage;
+
+pub fn router() -> Router<PolluxState> {
+    Router::new()
+        .route(
+            "/admin/credentials",
+            get(credentials::admin_credentials_overview),
+        )
+        .route(
+            "/admin/credentials/export",
+            get(transfer::admin_credentials_export),
+        )
+        .route(
+            "/admin/credentials/import",
+            post(transfer::admin_credentials_import),
+        )
+        .route(
+            "/admin/credentials/{provider}",
+            get(credentials::admin_credentials_list),
+        )
+        .route(
+            "/admin/credentials/{provider}/export",
+            get(transfer::admin_credentials_export_provider),
+        )
+        .route(
+            "/admin/credentials/{provider}/{id}",
+            get(credentials::admin_credential_get).delete(credentials::admin_credential_delete),
+        )
+        .route(
+            "/admin/credentials/{provider}/{id}/export",
+            get(transfer::admin_credential_export),
+        )
+        .route(
+            "/admin/credentials/{provider}/{id}/enable",
+            post(credentials::admin_credential_enable),
+        )
+        .route(
+            "/admin/credentials/{provider}/{id}/disable",
+            post(credentials::admin_credential_disable),
+        )
+        .route("/admin/reload", post(reload::admin_reload))
+        .route("/admin/scheduler", get(scheduler::admin_scheduler_snapshot))
+        .route("/admin/usage", get(usage::admin_usage))
+}
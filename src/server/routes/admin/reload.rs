@@ -0,0 +1,15 @@
+use crate::config::Config;
+use crate::error::PolluxError;
+use crate::server::reload::ReloadSummary;
+use crate::server::router::PolluxState;
+use axum::{Json, extract::State};
+
+/// POST /admin/reload
+///
+/// Re-read `config.toml` and apply it without restarting.
+pub async fn admin_reload(
+    State(state): State<PolluxState>,
+) -> Result<Json<ReloadSummary>, PolluxError> {
+    let cfg = Config::try_from_toml().map_err(PolluxError::InvalidConfig)?;
+    Ok(Json(state.reload(&cfg).await?))
+}
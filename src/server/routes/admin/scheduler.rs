@@ -0,0 +1,24 @@
+use crate::error::PolluxError;
+use crate::providers::snapshot::SchedulerSnapshot;
+use crate::server::router::PolluxState;
+use axum::{Json, extract::State};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct SchedulerOverview {
+    pub geminicli: SchedulerSnapshot,
+    pub codex: SchedulerSnapshot,
+}
+
+/// GET /admin/scheduler
+///
+/// Live scheduler state of every provider actor: per-model queue depth, cooldown
+/// deadlines, in-flight refreshes and decoded capability masks.
+pub async fn admin_scheduler_snapshot(
+    State(state): State<PolluxState>,
+) -> Result<Json<SchedulerOverview>, PolluxError> {
+    Ok(Json(SchedulerOverview {
+        geminicli: state.providers.geminicli.snapshot().await?,
+        codex: state.providers.codex.snapshot().await?,
+    }))
+}
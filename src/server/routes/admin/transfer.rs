@@ -0,0 +1,91 @@
+use crate::error::PolluxError;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::transfer::{
+    CODEX_AUTH_FILE, CodexAuthFile, GEMINICLI_CREDS_FILE, GeminiCliCredsFile, ImportBatch,
+    ImportReport, export_archive,
+};
+use crate::server::router::PolluxState;
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+fn attachment(content_type: &'static str, file_name: &str, body: Vec<u8>) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// GET /admin/credentials/export
+///
+/// Every enabled credential of both providers as a tar archive of CLI credential files.
+pub async fn admin_credentials_export(
+    State(state): State<PolluxState>,
+) -> Result<Response, PolluxError> {
+    let (archive, _) = export_archive(&state.providers.db, None).await?;
+    Ok(attachment(
+        "application/x-tar",
+        "pollux-credentials.tar",
+        archive,
+    ))
+}
+
+/// GET /admin/credentials/{provider}/export
+pub async fn admin_credentials_export_provider(
+    State(state): State<PolluxState>,
+    Path(provider): Path<ProviderKind>,
+) -> Result<Response, PolluxError> {
+    let (archive, _) = export_archive(&state.providers.db, Some(provider)).await?;
+    let file_name = match provider {
+        ProviderKind::GeminiCli => "pollux-geminicli.tar",
+        ProviderKind::Codex => "pollux-codex.tar",
+    };
+    Ok(attachment("application/x-tar", file_name, archive))
+}
+
+/// GET /admin/credentials/{provider}/{id}/export
+///
+/// One credential as the file its CLI reads, disabled ones included.
+pub async fn admin_credential_export(
+    State(state): State<PolluxState>,
+    Path((provider, id)): Path<(ProviderKind, u64)>,
+) -> Result<Response, PolluxError> {
+    let db_id =
+        i64::try_from(id).map_err(|_| PolluxError::NotFound(format!("Credential id={id}")))?;
+    let db = &state.providers.db;
+    let (file_name, body) = match provider {
+        ProviderKind::GeminiCli => {
+            let file = GeminiCliCredsFile::from(db.get_geminicli_by_id(db_id).await?);
+            (GEMINICLI_CREDS_FILE, serde_json::to_vec_pretty(&file)?)
+        }
+        ProviderKind::Codex => {
+            let file = CodexAuthFile::from(db.get_codex_by_id(db_id).await?);
+            (CODEX_AUTH_FILE, serde_json::to_vec_pretty(&file)?)
+        }
+    };
+    Ok(attachment("application/json", file_name, body))
+}
+
+/// POST /admin/credentials/import
+///
+/// Accepts an export archive, a single credential file or profile, or a JSON array of them.
+/// Returns `202` once the credentials are queued; onboarding outcomes are logged.
+pub async fn admin_credentials_import(
+    State(state): State<PolluxState>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<ImportReport>), PolluxError> {
+    let batch = ImportBatch::parse(&body)?;
+    let report = batch.submit(&state.providers).await;
+    Ok((StatusCode::ACCEPTED, Json(report)))
+}
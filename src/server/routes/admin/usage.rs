@@ -0,0 +1,58 @@
+use crate::db::{DbUsageSummary, UsageQuery};
+use crate::error::PolluxError;
+use crate::server::router::PolluxState;
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde::Serialize;
+
+/// Token usage of one (day, key, provider, model) group.
+#[derive(Debug, Serialize)]
+pub struct UsageView {
+    /// UTC day, `YYYY-MM-DD`.
+    pub day: String,
+    pub key: String,
+    pub provider: String,
+    pub model: String,
+    pub requests: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cached_tokens: i64,
+    pub reasoning_tokens: i64,
+}
+
+impl From<DbUsageSummary> for UsageView {
+    fn from(row: DbUsageSummary) -> Self {
+        Self {
+            day: row.day,
+            key: row.key_name,
+            provider: row.provider,
+            model: row.model,
+            requests: row.requests,
+            input_tokens: row.input_tokens,
+            output_tokens: row.output_tokens,
+            cached_tokens: row.cached_tokens,
+            reasoning_tokens: row.reasoning_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageReport {
+    pub items: Vec<UsageView>,
+}
+
+/// GET /admin/usage
+///
+/// Recorded token usage summed per UTC day, API key and model. Optional filters:
+/// `from`/`to` (inclusive `YYYY-MM-DD`), `key` and `model`.
+pub async fn admin_usage(
+    State(state): State<PolluxState>,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageReport>, PolluxError> {
+    let rows = state.providers.db.query_usage(query).await?;
+    Ok(Json(UsageReport {
+        items: rows.into_iter().map(Into::into).collect(),
+    }))
+}
@@ -0,0 +1,119 @@
+use crate::error::AnthropicError;
+use crate::metrics::RequestLabels;
+use crate::model_catalog;
+use crate::providers::geminicli::{GeminiContext, GeminiRpc};
+use crate::providers::{codex, geminicli};
+use crate::server::api_keys::ApiKeyIdentity;
+use crate::server::routes::codex::{CodexContext, extract::codex_fallback};
+use crate::server::routes::geminicli::extract::gemini_fallback;
+use crate::server::routes::{SESSION_HEADER, session_key};
+use axum::{
+    Json,
+    extract::{FromRequest, Request},
+    http::StatusCode,
+};
+use pollux_schema::anthropic::AnthropicMessagesRequest;
+use serde_json::Value;
+use tracing::warn;
+
+/// Pool selected for a Messages request, with the context its client expects.
+pub(crate) enum AnthropicTarget {
+    GeminiCli(GeminiContext),
+    Codex(CodexContext),
+}
+
+/// Messages request with its target pool and, when `[models.fallbacks]` names models of the
+/// other pool, the target to try once the first pool has no capacity.
+pub(crate) struct AnthropicPreprocess(
+    pub(crate) AnthropicMessagesRequest,
+    pub(crate) AnthropicTarget,
+    pub(crate) Option<AnthropicTarget>,
+);
+
+impl<S> FromRequest<S> for AnthropicPreprocess
+where
+    S: Send + Sync,
+{
+    type Rejection = AnthropicError;
+
+    /// Validate the body and pick a pool by model mask. Gemini CLI is checked first, so a
+    /// model name configured for both providers is served by Gemini CLI.
+    async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
+        let labels = req.extensions().get::<RequestLabels>().cloned();
+        let api_key = req.extensions().get::<ApiKeyIdentity>().cloned();
+        let path = req.uri().path().to_string();
+        let session_header = req.headers().get(SESSION_HEADER).cloned();
+        let Json(mut body) = Json::<AnthropicMessagesRequest>::from_request(req, &()).await?;
+
+        if body.model.is_empty() {
+            return Err(AnthropicError::invalid_request("missing or empty model"));
+        }
+        if body.messages.is_empty() {
+            return Err(AnthropicError::invalid_request(
+                "messages must not be empty",
+            ));
+        }
+
+        model_catalog::resolve_alias(&mut body.model);
+        let model = body.model.clone();
+        let stream = body.stream;
+        let session = session_key(
+            session_header.as_ref(),
+            body.metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get("user_id"))
+                .and_then(Value::as_str),
+        );
+        let allowed = api_key.as_ref().map_or(u64::MAX, |key| key.allowed_mask());
+        let (provider, target, fallback) = if let Some(model_mask) = geminicli::model_mask(&model) {
+            let fallback = codex_fallback(&model, allowed, stream, session.clone())
+                .map(AnthropicTarget::Codex);
+            let ctx = GeminiContext {
+                fallbacks: geminicli::fallbacks(&model, allowed),
+                model,
+                stream,
+                path,
+                model_mask,
+                rpc: GeminiRpc::generate(stream),
+                session: session.clone(),
+            };
+            ("geminicli", AnthropicTarget::GeminiCli(ctx), fallback)
+        } else if let Some(model_mask) = codex::model_mask(&model) {
+            let fallback = gemini_fallback(&model, allowed, stream, path.clone(), session.clone())
+                .map(AnthropicTarget::GeminiCli);
+            let ctx = CodexContext {
+                fallbacks: codex::fallbacks(&model, allowed),
+                model,
+                stream,
+                model_mask,
+                session,
+            };
+            ("codex", AnthropicTarget::Codex(ctx), fallback)
+        } else {
+            warn!("Rejected messages request for unsupported model: {}", model);
+            return Err(AnthropicError::invalid_request(format!(
+                "unsupported model: {model}"
+            )));
+        };
+
+        let model_mask = match &target {
+            AnthropicTarget::GeminiCli(ctx) => ctx.model_mask,
+            AnthropicTarget::Codex(ctx) => ctx.model_mask,
+        };
+        if let Some(message) = api_key
+            .as_ref()
+            .and_then(|key| key.denies(&body.model, model_mask))
+        {
+            return Err(AnthropicError::RequestRejected {
+                status: StatusCode::FORBIDDEN,
+                message,
+                debug_message: None,
+            });
+        }
+
+        if let Some(labels) = labels {
+            labels.set(provider, &body.model);
+        }
+        Ok(AnthropicPreprocess(body, target, fallback))
+    }
+}
@@ -0,0 +1,133 @@
+use super::{
+    extract::{AnthropicPreprocess, AnthropicTarget},
+    respond::build_stream_response,
+};
+use crate::error::AnthropicError;
+use crate::providers::UpstreamResponse;
+use crate::server::api_keys::ApiKeyIdentity;
+use crate::server::router::PolluxState;
+use crate::server::routes::{codex, geminicli, with_served_model};
+use crate::usage::UsageTracker;
+use axum::{
+    Extension, Json,
+    extract::State,
+    response::{IntoResponse, Response},
+};
+use futures::TryStreamExt;
+use pollux_schema::CodexRequestBody;
+use pollux_schema::anthropic::{AnthropicChatStream, AnthropicMessageResponse};
+use pollux_schema::gemini::{GeminiChatStream, gemini_request_from_chat};
+use pollux_schema::openai::{OpenaiChatRequestBody, OpenaiRequestBody, ResponsesChatStream};
+use tracing::{debug, info};
+
+/// Anthropic Messages over whichever pool serves the requested model.
+///
+/// Both pools already speak Chat Completions, so the Messages body is converted to chat on
+/// the way in and the chat output is converted back, reusing each pool's translators.
+///
+/// When the first pool has no capacity and `[models.fallbacks]` names a model of the other
+/// pool, the request is retried there.
+pub(crate) async fn anthropic_messages_handler(
+    State(state): State<PolluxState>,
+    Extension(api_key): Extension<ApiKeyIdentity>,
+    AnthropicPreprocess(body, target, fallback): AnthropicPreprocess,
+) -> Result<Response, AnthropicError> {
+    let chat: OpenaiChatRequestBody = body.into();
+
+    let Some(fallback) = fallback else {
+        return serve(&state, &api_key, chat, target).await;
+    };
+    match serve(&state, &api_key, chat.clone(), target).await {
+        Err(e) if e.allows_fallback() => {
+            info!("Messages request falling back to the other pool after: {e}");
+            serve(&state, &api_key, chat, fallback).await
+        }
+        result => result,
+    }
+}
+
+async fn serve(
+    state: &PolluxState,
+    api_key: &ApiKeyIdentity,
+    chat: OpenaiChatRequestBody,
+    target: AnthropicTarget,
+) -> Result<Response, AnthropicError> {
+    match target {
+        AnthropicTarget::GeminiCli(ctx) => {
+            debug!(model = %ctx.model, stream = ctx.stream, "Incoming Messages request for Gemini CLI");
+            let caller = state.geminicli_caller();
+            let request = gemini_request_from_chat(&chat);
+            let UpstreamResponse {
+                credential_id,
+                model,
+                resp: upstream_resp,
+                lease,
+            } = caller
+                .call_gemini_cli(&state.providers.geminicli, &ctx, &request)
+                .await?;
+            let usage = UsageTracker::new(
+                &state.providers.db,
+                &api_key.name,
+                "geminicli",
+                &model,
+                credential_id,
+            )
+            .holding(lease);
+
+            let response = if ctx.stream {
+                let translator = GeminiChatStream::new(model.clone(), true);
+                let chunks =
+                    geminicli::respond::chat_chunk_stream(upstream_resp, translator, usage)
+                        .map_err(|e| AnthropicError::from(e).into_parts().1);
+                build_stream_response(chunks, AnthropicChatStream::new(model.clone()))
+                    .into_response()
+            } else {
+                let (status, Json(completion)) =
+                    geminicli::respond::build_chat_json_response(upstream_resp, &model, usage)
+                        .await?;
+                (status, Json(AnthropicMessageResponse::from(completion))).into_response()
+            };
+            Ok(with_served_model(response, &model))
+        }
+        AnthropicTarget::Codex(ctx) => {
+            debug!(model = %ctx.model, stream = ctx.stream, "Incoming Messages request for Codex");
+            let responses_body: OpenaiRequestBody = chat.into();
+            let codex_body: CodexRequestBody = responses_body.into();
+            let caller = state.codex_caller();
+            let UpstreamResponse {
+                credential_id,
+                model,
+                resp: upstream_resp,
+                lease,
+            } = caller
+                .call_codex(&state.providers.codex, &ctx, &codex_body)
+                .await?;
+            let usage = UsageTracker::new(
+                &state.providers.db,
+                &api_key.name,
+                "codex",
+                &model,
+                credential_id,
+            )
+            .holding(lease);
+
+            let translator = ResponsesChatStream::new(model.clone(), true);
+            let response = if ctx.stream {
+                let chunks = codex::respond::chat_chunk_stream(upstream_resp, translator, usage)
+                    .map_err(|e| AnthropicError::from(*e).into_parts().1);
+                build_stream_response(chunks, AnthropicChatStream::new(model.clone()))
+                    .into_response()
+            } else {
+                let (status, Json(completion)) =
+                    codex::respond::build_chat_json_response_from_stream(
+                        upstream_resp,
+                        translator,
+                        usage,
+                    )
+                    .await?;
+                (status, Json(AnthropicMessageResponse::from(completion))).into_response()
+            };
+            Ok(with_served_model(response, &model))
+        }
+    }
+}
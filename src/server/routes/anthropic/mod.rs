@@ -0,0 +1,14 @@
+use crate::server::router::PolluxState;
+use axum::{Router, routing::post};
+
+pub mod extract;
+pub mod handlers;
+pub mod respond;
+
+use handlers::anthropic_messages_handler;
+
+/// Anthropic Messages API. Not tied to one provider: each request is served by the pool
+/// whose model list contains the requested model.
+pub fn router() -> Router<PolluxState> {
+    Router::new().route("/anthropic/v1/messages", post(anthropic_messages_handler))
+}
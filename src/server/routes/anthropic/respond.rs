@@ -0,0 +1,47 @@
+use axum::response::{
+    IntoResponse,
+    sse::{Event, KeepAlive, Sse},
+};
+use futures::Stream;
+use pollux_schema::anthropic::{AnthropicChatStream, AnthropicErrorObject, AnthropicStreamEvent};
+use pollux_schema::openai::OpenaiChatCompletionChunk;
+use std::convert::Infallible;
+use tokio_stream::StreamExt;
+use tracing::warn;
+
+/// Build a Messages SSE stream from a pool's `chat.completion.chunk` stream.
+///
+/// An upstream failure after the response has started is reported the way Anthropic does:
+/// a final `error` event, with no `message_stop`.
+pub(super) fn build_stream_response<S>(
+    chunks: S,
+    translator: AnthropicChatStream,
+) -> impl IntoResponse
+where
+    S: Stream<Item = Result<OpenaiChatCompletionChunk, AnthropicErrorObject>> + Send + 'static,
+{
+    let batches =
+        futures::stream::unfold(Some((Box::pin(chunks), translator)), |state| async move {
+            let (mut chunks, mut translator) = state?;
+            let batch = match chunks.next().await {
+                Some(Ok(chunk)) => {
+                    let batch = translator.push(chunk);
+                    return Some((batch, Some((chunks, translator))));
+                }
+                Some(Err(error)) => vec![AnthropicStreamEvent::Error { error }],
+                None => translator.finish(),
+            };
+            Some((batch, None))
+        });
+    let events = futures::StreamExt::flat_map(batches, futures::stream::iter).filter_map(|event| {
+        match Event::default().event(event.event_name()).json_data(&event) {
+            Ok(ev) => Some(Ok::<_, Infallible>(ev)),
+            Err(e) => {
+                warn!("Failed to serialize Messages stream event: {}", e);
+                None
+            }
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
@@ -1,13 +1,22 @@
 use crate::error::CodexError;
-use crate::providers::codex::model_mask;
+use crate::metrics::RequestLabels;
+use crate::model_catalog;
+use crate::providers::codex::{fallbacks, model_mask, supported_model_mask};
+use crate::providers::geminicli::{self, GeminiContext};
+use crate::server::api_keys::ApiKeyIdentity;
+use crate::server::routes::geminicli::extract::gemini_fallback;
+use crate::server::routes::{SESSION_HEADER, session_key, split_chain};
 use axum::{
     Json,
     extract::{FromRequest, Request},
     http::StatusCode,
 };
 use pollux_schema::OpenaiResponsesErrorObject;
+use serde_json::Value;
+use std::sync::Arc;
 
 use pollux_schema::OpenaiRequestBody;
+use pollux_schema::openai::OpenaiChatRequestBody;
 
 use super::CodexContext;
 
@@ -33,12 +42,19 @@ where
     /// - Model not present in this deployment's configured model set => `UNSUPPORTED_MODEL`.
     ///
     /// Notes:
-    /// - We intentionally do not `trim()` or otherwise normalize `model`; matching is exact.
+    /// - We intentionally do not `trim()` or otherwise normalize `model`: it must name a
+    ///   configured model or match a `[models.aliases]` entry as sent.
+    /// - An alias is rewritten to its target, so upstream, logs and metrics only ever see
+    ///   configured model names.
+    /// - The session key never falls back to `previous_response_id`: it names the previous
+    ///   turn's response, so it changes every turn and could not pin a conversation.
     async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
-        let Json(body) = Json::<OpenaiRequestBody>::from_request(req, &()).await?;
+        let labels = req.extensions().get::<RequestLabels>().cloned();
+        let api_key = req.extensions().get::<ApiKeyIdentity>().cloned();
+        let session_header = req.headers().get(SESSION_HEADER).cloned();
+        let Json(mut body) = Json::<OpenaiRequestBody>::from_request(req, &()).await?;
 
-        let model = body.model.as_str();
-        if model.is_empty() {
+        if body.model.is_empty() {
             return Err(CodexError::RequestRejected {
                 status: StatusCode::BAD_REQUEST,
                 body: OpenaiResponsesErrorObject {
@@ -50,6 +66,8 @@ where
                 debug_message: None,
             });
         };
+        model_catalog::resolve_alias(&mut body.model);
+        let model = body.model.as_str();
 
         let stream = body.stream;
 
@@ -58,7 +76,8 @@ where
                 status: StatusCode::BAD_REQUEST,
                 body: OpenaiResponsesErrorObject {
                     code: Some("UNSUPPORTED_MODEL".to_string()),
-                    message: "unsupported model (exact match required)".to_string(),
+                    message: "unsupported model (exact match or configured alias required)"
+                        .to_string(),
                     r#type: "UNSUPPORTED_MODEL".to_string(),
                     param: None,
                 },
@@ -66,12 +85,161 @@ where
             });
         };
 
+        if let Some(message) = api_key
+            .as_ref()
+            .and_then(|key| key.denies(model, model_mask))
+        {
+            return Err(model_not_allowed(message));
+        }
+
+        if let Some(labels) = labels {
+            labels.set("codex", model);
+        }
+
         let ctx = CodexContext {
             model: body.model.clone(),
             stream,
             model_mask,
+            fallbacks: fallbacks(model, api_key.map_or(u64::MAX, |key| key.allowed_mask())),
+            session: session_key(
+                session_header.as_ref(),
+                body.extra.get("prompt_cache_key").and_then(Value::as_str),
+            ),
         };
 
         Ok(Self(body, ctx))
     }
 }
+
+/// Chat Completions request for the Codex pool, kept in chat form so the handler knows how
+/// to translate the response back. When `[models.fallbacks]` names models of the Gemini CLI
+/// pool, it also carries the context to try there once this pool has no capacity.
+pub(crate) struct CodexChatPreprocess(
+    pub(crate) OpenaiChatRequestBody,
+    pub(crate) CodexContext,
+    pub(crate) Option<GeminiContext>,
+);
+
+impl<S> FromRequest<S> for CodexChatPreprocess
+where
+    S: Send + Sync,
+{
+    type Rejection = CodexError;
+
+    /// Same validation as `CodexPreprocess`, plus a non-empty `messages` array.
+    async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
+        let labels = req.extensions().get::<RequestLabels>().cloned();
+        let api_key = req.extensions().get::<ApiKeyIdentity>().cloned();
+        let path = req.uri().path().to_string();
+        let session_header = req.headers().get(SESSION_HEADER).cloned();
+        let Json(mut body) = Json::<OpenaiChatRequestBody>::from_request(req, &()).await?;
+
+        if body.model.is_empty() {
+            return Err(CodexError::RequestRejected {
+                status: StatusCode::BAD_REQUEST,
+                body: OpenaiResponsesErrorObject {
+                    code: Some("INVALID_MODEL".to_string()),
+                    message: "missing or empty model".to_string(),
+                    r#type: "INVALID_MODEL".to_string(),
+                    param: None,
+                },
+                debug_message: None,
+            });
+        }
+
+        if body.messages.is_empty() {
+            return Err(CodexError::RequestRejected {
+                status: StatusCode::BAD_REQUEST,
+                body: OpenaiResponsesErrorObject {
+                    code: Some("INVALID_REQUEST".to_string()),
+                    message: "messages must not be empty".to_string(),
+                    r#type: "INVALID_REQUEST".to_string(),
+                    param: Some("messages".into()),
+                },
+                debug_message: None,
+            });
+        }
+
+        model_catalog::resolve_alias(&mut body.model);
+        let model = body.model.as_str();
+        let Some(model_mask) = model_mask(model) else {
+            return Err(CodexError::RequestRejected {
+                status: StatusCode::BAD_REQUEST,
+                body: OpenaiResponsesErrorObject {
+                    code: Some("UNSUPPORTED_MODEL".to_string()),
+                    message: "unsupported model (exact match or configured alias required)"
+                        .to_string(),
+                    r#type: "UNSUPPORTED_MODEL".to_string(),
+                    param: None,
+                },
+                debug_message: None,
+            });
+        };
+
+        if let Some(message) = api_key
+            .as_ref()
+            .and_then(|key| key.denies(model, model_mask))
+        {
+            return Err(model_not_allowed(message));
+        }
+
+        if let Some(labels) = labels {
+            labels.set("codex", model);
+        }
+
+        let allowed = api_key.map_or(u64::MAX, |key| key.allowed_mask());
+        let session = session_key(
+            session_header.as_ref(),
+            body.extra.get("prompt_cache_key").and_then(Value::as_str),
+        );
+        let fallback = gemini_fallback(model, allowed, body.stream, path, session.clone());
+        let ctx = CodexContext {
+            model: body.model.clone(),
+            stream: body.stream,
+            model_mask,
+            fallbacks: fallbacks(model, allowed),
+            session,
+        };
+
+        Ok(Self(body, ctx, fallback))
+    }
+}
+
+/// Context for serving `model`'s `[models.fallbacks]` entries from the Codex pool after
+/// another pool ran out of capacity; `None` when this pool serves none of them.
+pub(crate) fn codex_fallback(
+    model: &str,
+    allowed: u64,
+    stream: bool,
+    session: Option<Arc<str>>,
+) -> Option<CodexContext> {
+    split_chain(fallbacks(model, allowed)).map(|(first, rest)| CodexContext {
+        model: first.name,
+        stream,
+        model_mask: first.mask,
+        fallbacks: rest,
+        session,
+    })
+}
+
+/// `[models.fallbacks]` entry of `model` that only the Gemini CLI pool serves, which
+/// `/codex/v1/responses` cannot reach.
+pub(crate) fn unreachable_fallback(model: &str, allowed: u64) -> Option<String> {
+    geminicli::fallbacks(model, allowed & !supported_model_mask())
+        .into_iter()
+        .next()
+        .map(|route| route.name)
+}
+
+fn model_not_allowed(message: String) -> CodexError {
+    CodexError::RequestRejected {
+        status: StatusCode::FORBIDDEN,
+        body: OpenaiResponsesErrorObject {
+            code: Some("MODEL_NOT_ALLOWED".to_string()),
+            message,
+            r#type: "MODEL_NOT_ALLOWED".to_string(),
+            param: None,
+        },
+        debug_message: None,
+    }
+}
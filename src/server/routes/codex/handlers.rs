@@ -1,18 +1,29 @@
-use super::{extract::CodexPreprocess, respond};
+use super::{
+    CodexContext,
+    extract::{CodexChatPreprocess, CodexPreprocess, unreachable_fallback},
+    respond,
+};
 use crate::error::CodexError;
-use crate::providers::codex::client::CodexClient;
+use crate::providers::UpstreamResponse;
+use crate::server::api_keys::ApiKeyIdentity;
 use crate::server::router::PolluxState;
+use crate::server::routes::{geminicli, with_served_model};
+use crate::usage::UsageTracker;
 use axum::{
-    Json,
+    Extension, Json,
     extract::State,
+    http::StatusCode,
     response::{IntoResponse, Response},
 };
-use pollux_schema::CodexRequestBody;
-use pollux_schema::openai::OpenaiModelList;
-use tracing::debug;
+use pollux_schema::openai::{
+    OpenaiChatRequestBody, OpenaiModelList, OpenaiRequestBody, ResponsesChatStream,
+};
+use pollux_schema::{CodexRequestBody, OpenaiResponsesErrorObject};
+use tracing::{debug, info};
 
 pub(super) async fn codex_response_handler(
     State(state): State<PolluxState>,
+    Extension(api_key): Extension<ApiKeyIdentity>,
     CodexPreprocess(body, ctx): CodexPreprocess,
 ) -> Result<Response, CodexError> {
     let codex_body: CodexRequestBody = body.into();
@@ -25,29 +36,131 @@ pub(super) async fn codex_response_handler(
         "Incoming Codex request"
     );
 
-    let caller = CodexClient::new(
-        state.providers.codex_cfg.as_ref(),
-        state.codex_client.clone(),
+    let caller = state.codex_caller();
+
+    let UpstreamResponse {
+        credential_id,
+        model,
+        resp: upstream_resp,
+        lease,
+    } = caller
+        .call_codex(&state.providers.codex, &ctx, &codex_body)
+        .await
+        .map_err(
+            |e| match unreachable_fallback(&ctx.model, api_key.allowed_mask()) {
+                Some(target) if e.allows_fallback() => fallback_unreachable(e, &ctx.model, &target),
+                _ => e,
+            },
+        )?;
+    let usage = UsageTracker::new(
+        &state.providers.db,
+        &api_key.name,
+        "codex",
+        &model,
+        credential_id,
+    )
+    .holding(lease);
+
+    let response = if ctx.stream {
+        respond::build_stream_response(upstream_resp, usage).into_response()
+    } else {
+        let (status, body) = respond::build_json_response_from_stream(upstream_resp, usage).await?;
+        (status, body).into_response()
+    };
+    Ok(with_served_model(response, &model))
+}
+
+/// Chat Completions over the Codex pool: chat -> Responses -> Codex, and the Responses
+/// event stream translated back into chat chunks.
+///
+/// When this pool has no capacity and `[models.fallbacks]` names a model of the Gemini CLI
+/// pool, the request is retried there.
+pub(super) async fn codex_chat_completions_handler(
+    State(state): State<PolluxState>,
+    Extension(api_key): Extension<ApiKeyIdentity>,
+    CodexChatPreprocess(body, ctx, fallback): CodexChatPreprocess,
+) -> Result<Response, CodexError> {
+    let Some(fallback) = fallback else {
+        return serve_chat(&state, &api_key, body, ctx).await;
+    };
+    match serve_chat(&state, &api_key, body.clone(), ctx).await {
+        Err(e) if e.allows_fallback() => {
+            info!("Chat request falling back to the Gemini CLI pool after: {e}");
+            Ok(
+                geminicli::handlers::serve_chat(&state, &api_key, &body, fallback)
+                    .await
+                    .into_response(),
+            )
+        }
+        result => result,
+    }
+}
+
+pub(crate) async fn serve_chat(
+    state: &PolluxState,
+    api_key: &ApiKeyIdentity,
+    body: OpenaiChatRequestBody,
+    ctx: CodexContext,
+) -> Result<Response, CodexError> {
+    let include_usage = body.include_usage();
+    let responses_body: OpenaiRequestBody = body.into();
+    let codex_body: CodexRequestBody = responses_body.into();
+
+    debug!(
+        model = %ctx.model,
+        client_stream = ctx.stream,
+        model_mask = format_args!("0x{:016x}", ctx.model_mask),
+        "Incoming Codex chat completions request"
     );
 
-    let upstream_resp = caller
-        .call_codex(
-            &state.providers.codex,
-            ctx.model.as_str(),
-            ctx.model_mask,
-            ctx.stream,
-            &codex_body,
-        )
+    let caller = state.codex_caller();
+
+    let UpstreamResponse {
+        credential_id,
+        model,
+        resp: upstream_resp,
+        lease,
+    } = caller
+        .call_codex(&state.providers.codex, &ctx, &codex_body)
         .await?;
+    let usage = UsageTracker::new(
+        &state.providers.db,
+        &api_key.name,
+        "codex",
+        &model,
+        credential_id,
+    )
+    .holding(lease);
 
-    if ctx.stream {
-        Ok(respond::build_stream_response(upstream_resp).into_response())
+    let translator = ResponsesChatStream::new(model.clone(), include_usage);
+    let response = if ctx.stream {
+        respond::build_chat_stream_response(upstream_resp, translator, usage).into_response()
     } else {
-        let (status, body) = respond::build_json_response_from_stream(upstream_resp).await?;
-        Ok((status, body).into_response())
+        let (status, body) =
+            respond::build_chat_json_response_from_stream(upstream_resp, translator, usage).await?;
+        (status, body).into_response()
+    };
+    Ok(with_served_model(response, &model))
+}
+
+/// `/codex/v1/responses` speaks only Codex's protocol, so a `[models.fallbacks]` entry served by
+/// Gemini CLI alone cannot be tried here. Say so instead of answering as if the chain had ended.
+fn fallback_unreachable(err: CodexError, model: &str, target: &str) -> CodexError {
+    CodexError::RequestRejected {
+        status: StatusCode::SERVICE_UNAVAILABLE,
+        body: OpenaiResponsesErrorObject {
+            code: Some("FALLBACK_UNREACHABLE".to_string()),
+            message: format!(
+                "no credential can serve {model}; its fallback {target} is served by Gemini CLI \
+                 only, use /codex/v1/chat/completions or /anthropic/v1/messages to reach it"
+            ),
+            r#type: "FALLBACK_UNREACHABLE".to_string(),
+            param: None,
+        },
+        debug_message: Some(err.to_string()),
     }
 }
 
 pub(super) async fn codex_models_handler() -> Result<Json<OpenaiModelList>, CodexError> {
-    Ok(Json(super::CODEX_MODEL_LIST.clone()))
+    Ok(Json(super::codex_model_list()))
 }
@@ -10,19 +10,18 @@ pub mod oauth;
 pub mod resource;
 pub mod respond;
 
-use crate::providers::codex::SUPPORTED_MODEL_NAMES;
-use pollux_schema::openai::OpenaiModelList;
-use std::sync::LazyLock;
+pub use crate::providers::codex::CodexContext;
 
-pub static CODEX_MODEL_LIST: LazyLock<OpenaiModelList> = LazyLock::new(|| {
-    OpenaiModelList::from_model_names(SUPPORTED_MODEL_NAMES.iter().cloned(), "codex".to_string())
-});
+use crate::model_catalog;
+use crate::providers::codex::supported_model_names;
+use pollux_schema::openai::OpenaiModelList;
 
-#[derive(Debug, Clone)]
-pub struct CodexContext {
-    pub model: String,
-    pub stream: bool,
-    pub model_mask: u64,
+/// Built per request so a config reload shows up immediately.
+pub fn codex_model_list() -> OpenaiModelList {
+    OpenaiModelList::from_model_names(
+        model_catalog::names_with_aliases(&supported_model_names()),
+        "codex".to_string(),
+    )
 }
 
 pub fn router() -> Router<PolluxState> {
@@ -31,6 +30,10 @@ pub fn router() -> Router<PolluxState> {
             "/codex/v1/responses",
             post(handlers::codex_response_handler),
         )
+        .route(
+            "/codex/v1/chat/completions",
+            post(handlers::codex_chat_completions_handler),
+        )
         .route("/codex/v1/models", get(handlers::codex_models_handler))
         .route("/codex/resource:add", post(resource::codex_resource_add))
 }
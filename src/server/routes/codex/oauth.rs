@@ -100,7 +100,7 @@ async fn process_oauth_exchange(
     let token_response: OauthTokenResponse = CodexOauthEndpoints::exchange_authorization_code(
         AuthorizationCode::new(code.to_string()),
         PkceCodeVerifier::new(pkce_verifier),
-        state.codex_client.clone(),
+        state.clients.load().codex.clone(),
     )
     .await
     .map_err(|e| OauthError::Flow {
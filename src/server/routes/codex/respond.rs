@@ -1,4 +1,5 @@
 use crate::error::CodexError;
+use crate::usage::{TokenUsage, UsageTracker};
 use axum::{
     Json,
     body::Bytes,
@@ -9,7 +10,8 @@ use axum::{
     },
 };
 use eventsource_stream::Eventsource;
-use futures::{Stream, TryStreamExt};
+use futures::Stream;
+use pollux_schema::openai::{OpenaiChatCompletion, OpenaiChatCompletionChunk, ResponsesChatStream};
 use serde_json::Value;
 use std::time::Duration;
 use tokio_stream::StreamExt;
@@ -18,21 +20,24 @@ use tracing::error;
 const SSE_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
 
 /// Build SSE stream response.
-pub(super) fn build_stream_response(upstream_resp: reqwest::Response) -> impl IntoResponse {
+pub(super) fn build_stream_response(
+    upstream_resp: reqwest::Response,
+    usage: UsageTracker,
+) -> impl IntoResponse {
     let raw_stream = upstream_resp.bytes_stream().eventsource();
-    let timed_stream =
-        transform_stream(raw_stream)
-            .timeout(SSE_IDLE_TIMEOUT)
-            .map(|item| match item {
-                Ok(Ok(event)) => Ok(event),
-                Ok(Err(e)) => Err(CodexError::StreamProtocolError(e.to_string())),
-                Err(_) => {
-                    error!("Upstream Codex SSE stream timed out (idle > 60s)");
-                    Err(CodexError::StreamProtocolError(
-                        "Stream idle timeout".to_string(),
-                    ))
-                }
-            });
+    let timed_stream = transform_stream(raw_stream, usage)
+        .timeout(SSE_IDLE_TIMEOUT)
+        // Boxed: `CodexError` is large, and the SSE body only needs a terminal error.
+        .map(|item| match item {
+            Ok(Ok(event)) => Ok(event),
+            Ok(Err(e)) => Err(Box::new(CodexError::StreamProtocolError(e.to_string()))),
+            Err(_) => {
+                error!("Upstream Codex SSE stream timed out (idle > 60s)");
+                Err(Box::new(CodexError::StreamProtocolError(
+                    "Stream idle timeout".to_string(),
+                )))
+            }
+        });
 
     Sse::new(timed_stream).keep_alive(KeepAlive::default())
 }
@@ -44,10 +49,14 @@ pub(super) fn build_stream_response(upstream_resp: reqwest::Response) -> impl In
 /// final `response.completed` event and return the embedded `response` as JSON.
 pub(super) async fn build_json_response_from_stream(
     upstream_resp: reqwest::Response,
+    mut usage: UsageTracker,
 ) -> Result<(StatusCode, Json<Value>), CodexError> {
     let status = upstream_resp.status();
 
     let body = parse_upstream_sse_to_json(upstream_resp.bytes_stream()).await?;
+    if let Some(reported) = body.get("usage").filter(|u| u.is_object()) {
+        usage.observe(TokenUsage::from_responses(reported));
+    }
     Ok((status, Json(body)))
 }
 
@@ -110,16 +119,163 @@ where
     Ok(Value::Null)
 }
 
+/// Build an OpenAI `chat.completion.chunk` SSE stream from Codex Responses events,
+/// terminated by `data: [DONE]`.
+pub(super) fn build_chat_stream_response(
+    upstream_resp: reqwest::Response,
+    translator: ResponsesChatStream,
+    usage: UsageTracker,
+) -> impl IntoResponse {
+    let events = chat_chunk_stream(upstream_resp, translator, usage)
+        .filter_map(|item| match item {
+            Ok(chunk) => Event::default().json_data(chunk).ok().map(Ok),
+            Err(e) => Some(Err(e)),
+        })
+        .chain(tokio_stream::once(Ok(Event::default().data("[DONE]"))));
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Translate Codex Responses events into `chat.completion.chunk`s. Shared by every route
+/// that speaks chat to the Codex pool.
+pub(crate) fn chat_chunk_stream(
+    upstream_resp: reqwest::Response,
+    translator: ResponsesChatStream,
+    usage: UsageTracker,
+) -> impl Stream<Item = Result<OpenaiChatCompletionChunk, Box<CodexError>>> {
+    let events = upstream_resp
+        .bytes_stream()
+        .eventsource()
+        .timeout(SSE_IDLE_TIMEOUT);
+
+    // Each step yields a batch: one Responses event can map to a finish chunk plus a usage
+    // chunk. The usage tracker records when the state is dropped.
+    let state = (Box::pin(events), translator, usage);
+    let batches = futures::stream::unfold(Some(state), |state| async move {
+        let (mut events, mut translator, mut usage) = state?;
+        let batch = match events.next().await {
+            Some(Ok(Ok(event))) => {
+                match translate_chat_event(&mut translator, &mut usage, &event.data) {
+                    Ok(batch) => {
+                        let batch = batch.into_iter().map(Ok).collect();
+                        return Some((batch, Some((events, translator, usage))));
+                    }
+                    Err(message) => vec![Err(Box::new(CodexError::StreamProtocolError(message)))],
+                }
+            }
+            Some(Ok(Err(e))) => vec![Err(Box::new(CodexError::StreamProtocolError(
+                e.to_string(),
+            )))],
+            Some(Err(_)) => {
+                error!("Upstream Codex SSE stream timed out (idle > 60s)");
+                vec![Err(Box::new(CodexError::StreamProtocolError(
+                    "Stream idle timeout".to_string(),
+                )))]
+            }
+            None => Vec::new(),
+        };
+        Some((batch, None))
+    });
+    futures::StreamExt::flat_map(batches, futures::stream::iter)
+}
+
+/// Buffer Codex Responses events into a single `chat.completion`, the chat counterpart of
+/// `build_json_response_from_stream`.
+pub(crate) async fn build_chat_json_response_from_stream(
+    upstream_resp: reqwest::Response,
+    translator: ResponsesChatStream,
+    mut usage: UsageTracker,
+) -> Result<(StatusCode, Json<OpenaiChatCompletion>), CodexError> {
+    let status = upstream_resp.status();
+    let completion =
+        parse_upstream_sse_to_chat(upstream_resp.bytes_stream(), translator, &mut usage).await?;
+    Ok((status, Json(completion)))
+}
+
+async fn parse_upstream_sse_to_chat<S, E>(
+    stream: S,
+    mut translator: ResponsesChatStream,
+    usage: &mut UsageTracker,
+) -> Result<OpenaiChatCompletion, CodexError>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let mut chunks = Vec::new();
+
+    let timed_stream = stream.eventsource().timeout(SSE_IDLE_TIMEOUT);
+    tokio::pin!(timed_stream);
+
+    while let Some(item) = timed_stream.next().await {
+        let upstream_event = match item {
+            Ok(Ok(event)) => event,
+            Ok(Err(e)) => return Err(CodexError::StreamProtocolError(e.to_string())),
+            Err(_) => {
+                error!("Upstream Codex stream timed out (idle > 60s)");
+                return Err(CodexError::StreamProtocolError(
+                    "Stream idle timeout".to_string(),
+                ));
+            }
+        };
+        if upstream_event.data == "[DONE]" {
+            break;
+        }
+        let Ok(value) = serde_json::from_str::<Value>(&upstream_event.data) else {
+            continue;
+        };
+        usage.observe_responses_event(&value);
+        chunks.extend(
+            translator
+                .push(&value)
+                .map_err(CodexError::StreamProtocolError)?,
+        );
+        if translator.is_finished() {
+            break;
+        }
+    }
+
+    OpenaiChatCompletion::from_chunks(chunks).ok_or_else(|| {
+        CodexError::StreamProtocolError("upstream stream ended without output".to_string())
+    })
+}
+
+/// `Err` carries the message of an upstream `response.failed` event.
+fn translate_chat_event(
+    translator: &mut ResponsesChatStream,
+    usage: &mut UsageTracker,
+    data: &str,
+) -> Result<Vec<OpenaiChatCompletionChunk>, String> {
+    if data.is_empty() || data == "[DONE]" {
+        return Ok(Vec::new());
+    }
+    let Ok(value) = serde_json::from_str::<Value>(data) else {
+        return Ok(Vec::new());
+    };
+    usage.observe_responses_event(&value);
+    translator.push(&value)
+}
+
 /// Convert upstream SSE events into SSE `Event`s for clients.
-pub fn transform_stream<I, E>(s: I) -> impl Stream<Item = Result<Event, E>>
+///
+/// Events are forwarded verbatim; only terminal events are parsed, for their usage.
+pub fn transform_stream<I, E>(s: I, mut usage: UsageTracker) -> impl Stream<Item = Result<Event, E>>
 where
     I: Stream<Item = Result<eventsource_stream::Event, E>>,
 {
-    s.try_filter_map(move |upstream_event| async move {
+    s.filter_map(move |item| {
+        let upstream_event = match item {
+            Ok(event) => event,
+            Err(e) => return Some(Err(e)),
+        };
         if upstream_event.data.is_empty() {
-            return Ok(None);
+            return None;
+        }
+        if upstream_event.data.contains("\"usage\"")
+            && let Ok(value) = serde_json::from_str::<Value>(&upstream_event.data)
+        {
+            usage.observe_responses_event(&value);
         }
-        Ok(Some(Event::default().data(upstream_event.data)))
+        Some(Ok(Event::default().data(upstream_event.data)))
     })
 }
 
@@ -144,6 +300,35 @@ mod tests {
         assert_eq!(body, json!({"id":"r1","object":"response"}));
     }
 
+    #[tokio::test]
+    async fn parse_upstream_sse_to_chat_aggregates_deltas() {
+        let sse_body = concat!(
+            "data: {\"type\":\"response.created\",\"response\":{\"id\":\"r3\"}}\n\n",
+            "data: {\"type\":\"response.output_text.delta\",\"delta\":\"hel\"}\n\n",
+            "data: {\"type\":\"response.output_text.delta\",\"delta\":\"lo\"}\n\n",
+            "data: {\"type\":\"response.completed\",\"response\":{\"usage\":{\"input_tokens\":1,\"output_tokens\":2}}}\n\n",
+        );
+
+        let stream = stream::iter([Ok::<_, std::convert::Infallible>(Bytes::from_static(
+            sse_body.as_bytes(),
+        ))]);
+        let mut usage = UsageTracker::disabled();
+        let completion = parse_upstream_sse_to_chat(
+            stream,
+            ResponsesChatStream::new("gpt-5-codex", true),
+            &mut usage,
+        )
+        .await
+        .unwrap();
+        assert_eq!(completion.id, "chatcmpl-r3");
+        assert_eq!(
+            completion.choices[0].message.content.as_deref(),
+            Some("hello")
+        );
+        assert_eq!(completion.choices[0].finish_reason.as_deref(), Some("stop"));
+        assert_eq!(completion.usage.unwrap().total_tokens, 3);
+    }
+
     #[tokio::test]
     async fn parse_upstream_sse_to_json_handles_chunked_sse_payload() {
         let sse_body = concat!(
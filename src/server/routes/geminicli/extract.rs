@@ -1,11 +1,21 @@
-use crate::providers::geminicli::{GeminiContext, model_mask};
+use crate::metrics::RequestLabels;
+use crate::model_catalog;
+use crate::providers::codex::{self, CodexContext};
+use crate::providers::geminicli::{
+    GeminiContext, GeminiRpc, fallbacks, model_mask, supported_model_mask,
+};
+use crate::server::api_keys::ApiKeyIdentity;
+use crate::server::routes::codex::extract::codex_fallback;
+use crate::server::routes::{SESSION_HEADER, session_key, split_chain};
 use crate::{error::GeminiCliError, error::GeminiErrorObject};
 use axum::{
     Json, RequestExt,
     extract::{FromRequest, Path, Request},
     http::StatusCode,
 };
-use pollux_schema::gemini::GeminiRequestBody;
+use pollux_schema::{gemini::GeminiRequestBody, openai::OpenaiChatRequestBody};
+use serde_json::Value;
+use std::sync::Arc;
 use tracing::warn;
 
 pub struct GeminiPreprocess(pub GeminiRequestBody, pub GeminiContext);
@@ -30,23 +40,47 @@ where
                 debug_message: Some(rejection.to_string()),
             })?;
 
-        // Determine model and optional rpc from the last path segment
-        let last_seg = path.split('/').next_back().map(|s| s.to_string());
-        let Some(last_seg) = last_seg else {
+        // The last path segment is `{model}:{rpc}`.
+        let last_seg = path.split('/').next_back().unwrap_or_default();
+        let Some((model, verb)) = last_seg.split_once(':') else {
             return Err(GeminiCliError::RequestRejected {
                 status: StatusCode::BAD_REQUEST,
                 body: GeminiErrorObject::for_status(
                     StatusCode::BAD_REQUEST,
                     "INVALID_ARGUMENT",
-                    "model not found in path",
+                    "missing RPC method in path, expected {model}:{method}",
                 ),
                 debug_message: None,
             });
         };
-        let model = if let Some((m, _r)) = last_seg.split_once(':') {
-            m.to_string()
-        } else {
-            last_seg
+        let mut model = model.to_string();
+        model_catalog::resolve_alias(&mut model);
+
+        let Some(rpc) = GeminiRpc::from_verb(verb) else {
+            // Gemini CLI itself has no embeddings on Code Assist; say so rather than treating
+            // the verb as unknown.
+            if matches!(verb, "embedContent" | "batchEmbedContents") {
+                warn!("Rejected embeddings request: {}", verb);
+                return Err(GeminiCliError::RequestRejected {
+                    status: StatusCode::NOT_IMPLEMENTED,
+                    body: GeminiErrorObject::for_status(
+                        StatusCode::NOT_IMPLEMENTED,
+                        "UNIMPLEMENTED",
+                        format!("{verb} is not served by the Gemini CLI backend"),
+                    ),
+                    debug_message: None,
+                });
+            }
+            warn!("Rejected request for unsupported RPC method: {}", verb);
+            return Err(GeminiCliError::RequestRejected {
+                status: StatusCode::BAD_REQUEST,
+                body: GeminiErrorObject::for_status(
+                    StatusCode::BAD_REQUEST,
+                    "INVALID_ARGUMENT",
+                    format!("unsupported RPC method: {verb}"),
+                ),
+                debug_message: None,
+            });
         };
 
         let Some(model_mask) = model_mask(model.as_str()) else {
@@ -63,16 +97,156 @@ where
             });
         };
 
-        let stream = path.contains("streamGenerateContent");
+        let api_key = req.extensions().get::<ApiKeyIdentity>();
+        if let Some(message) = api_key.and_then(|key| key.denies(&model, model_mask)) {
+            return Err(model_not_allowed(message));
+        }
+        let fallbacks = fallbacks(&model, api_key.map_or(u64::MAX, |key| key.allowed_mask()));
 
+        if let Some(labels) = req.extensions().get::<RequestLabels>() {
+            labels.set("geminicli", &model);
+        }
+
+        let session = session_key(req.headers().get(SESSION_HEADER), None);
         let Json(body) = Json::<GeminiRequestBody>::from_request(req, &()).await?;
 
         let ctx = GeminiContext {
             model,
-            stream,
+            stream: rpc == GeminiRpc::StreamGenerateContent,
             path,
             model_mask,
+            rpc,
+            fallbacks,
+            session,
         };
         Ok(GeminiPreprocess(body, ctx))
     }
 }
+
+/// OpenAI chat request routed to the Gemini CLI pool, with the Codex context to try once this
+/// pool has no capacity when `[models.fallbacks]` names models of the Codex pool.
+pub struct GeminiChatPreprocess(
+    pub OpenaiChatRequestBody,
+    pub GeminiContext,
+    pub Option<CodexContext>,
+);
+
+impl<S> FromRequest<S> for GeminiChatPreprocess
+where
+    S: Send + Sync,
+{
+    type Rejection = GeminiCliError;
+
+    async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
+        let labels = req.extensions().get::<RequestLabels>().cloned();
+        let api_key = req.extensions().get::<ApiKeyIdentity>().cloned();
+        let path = req.uri().path().to_string();
+        let session_header = req.headers().get(SESSION_HEADER).cloned();
+        let Json(mut body) = Json::<OpenaiChatRequestBody>::from_request(req, &()).await?;
+
+        if body.model.is_empty() {
+            return Err(GeminiCliError::RequestRejected {
+                status: StatusCode::BAD_REQUEST,
+                body: GeminiErrorObject::for_status(
+                    StatusCode::BAD_REQUEST,
+                    "INVALID_ARGUMENT",
+                    "missing or empty model",
+                ),
+                debug_message: None,
+            });
+        }
+        if body.messages.is_empty() {
+            return Err(GeminiCliError::RequestRejected {
+                status: StatusCode::BAD_REQUEST,
+                body: GeminiErrorObject::for_status(
+                    StatusCode::BAD_REQUEST,
+                    "INVALID_ARGUMENT",
+                    "messages must not be empty",
+                ),
+                debug_message: None,
+            });
+        }
+
+        model_catalog::resolve_alias(&mut body.model);
+        let Some(model_mask) = model_mask(body.model.as_str()) else {
+            warn!(
+                "Rejected chat request for unsupported model: {}",
+                body.model
+            );
+            return Err(GeminiCliError::RequestRejected {
+                status: StatusCode::BAD_REQUEST,
+                body: GeminiErrorObject::for_status(
+                    StatusCode::BAD_REQUEST,
+                    "INVALID_ARGUMENT",
+                    format!("unsupported model: {}", body.model),
+                ),
+                debug_message: None,
+            });
+        };
+
+        if let Some(message) = api_key
+            .as_ref()
+            .and_then(|key| key.denies(&body.model, model_mask))
+        {
+            return Err(model_not_allowed(message));
+        }
+
+        if let Some(labels) = labels {
+            labels.set("geminicli", &body.model);
+        }
+
+        let allowed = api_key.map_or(u64::MAX, |key| key.allowed_mask());
+        let session = session_key(
+            session_header.as_ref(),
+            body.extra.get("prompt_cache_key").and_then(Value::as_str),
+        );
+        let fallback = codex_fallback(&body.model, allowed, body.stream, session.clone());
+        let ctx = GeminiContext {
+            model: body.model.clone(),
+            stream: body.stream,
+            path,
+            model_mask,
+            rpc: GeminiRpc::generate(body.stream),
+            fallbacks: fallbacks(&body.model, allowed),
+            session,
+        };
+        Ok(GeminiChatPreprocess(body, ctx, fallback))
+    }
+}
+
+/// Context for serving `model`'s `[models.fallbacks]` entries from the Gemini CLI pool after
+/// another pool ran out of capacity; `None` when this pool serves none of them.
+pub(crate) fn gemini_fallback(
+    model: &str,
+    allowed: u64,
+    stream: bool,
+    path: String,
+    session: Option<Arc<str>>,
+) -> Option<GeminiContext> {
+    split_chain(fallbacks(model, allowed)).map(|(first, rest)| GeminiContext {
+        model: first.name,
+        stream,
+        path,
+        model_mask: first.mask,
+        rpc: GeminiRpc::generate(stream),
+        fallbacks: rest,
+        session,
+    })
+}
+
+/// `[models.fallbacks]` entry of `model` that only the Codex pool serves, which the native
+/// Gemini routes cannot reach.
+pub(crate) fn unreachable_fallback(model: &str, allowed: u64) -> Option<String> {
+    codex::fallbacks(model, allowed & !supported_model_mask())
+        .into_iter()
+        .next()
+        .map(|route| route.name)
+}
+
+fn model_not_allowed(message: String) -> GeminiCliError {
+    GeminiCliError::RequestRejected {
+        status: StatusCode::FORBIDDEN,
+        body: GeminiErrorObject::for_status(StatusCode::FORBIDDEN, "PERMISSION_DENIED", message),
+        debug_message: None,
+    }
+}
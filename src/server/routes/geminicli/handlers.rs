@@ -1,41 +1,163 @@
 use super::{
-    extract::GeminiPreprocess,
-    respond::{build_json_response, build_stream_response},
+    extract::{GeminiChatPreprocess, GeminiPreprocess, unreachable_fallback},
+    respond::{
+        build_chat_json_response, build_chat_stream_response, build_json_response,
+        build_passthrough_json_response, build_stream_response,
+    },
 };
-use crate::error::GeminiCliError;
-use crate::providers::geminicli::client::GeminiClient;
+use crate::error::{GeminiCliError, GeminiErrorObject};
+use crate::providers::UpstreamResponse;
+use crate::providers::geminicli::{GeminiContext, GeminiRpc};
+use crate::server::api_keys::ApiKeyIdentity;
 use crate::server::router::PolluxState;
+use crate::server::routes::{codex, with_served_model};
+use crate::usage::UsageTracker;
 use axum::{
-    Json,
+    Extension, Json,
     extract::State,
+    http::StatusCode,
     response::{IntoResponse, Response},
 };
-use pollux_schema::{gemini::GeminiModelList, openai::OpenaiModelList};
+use pollux_schema::{
+    gemini::{GeminiChatStream, GeminiModelList, gemini_request_from_chat},
+    openai::{OpenaiChatRequestBody, OpenaiModelList},
+};
+use tracing::info;
 
 pub async fn gemini_cli_handler(
     State(state): State<PolluxState>,
+    Extension(api_key): Extension<ApiKeyIdentity>,
     GeminiPreprocess(body, ctx): GeminiPreprocess,
 ) -> Result<Response, GeminiCliError> {
     // Construct caller
-    let caller = GeminiClient::new(state.providers.geminicli_cfg.as_ref(), state.client.clone());
+    let caller = state.geminicli_caller();
 
-    let upstream_resp = caller
+    let UpstreamResponse {
+        credential_id,
+        model,
+        resp: upstream_resp,
+        lease,
+    } = caller
         .call_gemini_cli(&state.providers.geminicli, &ctx, &body)
+        .await
+        .map_err(
+            |e| match unreachable_fallback(&ctx.model, api_key.allowed_mask()) {
+                Some(target) if e.allows_fallback() => fallback_unreachable(e, &ctx.model, &target),
+                _ => e,
+            },
+        )?;
+    let usage = UsageTracker::new(
+        &state.providers.db,
+        &api_key.name,
+        "geminicli",
+        &model,
+        credential_id,
+    )
+    .holding(lease);
+
+    let response = match ctx.rpc {
+        GeminiRpc::StreamGenerateContent => {
+            build_stream_response(upstream_resp, usage).into_response()
+        }
+        GeminiRpc::GenerateContent => build_json_response(upstream_resp, usage)
+            .await
+            .into_response(),
+        GeminiRpc::CountTokens => build_passthrough_json_response(upstream_resp)
+            .await
+            .into_response(),
+    };
+    Ok(with_served_model(response, &model))
+}
+
+/// OpenAI Chat Completions over the Gemini CLI pool.
+///
+/// The chat body is translated into a Gemini request up front, so credential rotation and
+/// retries in `call_gemini_cli` behave exactly as on the native route.
+///
+/// When this pool has no capacity and `[models.fallbacks]` names a model of the Codex pool,
+/// the request is retried there.
+pub async fn gemini_chat_completions_handler(
+    State(state): State<PolluxState>,
+    Extension(api_key): Extension<ApiKeyIdentity>,
+    GeminiChatPreprocess(body, ctx, fallback): GeminiChatPreprocess,
+) -> Result<Response, GeminiCliError> {
+    let Some(fallback) = fallback else {
+        return serve_chat(&state, &api_key, &body, ctx).await;
+    };
+    match serve_chat(&state, &api_key, &body, ctx).await {
+        Err(e) if e.allows_fallback() => {
+            info!("Chat request falling back to the Codex pool after: {e}");
+            Ok(
+                codex::handlers::serve_chat(&state, &api_key, body, fallback)
+                    .await
+                    .into_response(),
+            )
+        }
+        result => result,
+    }
+}
+
+pub(crate) async fn serve_chat(
+    state: &PolluxState,
+    api_key: &ApiKeyIdentity,
+    body: &OpenaiChatRequestBody,
+    ctx: GeminiContext,
+) -> Result<Response, GeminiCliError> {
+    let caller = state.geminicli_caller();
+    let request = gemini_request_from_chat(body);
+
+    let UpstreamResponse {
+        credential_id,
+        model,
+        resp: upstream_resp,
+        lease,
+    } = caller
+        .call_gemini_cli(&state.providers.geminicli, &ctx, &request)
         .await?;
+    let usage = UsageTracker::new(
+        &state.providers.db,
+        &api_key.name,
+        "geminicli",
+        &model,
+        credential_id,
+    )
+    .holding(lease);
 
-    if ctx.stream {
-        Ok(build_stream_response(upstream_resp).into_response())
+    let response = if ctx.stream {
+        let translator = GeminiChatStream::new(model.clone(), body.include_usage());
+        build_chat_stream_response(upstream_resp, translator, usage).into_response()
     } else {
-        Ok(build_json_response(upstream_resp).await.into_response())
+        build_chat_json_response(upstream_resp, &model, usage)
+            .await
+            .into_response()
+    };
+    Ok(with_served_model(response, &model))
+}
+
+/// The native Gemini routes speak only Gemini's protocol, so a `[models.fallbacks]` entry
+/// served by Codex alone cannot be tried here. Say so instead of answering as if the chain had
+/// ended.
+fn fallback_unreachable(err: GeminiCliError, model: &str, target: &str) -> GeminiCliError {
+    GeminiCliError::RequestRejected {
+        status: StatusCode::SERVICE_UNAVAILABLE,
+        body: GeminiErrorObject::for_status(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "UNAVAILABLE",
+            format!(
+                "no credential can serve {model}; its fallback {target} is served by Codex only, \
+                 use /geminicli/v1/chat/completions or /anthropic/v1/messages to reach it"
+            ),
+        ),
+        debug_message: Some(err.to_string()),
     }
 }
 
 /// Fetch Gemini native model list via API key and proxy through Pollux.
 pub async fn gemini_models_handler() -> Result<Json<GeminiModelList>, GeminiCliError> {
-    Ok(Json((super::GEMINI_MODEL_LIST).clone()))
+    Ok(Json(super::gemini_model_list()))
 }
 
 /// Fetch Gemini models in OpenAI-compatible list format.
 pub async fn gemini_openai_models_handler() -> Result<Json<OpenaiModelList>, GeminiCliError> {
-    Ok(Json((super::GEMINI_OPENAI_MODEL_LIST).clone()))
+    Ok(Json(super::gemini_openai_model_list()))
 }
@@ -4,9 +4,13 @@ pub mod oauth;
 pub mod resource;
 pub mod respond;
 
-use crate::providers::geminicli::SUPPORTED_MODEL_NAMES;
+use crate::model_catalog;
+use crate::providers::geminicli::supported_model_names;
 use crate::server::router::PolluxState;
-use handlers::{gemini_cli_handler, gemini_models_handler, gemini_openai_models_handler};
+use handlers::{
+    gemini_chat_completions_handler, gemini_cli_handler, gemini_models_handler,
+    gemini_openai_models_handler,
+};
 use pollux_schema::{gemini::GeminiModelList, openai::OpenaiModelList};
 use resource::geminicli_resource_add;
 
@@ -14,17 +18,18 @@ use axum::{
     Router,
     routing::{get, post},
 };
-use std::sync::LazyLock;
 
-pub static GEMINI_MODEL_LIST: LazyLock<GeminiModelList> =
-    LazyLock::new(|| GeminiModelList::from_model_names(SUPPORTED_MODEL_NAMES.iter().cloned()));
+/// Built per request so a config reload shows up immediately.
+pub fn gemini_model_list() -> GeminiModelList {
+    GeminiModelList::from_model_names(model_catalog::names_with_aliases(&supported_model_names()))
+}
 
-pub static GEMINI_OPENAI_MODEL_LIST: LazyLock<OpenaiModelList> = LazyLock::new(|| {
+pub fn gemini_openai_model_list() -> OpenaiModelList {
     OpenaiModelList::from_model_names(
-        SUPPORTED_MODEL_NAMES.iter().cloned(),
+        model_catalog::names_with_aliases(&supported_model_names()),
         "gemini-cli".to_string(),
     )
-});
+}
 
 pub fn router() -> Router<PolluxState> {
     Router::new()
@@ -34,5 +39,10 @@ pub fn router() -> Router<PolluxState> {
             get(gemini_openai_models_handler),
         )
         .route("/geminicli/v1beta/models/{*path}", post(gemini_cli_handler))
+        .route(
+            "/geminicli/v1/chat/completions",
+            post(gemini_chat_completions_handler),
+        )
+        .route("/geminicli/v1/models", get(gemini_openai_models_handler))
         .route("/geminicli/resource:add", post(geminicli_resource_add))
 }
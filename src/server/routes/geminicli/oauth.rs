@@ -56,9 +56,10 @@ pub async fn google_oauth_callback(
 ) -> impl IntoResponse {
     let (jar, session_data) = take_oauth_cookies(jar);
 
+    let client = state.clients.load().geminicli.clone();
     let result = process_oauth_exchange(
         &state.providers.geminicli,
-        &state.client,
+        &client,
         &query.code,
         &query.state,
         session_data,
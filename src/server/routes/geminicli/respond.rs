@@ -1,4 +1,5 @@
 use crate::error::GeminiCliError;
+use crate::usage::UsageTracker;
 use axum::{
     Json,
     http::StatusCode,
@@ -8,8 +9,13 @@ use axum::{
     },
 };
 use eventsource_stream::Eventsource;
-use futures::{Stream, TryStreamExt};
-use pollux_schema::{gemini::GeminiResponseBody, geminicli::GeminiCliResponseBody};
+use futures::Stream;
+use pollux_schema::{
+    gemini::{GeminiChatStream, GeminiResponseBody, chat_completion_from_gemini},
+    geminicli::GeminiCliResponseBody,
+    openai::{OpenaiChatCompletion, OpenaiChatCompletionChunk},
+};
+use serde_json::Value;
 use std::time::Duration;
 use tokio_stream::StreamExt;
 use tracing::{error, warn};
@@ -17,16 +23,30 @@ use tracing::{error, warn};
 /// Build JSON response from upstream CLI response.
 pub async fn build_json_response(
     upstream_resp: reqwest::Response,
+    usage: UsageTracker,
 ) -> Result<(StatusCode, Json<GeminiResponseBody>), GeminiCliError> {
     let status = upstream_resp.status();
-    let response_body = transform_nostream(upstream_resp).await?;
+    let response_body = transform_nostream(upstream_resp, usage).await?;
     Ok((status, Json(response_body)))
 }
 
+/// Forward a unary response that is not wrapped in the CLI envelope (e.g. `countTokens`,
+/// whose `{"totalTokens": n}` already matches the public API).
+pub async fn build_passthrough_json_response(
+    upstream_resp: reqwest::Response,
+) -> Result<(StatusCode, Json<Value>), GeminiCliError> {
+    let status = upstream_resp.status();
+    let body = upstream_resp.json::<Value>().await?;
+    Ok((status, Json(body)))
+}
+
 /// Build SSE stream response with timeout and protocol mapping.
-pub fn build_stream_response(upstream_resp: reqwest::Response) -> impl IntoResponse {
+pub fn build_stream_response(
+    upstream_resp: reqwest::Response,
+    usage: UsageTracker,
+) -> impl IntoResponse {
     let raw_stream = upstream_resp.bytes_stream().eventsource();
-    let timed_stream = transform_stream(raw_stream)
+    let timed_stream = transform_stream(raw_stream, usage)
         .timeout(Duration::from_secs(60))
         .map(|item| match item {
             Ok(Ok(event)) => Ok(event),
@@ -43,13 +63,19 @@ pub fn build_stream_response(upstream_resp: reqwest::Response) -> impl IntoRespo
 }
 
 /// Convert upstream SSE events carrying CLI envelopes into SSE `Event`s for clients.
-pub fn transform_stream<I, E>(s: I) -> impl Stream<Item = Result<Event, E>>
+///
+/// `usage` lives as long as the stream, so it records once the stream ends or is dropped.
+pub fn transform_stream<I, E>(s: I, mut usage: UsageTracker) -> impl Stream<Item = Result<Event, E>>
 where
     I: Stream<Item = Result<eventsource_stream::Event, E>>,
 {
-    s.try_filter_map(|upstream_event| async move {
+    s.filter_map(move |item| {
+        let upstream_event = match item {
+            Ok(event) => event,
+            Err(e) => return Some(Err(e)),
+        };
         if upstream_event.data.is_empty() {
-            return Ok(None);
+            return None;
         }
 
         let Ok(cli_resp) = serde_json::from_str::<GeminiCliResponseBody>(&upstream_event.data)
@@ -58,14 +84,15 @@ where
                 "Skipping invalid SSE JSON data: {:.50}...",
                 upstream_event.data
             );
-            return Ok(None);
+            return None;
         };
         let gemini_resp: GeminiResponseBody = cli_resp.into();
+        usage.observe_gemini(&gemini_resp);
         match Event::default().json_data(gemini_resp) {
-            Ok(ev) => Ok(Some(ev)),
+            Ok(ev) => Some(Ok(ev)),
             Err(e) => {
                 warn!("Failed to serialize GeminiResponse: {}", e);
-                Ok(None)
+                None
             }
         }
     })
@@ -74,7 +101,103 @@ where
 /// Convert non-streaming CLI envelope into `GeminiResponse`.
 pub async fn transform_nostream(
     upstream_resp: reqwest::Response,
+    mut usage: UsageTracker,
 ) -> Result<GeminiResponseBody, GeminiCliError> {
     let envelope = upstream_resp.json::<GeminiCliResponseBody>().await?;
-    Ok(envelope.into())
+    let response: GeminiResponseBody = envelope.into();
+    usage.observe_gemini(&response);
+    Ok(response)
+}
+
+/// Build an OpenAI `chat.completion` JSON response from a unary upstream response.
+pub async fn build_chat_json_response(
+    upstream_resp: reqwest::Response,
+    model: &str,
+    usage: UsageTracker,
+) -> Result<(StatusCode, Json<OpenaiChatCompletion>), GeminiCliError> {
+    let status = upstream_resp.status();
+    let response_body = transform_nostream(upstream_resp, usage).await?;
+    Ok((
+        status,
+        Json(chat_completion_from_gemini(response_body, model)),
+    ))
+}
+
+/// Build an OpenAI `chat.completion.chunk` SSE stream, terminated by `data: [DONE]`.
+pub fn build_chat_stream_response(
+    upstream_resp: reqwest::Response,
+    translator: GeminiChatStream,
+    usage: UsageTracker,
+) -> impl IntoResponse {
+    let events = chat_chunk_stream(upstream_resp, translator, usage)
+        .filter_map(|item| match item {
+            Ok(chunk) => match Event::default().json_data(chunk) {
+                Ok(ev) => Some(Ok(ev)),
+                Err(e) => {
+                    warn!("Failed to serialize chat completion chunk: {}", e);
+                    None
+                }
+            },
+            Err(e) => Some(Err(e)),
+        })
+        .chain(tokio_stream::once(Ok(Event::default().data("[DONE]"))));
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Translate upstream CLI events into `chat.completion.chunk`s, ending with the optional
+/// usage chunk. Shared by every route that speaks chat to the Gemini CLI pool.
+pub fn chat_chunk_stream(
+    upstream_resp: reqwest::Response,
+    translator: GeminiChatStream,
+    usage: UsageTracker,
+) -> impl Stream<Item = Result<OpenaiChatCompletionChunk, GeminiCliError>> {
+    let events = upstream_resp
+        .bytes_stream()
+        .eventsource()
+        .timeout(Duration::from_secs(60));
+
+    // Each step yields a batch so the end of the upstream stream can still emit the
+    // translator's trailing usage chunk. The usage tracker is dropped, and so recorded,
+    // together with the rest of the state.
+    let state = (Box::pin(events), translator, usage);
+    let batches = futures::stream::unfold(Some(state), |state| async move {
+        let (mut events, mut translator, mut usage) = state?;
+        let batch = match events.next().await {
+            Some(Ok(Ok(event))) => {
+                let batch = translate_chat_event(&mut translator, &mut usage, &event.data)
+                    .map(Ok)
+                    .into_iter()
+                    .collect();
+                return Some((batch, Some((events, translator, usage))));
+            }
+            Some(Ok(Err(e))) => vec![Err(GeminiCliError::StreamProtocolError(e.to_string()))],
+            Some(Err(_)) => {
+                error!("Upstream SSE stream timed out (idle > 60s)");
+                vec![Err(GeminiCliError::StreamProtocolError(
+                    "Stream idle timeout".to_string(),
+                ))]
+            }
+            None => translator.finish().map(Ok).into_iter().collect(),
+        };
+        Some((batch, None))
+    });
+    futures::StreamExt::flat_map(batches, futures::stream::iter)
+}
+
+fn translate_chat_event(
+    translator: &mut GeminiChatStream,
+    usage: &mut UsageTracker,
+    data: &str,
+) -> Option<OpenaiChatCompletionChunk> {
+    if data.is_empty() {
+        return None;
+    }
+    let Ok(cli_resp) = serde_json::from_str::<GeminiCliResponseBody>(data) else {
+        warn!("Skipping invalid SSE JSON data: {:.50}...", data);
+        return None;
+    };
+    let gemini_resp: GeminiResponseBody = cli_resp.into();
+    usage.observe_gemini(&gemini_resp);
+    translator.push(gemini_resp)
 }
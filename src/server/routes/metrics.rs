@@ -0,0 +1,27 @@
+use crate::error::PolluxError;
+use crate::metrics::METRICS;
+use crate::server::router::PolluxState;
+use axum::{
+    Router,
+    extract::State,
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+pub fn router() -> Router<PolluxState> {
+    Router::new().route("/metrics", get(metrics_handler))
+}
+
+/// GET /metrics
+///
+/// Prometheus text exposition of request, lease, upstream and refresher metrics,
+/// plus per-model pool gauges taken from live scheduler snapshots.
+pub async fn metrics_handler(State(state): State<PolluxState>) -> Result<Response, PolluxError> {
+    let geminicli = state.providers.geminicli.snapshot().await?;
+    let codex = state.providers.codex.snapshot().await?;
+    let body = METRICS.render(&[("geminicli", &geminicli), ("codex", &codex)]);
+    Ok(([(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], body).into_response())
+}
@@ -1,2 +1,75 @@
+pub mod admin;
+pub mod anthropic;
 pub mod codex;
 pub mod geminicli;
+pub mod metrics;
+
+use crate::model_catalog::ModelRoute;
+use axum::http::HeaderValue;
+use axum::response::Response;
+use std::sync::Arc;
+
+/// Response header naming the model that served the request. It differs from the requested
+/// model when a `[models.fallbacks]` entry was used.
+pub const SERVED_MODEL_HEADER: &str = "x-pollux-model";
+
+pub(crate) fn with_served_model(mut resp: Response, model: &str) -> Response {
+    if let Ok(value) = HeaderValue::from_str(model) {
+        resp.headers_mut().insert(SERVED_MODEL_HEADER, value);
+    }
+    resp
+}
+
+/// Splits a fallback chain into the model to request and the entries behind it.
+pub(crate) fn split_chain(mut chain: Vec<ModelRoute>) -> Option<(ModelRoute, Vec<ModelRoute>)> {
+    (!chain.is_empty()).then(|| {
+        let first = chain.remove(0);
+        (first, chain)
+    })
+}
+
+/// Request header naming a conversation for `session_affinity`.
+pub const SESSION_HEADER: &str = "x-pollux-session";
+
+/// Session key for `session_affinity`: the `SESSION_HEADER` value, else `from_body`. Blank
+/// values count as missing.
+pub(crate) fn session_key(
+    header: Option<&HeaderValue>,
+    from_body: Option<&str>,
+) -> Option<Arc<str>> {
+    fn non_blank(key: &str) -> Option<&str> {
+        Some(key.trim()).filter(|key| !key.is_empty())
+    }
+    header
+        .and_then(|value| value.to_str().ok())
+        .and_then(non_blank)
+        .or_else(|| from_body.and_then(non_blank))
+        .map(Arc::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_key_prefers_the_header_and_ignores_blank_values() {
+        let header = HeaderValue::from_static("from-header");
+        let blank = HeaderValue::from_static("  ");
+
+        assert_eq!(
+            session_key(Some(&header), Some("from-body")).as_deref(),
+            Some("from-header")
+        );
+        assert_eq!(
+            session_key(None, Some("from-body")).as_deref(),
+            Some("from-body")
+        );
+        assert_eq!(
+            session_key(Some(&blank), Some(" from-body ")).as_deref(),
+            Some("from-body")
+        );
+        assert_eq!(session_key(Some(&blank), Some(" ")), None);
+        assert_eq!(session_key(Some(&blank), None), None);
+        assert_eq!(session_key(None, None), None);
+    }
+}
@@ -0,0 +1,195 @@
+//! Per-request token accounting, persisted to the `token_usage` table.
+//!
+//! Response paths hand every upstream usage report to a [`UsageTracker`]; the tracker writes
+//! one row when it is dropped, i.e. once the JSON body is built or the SSE stream ends
+//! (including when the client disconnects early). For the same reason it carries the
+//! credential's [`LeaseGuard`].
+
+use crate::db::{DbActorHandle, UsageCreate};
+use crate::providers::LeaseGuard;
+use chrono::Utc;
+use pollux_schema::gemini::GeminiResponseBody;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Token counts normalised across providers.
+///
+/// `output_tokens` includes `reasoning_tokens` and `input_tokens` includes `cached_tokens`,
+/// matching OpenAI usage objects.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_tokens: u64,
+    pub reasoning_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Gemini `usageMetadata`. Thought tokens are billed as output, so they are added to it.
+    pub fn from_gemini(metadata: &Value) -> Self {
+        let count = |key: &str| metadata.get(key).and_then(Value::as_u64).unwrap_or(0);
+        let reasoning_tokens = count("thoughtsTokenCount");
+        Self {
+            input_tokens: count("promptTokenCount"),
+            output_tokens: count("candidatesTokenCount") + reasoning_tokens,
+            cached_tokens: count("cachedContentTokenCount"),
+            reasoning_tokens,
+        }
+    }
+
+    /// Responses API `usage` object.
+    pub fn from_responses(usage: &Value) -> Self {
+        let count = |pointer: &str| usage.pointer(pointer).and_then(Value::as_u64).unwrap_or(0);
+        Self {
+            input_tokens: count("/input_tokens"),
+            output_tokens: count("/output_tokens"),
+            cached_tokens: count("/input_tokens_details/cached_tokens"),
+            reasoning_tokens: count("/output_tokens_details/reasoning_tokens"),
+        }
+    }
+}
+
+struct UsageSink {
+    db: DbActorHandle,
+    key_name: Arc<str>,
+    provider: &'static str,
+    model: String,
+    credential_id: u64,
+}
+
+/// Collects the usage of one upstream call and records it on drop.
+///
+/// Calls that end before upstream reports any usage are not recorded.
+pub struct UsageTracker {
+    sink: Option<UsageSink>,
+    usage: Option<TokenUsage>,
+    lease: Option<LeaseGuard>,
+}
+
+impl UsageTracker {
+    pub fn new(
+        db: &DbActorHandle,
+        key_name: &Arc<str>,
+        provider: &'static str,
+        model: &str,
+        credential_id: u64,
+    ) -> Self {
+        Self {
+            sink: Some(UsageSink {
+                db: db.clone(),
+                key_name: key_name.clone(),
+                provider,
+                model: model.to_string(),
+                credential_id,
+            }),
+            usage: None,
+            lease: None,
+        }
+    }
+
+    /// Release `lease` only when this tracker is dropped, i.e. once the response has ended.
+    pub fn holding(mut self, lease: LeaseGuard) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    /// A tracker that records nothing.
+    pub fn disabled() -> Self {
+        Self {
+            sink: None,
+            usage: None,
+            lease: None,
+        }
+    }
+
+    /// Upstream reports are cumulative, so the latest one wins.
+    pub fn observe(&mut self, usage: TokenUsage) {
+        self.usage = Some(usage);
+    }
+
+    pub fn observe_gemini(&mut self, resp: &GeminiResponseBody) {
+        if let Some(metadata) = &resp.usageMetadata {
+            self.observe(TokenUsage::from_gemini(metadata));
+        }
+    }
+
+    /// A Responses stream event; only terminal events (`response.completed`, ...) embed a
+    /// `response.usage`.
+    pub fn observe_responses_event(&mut self, event: &Value) {
+        if let Some(usage) = event.pointer("/response/usage").filter(|u| u.is_object()) {
+            self.observe(TokenUsage::from_responses(usage));
+        }
+    }
+}
+
+impl Drop for UsageTracker {
+    fn drop(&mut self) {
+        let (Some(sink), Some(usage)) = (self.sink.take(), self.usage) else {
+            return;
+        };
+        let count = |n: u64| i64::try_from(n).unwrap_or(i64::MAX);
+        sink.db.record_usage(UsageCreate {
+            key_name: sink.key_name.to_string(),
+            provider: sink.provider,
+            model: sink.model,
+            credential_id: count(sink.credential_id),
+            input_tokens: count(usage.input_tokens),
+            output_tokens: count(usage.output_tokens),
+            cached_tokens: count(usage.cached_tokens),
+            reasoning_tokens: count(usage.reasoning_tokens),
+            created_at: Utc::now(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn gemini_usage_counts_thoughts_as_output() {
+        let usage = TokenUsage::from_gemini(&json!({
+            "promptTokenCount": 10,
+            "candidatesTokenCount": 5,
+            "thoughtsTokenCount": 3,
+            "cachedContentTokenCount": 4,
+            "totalTokenCount": 18
+        }));
+        assert_eq!(
+            usage,
+            TokenUsage {
+                input_tokens: 10,
+                output_tokens: 8,
+                cached_tokens: 4,
+                reasoning_tokens: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn responses_usage_reads_detail_objects_and_ignores_other_events() {
+        let mut tracker = UsageTracker::disabled();
+        tracker.observe_responses_event(&json!({"type": "response.output_text.delta"}));
+        assert_eq!(tracker.usage, None);
+
+        tracker.observe_responses_event(&json!({
+            "type": "response.completed",
+            "response": {"usage": {
+                "input_tokens": 7,
+                "input_tokens_details": {"cached_tokens": 2},
+                "output_tokens": 9,
+                "output_tokens_details": {"reasoning_tokens": 6}
+            }}
+        }));
+        assert_eq!(
+            tracker.usage,
+            Some(TokenUsage {
+                input_tokens: 7,
+                output_tokens: 9,
+                cached_tokens: 2,
+                reasoning_tokens: 6,
+            })
+        );
+    }
+}
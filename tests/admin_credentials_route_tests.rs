@@ -0,0 +1,168 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use chrono::{Duration, Utc};
+use pollux::db::{CodexCreate, GeminiCliCreate, ProviderCreate};
+use serde_json::Value;
+use std::{
+    fs,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn send(app: &Router, method: &str, uri: &str, key: Option<&str>) -> (StatusCode, String) {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(key) = key {
+        builder = builder.header("x-goog-api-key", key);
+    }
+    let resp = app
+        .clone()
+        .oneshot(
+            builder
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    (
+        status,
+        String::from_utf8(body.to_vec()).expect("response body was not utf-8"),
+    )
+}
+
+#[tokio::test]
+async fn admin_credentials_routes_list_toggle_and_delete() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-admin-credentials-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let expiry = Utc::now() + Duration::hours(1);
+    let gemini_id = db
+        .create(ProviderCreate::GeminiCli(GeminiCliCreate {
+            email: Some("gemini@example.com".to_string()),
+            sub: "google-sub".to_string(),
+            project_id: "proj-1".to_string(),
+            refresh_token: "gemini-refresh-secret".to_string(),
+            access_token: Some("gemini-access-secret".to_string()),
+            expiry,
+            tier: Some("standard-tier".to_string()),
+        }))
+        .await
+        .expect("create geminicli row");
+    let codex_id = db
+        .create(ProviderCreate::Codex(CodexCreate {
+            email: Some("codex@example.com".to_string()),
+            sub: "auth0|codex".to_string(),
+            account_id: "acct-1".to_string(),
+            refresh_token: "codex-refresh-secret".to_string(),
+            access_token: "codex-access-secret".to_string(),
+            expiry,
+            chatgpt_plan_type: Some("plus".to_string()),
+        }))
+        .await
+        .expect("create codex row");
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        pollux_key.clone(),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+    let key = Some(pollux_key.as_ref());
+
+    // 1) no key -> 401
+    let (status, _) = send(&app, "GET", "/admin/credentials", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 2) overview lists both providers and never leaks tokens
+    let (status, body) = send(&app, "GET", "/admin/credentials", key).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!body.contains("secret"), "tokens leaked: {body}");
+    let json: Value = serde_json::from_str(&body).expect("overview json");
+    assert_eq!(json["geminicli"]["total"], 1);
+    assert_eq!(json["geminicli"]["items"][0]["project_id"], "proj-1");
+    assert_eq!(json["geminicli"]["items"][0]["plan"], "standard-tier");
+    assert_eq!(json["codex"]["total"], 1);
+    assert_eq!(json["codex"]["items"][0]["account_id"], "acct-1");
+    assert_eq!(json["codex"]["items"][0]["plan"], "plus");
+
+    // 3) disable keeps the row visible with enabled=false
+    let uri = format!("/admin/credentials/codex/{codex_id}/disable");
+    let (status, body) = send(&app, "POST", &uri, key).await;
+    assert_eq!(status, StatusCode::OK);
+    let json: Value = serde_json::from_str(&body).expect("disable json");
+    assert_eq!(json["enabled"], false);
+    assert!(
+        db.list_active_codex()
+            .await
+            .expect("list active")
+            .is_empty()
+    );
+
+    let (status, body) = send(&app, "GET", "/admin/credentials/codex?limit=10", key).await;
+    assert_eq!(status, StatusCode::OK);
+    let json: Value = serde_json::from_str(&body).expect("list json");
+    assert_eq!(json["total"], 1);
+    assert_eq!(json["limit"], 10);
+    assert_eq!(json["items"][0]["enabled"], false);
+
+    // 4) re-enable
+    let uri = format!("/admin/credentials/codex/{codex_id}/enable");
+    let (status, body) = send(&app, "POST", &uri, key).await;
+    assert_eq!(status, StatusCode::OK);
+    let json: Value = serde_json::from_str(&body).expect("enable json");
+    assert_eq!(json["enabled"], true);
+    assert_eq!(db.list_active_codex().await.expect("list active").len(), 1);
+
+    // 5) scheduler snapshot sees both credentials back in memory
+    let (status, body) = send(&app, "GET", "/admin/scheduler", key).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!body.contains("secret"), "tokens leaked: {body}");
+    let json: Value = serde_json::from_str(&body).expect("scheduler json");
+    assert_eq!(json["codex"]["total_creds"], 1);
+    assert_eq!(json["codex"]["credentials"][0]["id"], codex_id);
+    assert_eq!(json["codex"]["credentials"][0]["label"], "acct-1");
+    assert_eq!(json["geminicli"]["total_creds"], 1);
+
+    // 6) delete, then the id is gone
+    let uri = format!("/admin/credentials/geminicli/{gemini_id}");
+    let (status, _) = send(&app, "DELETE", &uri, key).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = send(&app, "GET", "/admin/scheduler", key).await;
+    let json: Value = serde_json::from_str(&body).expect("scheduler json");
+    assert_eq!(json["geminicli"]["total_creds"], 0);
+    let (status, body) = send(&app, "GET", &uri, key).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.contains(r#""code":"NOT_FOUND""#));
+
+    // 7) unknown ids and providers are rejected
+    let (status, _) = send(&app, "POST", "/admin/credentials/codex/9999/disable", key).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&app, "GET", "/admin/credentials/unknown", key).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let _ = fs::remove_file(&temp_path);
+}
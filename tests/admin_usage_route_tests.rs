@@ -0,0 +1,138 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use chrono::{TimeZone, Utc};
+use pollux::db::UsageCreate;
+use pollux::usage::{TokenUsage, UsageTracker};
+use serde_json::{Value, json};
+use std::{
+    fs,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn get_json(app: &Router, key: &str, uri: &str) -> (StatusCode, Value) {
+    let req = Request::builder()
+        .uri(uri)
+        .header("x-goog-api-key", key)
+        .body(Body::empty())
+        .expect("failed to build request");
+    let resp = app.clone().oneshot(req).await.expect("request failed");
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn usage_row(key: &str, model: &str, day: u32, input: i64, output: i64) -> UsageCreate {
+    UsageCreate {
+        key_name: key.to_string(),
+        provider: "codex",
+        model: model.to_string(),
+        credential_id: 1,
+        input_tokens: input,
+        output_tokens: output,
+        cached_tokens: 1,
+        reasoning_tokens: 0,
+        created_at: Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn admin_usage_aggregates_recorded_usage_by_day_key_and_model() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-admin-usage-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        pollux_key.clone(),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+
+    db.record_usage(usage_row("team-a", "gpt-5.2", 1, 10, 20));
+    db.record_usage(usage_row("team-a", "gpt-5.2", 1, 5, 5));
+    db.record_usage(usage_row("team-a", "gpt-5.2", 2, 1, 1));
+    db.record_usage(usage_row("team-b", "gpt-5.2", 2, 100, 200));
+
+    // A tracker writes one row on drop, and only once upstream reported usage.
+    let key: Arc<str> = Arc::from("team-c");
+    drop(UsageTracker::new(
+        &db,
+        &key,
+        "geminicli",
+        "gemini-2.5-pro",
+        7,
+    ));
+    let mut tracker = UsageTracker::new(&db, &key, "geminicli", "gemini-2.5-pro", 7);
+    tracker.observe(TokenUsage {
+        input_tokens: 3,
+        output_tokens: 4,
+        cached_tokens: 0,
+        reasoning_tokens: 2,
+    });
+    drop(tracker);
+
+    // 1) everything, grouped and ordered by (day, key, provider, model)
+    let (status, body) = get_json(&app, &pollux_key, "/admin/usage?to=2026-03-02").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["items"],
+        json!([
+            {"day": "2026-03-01", "key": "team-a", "provider": "codex", "model": "gpt-5.2",
+             "requests": 2, "input_tokens": 15, "output_tokens": 25, "cached_tokens": 2,
+             "reasoning_tokens": 0},
+            {"day": "2026-03-02", "key": "team-a", "provider": "codex", "model": "gpt-5.2",
+             "requests": 1, "input_tokens": 1, "output_tokens": 1, "cached_tokens": 1,
+             "reasoning_tokens": 0},
+            {"day": "2026-03-02", "key": "team-b", "provider": "codex", "model": "gpt-5.2",
+             "requests": 1, "input_tokens": 100, "output_tokens": 200, "cached_tokens": 1,
+             "reasoning_tokens": 0},
+        ])
+    );
+
+    // 2) filters
+    let (_, body) = get_json(
+        &app,
+        &pollux_key,
+        "/admin/usage?from=2026-03-02&to=2026-03-02&key=team-a",
+    )
+    .await;
+    assert_eq!(body["items"].as_array().map(Vec::len), Some(1));
+    assert_eq!(body["items"][0]["input_tokens"], 1);
+
+    // 3) the tracker's row lands on today's date
+    let (_, body) = get_json(&app, &pollux_key, "/admin/usage?key=team-c").await;
+    let items = body["items"].as_array().expect("items");
+    assert_eq!(items.len(), 1, "{body}");
+    assert_eq!(items[0]["day"], Utc::now().format("%Y-%m-%d").to_string());
+    assert_eq!(items[0]["requests"], 1);
+    assert_eq!(items[0]["output_tokens"], 4);
+    assert_eq!(items[0]["reasoning_tokens"], 2);
+
+    // 4) malformed dates are rejected
+    let (status, _) = get_json(&app, &pollux_key, "/admin/usage?from=yesterday").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let _ = fs::remove_file(&temp_path);
+}
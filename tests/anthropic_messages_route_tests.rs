@@ -0,0 +1,149 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use std::{
+    fs,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn post_messages(app: &Router, key: Option<&str>, body: String) -> (StatusCode, String) {
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri("/anthropic/v1/messages")
+        .header("content-type", "application/json")
+        .header("anthropic-version", "2023-06-01");
+    if let Some(key) = key {
+        builder = builder.header("x-api-key", key);
+    }
+    let resp = app
+        .clone()
+        .oneshot(
+            builder
+                .body(Body::from(body))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    (
+        status,
+        String::from_utf8(body.to_vec()).expect("response body was not utf-8"),
+    )
+}
+
+#[tokio::test]
+async fn anthropic_messages_route_validates_and_dispatches_by_model() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-anthropic-messages-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    // Keep test behavior stable regardless of the repo's runtime `config.toml`.
+    let gemini_model = pollux::config::CONFIG
+        .geminicli()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gemini-2.5-pro".to_string());
+    let codex_model = pollux::config::CONFIG
+        .codex()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gpt-4o-mini".to_string());
+    cfg.providers.geminicli.model_list = vec![gemini_model.clone()];
+    cfg.providers.codex.model_list = vec![codex_model.clone()];
+
+    // No credentials in either pool => dispatched requests should yield 503.
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        pollux_key.clone(),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+    let key = Some(pollux_key.as_ref());
+    let hello = r#"[{"role":"user","content":"hi"}]"#;
+
+    // 1) no key -> 401
+    let (status, _) = post_messages(
+        &app,
+        None,
+        format!(r#"{{"model":"{codex_model}","max_tokens":16,"messages":{hello}}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 2) invalid JSON -> 400 in the Anthropic error envelope
+    let (status, body) = post_messages(&app, key, "not-json".to_string()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = serde_json::from_str(&body).expect("error json");
+    assert_eq!(body["type"], "error");
+    assert_eq!(body["error"]["type"], "invalid_request_error");
+
+    // 3) empty messages -> 400
+    let (status, body) = post_messages(
+        &app,
+        key,
+        format!(r#"{{"model":"{codex_model}","max_tokens":16,"messages":[]}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("messages must not be empty"), "{body}");
+
+    // 4) model served by neither pool -> 400
+    let (status, body) = post_messages(
+        &app,
+        key,
+        format!(r#"{{"model":"no-such-model","max_tokens":16,"messages":{hello}}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("unsupported model: no-such-model"), "{body}");
+
+    // 5) each pool's model reaches that pool -> 503 as overloaded_error
+    for model in [&gemini_model, &codex_model] {
+        let (status, body) = post_messages(
+            &app,
+            key,
+            format!(
+                r#"{{"model":"{model}","max_tokens":16,"stream":true,"messages":{hello},
+                    "system":"be brief",
+                    "tools":[{{"name":"f","input_schema":{{"type":"object"}}}}],
+                    "thinking":{{"type":"enabled","budget_tokens":2048}}}}"#
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{model}: {body}");
+
+        let body: serde_json::Value = serde_json::from_str(&body).expect("error json");
+        assert_eq!(body["type"], "error");
+        assert_eq!(body["error"]["type"], "overloaded_error");
+        assert_eq!(
+            body["error"]["message"],
+            "No available credentials to process the request."
+        );
+    }
+
+    let _ = fs::remove_file(&temp_path);
+}
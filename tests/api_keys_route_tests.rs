@@ -0,0 +1,180 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header::RETRY_AFTER},
+};
+use pollux::config::ApiKeyConfig;
+use std::{
+    fs,
+    num::NonZeroU32,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn send(app: &Router, req: Request<Body>) -> (StatusCode, Option<String>, String) {
+    let resp = app.clone().oneshot(req).await.expect("request failed");
+    let status = resp.status();
+    let retry_after = resp
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    (
+        status,
+        retry_after,
+        String::from_utf8(body.to_vec()).expect("response body was not utf-8"),
+    )
+}
+
+fn codex_responses(key: &str, model: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/codex/v1/responses")
+        .header("content-type", "application/json")
+        .header("authorization", format!("Bearer {key}"))
+        .body(Body::from(format!(r#"{{"model":"{model}"}}"#)))
+        .expect("failed to build request")
+}
+
+fn gemini_generate(key: &str, model: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/geminicli/v1beta/models/{model}:generateContent"))
+        .header("content-type", "application/json")
+        .header("x-goog-api-key", key)
+        .body(Body::from(
+            r#"{"contents":[{"role":"user","parts":[{"text":"hi"}]}]}"#,
+        ))
+        .expect("failed to build request")
+}
+
+fn get(key: &str, uri: &str) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .header("x-goog-api-key", key)
+        .body(Body::empty())
+        .expect("failed to build request")
+}
+
+fn api_key(name: &str, models: Option<&[&str]>, enabled: bool, rpm: Option<u32>) -> ApiKeyConfig {
+    ApiKeyConfig {
+        name: name.to_string(),
+        key: format!("sk-{name}"),
+        enabled,
+        models: models.map(|models| models.iter().map(|m| m.to_string()).collect()),
+        rpm: rpm.and_then(NonZeroU32::new),
+    }
+}
+
+#[tokio::test]
+async fn api_keys_enforce_allowlists_flags_and_rate_limits() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-api-keys-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    // Keep test behavior stable regardless of the repo's runtime `config.toml`.
+    let gemini_model = pollux::config::CONFIG
+        .geminicli()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gemini-2.5-pro".to_string());
+    let codex_model = pollux::config::CONFIG
+        .codex()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gpt-4o-mini".to_string());
+    cfg.providers.geminicli.model_list = vec![gemini_model.clone()];
+    cfg.providers.codex.model_list = vec![codex_model.clone()];
+
+    // No credentials in either pool => permitted requests should yield 503.
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        pollux_key.clone(),
+        cfg.basic.insecure_cookie,
+    )
+    .with_api_keys(
+        &cfg.basic.pollux_key,
+        &[
+            api_key("codex-only", Some(&["codex"]), true, None),
+            api_key("retired", None, false, None),
+            api_key("throttled", None, true, Some(1)),
+        ],
+    );
+    let app = pollux::server::router::pollux_router(state);
+
+    // 1) a scoped key reaches its provider but not the other one
+    let (status, _, _) = send(&app, codex_responses("sk-codex-only", &codex_model)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+    let (status, _, body) = send(&app, gemini_generate("sk-codex-only", &gemini_model)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+    let expected = format!("API key 'codex-only' is not allowed to use model: {gemini_model}");
+    assert!(body.contains(&expected), "{body}");
+
+    // 2) only the master key may use /admin/*
+    let (status, _, _) = send(&app, get("sk-codex-only", "/admin/scheduler")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _, _) = send(&app, get(&pollux_key, "/admin/scheduler")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // 3) a disabled key is recognised but refused
+    let (status, _, _) = send(&app, codex_responses("sk-retired", &codex_model)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // 4) rpm = 1 => the second request inside the minute is throttled
+    let (status, _, _) = send(&app, codex_responses("sk-throttled", &codex_model)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let (status, retry_after, _) = send(&app, codex_responses("sk-throttled", &codex_model)).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = retry_after
+        .expect("missing Retry-After")
+        .parse()
+        .expect("Retry-After was not seconds");
+    assert!((1..=60).contains(&retry_after), "{retry_after}");
+
+    // 5) unknown keys are still 401
+    let (status, _, _) = send(&app, codex_responses("sk-nobody", &codex_model)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 6) per-key counters
+    let (status, _, body) = send(&app, get(&pollux_key, "/metrics")).await;
+    assert_eq!(status, StatusCode::OK);
+    for sample in [
+        r#"pollux_key_requests_total{key="codex-only",status="503"} 1"#,
+        r#"pollux_key_requests_total{key="codex-only",status="403"} 2"#,
+        r#"pollux_key_requests_total{key="throttled",status="429"} 1"#,
+        r#"pollux_key_requests_total{key="master",status="200"} 1"#,
+    ] {
+        assert!(body.contains(sample), "missing `{sample}` in:\n{body}");
+    }
+    assert!(
+        !body.contains("sk-"),
+        "secrets leaked into metrics:\n{body}"
+    );
+    // ...which name every key, so client keys may not read them
+    let (status, _, _) = send(&app, get("sk-codex-only", "/metrics")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let _ = fs::remove_file(&temp_path);
+}
@@ -0,0 +1,131 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use std::{
+    fs,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn post_chat(app: &Router, key: Option<&str>, body: String) -> (StatusCode, String) {
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri("/codex/v1/chat/completions")
+        .header("content-type", "application/json");
+    if let Some(key) = key {
+        builder = builder.header("authorization", format!("Bearer {key}"));
+    }
+    let resp = app
+        .clone()
+        .oneshot(
+            builder
+                .body(Body::from(body))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    (
+        status,
+        String::from_utf8(body.to_vec()).expect("response body was not utf-8"),
+    )
+}
+
+#[tokio::test]
+async fn codex_chat_completions_route_validates_and_requires_key() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-codex-chat-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    // Keep test behavior stable regardless of the repo's runtime `config.toml`.
+    let model = pollux::config::CONFIG
+        .codex()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gpt-4o-mini".to_string());
+    cfg.providers.codex.model_list = vec![model.clone()];
+
+    // No Codex credentials inserted => valid requests should yield 503.
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        pollux_key.clone(),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+    let key = Some(pollux_key.as_ref());
+    let hello = r#"[{"role":"user","content":"hi"}]"#;
+
+    // 1) no key -> 401
+    let (status, _) = post_chat(
+        &app,
+        None,
+        format!(r#"{{"model":"{model}","messages":{hello}}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 2) invalid JSON -> 400
+    let (status, _) = post_chat(&app, key, "not-json".to_string()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // 3) missing model -> 400
+    let (status, body) = post_chat(&app, key, format!(r#"{{"messages":{hello}}}"#)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("missing or empty model"), "{body}");
+
+    // 4) empty messages -> 400
+    let (status, body) =
+        post_chat(&app, key, format!(r#"{{"model":"{model}","messages":[]}}"#)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("messages must not be empty"), "{body}");
+
+    // 5) unsupported model -> 400
+    let (status, body) = post_chat(
+        &app,
+        key,
+        format!(r#"{{"model":"no-such-model","messages":{hello}}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("UNSUPPORTED_MODEL"), "{body}");
+
+    // 6) valid chat request with tools and streaming -> 503 (no upstream credentials)
+    let (status, body) = post_chat(
+        &app,
+        key,
+        format!(
+            r#"{{"model":"{model}","stream":true,"messages":{hello},
+                "tools":[{{"type":"function","function":{{"name":"f","parameters":{{"type":"object"}}}}}}],
+                "response_format":{{"type":"json_object"}}}}"#
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{body}");
+
+    let body: serde_json::Value = serde_json::from_str(&body).expect("error json");
+    assert_eq!(body["error"]["code"], "NO_CREDENTIAL");
+
+    let _ = fs::remove_file(&temp_path);
+}
@@ -0,0 +1,67 @@
+use sqlx::{Connection, PgConnection};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Server the PostgreSQL half of the storage tests runs against, e.g.
+/// `postgres://postgres@127.0.0.1:5432/postgres`. Unset, those tests only cover SQLite.
+pub const POSTGRES_URL_ENV: &str = "POLLUX_TEST_POSTGRES_URL";
+
+/// A database created for one test; dropped from the server again when this goes out of
+/// scope, panics included.
+pub struct PostgresDatabase {
+    pub url: String,
+    server_url: String,
+    name: String,
+}
+
+/// Create an empty database on the [`POSTGRES_URL_ENV`] server, or `None` when the variable
+/// is unset.
+pub async fn postgres_database(name: &str) -> Option<PostgresDatabase> {
+    let server_url = std::env::var(POSTGRES_URL_ENV).ok()?;
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let database = format!("pollux_{name}_{}_{nanos}", std::process::id());
+
+    let mut conn = PgConnection::connect(&server_url)
+        .await
+        .expect("connect to the test PostgreSQL server");
+    sqlx::query(&format!("CREATE DATABASE {database}"))
+        .execute(&mut conn)
+        .await
+        .expect("create test database");
+    conn.close().await.expect("close admin connection");
+
+    let mut url = url::Url::parse(&server_url).expect("valid PostgreSQL url");
+    url.set_path(&database);
+    Some(PostgresDatabase {
+        url: url.to_string(),
+        server_url,
+        name: database,
+    })
+}
+
+impl Drop for PostgresDatabase {
+    /// `Drop` cannot await, and the test's runtime may be the current-thread kind, so the
+    /// `DROP DATABASE` runs on a runtime of its own. `FORCE` ends the connections the
+    /// `DbActor` still holds.
+    fn drop(&mut self) {
+        let server_url = self.server_url.clone();
+        let statement = format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", self.name);
+        let dropped = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("runtime for dropping the test database");
+            runtime.block_on(async move {
+                let mut conn = PgConnection::connect(&server_url).await?;
+                sqlx::query(&statement).execute(&mut conn).await?;
+                conn.close().await
+            })
+        })
+        .join();
+        if !matches!(dropped, Ok(Ok(()))) {
+            eprintln!("failed to drop test database {}", self.name);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use pollux::config::ApiKeyConfig;
+use pollux::error::PolluxError;
+use std::{
+    fs,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn send(app: &Router, req: Request<Body>) -> (StatusCode, String) {
+    let resp = app.clone().oneshot(req).await.expect("request failed");
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    (
+        status,
+        String::from_utf8(body.to_vec()).expect("response body was not utf-8"),
+    )
+}
+
+fn codex_responses(key: &str, model: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/codex/v1/responses")
+        .header("content-type", "application/json")
+        .header("authorization", format!("Bearer {key}"))
+        .body(Body::from(format!(r#"{{"model":"{model}"}}"#)))
+        .expect("failed to build request")
+}
+
+fn codex_models(key: &str) -> Request<Body> {
+    Request::builder()
+        .uri("/codex/v1/models")
+        .header("authorization", format!("Bearer {key}"))
+        .body(Body::empty())
+        .expect("failed to build request")
+}
+
+#[tokio::test]
+async fn reload_applies_model_lists_and_api_keys_in_place() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-config-reload-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    let codex_model = pollux::config::CONFIG
+        .codex()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gpt-4o-mini".to_string());
+    cfg.providers.codex.model_list = vec![codex_model.clone()];
+
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        pollux_key.clone(),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state.clone());
+
+    // 1) before the reload the new model is unknown and the new key is rejected
+    let added = "gpt-reload-test";
+    let (status, body) = send(&app, codex_responses(&pollux_key, added)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    let (status, _) = send(&app, codex_responses("sk-late", &codex_model)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 2) a config that fails validation changes nothing
+    let mut invalid = cfg.clone();
+    invalid.providers.codex.model_list.push(added.to_string());
+    invalid
+        .models
+        .fallbacks
+        .insert(added.to_string(), vec!["no-such-model".to_string()]);
+    let err = state.reload(&invalid).await.expect_err("invalid config");
+    assert!(matches!(err, PolluxError::InvalidConfig(_)), "{err}");
+    let (status, _) = send(&app, codex_responses(&pollux_key, added)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // ...and so does one only the HTTP client rejects: a proxy URL without a host
+    let mut unbuildable = cfg.clone();
+    unbuildable
+        .providers
+        .codex
+        .model_list
+        .push(added.to_string());
+    unbuildable.providers.codex.proxy = Some("data:text/plain,proxy".parse().expect("url"));
+    let err = state
+        .reload(&unbuildable)
+        .await
+        .expect_err("unbuildable client");
+    assert!(matches!(err, PolluxError::InvalidConfig(_)), "{err}");
+    assert!(
+        pollux::model_catalog::registry().get_index(added).is_none(),
+        "registry changed by a failed reload"
+    );
+    let (status, _) = send(&app, codex_responses(&pollux_key, added)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // 3) a valid reload adds the model and the key
+    let mut reloaded = cfg.clone();
+    reloaded.providers.codex.model_list.push(added.to_string());
+    reloaded.api_keys = vec![ApiKeyConfig {
+        name: "late".to_string(),
+        key: "sk-late".to_string(),
+        enabled: true,
+        models: None,
+        rpm: None,
+    }];
+    let summary = state.reload(&reloaded).await.expect("reload");
+    assert_eq!(summary.codex, vec![codex_model.clone(), added.to_string()]);
+
+    let (status, body) = send(&app, codex_models(&pollux_key)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains(&format!("\"id\":\"{added}\"")), "{body}");
+
+    // No credentials in the pool => the new model is routed and yields 503.
+    let (status, body) = send(&app, codex_responses(&pollux_key, added)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{body}");
+    let (status, _) = send(&app, codex_responses("sk-late", &codex_model)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+    // 4) reloading the original config takes them out again
+    state.reload(&cfg).await.expect("reload");
+    let (status, _) = send(&app, codex_responses(&pollux_key, added)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, codex_responses("sk-late", &codex_model)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let _ = fs::remove_file(&temp_path);
+}
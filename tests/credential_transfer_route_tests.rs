@@ -0,0 +1,305 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, Response, StatusCode, header},
+};
+use base64::Engine as _;
+use chrono::{Duration, Utc};
+use pollux::db::{CodexCreate, CodexPatch, GeminiCliCreate, ProviderCreate, ProviderPatch};
+use serde_json::{Value, json};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Read,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn send(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    key: Option<&str>,
+    body: Vec<u8>,
+) -> Response<Body> {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(key) = key {
+        builder = builder.header("authorization", format!("Bearer {key}"));
+    }
+    app.clone()
+        .oneshot(
+            builder
+                .body(Body::from(body))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed")
+}
+
+async fn body_bytes(resp: Response<Body>) -> Vec<u8> {
+    to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body")
+        .to_vec()
+}
+
+/// Archive path -> parsed JSON file.
+fn unpack(archive: &[u8]) -> BTreeMap<String, Value> {
+    let mut archive = tar::Archive::new(archive);
+    archive
+        .entries()
+        .expect("tar entries")
+        .map(|entry| {
+            let mut entry = entry.expect("tar entry");
+            let path = entry.path().expect("entry path").display().to_string();
+            let mut data = String::new();
+            entry.read_to_string(&mut data).expect("entry data");
+            (path, serde_json::from_str(&data).expect("entry json"))
+        })
+        .collect()
+}
+
+/// An access token Codex CLI would hold: a JWT whose `exp` claim carries the expiry.
+fn access_token_expiring_in(hours: i64) -> String {
+    let encode = |value: Value| {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value.to_string().as_bytes())
+    };
+    let exp = (Utc::now() + Duration::hours(hours)).timestamp();
+    format!(
+        "{}.{}.sig",
+        encode(json!({ "alg": "RS256" })),
+        encode(json!({ "exp": exp }))
+    )
+}
+
+#[tokio::test]
+async fn admin_credentials_export_and_import_cli_files() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-credential-transfer-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let expiry = Utc::now() + Duration::hours(1);
+    let gemini_id = db
+        .create(ProviderCreate::GeminiCli(GeminiCliCreate {
+            email: Some("gemini@example.com".to_string()),
+            sub: "google-sub".to_string(),
+            project_id: "proj-1".to_string(),
+            refresh_token: "gemini-refresh".to_string(),
+            access_token: Some("gemini-access".to_string()),
+            expiry,
+            tier: Some("standard-tier".to_string()),
+        }))
+        .await
+        .expect("create geminicli row");
+    let codex = |account_id: &str, refresh_token: &str| {
+        ProviderCreate::Codex(CodexCreate {
+            email: Some(format!("{account_id}@example.com")),
+            sub: format!("auth0|{account_id}"),
+            account_id: account_id.to_string(),
+            refresh_token: refresh_token.to_string(),
+            access_token: access_token_expiring_in(1),
+            expiry,
+            chatgpt_plan_type: Some("plus".to_string()),
+        })
+    };
+    let codex_id = db
+        .create(codex("acct-1", "codex-refresh"))
+        .await
+        .expect("create codex row");
+    let disabled_id = db
+        .create(codex("acct-off", "codex-refresh-off"))
+        .await
+        .expect("create codex row");
+    db.patch(ProviderPatch::Codex {
+        id: disabled_id as u64,
+        patch: CodexPatch {
+            status: Some(false),
+            ..Default::default()
+        },
+    })
+    .await
+    .expect("disable codex row");
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
+    let state = pollux::server::router::PolluxState::new(
+        providers.clone(),
+        pollux_key.clone(),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+    let key = Some(pollux_key.as_ref());
+
+    // 1) master key only
+    let resp = send(&app, "GET", "/admin/credentials/export", None, vec![]).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // 2) the archive holds one CLI file per enabled credential
+    let resp = send(&app, "GET", "/admin/credentials/export", key, vec![]).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/x-tar");
+    let files = unpack(&body_bytes(resp).await);
+    let paths: Vec<&str> = files.keys().map(String::as_str).collect();
+    let gemini_path = format!("geminicli/{gemini_id}-proj-1/oauth_creds.json");
+    let codex_path = format!("codex/{codex_id}-acct-1/auth.json");
+    assert_eq!(paths, [codex_path.as_str(), gemini_path.as_str()]);
+
+    let oauth_creds = &files[&gemini_path];
+    assert_eq!(oauth_creds["refresh_token"], "gemini-refresh");
+    assert_eq!(oauth_creds["access_token"], "gemini-access");
+    assert_eq!(oauth_creds["token_type"], "Bearer");
+    assert_eq!(oauth_creds["expiry_date"], expiry.timestamp_millis());
+
+    let auth = &files[&codex_path];
+    assert_eq!(auth["OPENAI_API_KEY"], Value::Null);
+    assert_eq!(auth["tokens"]["refresh_token"], "codex-refresh");
+    assert_eq!(auth["tokens"]["account_id"], "acct-1");
+    assert!(auth["tokens"]["id_token"].is_string());
+
+    // 3) per provider, and a single credential (disabled ones too) as a plain file
+    let resp = send(&app, "GET", "/admin/credentials/codex/export", key, vec![]).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let codex_archive = body_bytes(resp).await;
+    assert_eq!(
+        unpack(&codex_archive).keys().collect::<Vec<_>>(),
+        [&codex_path]
+    );
+
+    let uri = format!("/admin/credentials/codex/{disabled_id}/export");
+    let resp = send(&app, "GET", &uri, key, vec![]).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers()[header::CONTENT_DISPOSITION],
+        "attachment; filename=\"auth.json\""
+    );
+    let auth: Value = serde_json::from_slice(&body_bytes(resp).await).expect("auth.json");
+    assert_eq!(auth["tokens"]["refresh_token"], "codex-refresh-off");
+
+    let resp = send(
+        &app,
+        "GET",
+        "/admin/credentials/geminicli/999/export",
+        key,
+        vec![],
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // 4) importing an export updates the same account instead of adding a row
+    let resp = send(
+        &app,
+        "POST",
+        "/admin/credentials/import",
+        key,
+        codex_archive.clone(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    let report: Value = serde_json::from_slice(&body_bytes(resp).await).expect("report");
+    assert_eq!(report["codex"], 1);
+    assert_eq!(report["geminicli"], 0);
+
+    // ...but an export older than the stored tokens leaves them alone: after a refresh, the
+    // exported refresh token may already be spent.
+    db.patch(ProviderPatch::Codex {
+        id: codex_id as u64,
+        patch: CodexPatch {
+            refresh_token: Some("codex-refresh-rotated".to_string()),
+            access_token: Some(access_token_expiring_in(2)),
+            expiry: Some(Utc::now() + Duration::hours(2)),
+            ..Default::default()
+        },
+    })
+    .await
+    .expect("refresh codex row");
+    let resp = send(
+        &app,
+        "POST",
+        "/admin/credentials/import",
+        key,
+        codex_archive,
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    for _ in 0..25 {
+        let row = db.get_codex_by_id(codex_id).await.expect("codex row");
+        assert_eq!(row.refresh_token, "codex-refresh-rotated");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    // 5) a CodexProfile with tokens is stored and activated without a refresh
+    let body = json!([
+        {
+            "account_id": "acct-new",
+            "sub": "auth0|acct-new",
+            "refresh_token": "codex-refresh-new",
+            "access_token": access_token_expiring_in(1),
+            "expiry": expiry,
+        },
+        { "refresh_token": "which provider?" },
+    ]);
+    let resp = send(
+        &app,
+        "POST",
+        "/admin/credentials/import",
+        key,
+        body.to_string().into_bytes(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    let report: Value = serde_json::from_slice(&body_bytes(resp).await).expect("report");
+    assert_eq!(report["codex"], 1);
+    assert_eq!(report["rejected"][0]["source"], "body[1]");
+
+    let mut accounts = Vec::new();
+    for _ in 0..50 {
+        accounts = db
+            .list_active_codex()
+            .await
+            .expect("list codex")
+            .into_iter()
+            .map(|row| row.account_id)
+            .collect();
+        if accounts.len() == 2 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    accounts.sort();
+    assert_eq!(accounts, ["acct-1", "acct-new"]);
+    let snapshot = providers.codex.snapshot().await.expect("snapshot");
+    assert!(
+        snapshot.credentials.iter().any(|c| c.label == "acct-new"),
+        "{snapshot:?}"
+    );
+
+    // 6) bodies that are neither JSON nor an archive are refused
+    let resp = send(
+        &app,
+        "POST",
+        "/admin/credentials/import",
+        key,
+        b"refresh_token=abc".to_vec(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let error: Value = serde_json::from_slice(&body_bytes(resp).await).expect("error json");
+    assert_eq!(error["error"]["code"], "INVALID_IMPORT");
+
+    let _ = fs::remove_file(&temp_path);
+}
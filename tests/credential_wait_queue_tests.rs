@@ -0,0 +1,104 @@
+use chrono::{Duration, Utc};
+use pollux::PolluxError;
+use pollux::db::{CodexCreate, ProviderCreate};
+use std::{
+    fs,
+    num::{NonZeroU32, NonZeroU64},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+#[tokio::test]
+async fn busy_pool_queues_callers_in_order_and_sheds_beyond_the_cap() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-wait-queue-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    let model = cfg.providers.codex.model_list[0].clone();
+    cfg.providers.codex.max_concurrent_per_credential = NonZeroU32::new(1);
+    cfg.providers.codex.queue_timeout_secs = NonZeroU64::new(1);
+    cfg.providers.codex.queue_max_depth = 1;
+
+    db.create(ProviderCreate::Codex(CodexCreate {
+        email: None,
+        sub: "sub-1".to_string(),
+        account_id: "acct-1".to_string(),
+        refresh_token: "rt".to_string(),
+        access_token: "at".to_string(),
+        expiry: Utc::now() + Duration::days(1),
+        chatgpt_plan_type: None,
+    }))
+    .await
+    .expect("insert credential");
+
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let codex = providers.codex.clone();
+    let mask = pollux::model_catalog::mask(&model).expect("model registered");
+
+    let first = codex
+        .get_credential(mask, None, true)
+        .await
+        .expect("rpc")
+        .expect("idle credential");
+
+    // Without `wait` a busy pool answers at once.
+    assert!(
+        codex
+            .get_credential(mask, None, false)
+            .await
+            .expect("rpc")
+            .is_none()
+    );
+
+    // 1) the next caller waits ...
+    let waiting = tokio::spawn({
+        let codex = codex.clone();
+        async move { codex.get_credential(mask, None, true).await }
+    });
+    let mut snapshot = codex.snapshot().await.expect("snapshot");
+    for _ in 0..50 {
+        if snapshot.waiting == 1 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        snapshot = codex.snapshot().await.expect("snapshot");
+    }
+    assert_eq!(snapshot.waiting, 1);
+    assert_eq!(snapshot.credentials[0].in_flight, 1);
+
+    // 2) ... the one after that is shed, with the queue timeout as the retry hint ...
+    match codex.get_credential(mask, None, true).await {
+        Err(PolluxError::QueueFull { retry_after }) => {
+            assert_eq!(retry_after, std::time::Duration::from_secs(1));
+        }
+        other => panic!("expected QueueFull, got {other:?}"),
+    }
+
+    // 3) ... and the waiter is served as soon as the lease is released.
+    drop(first);
+    let second = tokio::time::timeout(std::time::Duration::from_millis(500), waiting)
+        .await
+        .expect("served before its deadline")
+        .expect("task")
+        .expect("rpc")
+        .expect("released credential");
+
+    // 4) a waiter nobody makes room for gets `None` at its deadline.
+    let started = Instant::now();
+    let timed_out = codex.get_credential(mask, None, true).await.expect("rpc");
+    assert!(timed_out.is_none());
+    assert!(started.elapsed() >= std::time::Duration::from_millis(900));
+    assert_eq!(codex.snapshot().await.expect("snapshot").waiting, 0);
+
+    drop(second);
+    let _ = fs::remove_file(&temp_path);
+}
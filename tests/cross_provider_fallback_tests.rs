@@ -0,0 +1,163 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header},
+    routing::post,
+};
+use chrono::{Duration, Utc};
+use pollux::db::{CodexCreate, ProviderCreate};
+use serde_json::{Value, json};
+use std::{
+    collections::BTreeMap,
+    fs,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+/// What the Codex backend streams for a one-word answer.
+const CODEX_EVENTS: &str = concat!(
+    "data: {\"type\":\"response.created\",\"response\":{\"id\":\"resp_1\",\"created_at\":7}}\n\n",
+    "data: {\"type\":\"response.output_text.delta\",\"output_index\":0,\"delta\":\"hi\"}\n\n",
+    "data: {\"type\":\"response.completed\",\"response\":{\"usage\":",
+    "{\"input_tokens\":3,\"output_tokens\":1,\"total_tokens\":4}}}\n\n",
+);
+
+/// Serve `CODEX_EVENTS` to every POST, standing in for the Codex backend.
+async fn spawn_codex_upstream() -> url::Url {
+    let upstream = Router::new().route(
+        "/responses",
+        post(|| async { ([(header::CONTENT_TYPE, "text/event-stream")], CODEX_EVENTS) }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind mock upstream");
+    let addr = listener.local_addr().expect("mock upstream addr");
+    tokio::spawn(async move { axum::serve(listener, upstream).await });
+    format!("http://{addr}/responses")
+        .parse()
+        .expect("mock upstream url")
+}
+
+async fn send(
+    app: &Router,
+    uri: &str,
+    key: &str,
+    body: Value,
+) -> (StatusCode, Option<String>, String) {
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {key}"))
+                .body(Body::from(body.to_string()))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    let status = resp.status();
+    let served = resp
+        .headers()
+        .get(pollux::server::routes::SERVED_MODEL_HEADER)
+        .map(|value| value.to_str().expect("header value").to_string());
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    (
+        status,
+        served,
+        String::from_utf8(body.to_vec()).expect("response body was not utf-8"),
+    )
+}
+
+#[tokio::test]
+async fn exhausted_pool_falls_back_across_providers_on_chat_routes_only() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-cross-fallback-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    // The Gemini CLI pool has no credentials; the Codex pool has one, backed by the mock.
+    db.create(ProviderCreate::Codex(CodexCreate {
+        email: None,
+        sub: "sub-1".to_string(),
+        account_id: "acct-1".to_string(),
+        refresh_token: "rt".to_string(),
+        access_token: "at".to_string(),
+        expiry: Utc::now() + Duration::days(1),
+        chatgpt_plan_type: None,
+    }))
+    .await
+    .expect("insert credential");
+
+    let primary = "gemini-2.5-pro";
+    let fallback = "gpt-4o-mini";
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    cfg.providers.geminicli.model_list = vec![primary.to_string()];
+    cfg.providers.codex.model_list = vec![fallback.to_string()];
+    cfg.providers.codex.responses_url = Some(spawn_codex_upstream().await);
+    cfg.models.fallbacks = BTreeMap::from([(primary.to_string(), vec![fallback.to_string()])]);
+
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        pollux_key.clone(),
+        cfg.basic.insecure_cookie,
+    );
+    // The model catalog starts from the process-wide config; install this one.
+    state.reload(&cfg).await.expect("reload");
+    let app = pollux::server::router::pollux_router(state);
+
+    // 1) chat completions: the Gemini CLI pool is empty, so the Codex entry answers
+    let chat = json!({
+        "model": primary,
+        "messages": [{ "role": "user", "content": "hello" }],
+    });
+    let (status, served, body) =
+        send(&app, "/geminicli/v1/chat/completions", &pollux_key, chat).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(served.as_deref(), Some(fallback));
+    let completion: Value = serde_json::from_str(&body).expect("completion json");
+    assert_eq!(completion["choices"][0]["message"]["content"], "hi");
+
+    // 2) so does the Anthropic route
+    let messages = json!({
+        "model": primary,
+        "max_tokens": 16,
+        "messages": [{ "role": "user", "content": "hello" }],
+    });
+    let (status, served, body) = send(&app, "/anthropic/v1/messages", &pollux_key, messages).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(served.as_deref(), Some(fallback));
+
+    // 3) the native route cannot speak Codex, and says which fallback it could not reach
+    let native = json!({ "contents": [{ "role": "user", "parts": [{ "text": "hello" }] }] });
+    let (status, served, body) = send(
+        &app,
+        &format!("/geminicli/v1beta/models/{primary}:generateContent"),
+        &pollux_key,
+        native,
+    )
+    .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{body}");
+    assert_eq!(served, None);
+    assert!(body.contains(fallback), "{body}");
+    assert!(body.contains("/geminicli/v1/chat/completions"), "{body}");
+
+    let _ = fs::remove_file(&temp_path);
+}
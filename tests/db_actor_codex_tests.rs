@@ -1,4 +1,7 @@
-use pollux::db::{CodexCreate, CodexPatch, ProviderCreate, ProviderPatch};
+mod common;
+
+use chrono::SubsecRound;
+use pollux::db::{CodexCreate, CodexPatch, DbActorHandle, ProviderCreate, ProviderPatch};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::time::SystemTime;
@@ -15,7 +18,24 @@ async fn test_codex_db_actor_baseline() {
 
     // Spawn DbActor
     let db_actor_handle = pollux::db::spawn(&database_url).await;
+    exercise(&db_actor_handle).await;
+
+    if let Some(database) = common::postgres_database("codex").await {
+        // Only one DbActor may run at a time.
+        db_actor_handle.stop().await;
+        let db_actor_handle = pollux::db::spawn(&database.url).await;
+        exercise(&db_actor_handle).await;
+    }
+
+    // Clean up the temporary database file
+    let wal_path = std::path::PathBuf::from(format!("{}-wal", db_path.to_string_lossy()));
+    let shm_path = std::path::PathBuf::from(format!("{}-shm", db_path.to_string_lossy()));
+    let _ = fs::remove_file(&wal_path).await;
+    let _ = fs::remove_file(&shm_path).await;
+    fs::remove_file(&db_path).await.unwrap();
+}
 
+async fn exercise(db_actor_handle: &DbActorHandle) {
     // 1. Assert list_active_codex() returns Ok(empty) on a fresh DB
     let active_codex_keys = db_actor_handle.list_active_codex().await.unwrap();
     assert!(
@@ -29,7 +49,8 @@ async fn test_codex_db_actor_baseline() {
     let sub = "auth0|test-subject".to_string();
     let refresh_token = "rt-test-token".to_string();
     let access_token = "at-test-token".to_string();
-    let expiry = chrono::Utc::now();
+    // PostgreSQL keeps microseconds, so round-trip an expiry both backends store exactly.
+    let expiry = chrono::Utc::now().trunc_subsecs(6);
     let chatgpt_plan_type = Some("plus".to_string());
 
     let create_data = CodexCreate {
@@ -87,10 +108,44 @@ async fn test_codex_db_actor_baseline() {
         "Expected no active Codex keys after disabling"
     );
 
-    // Clean up the temporary database file
-    let wal_path = std::path::PathBuf::from(format!("{}-wal", db_path.to_string_lossy()));
-    let shm_path = std::path::PathBuf::from(format!("{}-shm", db_path.to_string_lossy()));
-    let _ = fs::remove_file(&wal_path).await;
-    let _ = fs::remove_file(&shm_path).await;
-    fs::remove_file(&db_path).await.unwrap();
+    // 7. Upserting an older copy of the tokens re-enables the row but keeps the stored tokens
+    let stale = CodexCreate {
+        email: email.clone(),
+        account_id: account_id.clone(),
+        sub: sub.clone(),
+        refresh_token: "rt-stale-token".to_string(),
+        access_token: "at-stale-token".to_string(),
+        expiry: expiry - chrono::Duration::hours(1),
+        chatgpt_plan_type: chatgpt_plan_type.clone(),
+    };
+    let upserted_id = db_actor_handle
+        .create(ProviderCreate::Codex(stale))
+        .await
+        .unwrap();
+    assert_eq!(upserted_id, id);
+    let kept = db_actor_handle.get_codex_by_id(id).await.unwrap();
+    assert!(kept.status);
+    assert_eq!(kept.refresh_token, refresh_token);
+    assert_eq!(kept.access_token, access_token);
+    assert_eq!(kept.expiry, expiry);
+
+    // 8. A newer copy replaces them
+    let newer_expiry = expiry + chrono::Duration::hours(1);
+    let newer = CodexCreate {
+        email,
+        account_id,
+        sub,
+        refresh_token: "rt-newer-token".to_string(),
+        access_token: "at-newer-token".to_string(),
+        expiry: newer_expiry,
+        chatgpt_plan_type,
+    };
+    db_actor_handle
+        .create(ProviderCreate::Codex(newer))
+        .await
+        .unwrap();
+    let replaced = db_actor_handle.get_codex_by_id(id).await.unwrap();
+    assert_eq!(replaced.refresh_token, "rt-newer-token");
+    assert_eq!(replaced.access_token, "at-newer-token");
+    assert_eq!(replaced.expiry, newer_expiry);
 }
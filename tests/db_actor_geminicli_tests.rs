@@ -1,5 +1,7 @@
+mod common;
+
 use chrono::{Duration, Utc};
-use pollux::db::{GeminiCliCreate, GeminiCliPatch, ProviderCreate, ProviderPatch};
+use pollux::db::{DbActorHandle, GeminiCliCreate, GeminiCliPatch, ProviderCreate, ProviderPatch};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::time::SystemTime;
@@ -16,7 +18,24 @@ async fn test_geminicli_db_actor_baseline() {
 
     // Spawn DbActor
     let db_actor_handle = pollux::db::spawn(&database_url).await;
+    exercise(&db_actor_handle).await;
+
+    if let Some(database) = common::postgres_database("geminicli").await {
+        // Only one DbActor may run at a time.
+        db_actor_handle.stop().await;
+        let db_actor_handle = pollux::db::spawn(&database.url).await;
+        exercise(&db_actor_handle).await;
+    }
 
+    // Clean up the temporary database file
+    let wal_path = std::path::PathBuf::from(format!("{}-wal", db_path.to_string_lossy()));
+    let shm_path = std::path::PathBuf::from(format!("{}-shm", db_path.to_string_lossy()));
+    let _ = fs::remove_file(&wal_path).await;
+    let _ = fs::remove_file(&shm_path).await;
+    fs::remove_file(&db_path).await.unwrap();
+}
+
+async fn exercise(db_actor_handle: &DbActorHandle) {
     // 1. Assert list_active_geminicli() returns Ok(empty) on a fresh DB
     let active_geminicli = db_actor_handle.list_active_geminicli().await.unwrap();
     assert!(
@@ -39,6 +58,7 @@ async fn test_geminicli_db_actor_baseline() {
         refresh_token: refresh_token.clone(),
         access_token: access_token.clone(),
         expiry,
+        tier: None,
     };
     let provider_create = ProviderCreate::GeminiCli(create_data);
 
@@ -101,11 +121,4 @@ async fn test_geminicli_db_actor_baseline() {
         active_none.is_empty(),
         "Expected no active GeminiCli credentials after patching status=false"
     );
-
-    // Clean up the temporary database file
-    let wal_path = std::path::PathBuf::from(format!("{}-wal", db_path.to_string_lossy()));
-    let shm_path = std::path::PathBuf::from(format!("{}-shm", db_path.to_string_lossy()));
-    let _ = fs::remove_file(&wal_path).await;
-    let _ = fs::remove_file(&shm_path).await;
-    fs::remove_file(&db_path).await.unwrap();
 }
@@ -0,0 +1,195 @@
+use chrono::{Duration, Utc};
+use pollux::db::{GeminiCliCreate, MIGRATIONS, ProviderCreate, SCHEMA_VERSION};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
+use std::{
+    fs,
+    path::PathBuf,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// `gemini_cli` as it shipped before the `tier` column.
+const GEMINI_CLI_WITHOUT_TIER: &str = r#"
+CREATE TABLE gemini_cli (
+    id INTEGER PRIMARY KEY NOT NULL,
+    email TEXT NULL,
+    sub TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NULL,
+    expiry TEXT NOT NULL,
+    status INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE(sub, project_id)
+)
+"#;
+
+/// The whole schema as v0.4.0 created it, with one credential per provider.
+const V0_4_0_DATABASE: &str = r#"
+CREATE TABLE IF NOT EXISTS gemini_cli (
+    id INTEGER PRIMARY KEY NOT NULL,
+    email TEXT NULL,
+    sub TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NULL,
+    expiry TEXT NOT NULL,
+    status INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE(sub, project_id)
+);
+CREATE INDEX IF NOT EXISTS idx_gemini_cli_status ON gemini_cli(status);
+CREATE TABLE IF NOT EXISTS codex (
+    id INTEGER PRIMARY KEY NOT NULL,
+    email TEXT NULL,
+    sub TEXT NOT NULL,
+    account_id TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NOT NULL,
+    expiry TEXT NOT NULL,
+    chatgpt_plan_type TEXT NULL,
+    status INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE(sub, account_id)
+);
+CREATE INDEX IF NOT EXISTS idx_codex_status ON codex(status);
+INSERT INTO gemini_cli (id, sub, project_id, refresh_token, access_token, expiry, status, created_at, updated_at)
+VALUES (7, 'google-sub', 'proj-1', 'rt-gemini', 'at-gemini', '2030-01-01T00:00:00Z', 1, '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z');
+INSERT INTO codex (id, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, status, created_at, updated_at)
+VALUES (3, 'openai-sub', 'acct-1', 'rt-codex', 'at-codex', '2030-01-01T00:00:00Z', 'plus', 0, '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z')
+"#;
+
+fn temp_database(name: &str) -> (PathBuf, String) {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-{name}-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    (temp_path, database_url)
+}
+
+async fn open(database_url: &str) -> SqlitePool {
+    let options = SqliteConnectOptions::from_str(database_url)
+        .expect("valid sqlite url")
+        .create_if_missing(true);
+    SqlitePool::connect_with(options)
+        .await
+        .expect("open database")
+}
+
+#[tokio::test]
+async fn migrations_upgrade_a_v0_4_0_database_and_refuse_a_newer_one() {
+    let (temp_path, database_url) = temp_database("migrate-v0.4.0");
+    let pool = open(&database_url).await;
+    for stmt in V0_4_0_DATABASE.split(';') {
+        sqlx::query(stmt)
+            .execute(&pool)
+            .await
+            .expect("v0.4.0 fixture");
+    }
+
+    // 1) an unversioned database runs every migration, keeping its rows ...
+    assert_eq!(pollux::db::migrate(&pool).await.expect("upgrade"), 0);
+    let applied: Vec<u32> =
+        sqlx::query_scalar("SELECT version FROM schema_version ORDER BY version")
+            .fetch_all(&pool)
+            .await
+            .expect("schema_version");
+    assert_eq!(
+        applied,
+        MIGRATIONS.iter().map(|m| m.version).collect::<Vec<_>>()
+    );
+
+    let gemini: (String, Option<String>, Option<String>) =
+        sqlx::query_as("SELECT refresh_token, access_token, tier FROM gemini_cli WHERE id = 7")
+            .fetch_one(&pool)
+            .await
+            .expect("gemini row survives");
+    assert_eq!(
+        gemini,
+        ("rt-gemini".to_string(), Some("at-gemini".to_string()), None)
+    );
+    let codex: (String, bool) =
+        sqlx::query_as("SELECT chatgpt_plan_type, status FROM codex WHERE id = 3")
+            .fetch_one(&pool)
+            .await
+            .expect("codex row survives");
+    assert_eq!(codex, ("plus".to_string(), false));
+    for table in ["token_usage", "credential_model_state", "token_keys"] {
+        let present: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?)",
+        )
+        .bind(table)
+        .fetch_one(&pool)
+        .await
+        .expect("sqlite_master");
+        assert!(present, "{table} created");
+    }
+
+    // 2) ... a second run has nothing to do ...
+    assert_eq!(
+        pollux::db::migrate(&pool).await.expect("no-op"),
+        SCHEMA_VERSION
+    );
+
+    // 3) ... and a database from a newer build is left alone.
+    sqlx::query(
+        "INSERT INTO schema_version (version, description, applied_at) VALUES (?, 'future', '')",
+    )
+    .bind(SCHEMA_VERSION + 1)
+    .execute(&pool)
+    .await
+    .expect("future version");
+    let err = pollux::db::migrate(&pool)
+        .await
+        .expect_err("newer schema is refused");
+    assert!(err.to_string().contains("newer"), "{err}");
+
+    pool.close().await;
+    let _ = fs::remove_file(&temp_path);
+}
+
+#[tokio::test]
+async fn startup_adds_missing_columns_to_an_existing_database() {
+    let (temp_path, database_url) = temp_database("schema-upgrade");
+    let pool = open(&database_url).await;
+    sqlx::query(GEMINI_CLI_WITHOUT_TIER)
+        .execute(&pool)
+        .await
+        .expect("create old gemini_cli table");
+    pool.close().await;
+
+    let db = pollux::db::spawn(&database_url).await;
+    let create = |tier: Option<&str>| {
+        ProviderCreate::GeminiCli(GeminiCliCreate {
+            email: None,
+            sub: "google-sub".to_string(),
+            project_id: "proj-1".to_string(),
+            refresh_token: "refresh".to_string(),
+            access_token: Some("access".to_string()),
+            expiry: Utc::now() + Duration::hours(1),
+            tier: tier.map(str::to_string),
+        })
+    };
+
+    let id = db
+        .create(create(Some("standard-tier")))
+        .await
+        .expect("insert with tier");
+    // Re-adding without a tier (e.g. a plain token refresh) keeps the stored one.
+    assert_eq!(db.create(create(None)).await.expect("upsert"), id);
+
+    let row = db.get_geminicli_by_id(id).await.expect("row");
+    assert_eq!(row.tier.as_deref(), Some("standard-tier"));
+
+    let _ = fs::remove_file(&temp_path);
+}
@@ -0,0 +1,106 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use std::{
+    fs,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn post_rpc(app: &Router, key: &str, path: &str) -> (StatusCode, String) {
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/geminicli/v1beta/models/{path}?key={key}"))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"{"contents":[{"role":"user","parts":[{"text":"hi"}]}]}"#,
+                ))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    (
+        status,
+        String::from_utf8(body.to_vec()).expect("response body was not utf-8"),
+    )
+}
+
+#[tokio::test]
+async fn geminicli_route_dispatches_known_rpcs_and_rejects_others() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-geminicli-rpc-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    // Keep test behavior stable regardless of the repo's runtime `config.toml`.
+    let model = pollux::config::CONFIG
+        .geminicli()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gemini-2.5-pro".to_string());
+    cfg.providers.geminicli.model_list = vec![model.clone()];
+
+    // No credentials inserted => dispatched requests should yield 503.
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        pollux_key.clone(),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+    let key = pollux_key.as_ref();
+
+    // 1) known RPCs reach the credential pool -> 503
+    for rpc in ["generateContent", "streamGenerateContent", "countTokens"] {
+        let (status, body) = post_rpc(&app, key, &format!("{model}:{rpc}")).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{rpc}: {body}");
+    }
+
+    // 2) embeddings are not served by Code Assist -> 501 UNIMPLEMENTED
+    for rpc in ["embedContent", "batchEmbedContents"] {
+        let (status, body) = post_rpc(&app, key, &format!("{model}:{rpc}")).await;
+        assert_eq!(status, StatusCode::NOT_IMPLEMENTED, "{rpc}: {body}");
+        assert!(body.contains(r#""status":"UNIMPLEMENTED""#), "{body}");
+        assert!(
+            body.contains(&format!("{rpc} is not served by the Gemini CLI backend")),
+            "{body}"
+        );
+    }
+
+    // 3) unknown RPC verbs -> 400 INVALID_ARGUMENT
+    let (status, body) = post_rpc(&app, key, &format!("{model}:fooBar")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert!(body.contains(r#""status":"INVALID_ARGUMENT""#), "{body}");
+    assert!(body.contains("unsupported RPC method: fooBar"), "{body}");
+
+    // 4) no RPC verb at all -> 400
+    let (status, body) = post_rpc(&app, key, &model).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("missing RPC method"), "{body}");
+
+    let _ = fs::remove_file(&temp_path);
+}
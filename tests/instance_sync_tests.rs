@@ -0,0 +1,148 @@
+use chrono::{Duration, Utc};
+use pollux::db::{CodexCreate, CodexPatch, ModelStateUpdate, ProviderCreate, ProviderPatch};
+use std::{
+    fs,
+    time::{Duration as StdDuration, SystemTime, UNIX_EPOCH},
+};
+
+fn codex_account(account_id: &str, expiry: chrono::DateTime<Utc>) -> ProviderCreate {
+    ProviderCreate::Codex(CodexCreate {
+        email: None,
+        sub: format!("sub-{account_id}"),
+        account_id: account_id.to_string(),
+        refresh_token: "rt".to_string(),
+        access_token: "at".to_string(),
+        expiry,
+        chatgpt_plan_type: None,
+    })
+}
+
+/// Another instance sharing the database is simulated by writing through the same `DbActor`
+/// under a different lease holder.
+#[tokio::test]
+async fn codex_picks_up_changes_made_by_another_instance() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-instance-sync-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let db = pollux::db::spawn(&format!("sqlite:{}", temp_path.display())).await;
+
+    let mut cfg = pollux::config::Config::default();
+    let model = pollux::config::CONFIG
+        .codex()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gpt-4o-mini".to_string());
+    cfg.providers.codex.model_list = vec![model.clone()];
+
+    let kept = db
+        .create(codex_account("acct-kept", Utc::now() + Duration::days(1)))
+        .await
+        .unwrap();
+    let expired = db
+        .create(codex_account(
+            "acct-expired",
+            Utc::now() - Duration::hours(1),
+        ))
+        .await
+        .unwrap();
+
+    // The other instance is already refreshing the expired credential.
+    assert!(
+        db.acquire_refresh_lease(
+            "codex",
+            expired,
+            "other-instance",
+            StdDuration::from_secs(120)
+        )
+        .await
+        .unwrap()
+    );
+
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let snapshot = providers.codex.snapshot().await.unwrap();
+    assert_eq!(snapshot.total_creds, 2);
+    assert_eq!(snapshot.refreshing, vec![expired as u64]);
+
+    // 1) this instance waits for the other one's tokens instead of refreshing too
+    db.patch(ProviderPatch::Codex {
+        id: expired as u64,
+        patch: CodexPatch {
+            refresh_token: Some("rt-rotated".to_string()),
+            access_token: Some("at-rotated".to_string()),
+            expiry: Some(Utc::now() + Duration::hours(1)),
+            ..Default::default()
+        },
+    })
+    .await
+    .unwrap();
+    db.release_refresh_lease("codex", expired, "other-instance");
+
+    let mut adopted = None;
+    for _ in 0..50 {
+        let snapshot = providers.codex.snapshot().await.unwrap();
+        adopted = snapshot
+            .credentials
+            .into_iter()
+            .find(|c| c.id == expired as u64);
+        if adopted.as_ref().is_some_and(|c| !c.refreshing) {
+            break;
+        }
+        tokio::time::sleep(StdDuration::from_millis(100)).await;
+    }
+    let adopted = adopted.expect("credential in snapshot");
+    assert!(!adopted.refreshing && !adopted.expired, "{adopted:?}");
+    let stored = db.get_codex_by_id(expired).await.unwrap();
+    assert_eq!(stored.refresh_token, "rt-rotated");
+
+    // 2) onboarding, disabling and rate limits elsewhere reach this instance on the next sync
+    let added = db
+        .create(codex_account("acct-added", Utc::now() + Duration::days(1)))
+        .await
+        .unwrap();
+    db.patch(ProviderPatch::Codex {
+        id: expired as u64,
+        patch: CodexPatch {
+            status: Some(false),
+            ..Default::default()
+        },
+    })
+    .await
+    .unwrap();
+    db.save_model_state(ModelStateUpdate {
+        provider: "codex",
+        credential_id: kept,
+        model: model.clone(),
+        unsupported: false,
+        cooldown_until: Some(Utc::now() + Duration::minutes(30)),
+    });
+
+    providers.codex.sync_store().await.unwrap();
+    let snapshot = providers.codex.snapshot().await.unwrap();
+    let mut ids: Vec<u64> = snapshot.credentials.iter().map(|c| c.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![kept as u64, added as u64]);
+    let cooldowns = &snapshot
+        .credentials
+        .iter()
+        .find(|c| c.id == kept as u64)
+        .expect("credential in snapshot")
+        .cooldowns;
+    assert_eq!(cooldowns.len(), 1, "{cooldowns:?}");
+    assert!(cooldowns[0].remaining_secs > 1700, "{cooldowns:?}");
+
+    // 3) a deletion elsewhere too
+    db.delete_codex(added).await.unwrap();
+    providers.codex.sync_store().await.unwrap();
+    let snapshot = providers.codex.snapshot().await.unwrap();
+    assert_eq!(snapshot.total_creds, 1);
+
+    let _ = fs::remove_file(&temp_path);
+}
@@ -0,0 +1,140 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header::CONTENT_TYPE},
+};
+use std::{
+    fs,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn send(app: &Router, req: Request<Body>) -> (StatusCode, Option<String>, String) {
+    let resp = app.clone().oneshot(req).await.expect("request failed");
+    let status = resp.status();
+    let content_type = resp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    (
+        status,
+        content_type,
+        String::from_utf8(body.to_vec()).expect("response body was not utf-8"),
+    )
+}
+
+#[tokio::test]
+async fn metrics_route_exposes_request_counters_and_pool_gauges() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-metrics-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    let model = pollux::config::CONFIG
+        .codex()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gpt-4o-mini".to_string());
+    cfg.providers.codex.model_list = vec![model.clone()];
+
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        pollux_key.clone(),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+
+    // 1) no key -> 401
+    let req = Request::builder()
+        .uri("/metrics")
+        .body(Body::empty())
+        .expect("failed to build request");
+    let (status, _, _) = send(&app, req).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 2) a valid Codex request with no credentials -> 503, counted under its model
+    let req = Request::builder()
+        .method("POST")
+        .uri("/codex/v1/responses")
+        .header("content-type", "application/json")
+        .header("x-goog-api-key", pollux_key.as_ref())
+        .body(Body::from(format!(r#"{{"model":"{model}"}}"#)))
+        .expect("failed to build request");
+    let (status, _, _) = send(&app, req).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+    // 3) an unsupported model never becomes a label value
+    let req = Request::builder()
+        .method("POST")
+        .uri("/codex/v1/responses")
+        .header("content-type", "application/json")
+        .header("x-goog-api-key", pollux_key.as_ref())
+        .body(Body::from(r#"{"model":"no-such-model"}"#))
+        .expect("failed to build request");
+    let (status, _, _) = send(&app, req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // 4) scrape
+    let req = Request::builder()
+        .uri("/metrics")
+        .header("x-goog-api-key", pollux_key.as_ref())
+        .body(Body::empty())
+        .expect("failed to build request");
+    let (status, content_type, body) = send(&app, req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        content_type
+            .as_deref()
+            .is_some_and(|v| v.starts_with("text/plain; version=0.0.4")),
+        "unexpected content type: {content_type:?}"
+    );
+
+    for family in [
+        "# TYPE pollux_requests_total counter",
+        "# TYPE pollux_lease_wait_seconds histogram",
+        "# TYPE pollux_upstream_ttfb_seconds histogram",
+        "# TYPE pollux_upstream_error_actions_total counter",
+        "# TYPE pollux_refresh_total counter",
+        "# TYPE pollux_pool_credentials gauge",
+        "# TYPE pollux_available_credentials gauge",
+        "# TYPE pollux_cooling_credentials gauge",
+        "# TYPE pollux_refreshing_credentials gauge",
+    ] {
+        assert!(body.contains(family), "missing `{family}` in:\n{body}");
+    }
+
+    let served =
+        format!(r#"pollux_requests_total{{provider="codex",model="{model}",status="503"}} 1"#);
+    assert!(body.contains(&served), "missing `{served}` in:\n{body}");
+    let rejected = r#"pollux_requests_total{provider="codex",model="-",status="400"} 1"#;
+    assert!(body.contains(rejected), "missing `{rejected}` in:\n{body}");
+    assert!(!body.contains("no-such-model"));
+
+    let pool = format!(r#"pollux_pool_credentials{{provider="codex",model="{model}"}} 0"#);
+    assert!(body.contains(&pool), "missing `{pool}` in:\n{body}");
+
+    // Scrapes themselves are not proxy traffic.
+    assert!(!body.contains("/metrics"));
+
+    let _ = fs::remove_file(&temp_path);
+}
@@ -0,0 +1,192 @@
+use chrono::{Duration, Utc};
+use pollux::db::{CodexCreate, GeminiCliCreate, ModelStateUpdate, ProviderCreate};
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+fn codex_account(account_id: &str) -> ProviderCreate {
+    ProviderCreate::Codex(CodexCreate {
+        email: None,
+        sub: format!("sub-{account_id}"),
+        account_id: account_id.to_string(),
+        refresh_token: "rt".to_string(),
+        access_token: "at".to_string(),
+        expiry: Utc::now() + Duration::days(1),
+        chatgpt_plan_type: None,
+    })
+}
+
+fn geminicli_project(project_id: &str) -> ProviderCreate {
+    ProviderCreate::GeminiCli(GeminiCliCreate {
+        email: None,
+        sub: format!("sub-{project_id}"),
+        project_id: project_id.to_string(),
+        refresh_token: "rt".to_string(),
+        access_token: Some("at".to_string()),
+        expiry: Utc::now() + Duration::days(1),
+        tier: None,
+    })
+}
+
+/// Both providers share one test: the provider actors can only be spawned once per process.
+#[tokio::test]
+async fn model_state_survives_a_restart() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-model-state-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    let model = pollux::config::CONFIG
+        .codex()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gpt-4o-mini".to_string());
+    cfg.providers.codex.model_list = vec![model.clone()];
+    let gemini_model = pollux::config::CONFIG
+        .geminicli()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gemini-2.5-pro".to_string());
+    cfg.providers.geminicli.model_list = vec![gemini_model.clone()];
+
+    let unsupported = db.create(codex_account("acct-unsupported")).await.unwrap();
+    let over_quota = db.create(codex_account("acct-over-quota")).await.unwrap();
+    let recovered = db.create(codex_account("acct-recovered")).await.unwrap();
+
+    // State left behind by a previous run.
+    let save = |credential_id: i64, unsupported: bool, cooldown: Option<Duration>| {
+        db.save_model_state(ModelStateUpdate {
+            provider: "codex",
+            credential_id,
+            model: model.clone(),
+            unsupported,
+            cooldown_until: cooldown.map(|cooldown| Utc::now() + cooldown),
+        })
+    };
+    save(unsupported, true, None);
+    save(over_quota, false, Some(Duration::hours(2)));
+    save(recovered, false, Some(Duration::minutes(-5)));
+
+    let gemini_unsupported = db
+        .create(geminicli_project("proj-unsupported"))
+        .await
+        .unwrap();
+    let gemini_over_quota = db
+        .create(geminicli_project("proj-over-quota"))
+        .await
+        .unwrap();
+    let save_gemini = |credential_id: i64, unsupported: bool, cooldown: Option<Duration>| {
+        db.save_model_state(ModelStateUpdate {
+            provider: "geminicli",
+            credential_id,
+            model: gemini_model.clone(),
+            unsupported,
+            cooldown_until: cooldown.map(|cooldown| Utc::now() + cooldown),
+        })
+    };
+    save_gemini(gemini_unsupported, true, None);
+    save_gemini(gemini_over_quota, false, Some(Duration::hours(2)));
+
+    // 1) pre_start restores the saved state
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let snapshot = providers.codex.snapshot().await.unwrap();
+    let cred = |id: i64| {
+        snapshot
+            .credentials
+            .iter()
+            .find(|c| c.id == id as u64)
+            .expect("credential in snapshot")
+    };
+
+    assert!(
+        cred(unsupported).models.is_empty(),
+        "{:?}",
+        cred(unsupported)
+    );
+    assert!(cred(unsupported).cooldowns.is_empty());
+
+    let cooldowns = &cred(over_quota).cooldowns;
+    assert_eq!(cooldowns.len(), 1, "{cooldowns:?}");
+    assert_eq!(cooldowns[0].model, model);
+    assert!(cooldowns[0].remaining_secs > 7000, "{cooldowns:?}");
+    assert_eq!(cred(over_quota).models, vec![model.clone()]);
+
+    assert!(cred(recovered).cooldowns.is_empty());
+    assert_eq!(cred(recovered).models, vec![model.clone()]);
+
+    // The Gemini CLI actor restores its credentials the same way.
+    let snapshot = providers.geminicli.snapshot().await.unwrap();
+    let gemini_cred = |id: i64| {
+        snapshot
+            .credentials
+            .iter()
+            .find(|c| c.id == id as u64)
+            .expect("credential in snapshot")
+            .clone()
+    };
+    let restored = gemini_cred(gemini_unsupported);
+    assert!(restored.models.is_empty(), "{restored:?}");
+    assert!(restored.cooldowns.is_empty());
+    let restored = gemini_cred(gemini_over_quota);
+    assert_eq!(restored.cooldowns.len(), 1, "{restored:?}");
+    assert_eq!(restored.cooldowns[0].model, gemini_model);
+    assert!(restored.cooldowns[0].remaining_secs > 7000, "{restored:?}");
+    assert_eq!(restored.models, vec![gemini_model.clone()]);
+
+    // Expired cooldowns were pruned on load.
+    let saved = db.list_model_state("codex").await.unwrap();
+    let ids: Vec<i64> = saved.iter().map(|row| row.credential_id).collect();
+    assert_eq!(ids, vec![unsupported, over_quota]);
+
+    // 2) new reports are written through
+    let mask = pollux::model_catalog::mask(&model).expect("model registered");
+    providers
+        .codex
+        .report_rate_limit(recovered as u64, mask, std::time::Duration::from_secs(600))
+        .await;
+    let mut saved = Vec::new();
+    for _ in 0..50 {
+        saved = db.list_model_state("codex").await.unwrap();
+        if saved.len() == 3 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let row = saved
+        .iter()
+        .find(|row| row.credential_id == recovered)
+        .expect("cooldown saved");
+    assert!(!row.unsupported);
+    assert!(row.cooldown_until.is_some_and(|until| until > Utc::now()));
+
+    // 3) re-enabling through the admin path clears it
+    providers
+        .codex
+        .set_status(unsupported as u64, true)
+        .await
+        .unwrap();
+    let saved = db.list_model_state("codex").await.unwrap();
+    assert!(saved.iter().all(|row| row.credential_id != unsupported));
+
+    // 4) deleting a credential takes its state with it
+    db.delete_geminicli(gemini_over_quota).await.unwrap();
+    let saved = db.list_model_state("geminicli").await.unwrap();
+    let ids: Vec<i64> = saved.iter().map(|row| row.credential_id).collect();
+    assert_eq!(ids, vec![gemini_unsupported]);
+
+    let _ = fs::remove_file(&temp_path);
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use pollux::db::{CodexCreate, DbActorHandle, ProviderCreate};
+use std::time::{Duration as StdDuration, SystemTime, UNIX_EPOCH};
+
+const TTL: StdDuration = StdDuration::from_secs(120);
+
+#[tokio::test]
+async fn refresh_lease_admits_one_holder_at_a_time() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-refresh-lease-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+
+    let db = pollux::db::spawn(&format!("sqlite:{}", temp_path.display())).await;
+    exercise(&db).await;
+
+    if let Some(database) = common::postgres_database("refresh_lease").await {
+        // Only one DbActor may run at a time.
+        db.stop().await;
+        let db = pollux::db::spawn(&database.url).await;
+        exercise(&db).await;
+    }
+
+    let _ = std::fs::remove_file(&temp_path);
+}
+
+async fn exercise(db: &DbActorHandle) {
+    let id = db
+        .create(ProviderCreate::Codex(CodexCreate {
+            email: None,
+            sub: "sub-lease".to_string(),
+            account_id: "acct-lease".to_string(),
+            refresh_token: "rt".to_string(),
+            access_token: "at".to_string(),
+            expiry: Utc::now() + Duration::hours(1),
+            chatgpt_plan_type: None,
+        }))
+        .await
+        .unwrap();
+    let acquire = |holder: &'static str, ttl: StdDuration| async move {
+        db.acquire_refresh_lease("codex", id, holder, ttl)
+            .await
+            .unwrap()
+    };
+
+    // 1) the first instance gets it, the second waits, the holder may renew
+    assert!(acquire("instance-a", TTL).await);
+    assert!(!acquire("instance-b", TTL).await);
+    assert!(acquire("instance-a", TTL).await);
+
+    // 2) leases are per provider and credential
+    assert!(
+        db.acquire_refresh_lease("geminicli", id, "instance-b", TTL)
+            .await
+            .unwrap()
+    );
+    assert!(
+        db.acquire_refresh_lease("codex", id + 1, "instance-b", TTL)
+            .await
+            .unwrap()
+    );
+
+    // 3) only the holder can release it
+    db.release_refresh_lease("codex", id, "instance-b");
+    assert!(!acquire("instance-b", TTL).await);
+    db.release_refresh_lease("codex", id, "instance-a");
+    assert!(acquire("instance-b", StdDuration::ZERO).await);
+
+    // 4) an expired lease is taken over
+    assert!(acquire("instance-a", TTL).await);
+    assert!(!acquire("instance-b", TTL).await);
+}
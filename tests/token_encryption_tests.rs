@@ -0,0 +1,154 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{Duration, Utc};
+use pollux::db::crypto::SEALED_PREFIX;
+use pollux::db::{
+    CodexCreate, CodexPatch, GeminiCliPatch, ProviderCreate, ProviderPatch, TokenKeyring,
+};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
+use std::{
+    fs,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+async fn stored_tokens(pool: &SqlitePool, table: &str, id: i64) -> (String, Option<String>) {
+    sqlx::query_as(&format!(
+        "SELECT refresh_token, access_token FROM {table} WHERE id = ?"
+    ))
+    .bind(id)
+    .fetch_one(pool)
+    .await
+    .expect("stored row")
+}
+
+fn assert_sealed((refresh_token, access_token): (String, Option<String>)) {
+    assert!(refresh_token.starts_with(SEALED_PREFIX), "{refresh_token}");
+    let access_token = access_token.expect("access token");
+    assert!(access_token.starts_with(SEALED_PREFIX), "{access_token}");
+}
+
+#[tokio::test]
+async fn tokens_are_sealed_at_rest_and_plain_through_the_handle() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-token-encryption-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+
+    // A database written before encryption was switched on.
+    let options = SqliteConnectOptions::from_str(&database_url)
+        .expect("valid sqlite url")
+        .create_if_missing(true);
+    let pool = SqlitePool::connect_with(options)
+        .await
+        .expect("open database");
+    pollux::db::migrate(&pool).await.expect("schema");
+    let now = Utc::now();
+    let gemini_id: i64 = sqlx::query_scalar(
+        "INSERT INTO gemini_cli (sub, project_id, refresh_token, access_token, expiry, \
+         created_at, updated_at) VALUES ('sub', 'proj', 'rt-old', 'at-old', ?, ?, ?) RETURNING id",
+    )
+    .bind(now + Duration::hours(1))
+    .bind(now)
+    .bind(now)
+    .fetch_one(&pool)
+    .await
+    .expect("plaintext row");
+
+    let keyring = TokenKeyring::new(&STANDARD.encode([42u8; 32]), &[]).expect("valid key");
+    let db = pollux::db::spawn_with(&database_url, Some(keyring)).await;
+
+    // 1) startup sealed the existing row ...
+    assert_sealed(stored_tokens(&pool, "gemini_cli", gemini_id).await);
+    let row = db.get_geminicli_by_id(gemini_id).await.expect("row");
+    assert_eq!(row.refresh_token, "rt-old");
+    assert_eq!(row.access_token.as_deref(), Some("at-old"));
+
+    // 2) ... patches are sealed on the way in ...
+    db.patch(ProviderPatch::GeminiCli {
+        id: gemini_id as u64,
+        patch: GeminiCliPatch {
+            access_token: Some("at-new".to_string()),
+            ..Default::default()
+        },
+    })
+    .await
+    .expect("patch");
+    assert_sealed(stored_tokens(&pool, "gemini_cli", gemini_id).await);
+    let active = db.list_active_geminicli().await.expect("list");
+    assert_eq!(active[0].access_token.as_deref(), Some("at-new"));
+
+    // 3) ... and so are new credentials.
+    let codex_id = db
+        .create(ProviderCreate::Codex(CodexCreate {
+            email: None,
+            sub: "sub".to_string(),
+            account_id: "acct".to_string(),
+            refresh_token: "rt-codex".to_string(),
+            access_token: "at-codex".to_string(),
+            expiry: now + Duration::hours(1),
+            chatgpt_plan_type: None,
+        }))
+        .await
+        .expect("create");
+    assert_sealed(stored_tokens(&pool, "codex", codex_id).await);
+    let page = db.list_codex(0, 10).await.expect("page");
+    assert_eq!(page.items[0].refresh_token, "rt-codex");
+    assert_eq!(page.items[0].access_token, "at-codex");
+
+    // 4) a sealed token copied onto another credential, even of the other provider, does not
+    // open there.
+    let other_id = db
+        .create(ProviderCreate::Codex(CodexCreate {
+            email: None,
+            sub: "sub".to_string(),
+            account_id: "acct-other".to_string(),
+            refresh_token: "rt-other".to_string(),
+            access_token: "at-other".to_string(),
+            expiry: now + Duration::hours(1),
+            chatgpt_plan_type: None,
+        }))
+        .await
+        .expect("create");
+    let (sealed_codex, _) = stored_tokens(&pool, "codex", codex_id).await;
+    let (sealed_gemini, _) = stored_tokens(&pool, "gemini_cli", gemini_id).await;
+    for (table, id, sealed) in [
+        ("codex", other_id, &sealed_codex),
+        ("codex", other_id, &sealed_gemini),
+        ("gemini_cli", gemini_id, &sealed_codex),
+    ] {
+        sqlx::query(&format!(
+            "UPDATE {table} SET refresh_token = ? WHERE id = ?"
+        ))
+        .bind(sealed)
+        .bind(id)
+        .execute(&pool)
+        .await
+        .expect("swap token");
+    }
+    assert!(db.get_codex_by_id(other_id).await.is_err());
+    assert!(db.get_geminicli_by_id(gemini_id).await.is_err());
+
+    // 5) changing the account a row belongs to re-seals its tokens for it
+    db.patch(ProviderPatch::Codex {
+        id: codex_id as u64,
+        patch: CodexPatch {
+            account_id: Some("acct-moved".to_string()),
+            ..Default::default()
+        },
+    })
+    .await
+    .expect("patch");
+    let row = db.get_codex_by_id(codex_id).await.expect("row");
+    assert_eq!(row.account_id, "acct-moved");
+    assert_eq!(row.refresh_token, "rt-codex");
+
+    pool.close().await;
+    let _ = fs::remove_file(&temp_path);
+}